@@ -0,0 +1,92 @@
+//! Benchmarks of the hot paths of the crate: encoding, decoding, signature verification, node id
+//! computation and field access, for `secp256k1` and `ed25519` records, and decoding of many
+//! records.
+//!
+//! Run with `cargo bench --features ed25519`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use enr::{Bytes, CombinedKey, Enr, EnrBuilder, EnrKey, NodeId};
+use std::net::Ipv4Addr;
+
+/// Builds a typical record with an IPv4 endpoint.
+fn build(key: &CombinedKey) -> Enr<CombinedKey> {
+    EnrBuilder::new("v4")
+        .ip4(Ipv4Addr::new(192, 0, 2, 1))
+        .tcp(30303)
+        .udp(30303)
+        .build(key)
+        .unwrap()
+}
+
+fn bench_key_type(c: &mut Criterion, name: &str, key: &CombinedKey) {
+    let enr = build(key);
+    let encoded = enr.encode();
+    let public_key = key.public();
+
+    c.bench_function(&format!("{name} encode"), |b| {
+        b.iter(|| black_box(&enr).encode())
+    });
+    c.bench_function(&format!("{name} decode"), |b| {
+        b.iter(|| rlp::decode::<Enr<CombinedKey>>(black_box(&encoded)).unwrap())
+    });
+    c.bench_function(&format!("{name} verify"), |b| {
+        b.iter(|| assert!(black_box(&enr).verify()))
+    });
+    c.bench_function(&format!("{name} node_id"), |b| {
+        b.iter(|| NodeId::from(black_box(public_key.clone())))
+    });
+}
+
+fn bench_secp256k1(c: &mut Criterion) {
+    bench_key_type(c, "secp256k1", &CombinedKey::generate_secp256k1());
+}
+
+fn bench_ed25519(c: &mut Criterion) {
+    bench_key_type(c, "ed25519", &CombinedKey::generate_ed25519());
+}
+
+fn bench_field_access(c: &mut Criterion) {
+    let enr = build(&CombinedKey::generate_secp256k1());
+
+    c.bench_function("ip", |b| b.iter(|| black_box(&enr).ip()));
+    c.bench_function("tcp", |b| b.iter(|| black_box(&enr).tcp()));
+    c.bench_function("udp_socket", |b| b.iter(|| black_box(&enr).udp_socket()));
+}
+
+fn bench_decode_many(c: &mut Criterion) {
+    let key = CombinedKey::generate_secp256k1();
+    let mut enr = build(&key);
+    let encoded: Vec<Bytes> = (0..10_000)
+        .map(|port| {
+            enr.set_udp(port + 1, &key).unwrap();
+            Bytes::from(enr.encode())
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("decode 10k records");
+    group.sample_size(10);
+    group.bench_function("rlp::decode", |b| {
+        b.iter(|| {
+            for bytes in &encoded {
+                black_box(rlp::decode::<Enr<CombinedKey>>(bytes).unwrap());
+            }
+        })
+    });
+    group.bench_function("decode_bytes", |b| {
+        b.iter(|| {
+            for bytes in &encoded {
+                black_box(Enr::<CombinedKey>::decode_bytes(bytes).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_secp256k1,
+    bench_ed25519,
+    bench_field_access,
+    bench_decode_many
+);
+criterion_main!(benches);
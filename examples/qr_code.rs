@@ -0,0 +1,41 @@
+//! Prints a QR code sharing a record to the terminal.
+//!
+//! Run with `cargo run --example qr_code [enr:-...]`. A record of a new key is shown if none is
+//! given.
+
+use enr::{secp256k1::SecretKey, Enr, EnrBuilder};
+use qrcode::{render::unicode::Dense1x2, EcLevel, QrCode};
+use std::net::Ipv4Addr;
+
+fn main() {
+    let enr: Enr<SecretKey> = std::env::args().nth(1).map_or_else(
+        || {
+            let key = SecretKey::random(&mut rand::thread_rng());
+            EnrBuilder::new("v4")
+                .ip4(Ipv4Addr::new(192, 0, 2, 1))
+                .udp(30303)
+                .tcp(30303)
+                .build(&key)
+                .unwrap()
+        },
+        |text| text.parse().expect("a valid ENR"),
+    );
+
+    let payload = enr.to_qr_payload();
+    let code = QrCode::with_error_correction_level(&payload, EcLevel::M).unwrap();
+    println!(
+        "{}",
+        code.render::<Dense1x2>()
+            .dark_color(Dense1x2::Light)
+            .light_color(Dense1x2::Dark)
+            .build()
+    );
+    println!("{payload}");
+    println!(
+        "version {:?}, error correction {:?}",
+        code.version(),
+        code.error_correction_level()
+    );
+
+    assert_eq!(Enr::from_qr_payload(&payload).unwrap(), enr);
+}
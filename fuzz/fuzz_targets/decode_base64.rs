@@ -0,0 +1,13 @@
+//! Parses arbitrary strings as base64-encoded text records.
+#![no_main]
+
+use enr::{CombinedKey, Enr};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    if let Ok(enr) = data.parse::<Enr<CombinedKey>>() {
+        let decoded = enr.to_base64().parse::<Enr<CombinedKey>>().unwrap();
+        assert_eq!(decoded, enr);
+        assert!(enr.verify());
+    }
+});
@@ -0,0 +1,28 @@
+//! Decodes arbitrary bytes as an RLP-encoded record.
+#![no_main]
+
+use enr::{CombinedKey, Enr, EnrDecodeOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(enr) = rlp::decode::<Enr<CombinedKey>>(data) {
+        // the decoded record re-encodes to a record with the same content and signature
+        let encoded = enr.encode();
+        let decoded = rlp::decode::<Enr<CombinedKey>>(&encoded).unwrap();
+        assert_eq!(decoded, enr);
+        assert_eq!(decoded.encode(), encoded);
+        assert!(enr.verify());
+    }
+
+    // records of unknown identity schemes are only kept on request, and never verify
+    let mut opts = EnrDecodeOptions::new();
+    opts.allow_unknown_schemes(true);
+    if let Ok(enr) = Enr::<CombinedKey>::decode_with_options(data, &opts) {
+        assert_eq!(enr.verify(), enr.identity_scheme().is_some());
+    }
+
+    // a canonically encoded record re-encodes to the same bytes
+    if let Ok(enr) = Enr::<CombinedKey>::decode_strict(data) {
+        assert_eq!(enr.encode(), data);
+    }
+});
@@ -0,0 +1,80 @@
+#ifndef ENR_H
+#define ENR_H
+
+/* Generated with cbindgen from src/enr_ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The operation succeeded.
+#define ENR_OK 0
+
+// A required pointer argument was null.
+#define ENR_ERR_NULL_POINTER -1
+
+// The bytes are not a valid record.
+#define ENR_ERR_DECODE -2
+
+// The output buffer is too small. The required length has been written to `out_len`.
+#define ENR_ERR_BUFFER_TOO_SMALL -3
+
+// The requested field is not present in the record.
+#define ENR_ERR_NOT_FOUND -4
+
+// A decoded record, only accessed through an [`EnrHandle`].
+typedef struct OpaqueEnr OpaqueEnr;
+
+// An opaque pointer to a decoded record, created by [`enr_decode`] and released by
+// [`enr_free`].
+typedef struct OpaqueEnr *EnrHandle;
+
+// Decodes the RLP-encoded record of `len` bytes at `bytes`, writing a new handle to `out`.
+//
+// The signature is verified as part of decoding. On failure `out` is left untouched.
+//
+// # Safety
+//
+// `bytes` must point to `len` readable bytes and `out` must be valid for writes.
+int32_t enr_decode(const uint8_t *bytes, size_t len, EnrHandle *out);
+
+// Writes the RLP encoding of the record to `out`.
+//
+// `out_len` holds the capacity of `out` on input and the length of the encoding on output. If
+// `out` is null or too small, nothing is written to it and [`ENR_ERR_BUFFER_TOO_SMALL`] is
+// returned, so the required length can be queried by passing a null `out`.
+//
+// # Safety
+//
+// `handle` must have been created by [`enr_decode`] and not yet freed, `out_len` must be valid
+// for reads and writes, and `out`, if not null, must point to `*out_len` writable bytes.
+int32_t enr_encode(EnrHandle handle, uint8_t *out, size_t *out_len);
+
+// Writes the 4 octets of the `ip` field to `out_ip`.
+//
+// Returns [`ENR_ERR_NOT_FOUND`] if the record has no valid `ip` field.
+//
+// # Safety
+//
+// `handle` must have been created by [`enr_decode`] and not yet freed, and `out_ip` must point
+// to 4 writable bytes.
+int32_t enr_get_ip(EnrHandle handle, uint8_t *out_ip);
+
+// Verifies the signature of the record. Returns 1 if it is valid and 0 if it is not.
+//
+// # Safety
+//
+// `handle` must have been created by [`enr_decode`] and not yet freed.
+int32_t enr_verify(EnrHandle handle);
+
+// Releases a record. Passing a null handle does nothing.
+//
+// # Safety
+//
+// `handle` must be null or have been created by [`enr_decode`] and not yet freed. It must not
+// be used afterwards.
+void enr_free(EnrHandle handle);
+
+#endif /* ENR_H */
@@ -0,0 +1,15 @@
+#!/usr/bin/env sh
+# Runs the test suite under miri to detect undefined behaviour.
+#
+# The signing libraries cannot be interpreted by miri: libsecp256k1 0.3.5 builds its arrays with
+# generic-array 0.12, which reads uninitialised memory. Tests that sign or verify records are
+# therefore ignored under `cfg(miri)`, with miri's validity checks left on for the rest. The
+# remaining tests sign with the test-only `MockSigner` or use fixed fixtures, and exercise the
+# record as a data structure: the content map, RLP encoding and decoding and field access.
+# Tests that open sockets, which miri's isolation forbids, and statistical tests over thousands
+# of random node ids, which take too long to interpret, are ignored as well. Benchmarks and
+# doctests are not run. The seed fixes the randomness miri provides.
+set -e
+
+rustup +nightly component add miri
+MIRIFLAGS="${MIRIFLAGS:--Zmiri-seed=1}" cargo +nightly miri test --tests "$@"
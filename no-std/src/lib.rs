@@ -0,0 +1,31 @@
+//! Checks that `enr` builds without the standard library. The crate is `no_std` and depends on
+//! `enr` without its default `std` feature, so building it for a target without `std` fails if
+//! `enr` uses `std`.
+
+#![no_std]
+
+use enr::{secp256k1::SecretKey, Enr};
+
+/// The record of the EIP-778 test vector.
+pub const EIP_778: &str = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+
+/// Decodes the EIP-778 test vector, verifying its signature, and returns its UDP port.
+#[must_use]
+pub fn eip_778_udp_port() -> Option<u16> {
+    let enr: Enr<SecretKey> = EIP_778.parse().ok()?;
+    if enr.verify() {
+        enr.udp4()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eip_778() {
+        assert_eq!(eip_778_udp_port(), Some(30303));
+    }
+}
@@ -0,0 +1,210 @@
+//! Classification of IP addresses: the families a record advertises and the ranges that are not
+//! publicly routable.
+//!
+//! Records intended for public peer exchange should only advertise addresses other nodes can
+//! reach. [`EnrBuilder::with_validation`] rejects addresses in any of the [`AddressRange`]s.
+//!
+//! [`EnrBuilder::with_validation`]: crate::EnrBuilder::with_validation
+
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// A range of IP addresses that are not publicly routable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AddressRange {
+    /// The unspecified address, `0.0.0.0` or `::`.
+    Unspecified,
+    /// The loopback range, `127.0.0.0/8` or `::1`.
+    Loopback,
+    /// The private ranges of RFC 1918, `10.0.0.0/8`, `172.16.0.0/12` and `192.168.0.0/16`.
+    Private,
+    /// The shared address space of RFC 6598, `100.64.0.0/10`, used for carrier-grade NAT.
+    SharedAddressSpace,
+    /// The link-local range, `169.254.0.0/16` or `fe80::/10`.
+    LinkLocal,
+    /// The documentation ranges, `192.0.2.0/24`, `198.51.100.0/24`, `203.0.113.0/24` or
+    /// `2001:db8::/32`.
+    Documentation,
+    /// The IPv6 unique local range of RFC 4193, `fc00::/7`.
+    UniqueLocal,
+    /// The multicast range, `224.0.0.0/4` or `ff00::/8`.
+    Multicast,
+    /// The broadcast address, `255.255.255.255`.
+    Broadcast,
+}
+
+/// The IP address families a record advertises an address of, see
+/// [`Enr::address_family`](crate::Enr::address_family).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AddressFamily {
+    /// Only an IPv4 address, the `ip` field.
+    V4Only,
+    /// Only an IPv6 address, the `ip6` field.
+    V6Only,
+    /// Both an IPv4 and an IPv6 address.
+    DualStack,
+    /// No address.
+    None,
+}
+
+impl AddressFamily {
+    /// The family of a record with the given addresses present.
+    #[must_use]
+    pub const fn from_presence(ip4: bool, ip6: bool) -> Self {
+        match (ip4, ip6) {
+            (true, false) => Self::V4Only,
+            (false, true) => Self::V6Only,
+            (true, true) => Self::DualStack,
+            (false, false) => Self::None,
+        }
+    }
+
+    /// Returns `true` if an IPv4 address is advertised.
+    #[must_use]
+    pub const fn has_ipv4(self) -> bool {
+        matches!(self, Self::V4Only | Self::DualStack)
+    }
+
+    /// Returns `true` if an IPv6 address is advertised.
+    #[must_use]
+    pub const fn has_ipv6(self) -> bool {
+        matches!(self, Self::V6Only | Self::DualStack)
+    }
+}
+
+impl AddressRange {
+    /// Returns the non-public range the address belongs to, or `None` for a publicly routable
+    /// address. IPv4-mapped IPv6 addresses are classified as their IPv4 address.
+    #[must_use]
+    pub const fn of(ip: IpAddr) -> Option<Self> {
+        match ip {
+            IpAddr::V4(ip) => Self::of_ipv4(ip),
+            IpAddr::V6(ip) => Self::of_ipv6(ip),
+        }
+    }
+
+    /// Returns the non-public range of an IPv4 address, if any.
+    #[must_use]
+    pub const fn of_ipv4(ip: Ipv4Addr) -> Option<Self> {
+        let octets = ip.octets();
+        if ip.is_unspecified() {
+            Some(Self::Unspecified)
+        } else if ip.is_loopback() {
+            Some(Self::Loopback)
+        } else if ip.is_private() {
+            Some(Self::Private)
+        } else if octets[0] == 100 && octets[1] & 0xc0 == 64 {
+            Some(Self::SharedAddressSpace)
+        } else if ip.is_link_local() {
+            Some(Self::LinkLocal)
+        } else if ip.is_documentation() {
+            Some(Self::Documentation)
+        } else if ip.is_multicast() {
+            Some(Self::Multicast)
+        } else if ip.is_broadcast() {
+            Some(Self::Broadcast)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the non-public range of an IPv6 address, if any.
+    #[must_use]
+    pub const fn of_ipv6(ip: Ipv6Addr) -> Option<Self> {
+        if let Some(ip) = ip.to_ipv4_mapped() {
+            return Self::of_ipv4(ip);
+        }
+        let segments = ip.segments();
+        if ip.is_unspecified() {
+            Some(Self::Unspecified)
+        } else if ip.is_loopback() {
+            Some(Self::Loopback)
+        } else if segments[0] & 0xffc0 == 0xfe80 {
+            Some(Self::LinkLocal)
+        } else if segments[0] == 0x2001 && segments[1] == 0xdb8 {
+            Some(Self::Documentation)
+        } else if segments[0] & 0xfe00 == 0xfc00 {
+            Some(Self::UniqueLocal)
+        } else if ip.is_multicast() {
+            Some(Self::Multicast)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the IPv4 address embedded in an IPv4-mapped (`::ffff:a.b.c.d`) or IPv4-compatible
+/// (`::a.b.c.d`) IPv6 address. The unspecified (`::`) and loopback (`::1`) addresses are genuine
+/// IPv6 addresses and have none.
+#[must_use]
+pub const fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    if ip.is_unspecified() || ip.is_loopback() {
+        return None;
+    }
+    ip.to_ipv4()
+}
+
+impl core::fmt::Display for AddressRange {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let range = match self {
+            Self::Unspecified => "unspecified",
+            Self::Loopback => "loopback",
+            Self::Private => "private",
+            Self::SharedAddressSpace => "shared address space",
+            Self::LinkLocal => "link-local",
+            Self::Documentation => "documentation",
+            Self::UniqueLocal => "unique local",
+            Self::Multicast => "multicast",
+            Self::Broadcast => "broadcast",
+        };
+        f.write_str(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_ranges() {
+        let cases: &[(&str, Option<AddressRange>)] = &[
+            ("0.0.0.0", Some(AddressRange::Unspecified)),
+            ("127.0.0.1", Some(AddressRange::Loopback)),
+            ("10.1.2.3", Some(AddressRange::Private)),
+            ("172.16.0.1", Some(AddressRange::Private)),
+            ("192.168.1.1", Some(AddressRange::Private)),
+            ("100.64.0.1", Some(AddressRange::SharedAddressSpace)),
+            ("100.128.0.1", None),
+            ("169.254.1.1", Some(AddressRange::LinkLocal)),
+            ("192.0.2.1", Some(AddressRange::Documentation)),
+            ("224.0.0.1", Some(AddressRange::Multicast)),
+            ("255.255.255.255", Some(AddressRange::Broadcast)),
+            ("8.8.8.8", None),
+            ("::", Some(AddressRange::Unspecified)),
+            ("::1", Some(AddressRange::Loopback)),
+            ("fe80::1", Some(AddressRange::LinkLocal)),
+            ("2001:db8::1", Some(AddressRange::Documentation)),
+            ("fd00::1", Some(AddressRange::UniqueLocal)),
+            ("ff02::1", Some(AddressRange::Multicast)),
+            ("::ffff:192.168.1.1", Some(AddressRange::Private)),
+            ("2606:4700::1111", None),
+        ];
+        for (ip, range) in cases {
+            assert_eq!(AddressRange::of(ip.parse().unwrap()), *range, "{ip}");
+        }
+    }
+
+    #[test]
+    fn test_embedded_ipv4() {
+        let cases: &[(&str, Option<Ipv4Addr>)] = &[
+            ("::ffff:1.2.3.4", Some(Ipv4Addr::new(1, 2, 3, 4))),
+            ("::1.2.3.4", Some(Ipv4Addr::new(1, 2, 3, 4))),
+            ("::", None),
+            ("::1", None),
+            ("2606:4700::1111", None),
+            ("64:ff9b::1.2.3.4", None),
+        ];
+        for (ip, ipv4) in cases {
+            assert_eq!(embedded_ipv4(ip.parse().unwrap()), *ipv4, "{ip}");
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Configurable text forms of ENR records.
+//!
+//! EIP-778 specifies the text form of a record as `enr:` followed by unpadded URL-safe base64,
+//! which is what [`Enr::to_base64`] produces. Some tooling expects the base64 without the prefix,
+//! with padding or in the standard alphabet. [`Base64Options`] describes such a form, for
+//! [`Enr::to_base64_with`] and [`Enr::from_base64_with`].
+
+use crate::{Enr, EnrKey};
+use alloc::{format, string::String};
+use base64::Config;
+
+/// The prefix of the text form of a record.
+const PREFIX: &str = "enr:";
+
+/// Options for the text form of a record, see [`Enr::to_base64_with`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Base64Options {
+    /// Prefixes the base64 with `enr:`.
+    prefix: bool,
+
+    /// Pads the base64 with `=` to a multiple of 4 characters.
+    padding: bool,
+
+    /// Uses the URL-safe alphabet, `-` and `_`, rather than the standard one, `+` and `/`.
+    url_safe: bool,
+}
+
+impl Base64Options {
+    /// Constructs the options of the text form specified by EIP-778, prefixed by `enr:`, unpadded
+    /// and URL-safe.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            prefix: true,
+            padding: false,
+            url_safe: true,
+        }
+    }
+
+    /// Sets whether the base64 is prefixed by `enr:`.
+    pub const fn prefix(&mut self, prefix: bool) -> &mut Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Sets whether the base64 is padded with `=`.
+    pub const fn padding(&mut self, padding: bool) -> &mut Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets whether the base64 uses the URL-safe alphabet rather than the standard one.
+    pub const fn url_safe(&mut self, url_safe: bool) -> &mut Self {
+        self.url_safe = url_safe;
+        self
+    }
+
+    /// The base64 configuration of the alphabet and padding.
+    const fn config(&self) -> Config {
+        match (self.url_safe, self.padding) {
+            (true, false) => base64::URL_SAFE_NO_PAD,
+            (true, true) => base64::URL_SAFE,
+            (false, false) => base64::STANDARD_NO_PAD,
+            (false, true) => base64::STANDARD,
+        }
+    }
+}
+
+impl Default for Base64Options {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// Provides the base64 text form of the record according to the given options. With the
+    /// default options this is [`Enr::to_base64`].
+    #[must_use]
+    pub fn to_base64_with(&self, opts: &Base64Options) -> String {
+        let encoded = base64::encode_config(self.encode(), opts.config());
+        if opts.prefix {
+            format!("{PREFIX}{encoded}")
+        } else {
+            encoded
+        }
+    }
+
+    /// Decodes a text form produced by [`Enr::to_base64_with`] with the same options, verifying
+    /// its signature.
+    ///
+    /// Only that exact form is accepted: the prefix, the padding and the alphabet must all match
+    /// the options. See [`str::parse`] for lenient parsing.
+    pub fn from_base64_with(text: &str, opts: &Base64Options) -> Result<Self, String> {
+        let encoded = match (opts.prefix, text.strip_prefix(PREFIX)) {
+            (true, Some(encoded)) => encoded,
+            (true, None) => return Err("Invalid ENR string: missing the enr: prefix".into()),
+            (false, Some(_)) => return Err("Invalid ENR string: unexpected enr: prefix".into()),
+            (false, None) => text,
+        };
+        let bytes = base64::decode_config(encoded, opts.config())
+            .map_err(|e| format!("Invalid base64 encoding: {e:?}"))?;
+        // the decoder tolerates missing or superfluous padding
+        if base64::encode_config(&bytes, opts.config()) != encoded {
+            return Err("Invalid base64 encoding: padding".into());
+        }
+        Self::decode_bytes(&bytes.into()).map_err(|e| format!("Invalid ENR: {e:?}"))
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "libsecp256k1")]
+mod tests {
+    use super::*;
+    use crate::secp256k1::SecretKey;
+    use std::collections::HashSet;
+
+    type DefaultEnr = Enr<SecretKey>;
+
+    /// The record of the EIP-778 test vector, 134 bytes with both `-` and `_` in its text form.
+    const EIP_778: &str = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+
+    fn all_options() -> Vec<Base64Options> {
+        let mut all = Vec::new();
+        for &prefix in &[false, true] {
+            for &padding in &[false, true] {
+                for &url_safe in &[false, true] {
+                    all.push(
+                        Base64Options::new()
+                            .prefix(prefix)
+                            .padding(padding)
+                            .url_safe(url_safe)
+                            .clone(),
+                    );
+                }
+            }
+        }
+        all
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_base64_options_roundtrip() {
+        let enr: DefaultEnr = EIP_778.parse().unwrap();
+        assert_eq!(enr.to_base64_with(&Base64Options::default()), EIP_778);
+
+        let all = all_options();
+        let texts: Vec<_> = all.iter().map(|opts| enr.to_base64_with(opts)).collect();
+        // every combination gives a distinct text form
+        assert_eq!(texts.iter().collect::<HashSet<_>>().len(), 8);
+
+        for (opts, text) in all.iter().zip(&texts) {
+            let decoded = DefaultEnr::from_base64_with(text, opts).unwrap();
+            assert_eq!(decoded, enr, "{opts:?}");
+            assert_eq!(decoded.encode(), enr.encode());
+            assert_eq!(text.parse::<DefaultEnr>(), Ok(enr.clone()));
+
+            // the other forms are refused
+            for other in all.iter().filter(|&other| other != opts) {
+                assert!(
+                    DefaultEnr::from_base64_with(text, other).is_err(),
+                    "{} decoded with {:?}",
+                    text,
+                    other
+                );
+            }
+        }
+
+        // records of unknown schemes are refused in every form
+        let forged = crate::forge_unknown_scheme(&enr);
+        for opts in &all {
+            let error =
+                DefaultEnr::from_base64_with(&forged.to_base64_with(opts), opts).unwrap_err();
+            assert!(error.contains("Unsupported identity scheme"), "{}", error);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! A bloom filter of node ids, a compact alternative to a set of the node ids known to a node.
+
+use crate::{digest, Enr, EnrKey, NodeId};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+/// A bloom filter of node ids, sized for a number of node ids and a false positive rate.
+///
+/// [`EnrBloomFilter::probably_contains`] never returns `false` for an inserted node id, and
+/// returns `true` for other node ids with about the configured false positive rate while at most
+/// the configured number of node ids are inserted.
+///
+/// The bit positions of a node id are derived from the keccak256 hash of its bytes, the hash of
+/// [`NodeId`] computation, by double hashing. With the `serde` feature the filter can be
+/// persisted, and is validated when deserialized.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawBloomFilter"))]
+pub struct EnrBloomFilter {
+    /// The bits of the filter, a non-empty multiple of 64.
+    bits: Vec<u64>,
+    /// The number of bits set for each node id, at least 1.
+    num_hashes: u32,
+}
+
+/// An unvalidated, deserialized [`EnrBloomFilter`].
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawBloomFilter {
+    bits: Vec<u64>,
+    num_hashes: u32,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawBloomFilter> for EnrBloomFilter {
+    type Error = &'static str;
+
+    fn try_from(raw: RawBloomFilter) -> Result<Self, Self::Error> {
+        if raw.bits.is_empty() {
+            return Err("A bloom filter needs at least 64 bits");
+        }
+        if raw.num_hashes == 0 {
+            return Err("A bloom filter needs at least one hash");
+        }
+        Ok(Self {
+            bits: raw.bits,
+            num_hashes: raw.num_hashes,
+        })
+    }
+}
+
+impl EnrBloomFilter {
+    /// Creates an empty filter for `expected_items` node ids with the given false positive rate,
+    /// such as `0.01`.
+    ///
+    /// The number of bits is `-n ln(p) / ln(2)^2`, rounded up to a multiple of 64, and the number
+    /// of hashes `m / n ln(2)`, for `n` node ids, a false positive rate `p` and `m` bits.
+    ///
+    /// # Panics
+    /// Panics if the false positive rate is not strictly between 0 and 1.
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "the false positive rate must be between 0 and 1, got {}",
+            false_positive_rate
+        );
+        let items = expected_items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = (-items * false_positive_rate.ln() / (ln2 * ln2)).ceil() as usize;
+        let words = num_bits.div_ceil(64).max(1);
+        let num_hashes = ((words * 64) as f64 / items * ln2).round().max(1.0) as u32;
+        Self {
+            bits: vec![0; words],
+            num_hashes,
+        }
+    }
+
+    /// The number of bits of the filter.
+    #[must_use]
+    pub const fn num_bits(&self) -> usize {
+        self.bits.len() * 64
+    }
+
+    /// The number of bits set for each node id.
+    #[must_use]
+    pub const fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    /// Inserts the node id of a record.
+    pub fn insert<K: EnrKey>(&mut self, enr: &Enr<K>) {
+        self.insert_node_id(&enr.node_id());
+    }
+
+    /// Inserts a node id.
+    pub fn insert_node_id(&mut self, node_id: &NodeId) {
+        for index in self.indices(node_id) {
+            self.bits[index / 64] |= 1 << (index % 64);
+        }
+    }
+
+    /// Returns `false` if the node id has certainly not been inserted, and `true` if it probably
+    /// has.
+    #[must_use]
+    pub fn probably_contains(&self, node_id: &NodeId) -> bool {
+        self.indices(node_id)
+            .all(|index| self.bits[index / 64] & (1 << (index % 64)) != 0)
+    }
+
+    /// Removes all node ids.
+    pub fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|word| *word = 0);
+    }
+
+    /// The bit positions of a node id, `h1 + i * h2` for the first two 8-byte words of its hash.
+    #[allow(clippy::cast_possible_truncation)]
+    fn indices(&self, node_id: &NodeId) -> impl Iterator<Item = usize> {
+        let hash = digest(&node_id.raw());
+        let word = |i: usize| {
+            let mut bytes = [0_u8; 8];
+            bytes.copy_from_slice(&hash[i * 8..i * 8 + 8]);
+            u64::from_be_bytes(bytes)
+        };
+        let (h1, h2) = (word(0), word(1) | 1);
+        let num_bits = self.num_bits() as u64;
+        (0..u64::from(self.num_hashes))
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder};
+
+    #[test]
+    fn test_bloom_filter_sizing() {
+        // 9.59 bits and 6.64 hashes per node id for a rate of 1%
+        let filter = EnrBloomFilter::new(1000, 0.01);
+        assert_eq!(filter.num_bits(), 9600);
+        assert_eq!(filter.num_hashes(), 7);
+
+        let filter = EnrBloomFilter::new(0, 0.5);
+        assert_eq!(filter.num_bits(), 64);
+        assert!(filter.num_hashes() >= 1);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_bloom_filter_membership() {
+        let mut filter = EnrBloomFilter::new(1000, 0.01);
+        let enr = EnrBuilder::new("v4").build(&MockSigner(1)).unwrap();
+        assert!(!filter.probably_contains(&enr.node_id()));
+        filter.insert(&enr);
+        assert!(filter.probably_contains(&enr.node_id()));
+
+        let inserted: Vec<_> = (0..1000).map(|_| NodeId::random()).collect();
+        for node_id in &inserted {
+            filter.insert_node_id(node_id);
+        }
+        assert!(inserted
+            .iter()
+            .all(|node_id| filter.probably_contains(node_id)));
+
+        // the false positive rate is close to the configured rate
+        let false_positives = (0..10_000)
+            .filter(|_| filter.probably_contains(&NodeId::random()))
+            .count();
+        assert!(false_positives < 300, "{} false positives", false_positives);
+
+        filter.clear();
+        assert!(!filter.probably_contains(&enr.node_id()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_bloom_filter_serde() {
+        let mut filter = EnrBloomFilter::new(100, 0.01);
+        let node_id = NodeId::random();
+        filter.insert_node_id(&node_id);
+        let json = serde_json::to_string(&filter).unwrap();
+        let decoded: EnrBloomFilter = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, filter);
+        assert!(decoded.probably_contains(&node_id));
+
+        assert!(serde_json::from_str::<EnrBloomFilter>(r#"{"bits":[],"num_hashes":3}"#).is_err());
+        assert!(serde_json::from_str::<EnrBloomFilter>(r#"{"bits":[0],"num_hashes":0}"#).is_err());
+    }
+}
@@ -1,6 +1,28 @@
-use crate::{Enr, EnrError, EnrKey, EnrPublicKey, NodeId, MAX_ENR_SIZE};
+use crate::{
+    address,
+    field::{self, FieldError},
+    fields::{Ip4, Ip6},
+    validate_raw_rlp, AddressRange, Enr, EnrError, EnrField, EnrKey, EnrPublicKey, EnrWarning,
+    IdentityScheme, NodeId, V4Scheme, MAX_ENR_SIZE,
+};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+    sync::Arc,
+    vec::Vec,
+};
+use bytes::Bytes;
+use core::{
+    marker::PhantomData,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+};
 use rlp::RlpStream;
-use std::{collections::BTreeMap, marker::PhantomData, net::IpAddr};
+#[cfg(feature = "std")]
+use std::{io, net::UdpSocket};
+
+/// The port fields that require an `ip6` field, see [`EnrBuilder::allow_orphan_ports`].
+const IPV6_PORTS: [&str; 3] = ["tcp6", "udp6", "quic6"];
 
 ///! The base builder for generating ENR records with arbitrary signing algorithms.
 pub struct EnrBuilder<K: EnrKey> {
@@ -10,8 +32,26 @@ pub struct EnrBuilder<K: EnrKey> {
     /// The starting sequence number for the ENR record.
     seq: u64,
 
-    /// The key-value pairs for the ENR record.
-    content: BTreeMap<String, Vec<u8>>,
+    /// The key-value pairs for the ENR record. Values are stored RLP-encoded.
+    content: BTreeMap<String, Bytes>,
+
+    /// A custom identity scheme, if set.
+    scheme: Option<Arc<dyn IdentityScheme<K>>>,
+
+    /// Whether addresses that are not publicly routable are rejected.
+    validate_ip: bool,
+
+    /// Whether IPv6 ports are accepted without an `ip6` field.
+    allow_orphan_ports: bool,
+
+    /// Whether the `id` and public key fields may be set manually.
+    override_reserved: bool,
+
+    /// The keys set through the builder, each of which may only be added once.
+    added: BTreeSet<String>,
+
+    /// The first invalid address passed to the builder, reported by [`EnrBuilder::build`].
+    error: Option<EnrError>,
 
     /// Pins the generic key types.
     phantom: PhantomData<K>,
@@ -19,42 +59,195 @@ pub struct EnrBuilder<K: EnrKey> {
 
 impl<K: EnrKey> EnrBuilder<K> {
     /// Constructs a minimal `EnrBuilder` providing only a sequence number.
-    /// Currently only supports the id v4 scheme and therefore disallows creation of any other
-    /// scheme.
+    /// Only the v4 scheme is supported by default, other schemes must be set via
+    /// [`EnrBuilder::identity_scheme`].
     pub fn new(id: impl Into<String>) -> Self {
         Self {
             id: id.into(),
             seq: 1,
             content: BTreeMap::new(),
+            scheme: None,
+            validate_ip: false,
+            allow_orphan_ports: false,
+            override_reserved: false,
+            added: BTreeSet::new(),
+            error: None,
             phantom: PhantomData,
         }
     }
 
+    /// Sets a custom identity scheme used to sign the ENR record, replacing the id given to
+    /// [`EnrBuilder::new`].
+    pub fn identity_scheme(&mut self, scheme: impl IdentityScheme<K> + 'static) -> &mut Self {
+        self.id = scheme.scheme_id().into();
+        self.scheme = Some(Arc::new(scheme));
+        self
+    }
+
     /// Modifies the sequence number of the builder.
     pub fn seq(&mut self, seq: u64) -> &mut Self {
         self.seq = seq;
         self
     }
 
-    /// Adds an arbitrary key-value to the `ENRBuilder`.
+    /// Sets whether addresses that are not publicly routable, such as loopback, private or
+    /// link-local addresses, are rejected. Disabled by default.
+    ///
+    /// The `ip` and `ip6` fields are checked by [`EnrBuilder::build`], however they were set and
+    /// whether this is set before or after them. A rejected address causes `build` to fail with
+    /// [`EnrError::NonPublicAddress`], specifying the [`AddressRange`] it belongs to.
+    pub const fn with_validation(&mut self, validate_ip: bool) -> &mut Self {
+        self.validate_ip = validate_ip;
+        self
+    }
+
+    /// Sets whether a `udp6`, `tcp6` or `quic6` port is accepted without an `ip6` address. Disabled by
+    /// default, causing [`EnrBuilder::build`] to fail with [`EnrError::OrphanPort`].
+    ///
+    /// A node that learns its IPv6 address from its peers may advertise its ports before the
+    /// address is known.
+    pub const fn allow_orphan_ports(&mut self, allow: bool) -> &mut Self {
+        self.allow_orphan_ports = allow;
+        self
+    }
+
+    /// Allows the `id` and public key fields, which [`EnrBuilder::build`] otherwise writes, to
+    /// be set with [`EnrBuilder::add_value`]. The manually set values are kept as given.
+    ///
+    /// This is an escape hatch for producing unusual or deliberately invalid records, such as
+    /// test vectors. A public key that does not belong to the signing key results in a record
+    /// whose signature does not verify.
+    pub const fn override_reserved(&mut self) -> &mut Self {
+        self.override_reserved = true;
+        self
+    }
+
+    /// Adds an arbitrary key-value to the `ENRBuilder`. The value is RLP-encoded as a byte
+    /// string.
+    ///
+    /// Each key may only be added once, a repeated key causes [`EnrBuilder::build`] to fail with
+    /// [`EnrError::DuplicateKey`]. The `id` and public key fields are written by `build` and
+    /// cause it to fail with [`EnrError::ReservedKey`], unless
+    /// [`EnrBuilder::override_reserved`] is set. A malformed value of a well-known key, such as
+    /// an `ip` that is not 4 bytes, causes `build` to fail with [`EnrError::InvalidField`].
+    #[allow(clippy::needless_pass_by_value)]
     pub fn add_value(&mut self, key: String, value: Vec<u8>) -> &mut Self {
-        self.content.insert(key, value);
+        if !self.added.insert(key.clone()) {
+            self.reject(EnrError::DuplicateKey(key));
+            return self;
+        }
+        let value = rlp::encode(&value);
+        // a reserved `id` is refused by `build`, or kept as given if overridden
+        match field::validate(&key, &value) {
+            Ok(()) | Err(FieldError::UnknownIdentityScheme(_)) => {
+                self.content.insert(key, value.into());
+            }
+            Err(error) => self.reject(EnrError::InvalidField(error)),
+        }
         self
     }
 
-    /// Adds an `ip` field to the `ENRBuilder`.
+    /// Adds a string to the `ENRBuilder`. The UTF-8 bytes of the string are RLP-encoded as a
+    /// byte string, read back with [`Enr::get_utf8`].
+    pub fn add_utf8(&mut self, key: String, value: &str) -> &mut Self {
+        self.add_value(key, value.as_bytes().to_vec())
+    }
+
+    /// Adds an `ip` field for IPv4 addresses, or an `ip6` field for IPv6 addresses, to the
+    /// `ENRBuilder`. IPv4-mapped IPv6 addresses are added to the `ip6` field unchanged.
+    #[deprecated(
+        since = "0.1.0-alpha.7",
+        note = "use `ip4` or `ip6`, which reject IPv4-mapped IPv6 addresses"
+    )]
     pub fn ip(&mut self, ip: IpAddr) -> &mut Self {
         match ip {
-            IpAddr::V4(addr) => {
-                self.content
-                    .insert(String::from("ip"), addr.octets().to_vec());
-            }
-            IpAddr::V6(addr) => {
-                self.content
-                    .insert(String::from("ip6"), addr.octets().to_vec());
+            IpAddr::V4(addr) => self.add_typed("ip", rlp::encode(&addr.octets().to_vec())),
+            IpAddr::V6(addr) => self.add_typed("ip6", rlp::encode(&addr.octets().to_vec())),
+        }
+    }
+
+    /// Adds an `ip` field to the `ENRBuilder`, replacing any previous address.
+    ///
+    /// With [`EnrBuilder::with_validation`] enabled, addresses that are not publicly routable
+    /// are rejected.
+    pub fn ip4(&mut self, ip: Ipv4Addr) -> &mut Self {
+        self.add_typed("ip", rlp::encode(&ip.octets().to_vec()))
+    }
+
+    /// Adds an `ip6` field to the `ENRBuilder`, replacing any previous address.
+    ///
+    /// IPv4-mapped and IPv4-compatible IPv6 addresses are rejected, causing
+    /// [`EnrBuilder::build`] to fail with [`EnrError::Ipv4MappedAddress`]. IPv4 addresses belong
+    /// in the `ip` field, see [`EnrBuilder::ip4`]. With [`EnrBuilder::with_validation`] enabled,
+    /// addresses that are not publicly routable are rejected as well.
+    pub fn ip6(&mut self, ip: Ipv6Addr) -> &mut Self {
+        if address::embedded_ipv4(ip).is_some() {
+            self.reject(EnrError::Ipv4MappedAddress(ip));
+            return self;
+        }
+        self.add_typed("ip6", rlp::encode(&ip.octets().to_vec()))
+    }
+
+    /// Adds the local address of a bound UDP socket as the `ip` or `ip6` field, see
+    /// [`EnrBuilder::ip4`] and [`EnrBuilder::ip6`].
+    ///
+    /// A socket bound to the unspecified address only has a specific local address once
+    /// connected. Such an unconnected socket is rejected with [`io::ErrorKind::AddrNotAvailable`].
+    #[cfg(feature = "std")]
+    pub fn set_ip_from_socket(&mut self, socket: &UdpSocket) -> io::Result<&mut Self> {
+        let ip = socket.local_addr()?.ip();
+        if ip.is_unspecified() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrNotAvailable,
+                "the socket is bound to the unspecified address",
+            ));
+        }
+        Ok(match ip {
+            IpAddr::V4(ip) => self.ip4(ip),
+            IpAddr::V6(ip) => self.ip6(ip),
+        })
+    }
+
+    /// Adds the IPv4 address the operating system uses for outbound traffic as the `ip` field.
+    ///
+    /// The address is the local address of a UDP socket connected to a public address. Connecting
+    /// a UDP socket sends no packets, but fails if the host has no route to the internet.
+    #[cfg(feature = "std")]
+    pub fn default_ip_from_os(&mut self) -> io::Result<&mut Self> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        // a documentation address, routed like any public address but never answered
+        socket.connect((Ipv4Addr::new(192, 0, 2, 1), 9))?;
+        self.set_ip_from_socket(&socket)
+    }
+
+    /// Checks the `ip` and `ip6` fields against the validation setting.
+    fn validate_addresses(&self) -> Result<(), EnrError> {
+        if !self.validate_ip {
+            return Ok(());
+        }
+        let ip4 = self
+            .content
+            .get(Ip4::KEY)
+            .and_then(|raw| Ip4::decode(raw).ok());
+        let ip6 = self
+            .content
+            .get(Ip6::KEY)
+            .and_then(|raw| Ip6::decode(raw).ok());
+        let addresses = ip4
+            .map(|Ip4(ip)| IpAddr::V4(ip))
+            .into_iter()
+            .chain(ip6.map(|Ip6(ip)| IpAddr::V6(ip)));
+        for ip in addresses {
+            if let Some(range) = AddressRange::of(ip) {
+                return Err(EnrError::NonPublicAddress(ip, range));
             }
         }
-        self
+        Ok(())
+    }
+
+    /// Records an invalid input, the first of which is reported by [`EnrBuilder::build`].
+    fn reject(&mut self, error: EnrError) {
+        self.error.get_or_insert(error);
     }
 
     /*
@@ -70,29 +263,86 @@ impl<K: EnrKey> EnrBuilder<K> {
 
     /// Adds a `tcp` field to the `ENRBuilder`.
     pub fn tcp(&mut self, tcp: u16) -> &mut Self {
-        self.content
-            .insert("tcp".into(), tcp.to_be_bytes().to_vec());
-        self
+        self.add_port("tcp", tcp)
     }
 
-    /// Adds a `tcp6` field to the `ENRBuilder`.
+    /// Adds a `tcp6` field to the `ENRBuilder`. The record also requires an `ip6` field, see
+    /// [`EnrBuilder::allow_orphan_ports`].
     pub fn tcp6(&mut self, tcp: u16) -> &mut Self {
-        self.content
-            .insert("tcp6".into(), tcp.to_be_bytes().to_vec());
-        self
+        self.add_port("tcp6", tcp)
     }
 
     /// Adds a `udp` field to the `ENRBuilder`.
     pub fn udp(&mut self, udp: u16) -> &mut Self {
-        self.content
-            .insert("udp".into(), udp.to_be_bytes().to_vec());
-        self
+        self.add_port("udp", udp)
     }
 
-    /// Adds a `udp6` field to the `ENRBuilder`.
+    /// Adds a `udp6` field to the `ENRBuilder`. The record also requires an `ip6` field, see
+    /// [`EnrBuilder::allow_orphan_ports`].
     pub fn udp6(&mut self, udp: u16) -> &mut Self {
-        self.content
-            .insert("udp6".into(), udp.to_be_bytes().to_vec());
+        self.add_port("udp6", udp)
+    }
+
+    /// Adds a `quic` field to the `ENRBuilder`.
+    pub fn quic(&mut self, quic: u16) -> &mut Self {
+        self.add_port("quic", quic)
+    }
+
+    /// Adds a `quic6` field to the `ENRBuilder`. The record also requires an `ip6` field, see
+    /// [`EnrBuilder::allow_orphan_ports`].
+    pub fn quic6(&mut self, quic: u16) -> &mut Self {
+        self.add_port("quic6", quic)
+    }
+
+    /// Adds a typed field to the `ENRBuilder`, replacing any previous value of the field.
+    ///
+    /// A value that is not a single well-formed RLP item, or a malformed value of a well-known
+    /// key, causes [`EnrBuilder::build`] to fail. Addresses and ports are checked as by the
+    /// named setters, such as [`EnrBuilder::ip6`] and [`EnrBuilder::udp`].
+    pub fn field<F: EnrField>(&mut self, value: &F) -> &mut Self {
+        let value = value.encode();
+        let checked = validate_raw_rlp(&value, true)
+            .and_then(|()| field::validate(F::KEY, &value).map_err(EnrError::InvalidField));
+        if let Err(error) = checked {
+            self.reject(error);
+            return self;
+        }
+        if F::KEY == Ip6::KEY {
+            if let Ok(Ip6(ip)) = Ip6::decode(&value) {
+                return self.ip6(ip);
+            }
+        }
+        if field::PORT_KEYS.contains(&F::KEY) {
+            if let Ok(port) = rlp::decode::<u16>(&value) {
+                return self.add_port(F::KEY, port);
+            }
+        }
+        self.add_typed(F::KEY, value)
+    }
+
+    /// Adds a port field, which a later [`EnrBuilder::add_value`] of the same key may not
+    /// replace. Like the addresses, a port set again replaces the previous one.
+    fn add_port(&mut self, key: &str, port: u16) -> &mut Self {
+        if port == 0 {
+            self.reject(EnrError::InvalidPort(key.into()));
+            return self;
+        }
+        self.add_typed(key, rlp::encode(&port))
+    }
+
+    /// Adds the RLP-encoded value of a field set through a typed method, replacing any previous
+    /// value.
+    pub(crate) fn add_typed(&mut self, key: &str, value: impl Into<Bytes>) -> &mut Self {
+        self.added.insert(key.into());
+        self.content.insert(key.into(), value.into());
+        self
+    }
+
+    /// Removes a field set through a typed method.
+    #[cfg(feature = "eth")]
+    pub(crate) fn remove_typed(&mut self, key: &str) -> &mut Self {
+        self.added.remove(key);
+        self.content.remove(key);
         self
     }
 
@@ -103,44 +353,81 @@ impl<K: EnrKey> EnrBuilder<K> {
         stream.append(&self.seq);
         for (k, v) in &self.content {
             stream.append(k);
-            stream.append(v);
-        }
-        stream.drain()
-    }
-
-    /// Signs record based on the identity scheme. Currently only "v4" is supported.
-    fn signature(&self, key: &K) -> Result<Vec<u8>, EnrError> {
-        match self.id.as_str() {
-            "v4" => key
-                .sign_v4(&self.rlp_content())
-                .map_err(|_| EnrError::SigningError),
-            // unsupported identity schemes
-            _ => Err(EnrError::SigningError),
+            // values are stored RLP-encoded
+            stream.append_raw(v, 1);
         }
+        stream.out().into()
     }
 
-    /// Adds a public key to the ENR builder.
-    fn add_public_key(&mut self, key: &K::PublicKey) {
-        self.add_value(key.enr_key(), key.encode());
+    /// Signs record based on the identity scheme.
+    fn signature(&self, scheme: &dyn IdentityScheme<K>, key: &K) -> Result<Vec<u8>, EnrError> {
+        scheme
+            .sign(key, &self.rlp_content())
+            .map_err(|_| EnrError::SigningError)
     }
 
     /// Constructs an ENR from the `EnrBuilder`.
     ///
     /// # Errors
-    /// Fails if the identity scheme is not supported, or the record size exceeds `MAX_ENR_SIZE`.
+    /// Fails if the identity scheme is not supported, an address was rejected, see
+    /// [`EnrBuilder::ip6`] and [`EnrBuilder::with_validation`], an IPv6 port lacks its address,
+    /// see [`EnrBuilder::allow_orphan_ports`], a port is 0, a key was added twice or is reserved,
+    /// see [`EnrBuilder::add_value`], or the record size exceeds `MAX_ENR_SIZE`.
     pub fn build(&mut self, key: &K) -> Result<Enr<K>, EnrError> {
-        // add the identity scheme to the content
-        if self.id != "v4" {
-            return Err(EnrError::UnsupportedIdentityScheme);
+        if let Some(ref error) = self.error {
+            return Err(error.clone());
         }
+        self.validate_addresses()?;
 
-        self.content
-            .insert("id".into(), self.id.as_bytes().to_vec());
+        if !self.allow_orphan_ports && !self.content.contains_key("ip6") {
+            if let Some(port) = IPV6_PORTS
+                .iter()
+                .find(|port| self.content.contains_key(**port))
+            {
+                return Err(EnrError::OrphanPort(port));
+            }
+        }
+
+        // only the v4 identity scheme is supported without a custom scheme
+        let scheme: Arc<dyn IdentityScheme<K>> = match self.scheme {
+            Some(ref scheme) => scheme.clone(),
+            None if self.id == "v4" => Arc::new(V4Scheme),
+            None => return Err(EnrError::UnsupportedIdentityScheme),
+        };
+
+        let public_key = key.public();
+        let public_key_field = public_key.enr_key();
+        // reserved fields added manually are only kept if overridden
+        let manual_id = self.added.contains("id");
+        let manual_public_key = self.added.contains(&public_key_field);
+        if !self.override_reserved {
+            if manual_id {
+                return Err(EnrError::ReservedKey("id".into()));
+            }
+            if manual_public_key {
+                return Err(EnrError::ReservedKey(public_key_field));
+            }
+        }
 
-        self.add_public_key(&key.public());
+        // add the identity scheme and public key to the content
+        if !manual_id {
+            self.content
+                .insert("id".into(), rlp::encode(&self.id.as_bytes()).into());
+        }
+        if !manual_public_key {
+            self.content
+                .insert(public_key_field, rlp::encode(&public_key.encode()).into());
+        }
         let rlp_content = self.rlp_content();
 
-        let signature = self.signature(key)?;
+        let signature = self.signature(scheme.as_ref(), key)?;
+
+        // the node id follows the public key in the record
+        let node_key = if manual_public_key {
+            K::enr_to_public(&self.content).map_err(EnrError::InvalidRlpData)?
+        } else {
+            public_key
+        };
 
         // check the size of the record
         if rlp_content.len() + signature.len() + 8 > MAX_ENR_SIZE {
@@ -149,10 +436,183 @@ impl<K: EnrKey> EnrBuilder<K> {
 
         Ok(Enr {
             seq: self.seq,
-            node_id: NodeId::from(key.public()),
+            node_id: NodeId::new(&scheme.node_id(&node_key)),
             content: self.content.clone(),
             signature,
+            scheme: Some(scheme),
             phantom: PhantomData,
         })
     }
 }
+
+/// The transport protocols a node accepts connections over, see [`Enr::from_socket`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Transport {
+    /// TCP, advertised in the `tcp` or `tcp6` field.
+    Tcp,
+    /// UDP, advertised in the `udp` or `udp6` field.
+    Udp,
+    /// Both TCP and UDP on the same port.
+    Both,
+    /// QUIC, advertised in the `quic` or `quic6` field.
+    Quic,
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// Constructs a signed record advertising a single socket at sequence number 1. IPv4 sockets
+    /// are advertised in the `ip` field and the IPv4 port fields, IPv6 sockets in the `ip6` field
+    /// and the IPv6 port fields.
+    ///
+    /// A typical node startup, advertising the configured external UDP socket:
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "ed25519")] {
+    /// use enr::{CombinedKey, Enr, Transport};
+    /// use core::net::SocketAddr;
+    ///
+    /// let key = CombinedKey::generate_secp256k1();
+    /// let external: SocketAddr = "203.0.113.5:9000".parse().unwrap();
+    ///
+    /// let enr = Enr::from_socket(external, Transport::Udp, &key).unwrap();
+    /// assert_eq!(enr.udp_socket(), Some(external));
+    ///
+    /// // broadcast `enr.to_base64()` to peers
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::Ipv4MappedAddress`] for an IPv4-mapped IPv6 address, with
+    /// [`EnrError::InvalidPort`] for port 0, or if the record cannot be signed, see
+    /// [`EnrBuilder::build`].
+    pub fn from_socket(
+        addr: SocketAddr,
+        transport: Transport,
+        enr_key: &K,
+    ) -> Result<Self, EnrError> {
+        let mut builder = Self::builder();
+        let port = addr.port();
+        let suffix = match addr.ip() {
+            IpAddr::V4(ip) => {
+                builder.ip4(ip);
+                ""
+            }
+            IpAddr::V6(ip) => {
+                builder.ip6(ip);
+                "6"
+            }
+        };
+        let protocols: &[&str] = match transport {
+            Transport::Tcp => &["tcp"],
+            Transport::Udp => &["udp"],
+            Transport::Both => &["tcp", "udp"],
+            Transport::Quic => &["quic"],
+        };
+        for protocol in protocols {
+            builder.add_port(&format!("{protocol}{suffix}"), port);
+        }
+        builder.build(enr_key)
+    }
+
+    /// Updates the record through a builder holding its current content, only if its sequence
+    /// number is still `expected_seq`. The builder starts at the next sequence number and the
+    /// record is replaced by the record it builds.
+    ///
+    /// Returns `false`, leaving the record untouched, if the sequence number has changed since
+    /// it was read, in which case the caller should re-read the record and retry. Combined with a
+    /// lock held only for the update, concurrent tasks cannot overwrite each other's changes:
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "libsecp256k1")] {
+    /// use enr::{secp256k1::SecretKey, Enr};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let key = SecretKey::random(&mut rand::thread_rng());
+    /// let enr = Arc::new(Mutex::new(Enr::empty(&key).unwrap()));
+    ///
+    /// let seq = enr.lock().unwrap().seq();
+    /// // ... the new port is determined without holding the lock
+    /// let updated = enr
+    ///     .lock()
+    ///     .unwrap()
+    ///     .compare_and_update(seq, |builder| { builder.tcp(30303); }, &key)
+    ///     .unwrap();
+    /// assert!(updated);
+    /// assert_eq!(enr.lock().unwrap().tcp(), Some(30303));
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::SequenceNumberTooHigh`] if the sequence number cannot be
+    /// incremented, [`EnrError::StaleSequenceNumber`] if the builder's sequence number was
+    /// lowered, [`EnrError::UnsupportedIdentityScheme`] for records of an unknown identity scheme
+    /// and otherwise if the builder fails, see [`EnrBuilder::build`]. The record is unchanged on
+    /// failure.
+    pub fn compare_and_update(
+        &mut self,
+        expected_seq: u64,
+        mutate: impl FnOnce(&mut EnrBuilder<K>),
+        key: &K,
+    ) -> Result<bool, EnrError> {
+        if self.seq != expected_seq {
+            return Ok(false);
+        }
+        let Some(ref scheme) = self.scheme else {
+            return Err(EnrError::UnsupportedIdentityScheme);
+        };
+
+        let mut builder = EnrBuilder::new(scheme.scheme_id());
+        builder.seq = self.next_seq()?;
+        builder.scheme = Some(scheme.clone());
+        builder.content = self.content.clone();
+        // orphan ports already in the record are not re-checked
+        builder.allow_orphan_ports = !builder.content.contains_key("ip6")
+            && IPV6_PORTS
+                .iter()
+                .any(|port| builder.content.contains_key(*port));
+
+        mutate(&mut builder);
+        let enr = builder.build(key)?;
+        if enr.seq <= self.seq {
+            return Err(EnrError::StaleSequenceNumber);
+        }
+        *self = enr;
+        Ok(true)
+    }
+
+    /// Re-signs the record with a new key, keeping all fields other than the public key at the
+    /// next sequence number.
+    ///
+    /// The node id is derived from the public key, so the rotated record belongs to a new node
+    /// id. If the type of the public key changes, e.g. from `secp256k1` to `ed25519`, the record
+    /// is returned with an [`EnrWarning::PublicKeyTypeChanged`].
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::SequenceNumberTooHigh`] if the sequence number cannot be
+    /// incremented, [`EnrError::UnsupportedIdentityScheme`] for records of an identity scheme
+    /// other than "v4" and otherwise if the record cannot be built, see [`EnrBuilder::build`].
+    pub fn rotate<N: EnrKey>(&self, new_key: &N) -> Result<(Enr<N>, Vec<EnrWarning>), EnrError> {
+        // custom identity schemes are bound to the key type
+        match self.scheme {
+            Some(ref scheme) if scheme.scheme_id() == "v4" => {}
+            _ => return Err(EnrError::UnsupportedIdentityScheme),
+        }
+
+        let previous_key_field = self.public_key().enr_key();
+        let mut builder = EnrBuilder::<N>::new("v4");
+        builder.seq = self.next_seq()?;
+        builder.content = self.content.clone();
+        builder.content.remove(&previous_key_field);
+        builder.allow_orphan_ports = true;
+        let enr = builder.build(new_key)?;
+
+        let new_key_field = new_key.public().enr_key();
+        let mut warnings = Vec::new();
+        if new_key_field != previous_key_field {
+            warnings.push(EnrWarning::PublicKeyTypeChanged {
+                previous: previous_key_field,
+                new: new_key_field,
+            });
+        }
+        Ok((enr, warnings))
+    }
+}
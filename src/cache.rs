@@ -0,0 +1,207 @@
+//! A bounded, least-recently-used cache of ENR records keyed by their `NodeId`.
+//!
+//! The cache only replaces a stored record with a record of a higher sequence number. Records
+//! are evicted once either the maximum number of entries or, optionally, the maximum total
+//! encoded size of the stored records is exceeded.
+
+use crate::{Enr, EnrKey, NodeId};
+use lru::LruCache;
+use std::num::NonZeroUsize;
+
+/// A least-recently-used cache of ENR records keyed by `NodeId`.
+pub struct EnrCache<K: EnrKey> {
+    /// The cached records, with their encoded sizes.
+    cache: LruCache<NodeId, (Enr<K>, usize)>,
+
+    /// The maximum sum of the encoded sizes of the cached records, if any.
+    max_bytes: Option<usize>,
+
+    /// The current sum of the encoded sizes of the cached records.
+    size_bytes: usize,
+}
+
+impl<K: EnrKey> EnrCache<K> {
+    /// Creates a cache holding at most `max_entries` records.
+    #[must_use]
+    pub fn new(max_entries: NonZeroUsize) -> Self {
+        Self {
+            cache: LruCache::new(max_entries),
+            max_bytes: None,
+            size_bytes: 0,
+        }
+    }
+
+    /// Creates a cache holding at most `max_entries` records, whose encoded sizes sum to at most
+    /// `max_bytes`.
+    #[must_use]
+    pub fn with_max_bytes(max_entries: NonZeroUsize, max_bytes: usize) -> Self {
+        Self {
+            max_bytes: Some(max_bytes),
+            ..Self::new(max_entries)
+        }
+    }
+
+    /// Inserts a record into the cache, marking it as the most recently used.
+    ///
+    /// An existing record for the same node is only replaced if the new record has a higher
+    /// sequence number. Records of an unknown identity scheme are not verified and never
+    /// inserted. Returns `true` if the record was inserted.
+    pub fn insert(&mut self, enr: Enr<K>) -> bool {
+        if enr.identity_scheme().is_none() {
+            return false;
+        }
+        let node_id = enr.node_id();
+        if let Some((existing, _)) = self.cache.peek(&node_id) {
+            if existing.seq() >= enr.seq() {
+                return false;
+            }
+        }
+        if let Some((_, replaced_size)) = self.cache.pop(&node_id) {
+            self.size_bytes -= replaced_size;
+        }
+
+        let size = enr.encode().len();
+        self.size_bytes += size;
+        if let Some((_, (_, evicted_size))) = self.cache.push(node_id, (enr, size)) {
+            self.size_bytes -= evicted_size;
+        }
+
+        // evict until the size limit is respected, always keeping the newly inserted record
+        if let Some(max_bytes) = self.max_bytes {
+            while self.size_bytes > max_bytes && self.cache.len() > 1 {
+                self.evict_oldest();
+            }
+        }
+        true
+    }
+
+    /// Returns the record of a node, marking it as the most recently used.
+    pub fn get(&mut self, node_id: &NodeId) -> Option<&Enr<K>> {
+        self.cache.get(node_id).map(|(enr, _)| enr)
+    }
+
+    /// Returns the record of a node without modifying its position in the cache.
+    #[must_use]
+    pub fn peek(&self, node_id: &NodeId) -> Option<&Enr<K>> {
+        self.cache.peek(node_id).map(|(enr, _)| enr)
+    }
+
+    /// Removes and returns the least recently used record.
+    pub fn evict_oldest(&mut self) -> Option<Enr<K>> {
+        let (_, (evicted, size)) = self.cache.pop_lru()?;
+        self.size_bytes -= size;
+        Some(evicted)
+    }
+
+    /// The number of cached records.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Returns `true` if the cache holds no records.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// The sum of the encoded sizes of the cached records.
+    #[must_use]
+    pub const fn size_bytes(&self) -> usize {
+        self.size_bytes
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "libsecp256k1")]
+mod tests {
+    use super::*;
+    use crate::{secp256k1, EnrBuilder};
+
+    type DefaultEnr = Enr<secp256k1::SecretKey>;
+
+    fn random_enr() -> (secp256k1::SecretKey, DefaultEnr) {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let enr = EnrBuilder::new("v4").build(&key).unwrap();
+        (key, enr)
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_replaces_only_newer_records() {
+        let mut cache = EnrCache::new(NonZeroUsize::new(2).unwrap());
+        let (key, mut enr) = random_enr();
+        let old = enr.clone();
+        enr.set_udp(30303, &key).unwrap();
+
+        assert!(cache.insert(enr.clone()));
+        assert!(!cache.insert(old));
+        assert!(!cache.insert(enr.clone()));
+        assert_eq!(cache.get(&enr.node_id()), Some(&enr));
+        assert_eq!(cache.size_bytes(), enr.encode().len());
+
+        enr.set_udp(30304, &key).unwrap();
+        assert!(cache.insert(enr.clone()));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.peek(&enr.node_id()).unwrap().udp(), Some(30304));
+        assert_eq!(cache.size_bytes(), enr.encode().len());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_evicts_least_recently_used() {
+        let mut cache = EnrCache::new(NonZeroUsize::new(2).unwrap());
+        let (_, a) = random_enr();
+        let (_, b) = random_enr();
+        let (_, c) = random_enr();
+
+        cache.insert(a.clone());
+        cache.insert(b.clone());
+        // touch `a` so that `b` is the oldest
+        cache.get(&a.node_id());
+        cache.insert(c.clone());
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.peek(&b.node_id()).is_none());
+        assert_eq!(cache.evict_oldest(), Some(a));
+        assert_eq!(cache.evict_oldest(), Some(c));
+        assert!(cache.is_empty());
+        assert_eq!(cache.size_bytes(), 0);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_evicts_on_byte_limit() {
+        let (_, a) = random_enr();
+        let (_, b) = random_enr();
+        let record_size = a.encode().len();
+        let mut cache =
+            EnrCache::with_max_bytes(NonZeroUsize::new(10).unwrap(), record_size * 3 / 2);
+
+        cache.insert(a.clone());
+        cache.insert(b.clone());
+        assert_eq!(cache.len(), 1);
+        assert!(cache.peek(&a.node_id()).is_none());
+        assert_eq!(cache.peek(&b.node_id()), Some(&b));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_refuses_unknown_schemes() {
+        let mut cache = EnrCache::new(NonZeroUsize::new(2).unwrap());
+        let (_, enr) = random_enr();
+        assert!(cache.insert(enr.clone()));
+
+        // a newer record of the same node id, whose signature is not verified
+        let forged = DefaultEnr::decode_with_options(
+            &crate::forge_unknown_scheme(&enr).encode(),
+            crate::EnrDecodeOptions::new().allow_unknown_schemes(true),
+        )
+        .unwrap();
+        assert_eq!(forged.node_id(), enr.node_id());
+        assert!(forged.seq() > enr.seq());
+        assert!(!cache.insert(forged));
+        assert_eq!(cache.peek(&enr.node_id()), Some(&enr));
+        assert_eq!(cache.size_bytes(), enr.encode().len());
+    }
+}
@@ -0,0 +1,231 @@
+//! The `client` field, identifying the software a node runs.
+//!
+//! The value is an RLP list of the client name, the client version and an optional build
+//! description, all UTF-8 strings. Lists with further trailing items are read, ignoring the
+//! extra items, but never written.
+
+use crate::{Enr, EnrBuilder, EnrError, EnrField, EnrKey};
+use alloc::{string::String, vec::Vec};
+use rlp::{DecoderError, Rlp, RlpStream};
+
+/// The decoded `client` field of a record.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClientInfo {
+    /// The name of the client, e.g. `"geth"`.
+    pub name: String,
+    /// The version of the client, e.g. `"1.14.8"`.
+    pub version: String,
+    /// An optional build description, such as a commit hash or target.
+    pub build: Option<String>,
+}
+
+impl rlp::Encodable for ClientInfo {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(if self.build.is_some() { 3 } else { 2 });
+        s.append(&self.name);
+        s.append(&self.version);
+        if let Some(build) = &self.build {
+            s.append(build);
+        }
+    }
+}
+
+impl rlp::Decodable for ClientInfo {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        if !rlp.is_list() {
+            return Err(DecoderError::RlpExpectedToBeList);
+        }
+        if rlp.item_count()? < 2 {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+        let build = if rlp.item_count()? > 2 {
+            Some(rlp.val_at(2)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            name: rlp.val_at(0)?,
+            version: rlp.val_at(1)?,
+            build,
+        })
+    }
+}
+
+impl EnrField for ClientInfo {
+    const KEY: &'static str = "client";
+    type Error = DecoderError;
+
+    fn encode(&self) -> Vec<u8> {
+        rlp::encode(self).into()
+    }
+
+    fn decode(raw: &[u8]) -> Result<Self, Self::Error> {
+        Rlp::new(raw).as_val()
+    }
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// The `client` field of the record.
+    ///
+    /// Returns `None` if the field is not set, and an error if it is not a list of at least two
+    /// UTF-8 strings. Items after the build description are ignored.
+    #[must_use]
+    pub fn client_info(&self) -> Option<Result<ClientInfo, DecoderError>> {
+        self.get_field()
+    }
+
+    /// Sets the `client` field. Returns the previous value, if any was well-formed.
+    pub fn set_client_info(
+        &mut self,
+        info: &ClientInfo,
+        key: &K,
+    ) -> Result<Option<ClientInfo>, EnrError> {
+        self.set_field(info, key)
+    }
+}
+
+impl<K: EnrKey> EnrBuilder<K> {
+    /// Adds a `client` field to the `ENRBuilder`.
+    pub fn client_info(&mut self, info: &ClientInfo) -> &mut Self {
+        self.field(info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::MockSigner;
+
+    fn info(name: &str, version: &str, build: Option<&str>) -> ClientInfo {
+        ClientInfo {
+            name: name.into(),
+            version: version.into(),
+            build: build.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn test_client_info() {
+        let key = MockSigner(1);
+        let geth = info("geth", "1.14.8", Some("linux-amd64"));
+        let mut enr = EnrBuilder::new("v4")
+            .client_info(&geth)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.client_info(), Some(Ok(geth.clone())));
+        assert_eq!(
+            enr.get_raw_rlp("client"),
+            Some(&hex::decode("d8846765746886312e31342e388b6c696e75782d616d643634").unwrap()[..])
+        );
+
+        let reth = info("reth", "1.0.6", None);
+        assert_eq!(enr.set_client_info(&reth, &key).unwrap(), Some(geth));
+        assert_eq!(enr.client_info(), Some(Ok(reth)));
+        assert_eq!(
+            enr.get_raw_rlp("client"),
+            Some(&hex::decode("cb847265746885312e302e36").unwrap()[..])
+        );
+        assert!(enr.verify());
+
+        let enr = EnrBuilder::new("v4").build(&key).unwrap();
+        assert_eq!(enr.client_info(), None);
+    }
+
+    #[test]
+    fn test_client_info_encodings() {
+        // Hand-made encodings of the shapes a crawler may meet, not taken from live records
+        let key = MockSigner(1);
+        let cases: &[(&str, Option<Result<ClientInfo, DecoderError>>)] = &[
+            // ["Nethermind", "v1.28.0"]
+            (
+                "d38a4e65746865726d696e648776312e32382e30",
+                Some(Ok(info("Nethermind", "v1.28.0", None))),
+            ),
+            // ["lighthouse", "v5.3.0", "x86_64-linux", "extra", ["nested"]]
+            (
+                "ed8a6c69676874686f7573658676352e332e308c7838365f36342d6c696e7578856578747261c7866e\
+                 6573746564",
+                Some(Ok(info("lighthouse", "v5.3.0", Some("x86_64-linux")))),
+            ),
+            // ["geth"]
+            (
+                "c58467657468",
+                Some(Err(DecoderError::RlpIncorrectListLen)),
+            ),
+            // "geth"
+            ("8467657468", Some(Err(DecoderError::RlpExpectedToBeList))),
+            // ["geth", "1.14.8", ["linux"]]
+            (
+                "d3846765746886312e31342e38c6856c696e7578",
+                Some(Err(DecoderError::RlpExpectedToBeData)),
+            ),
+            // ["geth", 0xff]
+            (
+                "c7846765746881ff",
+                Some(Err(DecoderError::RlpExpectedToBeData)),
+            ),
+        ];
+        for (raw, expected) in cases {
+            let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+            enr.insert_raw_rlp_list("client", hex::decode(raw).unwrap(), &key)
+                .unwrap();
+            assert_eq!(&enr.client_info(), expected, "{raw}");
+        }
+
+        // extra items are dropped when the field is written back
+        let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+        enr.insert_raw_rlp_list("client", hex::decode(cases[1].0).unwrap(), &key)
+            .unwrap();
+        let read = enr.client_info().unwrap().unwrap();
+        enr.set_client_info(&read, &key).unwrap();
+        assert_eq!(
+            Rlp::new(enr.get_raw_rlp("client").unwrap()).item_count(),
+            Ok(3)
+        );
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "libsecp256k1")]
+mod mainnet_tests {
+    use super::*;
+    use crate::secp256k1;
+
+    type DefaultEnr = Enr<secp256k1::SecretKey>;
+
+    /// Mainnet boot nodes of Lighthouse, Teku, Prysm and Nimbus, as published by each client.
+    /// None of them carries a `client` field.
+    const BOOT_ENRS: [&str; 4] = [
+        "enr:-Le4QPUXJS2BTORXxyx2Ia-9ae4YqA_JWX3ssj4E_J-3z1A-HmFGrU8BpvpqhNabayXeOZ2Nq_sbeDgtzMJpLLnXFgAChGV0aDKQtTA_KgEAAAAAIgEAAAAAAIJpZIJ2NIJpcISsaa0Zg2lwNpAkAIkHAAAAAPA8kv_-awoTiXNlY3AyNTZrMaEDHAD2JKYevx89W0CcFJFiskdcEzkH_Wdv9iW42qLK79ODdWRwgiMohHVkcDaCI4I",
+        "enr:-KG4QNTx85fjxABbSq_Rta9wy56nQ1fHK0PewJbGjLm1M4bMGx5-3Qq4ZX2-iFJ0pys_O90sVXNNOxp2E7afBsGsBrgDhGV0aDKQu6TalgMAAAD__________4JpZIJ2NIJpcIQEnfA2iXNlY3AyNTZrMaECGXWQ-rQ2KZKRH1aOW4IlPDBkY4XDphxg9pxKytFCkayDdGNwgiMog3VkcIIjKA",
+        "enr:-Ku4QImhMc1z8yCiNJ1TyUxdcfNucje3BGwEHzodEZUan8PherEo4sF7pPHPSIB1NNuSg5fZy7qFsjmUKs2ea1Whi0EBh2F0dG5ldHOIAAAAAAAAAACEZXRoMpD1pf1CAAAAAP__________gmlkgnY0gmlwhBLf22SJc2VjcDI1NmsxoQOVphkDqal4QzPMksc5wnpuC3gvSC8AfbFOnZY_On34wIN1ZHCCIyg",
+        "enr:-LK4QA8FfhaAjlb_BXsXxSfiysR7R52Nhi9JBt4F8SPssu8hdE1BXQQEtVDC3qStCW60LSO7hEsVHv5zm8_6Vnjhcn0Bh2F0dG5ldHOIAAAAAAAAAACEZXRoMpC1MD8qAAAAAP__________gmlkgnY0gmlwhAN4aBKJc2VjcDI1NmsxoQJerDhsJ-KxZ8sHySMOCmTO6sHM3iCFQ6VMvLTe948MyYN0Y3CCI4yDdWRwgiOM",
+    ];
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_boot_nodes_without_client() {
+        let key = secp256k1::SecretKey::parse(&[0x42; 32]).unwrap();
+        for text in BOOT_ENRS {
+            let mut enr: DefaultEnr = text.parse().unwrap();
+            assert!(enr.verify(), "{}", text);
+            assert_eq!(enr.client_info(), None, "{text}");
+
+            // the field can be added to a live record without disturbing the others
+            let keys: Vec<String> = enr.iter().map(|(k, _)| k.clone()).collect();
+            let info = ClientInfo {
+                name: "lighthouse".into(),
+                version: "v5.3.0".into(),
+                build: None,
+            };
+            enr.set_client_info(&info, &key).unwrap();
+            assert_eq!(enr.client_info(), Some(Ok(info)));
+            assert!(enr.verify());
+            let mut with_client: Vec<String> = enr.iter().map(|(k, _)| k.clone()).collect();
+            with_client.retain(|k| k != "client" && k != "secp256k1");
+            let mut expected = keys;
+            expected.retain(|k| k != "secp256k1");
+            assert_eq!(with_client, expected);
+        }
+    }
+}
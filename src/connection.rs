@@ -0,0 +1,152 @@
+//! Connection strings of records.
+//!
+//! A connection string, `enr://192.0.2.1:30303/-IS4Q...`, gives the address to reach a node in
+//! its authority for readability, and carries the complete record, in unpadded URL-safe base64,
+//! in its path. The format is not standardised, it is meant for configuration files and
+//! documentation.
+
+use crate::{Enr, EnrError, EnrKey};
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::net::SocketAddr;
+
+/// The scheme of a connection string.
+const SCHEME: &str = "enr://";
+
+impl<K: EnrKey> Enr<K> {
+    /// The connection string of the record, see the [`connection`](self) module. The authority is
+    /// the UDP socket of the record, as given by [`Enr::udp_socket`], falling back to its TCP
+    /// socket. It is empty, as in `enr:///-IS4Q...`, if the record has neither.
+    #[must_use]
+    pub fn to_connection_string(&self) -> String {
+        let authority = self
+            .udp_socket()
+            .or_else(|| self.tcp_socket())
+            .map(|socket| socket.to_string())
+            .unwrap_or_default();
+        let encoded = base64::encode_config(self.encode(), base64::URL_SAFE_NO_PAD);
+        format!("{SCHEME}{authority}/{encoded}")
+    }
+
+    /// Decodes a connection string produced by [`Enr::to_connection_string`], verifying the
+    /// signature of the record.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidConnectionString`] if the string is malformed or its
+    /// authority is not a UDP or TCP socket of the record, [`EnrError::InvalidBase64`] if the path
+    /// is not unpadded URL-safe base64 and [`EnrError::InvalidRlpData`] if the record is invalid,
+    /// including a record of an unknown identity scheme.
+    pub fn from_connection_string(text: &str) -> Result<Self, EnrError> {
+        let (authority, encoded) = text
+            .trim()
+            .strip_prefix(SCHEME)
+            .and_then(|rest| rest.split_once('/'))
+            .ok_or(EnrError::InvalidConnectionString(
+                "Expected enr://<ip>:<port>/<base64>",
+            ))?;
+        let bytes = base64::decode_config(encoded, base64::URL_SAFE_NO_PAD)
+            .map_err(|_| EnrError::InvalidBase64)?;
+        let enr = Self::decode_bytes(&bytes.into()).map_err(EnrError::InvalidRlpData)?;
+        if !authority.is_empty() {
+            let socket: SocketAddr = authority
+                .parse()
+                .map_err(|_| EnrError::InvalidConnectionString("Invalid socket address"))?;
+            let mut sockets = enr
+                .udp_sockets()
+                .chain(enr.tcp4_socket().map(SocketAddr::V4))
+                .chain(enr.tcp6_socket().map(SocketAddr::V6));
+            if !sockets.any(|advertised| advertised == socket) {
+                return Err(EnrError::InvalidConnectionString(
+                    "The address is not a socket of the record",
+                ));
+            }
+        }
+        Ok(enr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder};
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_connection_string() {
+        let enr = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::new(192, 0, 2, 1))
+            .udp(30303)
+            .tcp(30304)
+            .build(&MockSigner(1))
+            .unwrap();
+        let encoded = enr.to_base64();
+        let text = enr.to_connection_string();
+        assert_eq!(
+            text,
+            format!("enr://192.0.2.1:30303/{}", &encoded["enr:".len()..])
+        );
+        assert_eq!(Enr::from_connection_string(&text).unwrap(), enr);
+
+        // the TCP socket and an empty authority are accepted too
+        let tcp = text.replace(":30303/", ":30304/");
+        assert_eq!(Enr::from_connection_string(&tcp).unwrap(), enr);
+        let anonymous = format!("enr:///{}", &encoded["enr:".len()..]);
+        assert_eq!(Enr::from_connection_string(&anonymous).unwrap(), enr);
+
+        let enr = EnrBuilder::new("v4")
+            .ip6(Ipv6Addr::LOCALHOST)
+            .tcp6(30303)
+            .build(&MockSigner(1))
+            .unwrap();
+        assert!(enr.to_connection_string().starts_with("enr://[::1]:30303/"));
+        assert_eq!(
+            Enr::from_connection_string(&enr.to_connection_string()).unwrap(),
+            enr
+        );
+
+        let enr = EnrBuilder::new("v4").build(&MockSigner(1)).unwrap();
+        assert!(enr.to_connection_string().starts_with("enr:///"));
+    }
+
+    #[test]
+    fn test_connection_string_errors() {
+        let enr = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::new(192, 0, 2, 1))
+            .udp(30303)
+            .build(&MockSigner(1))
+            .unwrap();
+        let text = enr.to_connection_string();
+        let decode = |text: &str| Enr::<MockSigner>::from_connection_string(text).unwrap_err();
+
+        assert!(matches!(
+            decode(&enr.to_base64()),
+            EnrError::InvalidConnectionString(_)
+        ));
+        assert!(matches!(
+            decode("enr://192.0.2.1:30303"),
+            EnrError::InvalidConnectionString(_)
+        ));
+        assert!(matches!(
+            decode(&text.replace("30303", "9000")),
+            EnrError::InvalidConnectionString(_)
+        ));
+        assert!(matches!(
+            decode(&text.replace("192.0.2.1", "example.com")),
+            EnrError::InvalidConnectionString(_)
+        ));
+        assert!(matches!(
+            decode(&format!("{text}=")),
+            EnrError::InvalidBase64
+        ));
+        assert!(matches!(
+            decode("enr://192.0.2.1:30303/AAAA"),
+            EnrError::InvalidRlpData(_)
+        ));
+        assert!(matches!(
+            decode(&crate::forge_unknown_scheme(&enr).to_connection_string()),
+            EnrError::InvalidRlpData(rlp::DecoderError::Custom("Unsupported identity scheme"))
+        ));
+    }
+}
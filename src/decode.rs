@@ -0,0 +1,243 @@
+//! Configurable decoding of ENR records.
+//!
+//! The default decoder accepts any verified record it can make sense of. [`EnrDecodeOptions`]
+//! allows compliance test suites to reject every deviation from EIP-778, and applications to
+//! bound the size of the records they accept or to keep records of unknown identity schemes.
+
+use crate::{Enr, EnrError, EnrKey, MAX_ENR_SIZE};
+use bytes::Bytes;
+use rlp::{DecoderError, Rlp};
+
+/// Options for decoding ENR records with [`Enr::decode_with_options`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnrDecodeOptions {
+    /// Rejects any deviation from EIP-778.
+    strict: bool,
+
+    /// The maximum size of the RLP-encoded record in bytes.
+    max_record_size: usize,
+
+    /// Accepts records of identity schemes other than "v4", without verifying them.
+    allow_unknown_schemes: bool,
+}
+
+impl EnrDecodeOptions {
+    /// Constructs the default, permissive, decode options. Verified records of up to 300 bytes
+    /// are accepted, records of unknown identity schemes are refused.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            strict: false,
+            max_record_size: MAX_ENR_SIZE,
+            allow_unknown_schemes: false,
+        }
+    }
+
+    /// Sets strict mode. A strictly decoded record must be canonically RLP-encoded, without
+    /// trailing bytes, no larger than the 300 bytes permitted by EIP-778 and of a known identity
+    /// scheme, regardless of the other options. The values of its well-known keys must be
+    /// well-formed, see [`Enr::validate_fields`].
+    pub const fn strict(&mut self, strict: bool) -> &mut Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets the maximum size of the RLP-encoded record in bytes.
+    pub const fn max_record_size(&mut self, max_record_size: usize) -> &mut Self {
+        self.max_record_size = max_record_size;
+        self
+    }
+
+    /// Sets whether records of identity schemes other than "v4" are accepted. Such records are
+    /// not verified, their `NodeId` is derived from the public key they claim and they are
+    /// refused by [`crate::EnrSet`]. A custom scheme can be attached and the record verified with
+    /// [`Enr::with_identity_scheme`]. Ignored in strict mode.
+    pub const fn allow_unknown_schemes(&mut self, allow_unknown_schemes: bool) -> &mut Self {
+        self.allow_unknown_schemes = allow_unknown_schemes;
+        self
+    }
+}
+
+impl Default for EnrDecodeOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// Decodes an RLP-encoded record according to the given options.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::ExceedsMaxSize`] if the record is too large,
+    /// [`EnrError::UnsupportedIdentityScheme`] if a record of an unknown identity scheme is
+    /// refused, [`EnrError::InvalidField`] if a well-known value is malformed in strict mode and
+    /// [`EnrError::InvalidRlpData`] if the record is malformed, not canonically encoded in strict
+    /// mode, or its signature does not verify.
+    pub fn decode_with_options(bytes: &[u8], opts: &EnrDecodeOptions) -> Result<Self, EnrError> {
+        let max_record_size = if opts.strict {
+            opts.max_record_size.min(MAX_ENR_SIZE)
+        } else {
+            opts.max_record_size
+        };
+        if bytes.len() > max_record_size {
+            return Err(EnrError::ExceedsMaxSize);
+        }
+
+        let buffer = Bytes::copy_from_slice(bytes);
+        let enr = Self::decode_rlp(&Rlp::new(&buffer), &buffer, true)
+            .map_err(EnrError::InvalidRlpData)?;
+
+        if (opts.strict || !opts.allow_unknown_schemes) && enr.identity_scheme().is_none() {
+            return Err(EnrError::UnsupportedIdentityScheme);
+        }
+        if opts.strict {
+            enr.validate_fields().map_err(EnrError::InvalidField)?;
+        }
+        // any non-canonical encoding, such as padded integers, non-minimal lengths or trailing
+        // bytes, differs from the re-encoded record
+        if opts.strict && enr.encode() != bytes {
+            return Err(EnrError::InvalidRlpData(DecoderError::Custom(
+                "Non-canonical encoding",
+            )));
+        }
+        Ok(enr)
+    }
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// Decodes an RLP-encoded record, rejecting any deviation from EIP-778. Equivalent to
+    /// [`Enr::decode_with_options`] in strict mode.
+    pub fn decode_strict(bytes: &[u8]) -> Result<Self, EnrError> {
+        Self::decode_with_options(bytes, EnrDecodeOptions::new().strict(true))
+    }
+}
+
+/// Decodes an RLP-encoded record of either key type according to the given options.
+///
+/// See [`Enr::decode_with_options`].
+#[cfg(feature = "ed25519")]
+pub fn decode_with_options(
+    bytes: &[u8],
+    opts: &EnrDecodeOptions,
+) -> Result<Enr<crate::CombinedKey>, EnrError> {
+    Enr::decode_with_options(bytes, opts)
+}
+
+#[cfg(test)]
+#[cfg(feature = "ed25519")]
+mod tests {
+    use super::*;
+    use crate::{CombinedKey, EnrBuilder, EnrKey, IdentityScheme, SigningError, V4Scheme};
+    use rlp::RlpStream;
+
+    /// The "v4" scheme under a different name.
+    struct RenamedScheme;
+
+    impl IdentityScheme<CombinedKey> for RenamedScheme {
+        fn scheme_id(&self) -> &'static str {
+            "renamed"
+        }
+
+        fn sign(&self, key: &CombinedKey, content: &[u8]) -> Result<Vec<u8>, SigningError> {
+            V4Scheme.sign(key, content)
+        }
+
+        fn verify(
+            &self,
+            public_key: &crate::CombinedPublicKey,
+            content: &[u8],
+            sig: &[u8],
+        ) -> bool {
+            IdentityScheme::<CombinedKey>::verify(&V4Scheme, public_key, content, sig)
+        }
+
+        fn node_id(&self, public_key: &crate::CombinedPublicKey) -> [u8; 32] {
+            IdentityScheme::<CombinedKey>::node_id(&V4Scheme, public_key)
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_decode_with_options() {
+        let key = CombinedKey::generate_secp256k1();
+        let enr = EnrBuilder::new("v4").udp(30303).build(&key).unwrap();
+        let bytes = enr.encode();
+
+        let permissive = EnrDecodeOptions::default();
+        let mut strict = EnrDecodeOptions::new();
+        strict.strict(true);
+        assert_eq!(decode_with_options(&bytes, &permissive).unwrap(), enr);
+        assert_eq!(decode_with_options(&bytes, &strict).unwrap(), enr);
+
+        // a zero-padded sequence number
+        let mut stream = RlpStream::new_list(2 + enr.content.len() * 2);
+        stream.append(&enr.signature());
+        stream.append(&vec![0_u8, 1]);
+        for (k, v) in &enr.content {
+            stream.append(k);
+            stream.append_raw(v, 1);
+        }
+        let padded = stream.out();
+        assert_eq!(decode_with_options(&padded, &permissive).unwrap(), enr);
+        assert!(matches!(
+            decode_with_options(&padded, &strict),
+            Err(EnrError::InvalidRlpData(_))
+        ));
+
+        // the size limit
+        assert!(matches!(
+            decode_with_options(
+                &bytes,
+                EnrDecodeOptions::new().max_record_size(bytes.len() - 1)
+            ),
+            Err(EnrError::ExceedsMaxSize)
+        ));
+        // a validly signed record exceeding the EIP-778 limit
+        let mut content = enr.content.clone();
+        content.insert("data".into(), rlp::encode(&vec![0_u8; 300]).into());
+        let encode = |signature: Option<&[u8]>| {
+            let mut stream =
+                RlpStream::new_list(content.len() * 2 + 1 + usize::from(signature.is_some()));
+            if let Some(signature) = signature {
+                stream.append(&signature);
+            }
+            stream.append(&1_u64);
+            for (k, v) in &content {
+                stream.append(k);
+                stream.append_raw(v, 1);
+            }
+            stream.out()
+        };
+        let signature = key.sign_v4(&encode(None)).unwrap();
+        let large = encode(Some(&signature));
+        let mut lenient = EnrDecodeOptions::new();
+        lenient.max_record_size(1000);
+        assert!(decode_with_options(&large, &lenient).is_ok());
+        assert!(matches!(
+            decode_with_options(&large, lenient.strict(true)),
+            Err(EnrError::ExceedsMaxSize)
+        ));
+
+        // unknown identity schemes
+        let renamed = EnrBuilder::new("v4")
+            .identity_scheme(RenamedScheme)
+            .build(&key)
+            .unwrap()
+            .encode();
+        assert!(matches!(
+            decode_with_options(&renamed, &permissive),
+            Err(EnrError::UnsupportedIdentityScheme)
+        ));
+        let decoded = decode_with_options(
+            &renamed,
+            EnrDecodeOptions::new().allow_unknown_schemes(true),
+        )
+        .unwrap();
+        assert_eq!(decoded.id().as_deref(), Some("renamed"));
+        assert!(decoded.identity_scheme().is_none());
+        assert!(matches!(
+            decode_with_options(&renamed, &strict),
+            Err(EnrError::UnsupportedIdentityScheme)
+        ));
+    }
+}
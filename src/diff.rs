@@ -0,0 +1,178 @@
+//! A comparison between two ENR records. This lists the fields that have been added, removed or
+//! changed between a stored record and a newer one, for example one just received from a peer.
+
+use crate::{Enr, EnrKey};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// The difference between two ENR records.
+///
+/// All values are the raw, RLP-encoded values stored in the records.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnrDiff {
+    /// The sequence number of the original record.
+    pub old_seq: u64,
+    /// The sequence number of the updated record.
+    pub new_seq: u64,
+    /// Whether the public key (and therefore the `NodeId`) differs between the records.
+    pub public_key_changed: bool,
+    /// Whether the signature of the updated record verifies.
+    pub new_signature_valid: bool,
+    /// Keys only present in the updated record, with their values.
+    pub added: Vec<(String, Vec<u8>)>,
+    /// Keys only present in the original record, with their values.
+    pub removed: Vec<(String, Vec<u8>)>,
+    /// Keys present in both records with differing values, as `(key, old, new)`.
+    pub changed: Vec<(String, Vec<u8>, Vec<u8>)>,
+}
+
+impl EnrDiff {
+    /// The change in sequence number from the original to the updated record.
+    #[must_use]
+    pub fn seq_delta(&self) -> i128 {
+        i128::from(self.new_seq) - i128::from(self.old_seq)
+    }
+
+    /// Returns `true` if no fields differ between the records. The sequence numbers may still
+    /// differ.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Returns `true` if the updated record could be a legitimate update of the original, that is,
+    /// it belongs to the same node, has a strictly greater sequence number and its signature
+    /// verifies.
+    #[must_use]
+    pub const fn is_plausible_update(&self) -> bool {
+        !self.public_key_changed && self.new_seq > self.old_seq && self.new_signature_valid
+    }
+}
+
+/// Displays a compact, single line summary of the form `seq=1->2 +key=new -key=old key=old->new`.
+/// Values are hex encoded.
+impl fmt::Display for EnrDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "seq={}->{}", self.old_seq, self.new_seq)?;
+        if self.public_key_changed {
+            write!(f, " public-key-changed")?;
+        }
+        if !self.new_signature_valid {
+            write!(f, " invalid-signature")?;
+        }
+        for (key, value) in &self.added {
+            write!(f, " +{}=0x{}", key, hex::encode(value))?;
+        }
+        for (key, value) in &self.removed {
+            write!(f, " -{}=0x{}", key, hex::encode(value))?;
+        }
+        for (key, old, new) in &self.changed {
+            write!(f, " {}=0x{}->0x{}", key, hex::encode(old), hex::encode(new))?;
+        }
+        Ok(())
+    }
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// Lists the fields that differ between this record and `other`, treating `other` as the
+    /// updated record.
+    ///
+    /// Only the stored content is compared, neither record is required to verify. See
+    /// [`EnrDiff::is_plausible_update`] to also take the sequence number and signature into
+    /// account.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> EnrDiff {
+        let mut diff = EnrDiff {
+            old_seq: self.seq,
+            new_seq: other.seq,
+            public_key_changed: self.node_id != other.node_id,
+            new_signature_valid: other.verify(),
+            ..EnrDiff::default()
+        };
+
+        for (key, old) in &self.content {
+            match other.content.get(key) {
+                None => diff.removed.push((key.clone(), old.to_vec())),
+                Some(new) if new != old => {
+                    diff.changed.push((key.clone(), old.to_vec(), new.to_vec()));
+                }
+                Some(_) => {}
+            }
+        }
+        for (key, new) in &other.content {
+            if !self.content.contains_key(key) {
+                diff.added.push((key.clone(), new.to_vec()));
+            }
+        }
+
+        diff
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "libsecp256k1")]
+mod tests {
+    use crate::{secp256k1, EnrBuilder};
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_diff() {
+        let mut rng = rand::thread_rng();
+        let key = secp256k1::SecretKey::random(&mut rng);
+
+        let old = EnrBuilder::new("v4")
+            .tcp(30303)
+            .udp(30303)
+            .build(&key)
+            .unwrap();
+        let mut new = old.clone();
+        new.set_tcp(30304, &key).unwrap();
+        new.remove("udp", &key).unwrap();
+        new.insert_value("custom", &1_u8, &key).unwrap();
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.seq_delta(), 3);
+        assert!(diff.is_plausible_update());
+        assert_eq!(diff.added, vec![("custom".into(), vec![1])]);
+        assert_eq!(
+            diff.removed,
+            vec![("udp".into(), rlp::encode(&vec![0x76_u8, 0x5f]).to_vec())]
+        );
+        assert_eq!(
+            diff.changed,
+            vec![(
+                "tcp".into(),
+                rlp::encode(&vec![0x76_u8, 0x5f]).to_vec(),
+                rlp::encode(&vec![0x76_u8, 0x60]).to_vec()
+            )]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "seq=1->4 +custom=0x01 -udp=0x82765f tcp=0x82765f->0x827660"
+        );
+
+        // the reverse diff
+        let diff = new.diff(&old);
+        assert_eq!(diff.seq_delta(), -3);
+        assert_eq!(diff.removed, vec![("custom".into(), vec![1])]);
+        assert!(!diff.is_plausible_update());
+
+        // identical records
+        let diff = old.diff(&old);
+        assert!(diff.is_empty());
+        assert!(!diff.is_plausible_update());
+
+        // different nodes
+        let other = EnrBuilder::new("v4")
+            .seq(5)
+            .tcp(30303)
+            .build(&secp256k1::SecretKey::random(&mut rng))
+            .unwrap();
+        let diff = old.diff(&other);
+        assert!(diff.public_key_changed);
+        assert!(!diff.is_plausible_update());
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].0, "secp256k1");
+        assert!(diff.to_string().starts_with("seq=1->5 public-key-changed "));
+    }
+}
@@ -0,0 +1,552 @@
+//! The entries of an [EIP-1459](https://eips.ethereum.org/EIPS/eip-1459) node tree, distributing
+//! records through DNS TXT records. Enabled by the `ed25519` and `libsecp256k1` features.
+//!
+//! Deprecated in favour of [`dns_tree`], which parses and verifies the same entries for records
+//! of any key type with [`dns_tree::Entry`], and builds the same trees with
+//! [`dns_tree::TreeBuilder`], which [`DnsTree::build`] delegates to.
+//!
+//! A tree is published under a domain. The root entry, stored at the domain itself, references
+//! the root of a subtree of records and the root of a subtree of links to other trees. Every
+//! other entry is stored at the subdomain named after its hash, see [`DnsEntry::hash`].
+//!
+//! The encoding follows go-ethereum's `p2p/dnsdisc` package.
+
+use crate::{
+    dns_tree::{
+        self, base32_encode, root_signed_text, sign_root, verify_root, Entry, TreeBuilder,
+        BRANCH_PREFIX, ENR_PREFIX, LINK_PREFIX,
+    },
+    secp256k1, CombinedKey, Enr, EnrError,
+};
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+pub use crate::dns_tree::MAX_BRANCH_CHILDREN;
+
+/// The root entry of a tree, `enrtree-root:v1 e=<enr-root> l=<link-root> seq=<seq> sig=<sig>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DnsRoot {
+    /// The sequence number of the tree, increased on every update.
+    pub seq: u64,
+    /// The hash of the root of the record subtree.
+    pub e_root: String,
+    /// The hash of the root of the link subtree.
+    pub l_root: String,
+    /// The 65 byte `[R || S || V]` secp256k1 signature of the root entry.
+    pub sig: Vec<u8>,
+}
+
+impl DnsRoot {
+    /// Encodes the entry as the content of a TXT record.
+    #[must_use]
+    pub fn to_txt_record(&self) -> String {
+        format!(
+            "{} sig={}",
+            self.signed_text(),
+            base64::encode_config(&self.sig, base64::URL_SAFE_NO_PAD)
+        )
+    }
+
+    /// Decodes the entry from the content of a TXT record. The signature is not verified, see
+    /// [`DnsRoot::verify`].
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidDnsEntry`] if the record is not a root entry.
+    pub fn from_txt_record(txt: &str) -> Result<Self, EnrError> {
+        match txt.parse()? {
+            Entry::<CombinedKey>::Root {
+                erecord,
+                lrecord,
+                seq,
+                sig,
+            } => Ok(Self {
+                seq,
+                e_root: erecord,
+                l_root: lrecord,
+                sig,
+            }),
+            _ => Err(EnrError::InvalidDnsEntry("Expected a root entry")),
+        }
+    }
+
+    /// Signs the entry, replacing its signature.
+    pub fn sign(&mut self, key: &secp256k1::SecretKey) {
+        self.sig = sign_root(&self.signed_text(), key);
+    }
+
+    /// Verifies the signature of the entry against the public key of the tree, as given by the
+    /// link to the tree.
+    #[must_use]
+    pub fn verify(&self, public_key: &secp256k1::PublicKey) -> bool {
+        verify_root(&self.signed_text(), &self.sig, public_key)
+    }
+
+    /// The content of the entry without the signature, which is signed.
+    fn signed_text(&self) -> String {
+        root_signed_text(&self.e_root, &self.l_root, self.seq)
+    }
+}
+
+/// A branch entry, `enrtree-branch:<h1>,<h2>,...`, listing the hashes of its children.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DnsBranch {
+    /// The hashes of the children.
+    pub hashes: Vec<String>,
+}
+
+impl DnsBranch {
+    /// Encodes the entry as the content of a TXT record.
+    #[must_use]
+    pub fn to_txt_record(&self) -> String {
+        format!("{}{}", BRANCH_PREFIX, self.hashes.join(","))
+    }
+
+    /// Decodes the entry from the content of a TXT record.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidDnsEntry`] if the record is not a branch entry.
+    pub fn from_txt_record(txt: &str) -> Result<Self, EnrError> {
+        if !txt.starts_with(BRANCH_PREFIX) {
+            return Err(EnrError::InvalidDnsEntry("Expected a branch entry"));
+        }
+        match txt.parse()? {
+            Entry::<CombinedKey>::Branch(hashes) => Ok(Self { hashes }),
+            _ => Err(EnrError::InvalidDnsEntry("Expected a branch entry")),
+        }
+    }
+}
+
+/// A leaf entry holding a record, `enr:<record>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DnsEnr(pub Enr<CombinedKey>);
+
+impl DnsEnr {
+    /// Encodes the entry as the content of a TXT record.
+    #[must_use]
+    pub fn to_txt_record(&self) -> String {
+        self.0.to_base64()
+    }
+
+    /// Decodes the entry from the content of a TXT record.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidDnsEntry`] if the record is not a valid record entry.
+    pub fn from_txt_record(txt: &str) -> Result<Self, EnrError> {
+        if !txt.starts_with(ENR_PREFIX) {
+            return Err(EnrError::InvalidDnsEntry("Expected a record entry"));
+        }
+        txt.parse()
+            .map(Self)
+            .map_err(|_| EnrError::InvalidDnsEntry("Invalid record"))
+    }
+}
+
+/// A leaf entry linking to another tree, `enrtree://<public-key>@<domain>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DnsLink {
+    /// The public key signing the linked tree.
+    pub public_key: secp256k1::PublicKey,
+    /// The domain of the linked tree.
+    pub domain: String,
+}
+
+impl DnsLink {
+    /// Encodes the entry as the content of a TXT record.
+    #[must_use]
+    pub fn to_txt_record(&self) -> String {
+        format!(
+            "{}{}@{}",
+            LINK_PREFIX,
+            base32_encode(&self.public_key.serialize_compressed()),
+            self.domain
+        )
+    }
+
+    /// Decodes the entry from the content of a TXT record.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidDnsEntry`] if the record is not a link entry.
+    pub fn from_txt_record(txt: &str) -> Result<Self, EnrError> {
+        if !txt.starts_with(LINK_PREFIX) {
+            return Err(EnrError::InvalidDnsEntry("Expected a link entry"));
+        }
+        match txt.parse()? {
+            Entry::<CombinedKey>::Link { pubkey, domain } => Ok(Self {
+                public_key: pubkey,
+                domain,
+            }),
+            _ => Err(EnrError::InvalidDnsEntry("Expected a link entry")),
+        }
+    }
+}
+
+/// An entry of a tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DnsEntry {
+    /// The root entry, stored at the domain of the tree.
+    Root(DnsRoot),
+    /// A branch entry.
+    Branch(DnsBranch),
+    /// A record.
+    Enr(DnsEnr),
+    /// A link to another tree.
+    Link(DnsLink),
+}
+
+impl DnsEntry {
+    /// Encodes the entry as the content of a TXT record.
+    #[must_use]
+    pub fn to_txt_record(&self) -> String {
+        match self {
+            Self::Root(root) => root.to_txt_record(),
+            Self::Branch(branch) => branch.to_txt_record(),
+            Self::Enr(enr) => enr.to_txt_record(),
+            Self::Link(link) => link.to_txt_record(),
+        }
+    }
+
+    /// Decodes an entry of any type from the content of a TXT record.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidDnsEntry`] if the record is not a valid entry.
+    pub fn from_txt_record(txt: &str) -> Result<Self, EnrError> {
+        Ok(match txt.parse()? {
+            Entry::Root {
+                erecord,
+                lrecord,
+                seq,
+                sig,
+            } => Self::Root(DnsRoot {
+                seq,
+                e_root: erecord,
+                l_root: lrecord,
+                sig,
+            }),
+            Entry::Branch(hashes) => Self::Branch(DnsBranch { hashes }),
+            Entry::Link { pubkey, domain } => Self::Link(DnsLink {
+                public_key: pubkey,
+                domain,
+            }),
+            Entry::Node(enr) => Self::Enr(DnsEnr(enr)),
+        })
+    }
+
+    /// The hash of the entry, naming the subdomain it is stored at, see [`dns_tree::hash`].
+    #[must_use]
+    pub fn hash(&self) -> String {
+        dns_tree::hash(&self.to_txt_record())
+    }
+}
+
+/// A complete, signed tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DnsTree {
+    /// The root entry.
+    root: DnsRoot,
+    /// The other entries, by hash.
+    entries: BTreeMap<String, DnsEntry>,
+}
+
+impl DnsTree {
+    /// Assembles a tree of records and links to other trees and signs its root.
+    ///
+    /// The tree is built by a [`TreeBuilder`]: the records are sorted by node id, a record
+    /// replacing an earlier record of the same node id, and the links by their text. Branches
+    /// have at most [`MAX_BRANCH_CHILDREN`] children, and a subtree of a single entry has no
+    /// branch.
+    #[must_use]
+    pub fn build(
+        seq: u64,
+        records: Vec<Enr<CombinedKey>>,
+        links: Vec<DnsLink>,
+        key: &secp256k1::SecretKey,
+    ) -> Self {
+        let mut builder = TreeBuilder::new();
+        let mut leaves = BTreeMap::new();
+        for enr in records {
+            builder.insert_enr(&enr);
+            insert(&mut leaves, DnsEntry::Enr(DnsEnr(enr)));
+        }
+        for link in links {
+            builder.insert_canonical_link(link.to_txt_record());
+            insert(&mut leaves, DnsEntry::Link(link));
+        }
+
+        let tree = builder.sign_tree(key, seq);
+        // the entries that are not records or links are branches
+        let entries = tree
+            .txt_records
+            .into_iter()
+            .map(|(hash, txt)| {
+                let entry = leaves.remove(&hash).unwrap_or_else(|| {
+                    DnsEntry::Branch(DnsBranch {
+                        hashes: txt[BRANCH_PREFIX.len()..]
+                            .split(',')
+                            .filter(|hash| !hash.is_empty())
+                            .map(ToString::to_string)
+                            .collect(),
+                    })
+                });
+                (hash, entry)
+            })
+            .collect();
+        let root = DnsRoot {
+            seq,
+            e_root: tree.erecord,
+            l_root: tree.lrecord,
+            sig: tree.sig,
+        };
+        Self { root, entries }
+    }
+
+    /// The root entry.
+    #[must_use]
+    pub const fn root(&self) -> &DnsRoot {
+        &self.root
+    }
+
+    /// The entry of a hash, if it is part of the tree.
+    #[must_use]
+    pub fn entry(&self, hash: &str) -> Option<&DnsEntry> {
+        self.entries.get(hash)
+    }
+
+    /// The records of the tree, ordered by node id.
+    pub fn records(&self) -> impl Iterator<Item = &Enr<CombinedKey>> {
+        let mut records = self
+            .entries
+            .values()
+            .filter_map(|entry| match entry {
+                DnsEntry::Enr(DnsEnr(enr)) => Some(enr),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        records.sort_by_key(|enr| enr.node_id());
+        records.into_iter()
+    }
+
+    /// The TXT records publishing the tree under a domain, by name. The root entry is stored at
+    /// the domain, every other entry at `<hash>.<domain>`.
+    #[must_use]
+    pub fn to_txt_records(&self, domain: &str) -> BTreeMap<String, String> {
+        let mut records = BTreeMap::new();
+        records.insert(domain.to_string(), self.root.to_txt_record());
+        for (hash, entry) in &self.entries {
+            records.insert(format!("{hash}.{domain}"), entry.to_txt_record());
+        }
+        records
+    }
+}
+
+/// Stores an entry under its hash.
+fn insert(entries: &mut BTreeMap<String, DnsEntry>, entry: DnsEntry) {
+    entries.insert(entry.hash(), entry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EnrBuilder;
+    use std::net::Ipv4Addr;
+
+    /// The example tree of EIP-1459, by subdomain.
+    const EIP_TREE: [(&str, &str); 6] = [
+        (
+            "",
+            "enrtree-root:v1 e=JWXYDBPXYWG6FX3GMDIBFA6CJ4 l=C7HRFPF3BLGF3YR4DY5KX3SMBE seq=1 sig=o908WmNp7LibOfPsr4btQwatZJ5URBr2ZAuxvK4UWHlsB9sUOTJQaGAlLPVAhM__XJesCHxLISo94z5Z2a463gA",
+        ),
+        (
+            "C7HRFPF3BLGF3YR4DY5KX3SMBE",
+            "enrtree://AM5FCQLWIZX2QFPNJAP7VUERCCRNGRHWZG3YYHIUV7BVDQ5FDPRT2@morenodes.example.org",
+        ),
+        (
+            "JWXYDBPXYWG6FX3GMDIBFA6CJ4",
+            "enrtree-branch:2XS2367YHAXJFGLZHVAWLQD4ZY,H4FHT4B454P6UXFD7JCYQ5PWDY,MHTDO6TMUBRIA2XWG5LUDACK24",
+        ),
+        (
+            "2XS2367YHAXJFGLZHVAWLQD4ZY",
+            "enr:-HW4QOFzoVLaFJnNhbgMoDXPnOvcdVuj7pDpqRvh6BRDO68aVi5ZcjB3vzQRZH2IcLBGHzo8uUN3snqmgTiE56CH3AMBgmlkgnY0iXNlY3AyNTZrMaECC2_24YYkYHEgdzxlSNKQEnHhuNAbNlMlWJxrJxbAFvA",
+        ),
+        (
+            "H4FHT4B454P6UXFD7JCYQ5PWDY",
+            "enr:-HW4QAggRauloj2SDLtIHN1XBkvhFZ1vtf1raYQp9TBW2RD5EEawDzbtSmlXUfnaHcvwOizhVYLtr7e6vw7NAf6mTuoCgmlkgnY0iXNlY3AyNTZrMaECjrXI8TLNXU0f8cthpAMxEshUyQlK-AM0PW2wfrnacNI",
+        ),
+        (
+            "MHTDO6TMUBRIA2XWG5LUDACK24",
+            "enr:-HW4QLAYqmrwllBEnzWWs7I5Ev2IAs7x_dZlbYdRdMUx5EyKHDXp7AV5CkuPGUPdvbv1_Ms1CPfhcGCvSElSosZmyoqAgmlkgnY0iXNlY3AyNTZrMaECriawHKWdDRk2xeZkrOXBQ0dfMFLHY4eENZwdufn1S1o",
+        ),
+    ];
+
+    /// The link to the example tree of EIP-1459.
+    const EIP_LINK: &str =
+        "enrtree://AKPYQIUQIL7PSIACI32J7FGZW56E5FKHEFCCOFHILBIMW3M6LWXS2@nodes.example.org";
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_eip_example() {
+        for (hash, txt) in &EIP_TREE[1..] {
+            let entry = DnsEntry::from_txt_record(txt).unwrap();
+            assert_eq!(entry.to_txt_record(), *txt);
+            assert_eq!(entry.hash(), *hash);
+        }
+
+        let root = DnsRoot::from_txt_record(EIP_TREE[0].1).unwrap();
+        assert_eq!(root.to_txt_record(), EIP_TREE[0].1);
+        assert_eq!(root.seq, 1);
+        assert_eq!(root.e_root, EIP_TREE[2].0);
+        assert_eq!(root.l_root, EIP_TREE[1].0);
+
+        let link = DnsLink::from_txt_record(EIP_LINK).unwrap();
+        assert_eq!(link.domain, "nodes.example.org");
+        assert_eq!(link.to_txt_record(), EIP_LINK);
+        assert!(root.verify(&link.public_key));
+
+        // the signature covers the content of the root
+        let mut tampered = root.clone();
+        tampered.seq = 2;
+        assert!(!tampered.verify(&link.public_key));
+        let other = DnsLink::from_txt_record(EIP_TREE[1].1).unwrap();
+        assert!(!root.verify(&other.public_key));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_invalid_entries() {
+        let invalid = [
+            "",
+            "foo",
+            "enrtree",
+            "enrtree-x=",
+            // missing signature
+            "enrtree-root:v1 e=JWXYDBPXYWG6FX3GMDIBFA6CJ4 l=C7HRFPF3BLGF3YR4DY5KX3SMBE seq=1 sig=",
+            // short hash
+            "enrtree-root:v1 e=JWXYDBPX l=C7HRFPF3BLGF3YR4DY5KX3SMBE seq=1 sig=o908WmNp7LibOfPsr4btQwatZJ5URBr2ZAuxvK4UWHlsB9sUOTJQaGAlLPVAhM__XJesCHxLISo94z5Z2a463gA",
+            "enrtree-branch:2XS2367YHAXJFGLZHVAWLQD4ZY,h4fht4b454p6uxfd7jcyq5pwdy",
+            "enrtree-branch:2XS2367YHAXJFGLZHVAWLQD4ZY,",
+            // missing public key
+            "enrtree://nodes.example.org",
+            // truncated public keys
+            "enrtree://AP62DT7WOTEQZGQZOU474PP3KMEGVTTE7A7NPRXKX3DUD57@nodes.example.org",
+            "enrtree://AP62DT7WONEQZGQZOU474PP3KMEGVTTE7A7NPRXKX3DUD57TQHGIA@nodes.example.org",
+            // padded base64
+            "enr:-HW4QLZHjM4vZXkbp-5xJoHsKSbE7W39FPC8283X-y8oHcHPTnDDlIlzL5ArvDUlHZVDPgmFASrh7cWgLOLxj4wprRkHgmlkgnY0iXNlY3AyNTZrMaEC3t2jLMhDpCDX5mbSEwDn4L3iUfyXzoO8G28XvjGRkrAg=",
+        ];
+        for txt in &invalid {
+            assert!(
+                matches!(
+                    DnsEntry::from_txt_record(txt),
+                    Err(EnrError::InvalidDnsEntry(_))
+                ),
+                "{}",
+                txt
+            );
+        }
+        assert_eq!(
+            DnsEntry::from_txt_record("enrtree-branch:").unwrap(),
+            DnsEntry::Branch(DnsBranch::default())
+        );
+    }
+
+    /// Resolves the records below a hash, as a client walking the published tree.
+    fn resolve(txt_records: &BTreeMap<String, String>, hash: &str) -> Vec<DnsEntry> {
+        let entry =
+            DnsEntry::from_txt_record(&txt_records[&format!("{hash}.nodes.example.org")]).unwrap();
+        assert_eq!(entry.hash(), hash);
+        match entry {
+            DnsEntry::Branch(branch) => {
+                assert!(branch.hashes.len() <= MAX_BRANCH_CHILDREN);
+                assert!(branch.to_txt_record().len() <= 370);
+                branch
+                    .hashes
+                    .iter()
+                    .flat_map(|hash| resolve(txt_records, hash))
+                    .collect()
+            }
+            leaf => vec![leaf],
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_build_tree() {
+        let tree_key = secp256k1::SecretKey::parse(&[0x42; 32]).unwrap();
+        let link = DnsLink::from_txt_record(EIP_LINK).unwrap();
+
+        for count in &[0, 1, 13, 14, 30, 200] {
+            let mut records = (0..*count)
+                .map(|i| {
+                    let key = if i % 2 == 0 {
+                        CombinedKey::generate_secp256k1()
+                    } else {
+                        CombinedKey::generate_ed25519()
+                    };
+                    EnrBuilder::new("v4")
+                        .ip4(Ipv4Addr::new(192, 0, 2, 1))
+                        .udp(30303)
+                        .build(&key)
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+            let tree = DnsTree::build(3, records.clone(), vec![link.clone()], &tree_key);
+            records.sort_by_key(Enr::node_id);
+            assert_eq!(tree.records().cloned().collect::<Vec<_>>(), records);
+
+            let txt_records = tree.to_txt_records("nodes.example.org");
+            let root = DnsRoot::from_txt_record(&txt_records["nodes.example.org"]).unwrap();
+            assert_eq!(&root, tree.root());
+            assert_eq!(root.seq, 3);
+            assert!(root.verify(&secp256k1::PublicKey::from_secret_key(&tree_key)));
+
+            // a single entry is the root of its subtree
+            assert_eq!(root.l_root, DnsEntry::Link(link.clone()).hash());
+            assert_eq!(
+                resolve(&txt_records, &root.l_root),
+                vec![DnsEntry::Link(link.clone())]
+            );
+            let resolved = resolve(&txt_records, &root.e_root);
+            assert_eq!(
+                resolved,
+                records
+                    .iter()
+                    .map(|enr| DnsEntry::Enr(DnsEnr(enr.clone())))
+                    .collect::<Vec<_>>()
+            );
+            assert!(tree.entry(&root.e_root).is_some());
+            for (hash, entry) in &tree.entries {
+                let txt = entry.to_txt_record();
+                assert_eq!(&DnsEntry::from_txt_record(&txt).unwrap(), entry);
+                assert_eq!(&entry.hash(), hash);
+            }
+
+            // the tree is the one signed by a `TreeBuilder`
+            let mut builder = TreeBuilder::new();
+            for enr in &records {
+                builder.insert_enr(enr);
+            }
+            builder.insert_link(EIP_LINK).unwrap();
+            let signed = builder.sign(&tree_key, 3);
+            assert_eq!(signed.len(), txt_records.len());
+            for (subdomain, txt) in &signed {
+                let name = if subdomain.is_empty() {
+                    "nodes.example.org".to_string()
+                } else {
+                    format!("{subdomain}.nodes.example.org")
+                };
+                assert_eq!(&txt_records[&name], txt);
+            }
+        }
+
+        // a later record replaces an earlier record of the same node id
+        let key = CombinedKey::generate_secp256k1();
+        let mut enr = EnrBuilder::new("v4").udp(30303).build(&key).unwrap();
+        let old = enr.clone();
+        enr.set_udp(30304, &key).unwrap();
+        let tree = DnsTree::build(1, vec![old.clone(), enr.clone()], Vec::new(), &tree_key);
+        assert_eq!(tree.records().collect::<Vec<_>>(), vec![&enr]);
+        assert!(tree.entry(&DnsEntry::Enr(DnsEnr(old)).hash()).is_none());
+    }
+}
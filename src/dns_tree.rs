@@ -0,0 +1,807 @@
+//! Parsing and verification of the entries of an [EIP-1459](https://eips.ethereum.org/EIPS/eip-1459)
+//! node tree, for records of any key type.
+//!
+//! Enabled by the `libsecp256k1` feature, as trees are signed with secp256k1 keys.
+//!
+//! A client resolving a tree reads the root entry at the domain of the tree, verifies it against
+//! the public key of the link to the tree, see [`Entry::verify`], and then follows the hashes of
+//! branch entries to the subdomains they name, see [`hash`]. This module performs no DNS lookups,
+//! the TXT records are handed to [`Entry::from_str`](core::str::FromStr::from_str). Building and
+//! publishing trees of [`CombinedKey`](crate::CombinedKey) records is done by the
+//! [`dns`](crate::dns) module.
+//!
+//! The other way round, [`TreeBuilder`] assembles and signs a tree of records of any key type and
+//! links, producing its TXT records.
+
+use crate::{digest, secp256k1, Enr, EnrError, EnrKey, NodeId};
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{convert::TryFrom, fmt, str::FromStr};
+
+/// The prefix of a root entry.
+pub(crate) const ROOT_PREFIX: &str = "enrtree-root:v1";
+/// The prefix of a branch entry.
+pub(crate) const BRANCH_PREFIX: &str = "enrtree-branch:";
+/// The prefix of a link entry.
+pub(crate) const LINK_PREFIX: &str = "enrtree://";
+/// The prefix of a record entry.
+pub(crate) const ENR_PREFIX: &str = "enr:";
+
+/// The maximum number of hashes of a branch entry, keeping its TXT record below 370 bytes.
+pub const MAX_BRANCH_CHILDREN: usize = 370 / (1 + 26);
+
+/// The decoded length bounds of a hash, as accepted by go-ethereum.
+const MIN_HASH_LENGTH: usize = 12;
+const MAX_HASH_LENGTH: usize = 32;
+
+/// An entry of a tree, as stored in a TXT record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Entry<K: EnrKey> {
+    /// The root entry, `enrtree-root:v1 e=<enr-root> l=<link-root> seq=<seq> sig=<sig>`, stored
+    /// at the domain of the tree.
+    Root {
+        /// The hash of the root of the record subtree.
+        erecord: String,
+        /// The hash of the root of the link subtree.
+        lrecord: String,
+        /// The sequence number of the tree, increased on every update.
+        seq: u64,
+        /// The 65 byte `[R || S || V]` secp256k1 signature of the root entry.
+        sig: Vec<u8>,
+    },
+    /// A branch entry, `enrtree-branch:<h1>,<h2>,...`, listing the hashes of its children.
+    Branch(Vec<String>),
+    /// A leaf entry linking to another tree, `enrtree://<public-key>@<domain>`.
+    Link {
+        /// The public key signing the linked tree, encoded compressed in the entry.
+        pubkey: secp256k1::PublicKey,
+        /// The domain of the linked tree.
+        domain: String,
+    },
+    /// A leaf entry holding a record, `enr:<record>`.
+    Node(Enr<K>),
+}
+
+impl<K: EnrKey> Entry<K> {
+    /// The hash of the entry, naming the subdomain it is stored at, see [`hash`].
+    #[must_use]
+    pub fn hash(&self) -> String {
+        hash(&self.to_string())
+    }
+
+    /// Verifies the signature of a root entry against the public key of the tree, as given by
+    /// the link to the tree. Entries other than roots are never valid.
+    #[must_use]
+    pub fn verify(&self, pubkey: &secp256k1::PublicKey) -> bool {
+        match self {
+            Self::Root {
+                erecord,
+                lrecord,
+                seq,
+                sig,
+            } => verify_root(&root_signed_text(erecord, lrecord, *seq), sig, pubkey),
+            _ => false,
+        }
+    }
+}
+
+impl<K: EnrKey> fmt::Display for Entry<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Root {
+                erecord,
+                lrecord,
+                seq,
+                sig,
+            } => write!(
+                f,
+                "{} sig={}",
+                root_signed_text(erecord, lrecord, *seq),
+                base64::encode_config(sig, base64::URL_SAFE_NO_PAD)
+            ),
+            Self::Branch(hashes) => write!(f, "{}{}", BRANCH_PREFIX, hashes.join(",")),
+            Self::Link { pubkey, domain } => write!(
+                f,
+                "{}{}@{}",
+                LINK_PREFIX,
+                base32_encode(&pubkey.serialize_compressed()),
+                domain
+            ),
+            Self::Node(enr) => f.write_str(&enr.to_base64()),
+        }
+    }
+}
+
+impl<K: EnrKey> FromStr for Entry<K> {
+    type Err = EnrError;
+
+    /// Decodes an entry of any type from the content of a TXT record. The signature of a root
+    /// entry is not verified, see [`Entry::verify`], the signature of a record is.
+    fn from_str(txt: &str) -> Result<Self, Self::Err> {
+        if txt.starts_with(ROOT_PREFIX) {
+            parse_root(txt)
+        } else if let Some(hashes) = txt.strip_prefix(BRANCH_PREFIX) {
+            parse_branch(hashes)
+        } else if let Some(link) = txt.strip_prefix(LINK_PREFIX) {
+            parse_link(link)
+        } else if txt.starts_with(ENR_PREFIX) {
+            txt.parse()
+                .map(Self::Node)
+                .map_err(|_| EnrError::InvalidDnsEntry("Invalid record"))
+        } else {
+            Err(EnrError::InvalidDnsEntry("Unknown entry type"))
+        }
+    }
+}
+
+/// The hash of the content of a TXT record, naming the subdomain the record is stored at: the
+/// unpadded base32 encoding of the first 16 bytes of its keccak256 hash.
+///
+/// A client should hash the TXT record as received, rather than an entry decoded from it, when
+/// checking it against the hash of a branch.
+#[must_use]
+pub fn hash(txt: &str) -> String {
+    base32_encode(&digest(txt.as_bytes())[..16])
+}
+
+/// Assembles a tree of records and links to other trees, and signs it into the TXT records to
+/// publish, the inverse of parsing the entries of a tree with [`Entry`].
+///
+/// The tree is laid out as go-ethereum's `p2p/dnsdisc` does: the records are ordered by node id,
+/// the links by their text, and each subtree groups its leaves into branches of at most
+/// [`TreeBuilder::fanout`] children, where a subtree of a single leaf has no branch.
+#[derive(Clone, Debug)]
+pub struct TreeBuilder {
+    /// The hash and content of the entry of each record, by node id.
+    records: BTreeMap<NodeId, (String, String)>,
+    /// The hash of each link entry, by its content.
+    links: BTreeMap<String, String>,
+    /// The maximum number of children of a branch.
+    fanout: usize,
+    /// The sequence number of the last signed tree.
+    seq: Option<u64>,
+}
+
+impl Default for TreeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TreeBuilder {
+    /// An empty tree, with branches of at most [`MAX_BRANCH_CHILDREN`] children.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            records: BTreeMap::new(),
+            links: BTreeMap::new(),
+            fanout: MAX_BRANCH_CHILDREN,
+            seq: None,
+        }
+    }
+
+    /// Sets the maximum number of children of a branch. Branches of more than
+    /// [`MAX_BRANCH_CHILDREN`] children exceed the size of a TXT record that clients accept.
+    ///
+    /// # Panics
+    /// Panics if `fanout` is below 2.
+    pub fn fanout(&mut self, fanout: usize) -> &mut Self {
+        assert!(fanout >= 2, "A branch needs at least 2 children");
+        self.fanout = fanout;
+        self
+    }
+
+    /// Adds a record, replacing the record of the same node id if there is one.
+    pub fn insert_enr<K: EnrKey>(&mut self, enr: &Enr<K>) -> &mut Self {
+        let txt = enr.to_base64();
+        self.records.insert(enr.node_id(), (hash(&txt), txt));
+        self
+    }
+
+    /// Removes the record of a node id. Returns `true` if the tree had a record of the node id.
+    pub fn remove_enr(&mut self, node_id: &NodeId) -> bool {
+        self.records.remove(node_id).is_some()
+    }
+
+    /// Adds a link to another tree, `enrtree://<public-key>@<domain>`.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidDnsEntry`] if `link` is not a link entry.
+    pub fn insert_link(&mut self, link: &str) -> Result<&mut Self, EnrError> {
+        if !link.starts_with(LINK_PREFIX) {
+            return Err(EnrError::InvalidDnsEntry("Expected a link entry"));
+        }
+        // the link is stored in its canonical form
+        let txt = parse_link::<secp256k1::SecretKey>(&link[LINK_PREFIX.len()..])?.to_string();
+        self.insert_canonical_link(txt);
+        Ok(self)
+    }
+
+    /// Adds a link entry that is already in its canonical form.
+    pub(crate) fn insert_canonical_link(&mut self, txt: String) {
+        let hash = hash(&txt);
+        self.links.insert(txt, hash);
+    }
+
+    /// Signs the tree with the key of the tree and a sequence number, which has to be higher than
+    /// that of the currently published tree for clients to pick up the update. Returns the TXT
+    /// records of the tree by subdomain: the root entry under the empty subdomain, to be
+    /// published at the domain of the tree, and every other entry under its hash.
+    ///
+    /// The root signature is the 65 byte `[R || S || V]` recoverable secp256k1 signature of the
+    /// keccak256 hash of the root entry, as go-ethereum verifies it.
+    pub fn sign(&mut self, root_key: &secp256k1::SecretKey, seq: u64) -> BTreeMap<String, String> {
+        let tree = self.sign_tree(root_key, seq);
+        let mut txt_records = tree.txt_records;
+        txt_records.insert(
+            String::new(),
+            format!(
+                "{} sig={}",
+                root_signed_text(&tree.erecord, &tree.lrecord, seq),
+                base64::encode_config(tree.sig, base64::URL_SAFE_NO_PAD)
+            ),
+        );
+        txt_records
+    }
+
+    /// Signs the tree, see [`TreeBuilder::sign`], and returns its parts.
+    pub(crate) fn sign_tree(&mut self, root_key: &secp256k1::SecretKey, seq: u64) -> SignedTree {
+        let mut txt_records = BTreeMap::new();
+        let mut subtree = |leaves: Vec<(&String, &String)>| {
+            let mut hashes = Vec::with_capacity(leaves.len());
+            for (leaf, txt) in leaves {
+                txt_records.insert(leaf.clone(), txt.clone());
+                hashes.push(leaf.clone());
+            }
+            build_subtree(hashes, self.fanout, &mut |children| {
+                let txt = format!("{}{}", BRANCH_PREFIX, children.join(","));
+                let branch = hash(&txt);
+                txt_records.insert(branch.clone(), txt);
+                branch
+            })
+        };
+        let erecord = subtree(
+            self.records
+                .values()
+                .map(|(hash, txt)| (hash, txt))
+                .collect(),
+        );
+        let lrecord = subtree(self.links.iter().map(|(txt, hash)| (hash, txt)).collect());
+
+        let sig = sign_root(&root_signed_text(&erecord, &lrecord, seq), root_key);
+        self.seq = Some(seq);
+        SignedTree {
+            erecord,
+            lrecord,
+            sig,
+            txt_records,
+        }
+    }
+
+    /// Signs the tree again after records or links changed, with the sequence number of the last
+    /// signed tree increased by one, see [`TreeBuilder::sign`].
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::UnsignedDnsTree`] if the tree was never signed, as its sequence
+    /// number is unknown, and with [`EnrError::SequenceNumberTooHigh`] if the sequence number of
+    /// the last signed tree is `u64::MAX`.
+    pub fn update(
+        &mut self,
+        root_key: &secp256k1::SecretKey,
+    ) -> Result<BTreeMap<String, String>, EnrError> {
+        let seq = self
+            .seq
+            .ok_or(EnrError::UnsignedDnsTree)?
+            .checked_add(1)
+            .ok_or(EnrError::SequenceNumberTooHigh)?;
+        Ok(self.sign(root_key, seq))
+    }
+}
+
+/// The parts of a tree signed by [`TreeBuilder::sign_tree`].
+pub(crate) struct SignedTree {
+    /// The hash of the root of the subtree of records.
+    pub(crate) erecord: String,
+    /// The hash of the root of the subtree of links.
+    pub(crate) lrecord: String,
+    /// The signature of the root entry.
+    pub(crate) sig: Vec<u8>,
+    /// The TXT records of every entry but the root, by hash.
+    pub(crate) txt_records: BTreeMap<String, String>,
+}
+
+/// Groups the hashes of the leaves of a subtree into branches of at most `fanout` children and
+/// returns the hash of its root. `branch` stores a branch of the given children and returns its
+/// hash. A subtree of a single leaf has no branch.
+pub(crate) fn build_subtree(
+    leaves: Vec<String>,
+    fanout: usize,
+    branch: &mut impl FnMut(Vec<String>) -> String,
+) -> String {
+    if let [leaf] = leaves.as_slice() {
+        return leaf.clone();
+    }
+    let mut level = leaves;
+    // group the entries into branches until a single branch remains
+    while level.len() > fanout {
+        level = level
+            .chunks(fanout)
+            .map(|chunk| match chunk {
+                [hash] => hash.clone(),
+                _ => branch(chunk.to_vec()),
+            })
+            .collect();
+    }
+    branch(level)
+}
+
+/// Decodes a root entry.
+fn parse_root<K: EnrKey>(txt: &str) -> Result<Entry<K>, EnrError> {
+    let mut parts = txt.split(' ');
+    if parts.next() != Some(ROOT_PREFIX) {
+        return Err(EnrError::InvalidDnsEntry("Expected a root entry"));
+    }
+    let mut field = |name: &str| {
+        parts
+            .next()
+            .and_then(|part| part.strip_prefix(name))
+            .and_then(|part| part.strip_prefix('='))
+            .ok_or(EnrError::InvalidDnsEntry("Invalid root entry syntax"))
+    };
+    let erecord = field("e")?.to_string();
+    let lrecord = field("l")?.to_string();
+    let seq = field("seq")?
+        .parse()
+        .map_err(|_| EnrError::InvalidDnsEntry("Invalid sequence number"))?;
+    let sig = base64::decode_config(field("sig")?, base64::URL_SAFE_NO_PAD)
+        .map_err(|_| EnrError::InvalidDnsEntry("Invalid signature"))?;
+    if parts.next().is_some() {
+        return Err(EnrError::InvalidDnsEntry("Invalid root entry syntax"));
+    }
+    if !is_valid_hash(&erecord) || !is_valid_hash(&lrecord) {
+        return Err(EnrError::InvalidDnsEntry("Invalid hash"));
+    }
+    if sig.len() != 65 {
+        return Err(EnrError::InvalidDnsEntry("Invalid signature"));
+    }
+    Ok(Entry::Root {
+        erecord,
+        lrecord,
+        seq,
+        sig,
+    })
+}
+
+/// Decodes the hashes of a branch entry, following its prefix.
+fn parse_branch<K: EnrKey>(hashes: &str) -> Result<Entry<K>, EnrError> {
+    if hashes.is_empty() {
+        return Ok(Entry::Branch(Vec::new()));
+    }
+    let hashes = hashes.split(',').map(String::from).collect::<Vec<_>>();
+    if !hashes.iter().all(|hash| is_valid_hash(hash)) {
+        return Err(EnrError::InvalidDnsEntry("Invalid hash"));
+    }
+    Ok(Entry::Branch(hashes))
+}
+
+/// Decodes a link entry, following its prefix.
+fn parse_link<K: EnrKey>(link: &str) -> Result<Entry<K>, EnrError> {
+    let (pubkey, domain) = link
+        .split_once('@')
+        .ok_or(EnrError::InvalidDnsEntry("Missing public key"))?;
+    let pubkey = base32_decode(pubkey)
+        .and_then(|bytes| {
+            secp256k1::PublicKey::parse_slice(&bytes, Some(secp256k1::PublicKeyFormat::Compressed))
+                .ok()
+        })
+        .ok_or(EnrError::InvalidDnsEntry("Invalid public key"))?;
+    Ok(Entry::Link {
+        pubkey,
+        domain: domain.into(),
+    })
+}
+
+/// The content of a root entry without the signature, which is signed.
+pub(crate) fn root_signed_text(erecord: &str, lrecord: &str, seq: u64) -> String {
+    format!("{ROOT_PREFIX} e={erecord} l={lrecord} seq={seq}")
+}
+
+/// Signs the content of a root entry, returning the `[R || S || V]` signature.
+pub(crate) fn sign_root(signed_text: &str, key: &secp256k1::SecretKey) -> Vec<u8> {
+    let message = secp256k1::Message::parse(&digest(signed_text.as_bytes()));
+    // the signature is in low-s form
+    let (signature, recovery_id) = secp256k1::sign(&message, key);
+    let mut sig = signature.serialize().to_vec();
+    sig.push(recovery_id.serialize());
+    sig
+}
+
+/// Verifies the `[R || S || V]` signature of the content of a root entry.
+pub(crate) fn verify_root(signed_text: &str, sig: &[u8], pubkey: &secp256k1::PublicKey) -> bool {
+    let message = secp256k1::Message::parse(&digest(signed_text.as_bytes()));
+    sig.len() == 65
+        && secp256k1::Signature::parse_slice(&sig[..64])
+            .is_ok_and(|signature| secp256k1::verify(&message, &signature, pubkey))
+}
+
+/// Returns `true` if the hash is unpadded base32 of 12 to 32 bytes.
+fn is_valid_hash(hash: &str) -> bool {
+    (MIN_HASH_LENGTH..=MAX_HASH_LENGTH).contains(&(hash.len() * 5 / 8))
+        && base32_decode(hash).is_some()
+}
+
+/// The RFC 4648 base32 alphabet.
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Encodes bytes as unpadded base32.
+pub(crate) fn base32_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let mut buffer = 0_u16;
+    let mut bits = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | u16::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_ALPHABET[usize::from((buffer >> bits) & 31)].into());
+        }
+    }
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[usize::from((buffer << (5 - bits)) & 31)].into());
+    }
+    encoded
+}
+
+/// Decodes unpadded base32. Unused trailing bits are ignored.
+fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+    // lengths leaving a partial byte of more than 4 bits cannot be produced by the encoder
+    if matches!(encoded.len() % 8, 1 | 3 | 6) {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(encoded.len() * 5 / 8);
+    let mut buffer = 0_u16;
+    let mut bits = 0;
+    for character in encoded.bytes() {
+        let value = BASE32_ALPHABET.iter().position(|c| *c == character)?;
+        buffer = (buffer << 5) | u16::try_from(value).ok()?;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push(u8::try_from((buffer >> bits) & 0xff).ok()?);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    type DefaultEntry = Entry<secp256k1::SecretKey>;
+
+    /// The domain of the fixture tree.
+    const DOMAIN: &str = "nodes.example.org";
+
+    /// The link to the fixture tree.
+    const LINK: &str =
+        "enrtree://AKPYQIUQIL7PSIACI32J7FGZW56E5FKHEFCCOFHILBIMW3M6LWXS2@nodes.example.org";
+
+    /// The TXT records of the fixture tree, by name.
+    fn fixture() -> BTreeMap<&'static str, &'static str> {
+        include_str!("../tests/fixtures/eip1459-tree.txt")
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.split_once(' ').unwrap())
+            .collect()
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_fixture_hashes() {
+        let records = fixture();
+        for (name, txt) in &records {
+            let entry: DefaultEntry = txt.parse().unwrap();
+            assert_eq!(entry.to_string(), *txt);
+            if let Some(subdomain) = name.strip_suffix(&format!(".{DOMAIN}")) {
+                assert_eq!(hash(txt), subdomain);
+                assert_eq!(entry.hash(), subdomain);
+            }
+            // every child of a branch is published
+            if let Entry::Branch(hashes) = entry {
+                for child in hashes {
+                    assert!(records.contains_key(format!("{child}.{DOMAIN}").as_str()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_fixture_root() {
+        let records = fixture();
+        let root: DefaultEntry = records[DOMAIN].parse().unwrap();
+        let (erecord, lrecord) = match &root {
+            Entry::Root {
+                erecord,
+                lrecord,
+                seq,
+                ..
+            } => {
+                assert_eq!(*seq, 1);
+                (erecord, lrecord)
+            }
+            entry => panic!("Expected a root entry, got {:?}", entry),
+        };
+        assert!(matches!(
+            records[format!("{erecord}.{DOMAIN}").as_str()].parse(),
+            Ok(DefaultEntry::Branch(_))
+        ));
+        assert!(matches!(
+            records[format!("{lrecord}.{DOMAIN}").as_str()].parse(),
+            Ok(DefaultEntry::Link { .. })
+        ));
+
+        let (pubkey, domain) = match LINK.parse::<DefaultEntry>().unwrap() {
+            Entry::Link { pubkey, domain } => (pubkey, domain),
+            entry => panic!("Expected a link entry, got {:?}", entry),
+        };
+        assert_eq!(domain, DOMAIN);
+        assert!(root.verify(&pubkey));
+
+        // the signature covers the content of the root
+        let tampered: DefaultEntry = records[DOMAIN].replace("seq=1", "seq=2").parse().unwrap();
+        assert!(!tampered.verify(&pubkey));
+        match records[format!("{lrecord}.{DOMAIN}").as_str()].parse() {
+            Ok(DefaultEntry::Link { pubkey, .. }) => assert!(!root.verify(&pubkey)),
+            entry => panic!("Expected a link entry, got {:?}", entry),
+        }
+        // only roots are signed
+        assert!(!LINK.parse::<DefaultEntry>().unwrap().verify(&pubkey));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_invalid_entries() {
+        let invalid = [
+            "",
+            "foo",
+            "enrtree-root:v1",
+            "enrtree-branch:2XS2367YHAXJFGLZHVAWLQD4ZY,",
+            "enrtree://nodes.example.org",
+            "enr:AAAA",
+        ];
+        for txt in &invalid {
+            assert!(
+                matches!(
+                    txt.parse::<DefaultEntry>(),
+                    Err(EnrError::InvalidDnsEntry(_))
+                ),
+                "{}",
+                txt
+            );
+        }
+
+        // a record of an unknown scheme cannot be verified
+        let forged = crate::forge_unknown_scheme(&records(1)[0]).to_base64();
+        assert!(matches!(
+            forged.parse::<DefaultEntry>(),
+            Err(EnrError::InvalidDnsEntry("Invalid record"))
+        ));
+    }
+
+    /// The key of trees built in tests.
+    fn tree_key() -> secp256k1::SecretKey {
+        secp256k1::SecretKey::parse(&[0x42; 32]).unwrap()
+    }
+
+    /// Records with distinct keys.
+    fn records(count: u8) -> Vec<Enr<secp256k1::SecretKey>> {
+        (1..=count)
+            .map(|i| {
+                let key = secp256k1::SecretKey::parse(&[i; 32]).unwrap();
+                crate::EnrBuilder::new("v4").udp(30303).build(&key).unwrap()
+            })
+            .collect()
+    }
+
+    /// Resolves the leaves below a hash, as a client walking the published tree.
+    fn resolve(
+        txt_records: &BTreeMap<String, String>,
+        subdomain: &str,
+        fanout: usize,
+    ) -> Vec<String> {
+        let txt = &txt_records[subdomain];
+        assert_eq!(hash(txt), subdomain);
+        match txt.parse::<DefaultEntry>().unwrap() {
+            Entry::Branch(hashes) => {
+                assert!(hashes.len() <= fanout);
+                hashes
+                    .iter()
+                    .flat_map(|child| resolve(txt_records, child, fanout))
+                    .collect()
+            }
+            _ => vec![txt.clone()],
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_tree_builder_fixture() {
+        let fixture = fixture();
+        let mut builder = TreeBuilder::new();
+        for txt in fixture.values() {
+            match txt.parse::<DefaultEntry>().unwrap() {
+                Entry::Node(enr) => {
+                    builder.insert_enr(&enr);
+                }
+                Entry::Link { .. } => {
+                    builder.insert_link(txt).unwrap();
+                }
+                _ => {}
+            }
+        }
+        let txt_records = builder.sign(&tree_key(), 1);
+
+        // the entries are identical to those of the fixture, the root up to the signature by a
+        // different key
+        assert_eq!(txt_records.len(), fixture.len());
+        for (subdomain, txt) in &txt_records {
+            if subdomain.is_empty() {
+                let (signed_text, _) = txt.split_once(" sig=").unwrap();
+                assert!(fixture[DOMAIN].starts_with(&format!("{signed_text} sig=")));
+            } else {
+                assert_eq!(fixture[format!("{subdomain}.{DOMAIN}").as_str()], txt);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_tree_builder_signature() {
+        let txt_records = TreeBuilder::new().sign(&tree_key(), 7);
+        let root: DefaultEntry = txt_records[""].parse().unwrap();
+        let public_key = secp256k1::PublicKey::from_secret_key(&tree_key());
+        assert!(root.verify(&public_key));
+
+        // the signature recovers the key of the tree, as go-ethereum's clients check
+        let (signed_text, sig) = match &root {
+            Entry::Root {
+                erecord,
+                lrecord,
+                seq,
+                sig,
+            } => {
+                assert_eq!(*seq, 7);
+                // the subtrees of an empty tree are empty branches
+                assert_eq!(erecord, &hash(BRANCH_PREFIX));
+                assert_eq!(lrecord, &hash(BRANCH_PREFIX));
+                (root_signed_text(erecord, lrecord, *seq), sig)
+            }
+            entry => panic!("Expected a root entry, got {:?}", entry),
+        };
+        let message = secp256k1::Message::parse(&digest(signed_text.as_bytes()));
+        let recovered = secp256k1::recover(
+            &message,
+            &secp256k1::Signature::parse_slice(&sig[..64]).unwrap(),
+            &secp256k1::RecoveryId::parse(sig[64]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(recovered, public_key);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_tree_builder_layout() {
+        let link = LINK.parse::<DefaultEntry>().unwrap().to_string();
+        for (count, fanout) in &[(1, 13), (13, 13), (14, 13), (30, 13), (30, 2)] {
+            let mut enrs = records(*count);
+            let mut builder = TreeBuilder::new();
+            builder.fanout(*fanout).insert_link(&link).unwrap();
+            for enr in &enrs {
+                builder.insert_enr(enr);
+            }
+            let txt_records = builder.sign(&tree_key(), 1);
+
+            let (erecord, lrecord) = match txt_records[""].parse().unwrap() {
+                DefaultEntry::Root {
+                    erecord, lrecord, ..
+                } => (erecord, lrecord),
+                entry => panic!("Expected a root entry, got {:?}", entry),
+            };
+            enrs.sort_by_key(Enr::node_id);
+            assert_eq!(
+                resolve(&txt_records, &erecord, *fanout),
+                enrs.iter().map(Enr::to_base64).collect::<Vec<_>>()
+            );
+            // a single entry is the root of its subtree
+            assert_eq!(resolve(&txt_records, &lrecord, *fanout), vec![link.clone()]);
+            assert_eq!(lrecord, hash(&link));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_tree_builder_update() {
+        let key = secp256k1::SecretKey::parse(&[1; 32]).unwrap();
+        let mut enrs = records(8);
+        let mut builder = TreeBuilder::new();
+        builder.fanout(2);
+        for enr in &enrs {
+            builder.insert_enr(enr);
+        }
+        let old = builder.sign(&tree_key(), 5);
+
+        // replace the record of one node
+        enrs[0].set_udp4(30304, &key).unwrap();
+        builder.insert_enr(&enrs[0]);
+        let new = builder.update(&tree_key()).unwrap();
+        match new[""].parse().unwrap() {
+            DefaultEntry::Root { seq, .. } => assert_eq!(seq, 6),
+            entry => panic!("Expected a root entry, got {:?}", entry),
+        }
+
+        // the record and the 3 branches above it changed, the rest of the tree is unchanged
+        let removed = old
+            .keys()
+            .filter(|subdomain| !new.contains_key(*subdomain))
+            .count();
+        let added = new
+            .keys()
+            .filter(|subdomain| !old.contains_key(*subdomain))
+            .count();
+        assert_eq!((removed, added), (4, 4));
+        assert_eq!(old.len(), new.len());
+
+        // removing a record updates the tree
+        assert!(builder.remove_enr(&enrs[0].node_id()));
+        assert!(!builder.remove_enr(&enrs[0].node_id()));
+        let new = builder.update(&tree_key()).unwrap();
+        assert!(!new.values().any(|txt| *txt == enrs[0].to_base64()));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_tree_builder_update_unsigned() {
+        assert!(matches!(
+            TreeBuilder::new().update(&tree_key()),
+            Err(EnrError::UnsignedDnsTree)
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_tree_builder_update_max_seq() {
+        let mut builder = TreeBuilder::new();
+        builder.insert_enr(&records(1)[0]);
+        let _ = builder.sign(&tree_key(), u64::MAX);
+        assert!(matches!(
+            builder.update(&tree_key()),
+            Err(EnrError::SequenceNumberTooHigh)
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_tree_builder_invalid_link() {
+        let mut builder = TreeBuilder::new();
+        assert!(builder.insert_link("enrtree://nodes.example.org").is_err());
+        assert!(builder.insert_link(&records(1)[0].to_base64()).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_base32() {
+        for bytes in &[&b""[..], b"f", b"fo", b"foo", b"foob", b"fooba", b"foobar"] {
+            let encoded = base32_encode(bytes);
+            assert_eq!(base32_decode(&encoded).as_deref(), Some(*bytes));
+        }
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32_decode("MZXW6YTBO"), None);
+        assert_eq!(base32_decode("MZXW6YTBO1"), None);
+    }
+}
@@ -0,0 +1,227 @@
+//! A C interface to decode, encode and verify records, provided by the `ffi` feature.
+//!
+//! Records are decoded into an [`EnrHandle`], an opaque pointer owning a boxed
+//! `Enr<CombinedKey>`, which must be released with [`enr_free`]. Every function returns
+//! [`ENR_OK`] on success and a negative error code otherwise.
+//!
+//! The C header `include/enr.h` is generated from this module with
+//! `cbindgen --config cbindgen.toml --output include/enr.h`. A shared or static library is built
+//! with `cargo rustc --release --features ffi --crate-type cdylib` (or `staticlib`).
+
+use crate::{CombinedKey, Enr};
+use std::{ptr, slice};
+
+/// The operation succeeded.
+pub const ENR_OK: i32 = 0;
+/// A required pointer argument was null.
+pub const ENR_ERR_NULL_POINTER: i32 = -1;
+/// The bytes are not a valid record.
+pub const ENR_ERR_DECODE: i32 = -2;
+/// The output buffer is too small. The required length has been written to `out_len`.
+pub const ENR_ERR_BUFFER_TOO_SMALL: i32 = -3;
+/// The requested field is not present in the record.
+pub const ENR_ERR_NOT_FOUND: i32 = -4;
+
+/// A decoded record, only accessed through an [`EnrHandle`].
+pub struct OpaqueEnr(Enr<CombinedKey>);
+
+/// An opaque pointer to a decoded record, created by [`enr_decode`] and released by
+/// [`enr_free`].
+pub type EnrHandle = *mut OpaqueEnr;
+
+/// Decodes the RLP-encoded record of `len` bytes at `bytes`, writing a new handle to `out`.
+///
+/// The signature is verified as part of decoding, and records of an identity scheme other than
+/// `v4` are rejected. On failure `out` is left untouched.
+///
+/// # Safety
+///
+/// `bytes` must point to `len` readable bytes and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn enr_decode(bytes: *const u8, len: usize, out: *mut EnrHandle) -> i32 {
+    if bytes.is_null() || out.is_null() {
+        return ENR_ERR_NULL_POINTER;
+    }
+    let bytes = slice::from_raw_parts(bytes, len);
+    rlp::decode::<Enr<CombinedKey>>(bytes).map_or(ENR_ERR_DECODE, |enr| {
+        *out = Box::into_raw(Box::new(OpaqueEnr(enr)));
+        ENR_OK
+    })
+}
+
+/// Writes the RLP encoding of the record to `out`.
+///
+/// `out_len` holds the capacity of `out` on input and the length of the encoding on output. If
+/// `out` is null or too small, nothing is written to it and [`ENR_ERR_BUFFER_TOO_SMALL`] is
+/// returned, so the required length can be queried by passing a null `out`.
+///
+/// # Safety
+///
+/// `handle` must have been created by [`enr_decode`] and not yet freed, `out_len` must be valid
+/// for reads and writes, and `out`, if not null, must point to `*out_len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn enr_encode(handle: EnrHandle, out: *mut u8, out_len: *mut usize) -> i32 {
+    if handle.is_null() || out_len.is_null() {
+        return ENR_ERR_NULL_POINTER;
+    }
+    let encoded = (*handle).0.encode();
+    let capacity = *out_len;
+    *out_len = encoded.len();
+    if out.is_null() || capacity < encoded.len() {
+        return ENR_ERR_BUFFER_TOO_SMALL;
+    }
+    ptr::copy_nonoverlapping(encoded.as_ptr(), out, encoded.len());
+    ENR_OK
+}
+
+/// Writes the 4 octets of the `ip` field to `out_ip`.
+///
+/// Returns [`ENR_ERR_NOT_FOUND`] if the record has no valid `ip` field.
+///
+/// # Safety
+///
+/// `handle` must have been created by [`enr_decode`] and not yet freed, and `out_ip` must point
+/// to 4 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn enr_get_ip(handle: EnrHandle, out_ip: *mut u8) -> i32 {
+    if handle.is_null() || out_ip.is_null() {
+        return ENR_ERR_NULL_POINTER;
+    }
+    (*handle).0.ip().map_or(ENR_ERR_NOT_FOUND, |ip| {
+        ptr::copy_nonoverlapping(ip.octets().as_ptr(), out_ip, 4);
+        ENR_OK
+    })
+}
+
+/// Verifies the signature of the record. Returns 1 if it is valid and 0 if it is not.
+///
+/// # Safety
+///
+/// `handle` must have been created by [`enr_decode`] and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn enr_verify(handle: EnrHandle) -> i32 {
+    if handle.is_null() {
+        return ENR_ERR_NULL_POINTER;
+    }
+    i32::from((*handle).0.verify())
+}
+
+/// Releases a record. Passing a null handle does nothing.
+///
+/// # Safety
+///
+/// `handle` must be null or have been created by [`enr_decode`] and not yet freed. It must not
+/// be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn enr_free(handle: EnrHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EnrBuilder;
+    use std::{
+        net::Ipv4Addr,
+        ptr::{addr_of_mut, null_mut},
+    };
+
+    fn record() -> Vec<u8> {
+        let key = CombinedKey::generate_secp256k1();
+        EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::new(192, 0, 2, 1))
+            .udp(30303)
+            .build(&key)
+            .unwrap()
+            .encode()
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_ffi_round_trip() {
+        let bytes = record();
+        let mut handle: EnrHandle = null_mut();
+        unsafe {
+            assert_eq!(
+                enr_decode(bytes.as_ptr(), bytes.len(), addr_of_mut!(handle)),
+                ENR_OK
+            );
+            assert!(!handle.is_null());
+            assert_eq!(enr_verify(handle), 1);
+
+            let mut ip = [0_u8; 4];
+            assert_eq!(enr_get_ip(handle, ip.as_mut_ptr()), ENR_OK);
+            assert_eq!(ip, [192, 0, 2, 1]);
+
+            // query the length, then encode into a buffer of that size
+            let mut len = 0;
+            assert_eq!(
+                enr_encode(handle, null_mut(), addr_of_mut!(len)),
+                ENR_ERR_BUFFER_TOO_SMALL
+            );
+            assert_eq!(len, bytes.len());
+            let mut out = vec![0_u8; len - 1];
+            len = out.len();
+            assert_eq!(
+                enr_encode(handle, out.as_mut_ptr(), addr_of_mut!(len)),
+                ENR_ERR_BUFFER_TOO_SMALL
+            );
+            out.resize(len, 0);
+            assert_eq!(
+                enr_encode(handle, out.as_mut_ptr(), addr_of_mut!(len)),
+                ENR_OK
+            );
+            assert_eq!(out, bytes);
+
+            enr_free(handle);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_ffi_errors() {
+        let mut handle: EnrHandle = null_mut();
+        let mut len = 0;
+        let mut ip = [0_u8; 4];
+        unsafe {
+            assert_eq!(
+                enr_decode([0xc0].as_ptr(), 1, addr_of_mut!(handle)),
+                ENR_ERR_DECODE
+            );
+            assert!(handle.is_null());
+            assert_eq!(
+                enr_decode(ptr::null(), 0, addr_of_mut!(handle)),
+                ENR_ERR_NULL_POINTER
+            );
+            assert_eq!(
+                enr_encode(handle, null_mut(), addr_of_mut!(len)),
+                ENR_ERR_NULL_POINTER
+            );
+            assert_eq!(enr_get_ip(handle, ip.as_mut_ptr()), ENR_ERR_NULL_POINTER);
+            assert_eq!(enr_verify(handle), ENR_ERR_NULL_POINTER);
+            enr_free(handle);
+
+            // a record without an ip field
+            let key = CombinedKey::generate_ed25519();
+            let bytes = EnrBuilder::new("v4").build(&key).unwrap().encode();
+            assert_eq!(
+                enr_decode(bytes.as_ptr(), bytes.len(), addr_of_mut!(handle)),
+                ENR_OK
+            );
+            assert_eq!(enr_get_ip(handle, ip.as_mut_ptr()), ENR_ERR_NOT_FOUND);
+            enr_free(handle);
+
+            // a record of an unknown scheme, which cannot be verified
+            handle = null_mut();
+            let enr = EnrBuilder::new("v4").build(&key).unwrap();
+            let bytes = crate::forge_unknown_scheme(&enr).encode();
+            assert_eq!(
+                enr_decode(bytes.as_ptr(), bytes.len(), addr_of_mut!(handle)),
+                ENR_ERR_DECODE
+            );
+            assert!(handle.is_null());
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Protobuf encoding of ENR records, provided by the `proto` feature.
+//!
+//! The messages correspond to the schema in `proto/enr.proto`. A record is represented by its
+//! signature, sequence number and key-value pairs, with the values kept in their RLP encoding so
+//! that any record can be reconstructed exactly and its signature verified.
+
+use crate::{EnrError, EnrKey};
+use rlp::RlpStream;
+
+/// A protobuf ENR record.
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct Enr {
+    /// The signature of the record.
+    #[prost(bytes = "vec", tag = "1")]
+    pub signature: Vec<u8>,
+    /// The sequence number of the record.
+    #[prost(uint64, tag = "2")]
+    pub seq: u64,
+    /// The key-value pairs of the record, sorted by key.
+    #[prost(message, repeated, tag = "3")]
+    pub pairs: Vec<Pair>,
+}
+
+/// A key-value pair of a protobuf ENR record.
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct Pair {
+    /// The key of the pair.
+    #[prost(string, tag = "1")]
+    pub key: String,
+    /// The RLP-encoded value of the pair.
+    #[prost(bytes = "vec", tag = "2")]
+    pub value: Vec<u8>,
+}
+
+impl<K: EnrKey> crate::Enr<K> {
+    /// Converts the record into its protobuf message.
+    #[must_use]
+    pub fn to_proto(&self) -> Enr {
+        Enr {
+            signature: self.signature.clone(),
+            seq: self.seq,
+            pairs: self
+                .content
+                .iter()
+                .map(|(key, value)| Pair {
+                    key: key.clone(),
+                    value: value.to_vec(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Reconstructs a record from its protobuf message, verifying its signature.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidSignature`] if the signature does not verify,
+    /// [`EnrError::UnsupportedIdentityScheme`] if the identity scheme is unknown, and otherwise if
+    /// the pairs are malformed or unsorted or the public key is unsupported.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn from_proto(msg: Enr) -> Result<Self, EnrError> {
+        let mut stream = RlpStream::new();
+        stream.begin_list(msg.pairs.len() * 2 + 2);
+        stream.append(&msg.signature);
+        stream.append(&msg.seq);
+        for pair in &msg.pairs {
+            crate::validate_raw_rlp(&pair.value, true)?;
+            stream.append(&pair.key);
+            stream.append_raw(&pair.value, 1);
+        }
+        Self::decode_bytes(&stream.out().freeze()).map_err(EnrError::from_decoder_error)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "ed25519")]
+mod tests {
+    use crate::{CombinedKey, EnrBuilder, EnrError};
+    use prost::Message;
+    use std::net::Ipv4Addr;
+
+    type Enr = crate::Enr<CombinedKey>;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_proto_roundtrip() {
+        for key in &[
+            CombinedKey::generate_secp256k1(),
+            CombinedKey::generate_ed25519(),
+        ] {
+            let mut enr = EnrBuilder::new("v4")
+                .ip4(Ipv4Addr::LOCALHOST)
+                .udp(30303)
+                .build(key)
+                .unwrap();
+            enr.insert_raw_rlp_list("list", vec![0xc2, 1, 2], key)
+                .unwrap();
+
+            let msg = enr.to_proto();
+            assert_eq!(msg.seq, enr.seq());
+            assert_eq!(msg.signature, enr.signature());
+            assert_eq!(msg.pairs.len(), 5);
+            let ip = msg.pairs.iter().position(|pair| pair.key == "ip").unwrap();
+            assert_eq!(
+                msg.pairs[ip].value,
+                rlp::encode(&vec![127_u8, 0, 0, 1]).to_vec()
+            );
+
+            let bytes = msg.encode_to_vec();
+            let decoded = Enr::from_proto(super::Enr::decode(&bytes[..]).unwrap()).unwrap();
+            assert_eq!(decoded, enr);
+            assert_eq!(decoded.ip(), Some(Ipv4Addr::LOCALHOST));
+            assert_eq!(decoded.udp(), Some(30303));
+            assert!(decoded.verify());
+
+            // a modified record fails verification
+            let mut tampered = enr.to_proto();
+            tampered.seq += 1;
+            assert!(matches!(
+                Enr::from_proto(tampered),
+                Err(EnrError::InvalidSignature)
+            ));
+
+            // records of unknown identity schemes cannot be verified
+            let mut unknown = enr.to_proto();
+            let id = unknown
+                .pairs
+                .iter()
+                .position(|pair| pair.key == "id")
+                .unwrap();
+            unknown.pairs[id].value = rlp::encode(&"v5").to_vec();
+            assert!(matches!(
+                Enr::from_proto(unknown),
+                Err(EnrError::UnsupportedIdentityScheme)
+            ));
+
+            // malformed values are rejected
+            let mut malformed = enr.to_proto();
+            malformed.pairs[ip].value = vec![0x84, 127];
+            assert!(Enr::from_proto(malformed).is_err());
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! SSZ encoding of ENR records, provided by the `ssz` feature.
+//!
+//! A record is encoded as the SSZ type `List[byte, 300]` holding its RLP encoding, with no other
+//! framing, so the SSZ bytes of a record are exactly [`Enr::encode`]. Decoding enforces the
+//! maximum record size and verifies the signature.
+//!
+//! The `tree_hash` crate cannot be resolved alongside the `cc` version pinned by `c-secp256k1`,
+//! so the hash tree root of the SSZ type is provided by [`Enr::tree_hash_root`] instead of a
+//! `TreeHash` implementation.
+
+use crate::{Enr, EnrKey, MAX_ENR_SIZE};
+use sha2::{Digest, Sha256};
+use ssz::{Decode, DecodeError, Encode};
+
+/// The size of an SSZ chunk.
+const CHUNK_SIZE: usize = 32;
+
+/// The maximum number of chunks of a record, the chunk limit of `List[byte, 300]`.
+const CHUNK_LIMIT: usize = MAX_ENR_SIZE.div_ceil(CHUNK_SIZE);
+
+/// The SHA-256 hash of two concatenated chunks.
+fn hash_pair(left: &[u8], right: &[u8]) -> [u8; CHUNK_SIZE] {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+impl<K: EnrKey> Encode for Enr<K> {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.encode());
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        self.encode().len()
+    }
+}
+
+impl<K: EnrKey> Decode for Enr<K> {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        if bytes.len() > MAX_ENR_SIZE {
+            return Err(DecodeError::BytesInvalid(format!(
+                "ENR of {} bytes exceeds the maximum of {MAX_ENR_SIZE} bytes",
+                bytes.len()
+            )));
+        }
+        rlp::decode(bytes).map_err(|e| DecodeError::BytesInvalid(format!("Invalid ENR: {e:?}")))
+    }
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// The SSZ hash tree root of the record as a `List[byte, 300]`, the merkleization of its RLP
+    /// encoding in 32-byte chunks, mixed in with its length.
+    #[must_use]
+    pub fn tree_hash_root(&self) -> [u8; 32] {
+        let bytes = self.encode();
+        let mut layer: Vec<[u8; CHUNK_SIZE]> = bytes
+            .chunks(CHUNK_SIZE)
+            .map(|chunk| {
+                let mut padded = [0_u8; CHUNK_SIZE];
+                padded[..chunk.len()].copy_from_slice(chunk);
+                padded
+            })
+            .collect();
+        layer.resize(CHUNK_LIMIT.next_power_of_two(), [0_u8; CHUNK_SIZE]);
+        while layer.len() > 1 {
+            layer = layer
+                .chunks(2)
+                .map(|pair| hash_pair(&pair[0], &pair[1]))
+                .collect();
+        }
+
+        let mut length = [0_u8; CHUNK_SIZE];
+        length[..8].copy_from_slice(&(bytes.len() as u64).to_le_bytes());
+        hash_pair(&layer[0], &length)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "libsecp256k1")]
+mod tests {
+    use crate::{secp256k1::SecretKey, Enr, EnrBuilder};
+    use ssz::{Decode, DecodeError, Encode};
+
+    type DefaultEnr = Enr<SecretKey>;
+
+    /// The record of the EIP-778 test vector.
+    const EIP_778: &str = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_ssz_roundtrip() {
+        let enr: DefaultEnr = EIP_778.parse().unwrap();
+        let bytes = enr.as_ssz_bytes();
+        // no framing beyond the RLP encoding
+        assert_eq!(bytes, rlp::encode(&enr).to_vec());
+        assert_eq!(enr.ssz_bytes_len(), bytes.len());
+        assert_eq!(DefaultEnr::from_ssz_bytes(&bytes).unwrap(), enr);
+
+        // records are variable-size items of containers
+        let key = SecretKey::random(&mut rand::thread_rng());
+        let other = EnrBuilder::new("v4").udp(9000).build(&key).unwrap();
+        let records = vec![enr, other];
+        let bytes = records.as_ssz_bytes();
+        assert_eq!(Vec::<DefaultEnr>::from_ssz_bytes(&bytes).unwrap(), records);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_ssz_rejects_invalid_records() {
+        let enr: DefaultEnr = EIP_778.parse().unwrap();
+        let mut oversized = enr.as_ssz_bytes();
+        oversized.resize(301, 0);
+        assert!(matches!(
+            DefaultEnr::from_ssz_bytes(&oversized),
+            Err(DecodeError::BytesInvalid(ref e)) if e.contains("maximum of 300 bytes")
+        ));
+
+        // raise the udp port, the last byte of the record, without re-signing
+        let mut tampered = enr.as_ssz_bytes();
+        *tampered.last_mut().unwrap() += 1;
+        assert!(matches!(
+            DefaultEnr::from_ssz_bytes(&tampered),
+            Err(DecodeError::BytesInvalid(ref e)) if e.contains("Invalid Signature")
+        ));
+        assert!(matches!(
+            DefaultEnr::from_ssz_bytes(&crate::forge_unknown_scheme(&enr).as_ssz_bytes()),
+            Err(DecodeError::BytesInvalid(ref e)) if e.contains("Unsupported identity scheme")
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_tree_hash_root() {
+        // computed independently as the hash tree root of a List[byte, 300]
+        let enr: DefaultEnr = EIP_778.parse().unwrap();
+        assert_eq!(
+            hex::encode(enr.tree_hash_root()),
+            "6ff1b8550a3bb2e101c8d32892c611139c8323c6c811568b411c726062621904"
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! The fields Ethereum execution clients add to their records. Enabled by the `eth` feature.
+//!
+//! - `eth`: the EIP-2124 fork id of the chain the node is on, `[[fork_hash, fork_next], ...]`.
+//! - `snap`: present if the node serves the snap protocol, an empty list.
+//! - `les`: present if the node serves the light client protocol, `[vflux_version, ...]`.
+//! - `client`: the software the node runs, see [`ClientInfo`](crate::ClientInfo).
+//!
+//! The values are lists whose trailing items are reserved for future use. They are ignored when
+//! read, and never written.
+
+use crate::{Enr, EnrBuilder, EnrKey};
+use alloc::string::String;
+use core::convert::TryFrom;
+use rlp::{DecoderError, Rlp, RlpStream};
+
+/// An EIP-2124 fork id, identifying the chain and the forks a node has applied.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ForkId {
+    /// The CRC32 checksum of the genesis hash and the passed fork block numbers or timestamps.
+    pub hash: [u8; 4],
+    /// The block number or timestamp of the next fork, or 0 if no fork is scheduled.
+    pub next: u64,
+}
+
+impl rlp::Encodable for ForkId {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2);
+        s.append(&self.hash.to_vec());
+        s.append(&self.next);
+    }
+}
+
+impl rlp::Decodable for ForkId {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 2 {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+        let hash = rlp.at(0)?.decoder().decode_value(|bytes| {
+            <[u8; 4]>::try_from(bytes).map_err(|_| DecoderError::RlpInvalidLength)
+        })?;
+        Ok(Self {
+            hash,
+            next: rlp.val_at(1)?,
+        })
+    }
+}
+
+/// Accessors of the Ethereum execution layer fields of a record.
+pub trait EthEnrExt {
+    /// The fork id of the `eth` field. `None` if the field is absent or malformed.
+    fn eth_fork_id(&self) -> Option<ForkId>;
+
+    /// Returns `true` if the record has a `snap` field.
+    fn snap_enabled(&self) -> bool;
+
+    /// The vflux version of the `les` field. `None` if the field is absent or malformed.
+    fn les2_version(&self) -> Option<u64>;
+
+    /// The client version of the `client` field. `None` if the field is absent or malformed.
+    fn client_version(&self) -> Option<String>;
+}
+
+impl<K: EnrKey> EthEnrExt for Enr<K> {
+    fn eth_fork_id(&self) -> Option<ForkId> {
+        first_item(self.get_raw_rlp("eth")?)
+    }
+
+    fn snap_enabled(&self) -> bool {
+        self.get_raw_rlp("snap").is_some()
+    }
+
+    fn les2_version(&self) -> Option<u64> {
+        first_item(self.get_raw_rlp("les")?)
+    }
+
+    fn client_version(&self) -> Option<String> {
+        self.client_info()?.ok().map(|info| info.version)
+    }
+}
+
+/// Setters of the Ethereum execution layer fields of a record.
+pub trait EthEnrBuilderExt {
+    /// Adds an `eth` field with the fork id.
+    fn set_eth_fork_id(&mut self, fork_id: ForkId) -> &mut Self;
+
+    /// Adds an empty `snap` field if `enabled` is set, and removes it otherwise.
+    fn set_snap_enabled(&mut self, enabled: bool) -> &mut Self;
+
+    /// Adds a `les` field with the vflux version.
+    fn set_les2_version(&mut self, version: u64) -> &mut Self;
+}
+
+impl<K: EnrKey> EthEnrBuilderExt for EnrBuilder<K> {
+    fn set_eth_fork_id(&mut self, fork_id: ForkId) -> &mut Self {
+        self.add_typed("eth", rlp::encode_list(&[fork_id]))
+    }
+
+    fn set_snap_enabled(&mut self, enabled: bool) -> &mut Self {
+        if enabled {
+            self.add_typed("snap", rlp::EMPTY_LIST_RLP.to_vec())
+        } else {
+            self.remove_typed("snap")
+        }
+    }
+
+    fn set_les2_version(&mut self, version: u64) -> &mut Self {
+        self.add_typed("les", rlp::encode_list(&[version]))
+    }
+}
+
+/// Decodes the first item of an RLP list.
+fn first_item<T: rlp::Decodable>(raw: &[u8]) -> Option<T> {
+    let rlp = Rlp::new(raw);
+    if !rlp.is_list() {
+        return None;
+    }
+    rlp.val_at(0).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, ClientInfo};
+
+    #[test]
+    fn test_eth_fields() {
+        let key = MockSigner(1);
+        let fork_id = ForkId {
+            hash: [0x9f, 0x3d, 0x22, 0x54],
+            next: 0,
+        };
+        let enr = EnrBuilder::new("v4")
+            .set_eth_fork_id(fork_id)
+            .set_snap_enabled(true)
+            .set_les2_version(1)
+            .client_info(&ClientInfo {
+                name: "geth".into(),
+                version: "1.14.8".into(),
+                build: None,
+            })
+            .build(&key)
+            .unwrap();
+
+        // the encodings of go-ethereum
+        assert_eq!(
+            enr.get_raw_rlp("eth"),
+            Some(&hex::decode("c7c6849f3d225480").unwrap()[..])
+        );
+        assert_eq!(enr.get_raw_rlp("snap"), Some(&[0xc0][..]));
+        assert_eq!(enr.get_raw_rlp("les"), Some(&[0xc1, 0x01][..]));
+
+        assert_eq!(enr.eth_fork_id(), Some(fork_id));
+        assert!(enr.snap_enabled());
+        assert_eq!(enr.les2_version(), Some(1));
+        assert_eq!(enr.client_version().as_deref(), Some("1.14.8"));
+
+        let enr = EnrBuilder::new("v4")
+            .set_snap_enabled(true)
+            .set_snap_enabled(false)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.eth_fork_id(), None);
+        assert!(!enr.snap_enabled());
+        assert_eq!(enr.les2_version(), None);
+        assert_eq!(enr.client_version(), None);
+    }
+
+    #[test]
+    fn test_eth_field_encodings() {
+        let key = MockSigner(1);
+        let cases: &[(&str, Option<ForkId>)] = &[
+            // trailing items are ignored
+            (
+                "cec6849f3d225480c6849f3d225401",
+                Some(ForkId {
+                    hash: [0x9f, 0x3d, 0x22, 0x54],
+                    next: 0,
+                }),
+            ),
+            // a fork id without a list
+            ("c6849f3d225480", None),
+            // a short fork hash
+            ("c6c5839f3d2280", None),
+            // a byte string
+            ("849f3d2254", None),
+        ];
+        for (raw, expected) in cases {
+            let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+            enr.insert_raw_rlp_list("eth", hex::decode(raw).unwrap(), &key)
+                .unwrap();
+            assert_eq!(enr.eth_fork_id(), *expected, "{raw}");
+        }
+    }
+}
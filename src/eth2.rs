@@ -0,0 +1,433 @@
+//! The fields Ethereum consensus clients add to their records. Enabled by the `eth2` feature.
+//!
+//! - `eth2`: the SSZ encoded `ENRForkID` of the node.
+//! - `attnets`: the attestation subnets the node is subscribed to, an SSZ `Bitvector[64]`.
+//! - `syncnets`: the sync committee subnets the node is subscribed to, an SSZ `Bitvector[4]`.
+//!
+//! The raw getters return the bytes of a field regardless of their length, the typed getters
+//! reject fields of an unexpected length with [`FieldError::InvalidLength`].
+
+use crate::{Enr, EnrBuilder, EnrError, EnrKey, FieldError};
+use alloc::{vec, vec::Vec};
+use core::convert::TryFrom;
+
+/// The number of attestation subnets.
+pub const ATTESTATION_SUBNET_COUNT: usize = 64;
+
+/// The number of sync committee subnets.
+pub const SYNC_COMMITTEE_SUBNET_COUNT: usize = 4;
+
+/// The `attnets` field, the attestation subnets a node is subscribed to. Subnet `i` is bit
+/// `i % 8` of byte `i / 8`, the little-endian bit order of SSZ bitvectors.
+///
+/// Together with [`Syncnets`] this is the subnet extension of records: [`Attnets::is_set`] and
+/// [`Attnets::set`] query and change the subscription to a subnet, [`Attnets::iter_set`] yields
+/// the subscribed subnets and [`Attnets::count`] counts them. [`Enr::attnets_bitfield`] and
+/// [`Enr::syncnets_bitfield`] read the bitfields of a record, [`Enr::set_attnets`] and
+/// [`Enr::set_syncnets`] update and re-sign it, and [`EnrBuilder::attnets`] and
+/// [`EnrBuilder::syncnets`] set them on a new record. There is no separate extension trait, as
+/// its `attnets` and `set_attnets` methods would be shadowed by the inherent methods of the same
+/// names.
+///
+/// ```rust
+/// use enr::Attnets;
+///
+/// let mut attnets = Attnets::default();
+/// attnets.set(3, true);
+/// attnets.set(60, true);
+/// assert!(attnets.is_set(3));
+/// assert_eq!(attnets.iter_set().collect::<Vec<_>>(), vec![3, 60]);
+/// assert_eq!(attnets.count(), 2);
+/// assert_eq!(attnets.0, [0x08, 0, 0, 0, 0, 0, 0, 0x10]);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Attnets(pub [u8; 8]);
+
+/// The attestation subnet bitfield, the type of the `attnets` field.
+pub type AttestationBitfield = Attnets;
+
+impl Attnets {
+    /// Returns `true` if the node is subscribed to the subnet. Subnets out of range are never
+    /// subscribed to.
+    #[must_use]
+    pub const fn is_set(&self, subnet: u8) -> bool {
+        let subnet = subnet as usize;
+        subnet < ATTESTATION_SUBNET_COUNT && self.0[subnet / 8] & (1 << (subnet % 8)) != 0
+    }
+
+    /// Subscribes to or unsubscribes from a subnet. Returns `false`, leaving the bitfield
+    /// unchanged, if the subnet is out of range.
+    pub const fn set(&mut self, subnet: u8, subscribed: bool) -> bool {
+        let subnet = subnet as usize;
+        if subnet >= ATTESTATION_SUBNET_COUNT {
+            return false;
+        }
+        let bit = 1 << (subnet % 8);
+        if subscribed {
+            self.0[subnet / 8] |= bit;
+        } else {
+            self.0[subnet / 8] &= !bit;
+        }
+        true
+    }
+
+    /// The subscribed subnets, in ascending order.
+    pub fn iter_set(&self) -> impl Iterator<Item = u8> + '_ {
+        (0_u8..)
+            .take(ATTESTATION_SUBNET_COUNT)
+            .filter(move |subnet| self.is_set(*subnet))
+    }
+
+    /// The number of subscribed subnets.
+    #[must_use]
+    pub const fn count(&self) -> usize {
+        u64::from_le_bytes(self.0).count_ones() as usize
+    }
+}
+
+impl TryFrom<&[u8]> for Attnets {
+    type Error = FieldError;
+
+    /// Reads the bitfield from the bytes of the `attnets` field, which must be exactly 8 bytes.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        exact_bytes("attnets", bytes).map(Self)
+    }
+}
+
+/// The `syncnets` field, the sync committee subnets a node is subscribed to. Subnet `i` is bit
+/// `i`, the upper four bits are unused.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Syncnets(pub u8);
+
+impl Syncnets {
+    /// Returns `true` if the node is subscribed to the subnet. Subnets out of range are never
+    /// subscribed to.
+    #[must_use]
+    pub const fn is_set(&self, subnet: u8) -> bool {
+        (subnet as usize) < SYNC_COMMITTEE_SUBNET_COUNT && self.0 & (1 << subnet) != 0
+    }
+
+    /// Subscribes to or unsubscribes from a subnet. Returns `false`, leaving the bitfield
+    /// unchanged, if the subnet is out of range.
+    pub const fn set(&mut self, subnet: u8, subscribed: bool) -> bool {
+        if subnet as usize >= SYNC_COMMITTEE_SUBNET_COUNT {
+            return false;
+        }
+        if subscribed {
+            self.0 |= 1 << subnet;
+        } else {
+            self.0 &= !(1 << subnet);
+        }
+        true
+    }
+
+    /// The subscribed subnets, in ascending order.
+    pub fn iter_set(&self) -> impl Iterator<Item = u8> + '_ {
+        (0_u8..)
+            .take(SYNC_COMMITTEE_SUBNET_COUNT)
+            .filter(move |subnet| self.is_set(*subnet))
+    }
+
+    /// The number of subscribed subnets.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.iter_set().count()
+    }
+}
+
+impl TryFrom<&[u8]> for Syncnets {
+    type Error = FieldError;
+
+    /// Reads the bitfield from the bytes of the `syncnets` field, which must be exactly 1 byte.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        exact_bytes("syncnets", bytes).map(|[byte]| Self(byte))
+    }
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// The raw `eth2` field, the SSZ encoded `ENRForkID` of the node.
+    #[must_use]
+    pub fn eth2(&self) -> Option<Vec<u8>> {
+        self.get_owned("eth2")
+    }
+
+    /// The raw `attnets` field.
+    #[must_use]
+    pub fn attnets(&self) -> Option<Vec<u8>> {
+        self.get_owned("attnets")
+    }
+
+    /// The raw `syncnets` field.
+    #[must_use]
+    pub fn syncnets(&self) -> Option<Vec<u8>> {
+        self.get_owned("syncnets")
+    }
+
+    /// The `attnets` field as a bitfield.
+    ///
+    /// # Errors
+    /// Fails with [`FieldError::InvalidLength`] if the field is not 8 bytes.
+    pub fn attnets_bitfield(&self) -> Result<Option<Attnets>, FieldError> {
+        self.get("attnets").map(Attnets::try_from).transpose()
+    }
+
+    /// The `syncnets` field as a bitfield.
+    ///
+    /// # Errors
+    /// Fails with [`FieldError::InvalidLength`] if the field is not 1 byte.
+    pub fn syncnets_bitfield(&self) -> Result<Option<Syncnets>, FieldError> {
+        self.get("syncnets").map(Syncnets::try_from).transpose()
+    }
+
+    /// Sets the `eth2` field. Returns the previous raw value, if any.
+    pub fn set_eth2(&mut self, fork_id: &[u8], key: &K) -> Result<Option<Vec<u8>>, EnrError> {
+        let previous = self.eth2();
+        self.insert_value("eth2", &fork_id.to_vec(), key)?;
+        Ok(previous)
+    }
+
+    /// Sets the `attnets` field. Returns the previous bitfield, if any was well-formed.
+    pub fn set_attnets(&mut self, attnets: Attnets, key: &K) -> Result<Option<Attnets>, EnrError> {
+        let previous = self.attnets_bitfield().ok().flatten();
+        self.insert_value("attnets", &attnets.0.to_vec(), key)?;
+        Ok(previous)
+    }
+
+    /// Sets the `syncnets` field. Returns the previous bitfield, if any was well-formed.
+    pub fn set_syncnets(
+        &mut self,
+        syncnets: Syncnets,
+        key: &K,
+    ) -> Result<Option<Syncnets>, EnrError> {
+        let previous = self.syncnets_bitfield().ok().flatten();
+        self.insert_value("syncnets", &vec![syncnets.0], key)?;
+        Ok(previous)
+    }
+}
+
+impl<K: EnrKey> EnrBuilder<K> {
+    /// Adds an `eth2` field, the SSZ encoded `ENRForkID` of the node, to the `ENRBuilder`.
+    pub fn eth2(&mut self, fork_id: &[u8]) -> &mut Self {
+        self.add_value("eth2".into(), fork_id.to_vec())
+    }
+
+    /// Adds an `attnets` field to the `ENRBuilder`.
+    pub fn attnets(&mut self, attnets: Attnets) -> &mut Self {
+        self.add_value("attnets".into(), attnets.0.to_vec())
+    }
+
+    /// Adds a `syncnets` field to the `ENRBuilder`.
+    pub fn syncnets(&mut self, syncnets: Syncnets) -> &mut Self {
+        self.add_value("syncnets".into(), vec![syncnets.0])
+    }
+}
+
+/// Reads the bytes of a field of exactly `N` bytes.
+fn exact_bytes<const N: usize>(key: &str, bytes: &[u8]) -> Result<[u8; N], FieldError> {
+    <[u8; N]>::try_from(bytes).map_err(|_| FieldError::InvalidLength {
+        key: key.into(),
+        expected: N,
+        got: bytes.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::MockSigner;
+
+    #[test]
+    fn test_bitfields() {
+        let mut attnets = Attnets::default();
+        assert!(attnets.set(0, true));
+        assert!(attnets.set(9, true));
+        assert!(attnets.set(63, true));
+        assert!(!attnets.set(64, true));
+        assert_eq!(attnets.0, [0x01, 0x02, 0, 0, 0, 0, 0, 0x80]);
+        assert!(attnets.is_set(9));
+        assert!(!attnets.is_set(8));
+        assert!(!attnets.is_set(64));
+        assert_eq!(attnets.iter_set().collect::<Vec<_>>(), vec![0, 9, 63]);
+        assert!(attnets.set(9, false));
+        assert!(!attnets.is_set(9));
+
+        let mut syncnets = Syncnets::default();
+        assert!(syncnets.set(2, true));
+        assert!(!syncnets.set(4, true));
+        assert_eq!(syncnets, Syncnets(0x04));
+        assert_eq!(syncnets.iter_set().collect::<Vec<_>>(), vec![2]);
+        // the unused upper bits are not subnets
+        assert_eq!(Syncnets(0xf0).iter_set().count(), 0);
+    }
+
+    #[test]
+    fn test_attnets_wire_vectors() {
+        // SSZ bitvectors are little-endian: subnet 0 is the least significant bit of byte 0
+        let vectors: &[(&str, &[u8])] = &[
+            ("0000000000000000", &[]),
+            ("0000020000000000", &[17]),
+            ("0100000000000080", &[0, 63]),
+            ("8000000000000000", &[7]),
+            ("0001000000000000", &[8]),
+            ("ff00000000000000", &[0, 1, 2, 3, 4, 5, 6, 7]),
+        ];
+        for (bytes, subnets) in vectors {
+            let bytes = hex::decode(bytes).unwrap();
+            let attnets = AttestationBitfield::try_from(&bytes[..]).unwrap();
+            assert_eq!(attnets.iter_set().collect::<Vec<_>>(), *subnets);
+            assert_eq!(attnets.count(), subnets.len());
+            for subnet in (0_u8..).take(ATTESTATION_SUBNET_COUNT + 1) {
+                assert_eq!(attnets.is_set(subnet), subnets.contains(&subnet));
+            }
+
+            let mut built = Attnets::default();
+            for subnet in *subnets {
+                built.set(*subnet, true);
+            }
+            assert_eq!(built.0.to_vec(), bytes);
+        }
+        assert_eq!(Attnets([0xff; 8]).count(), ATTESTATION_SUBNET_COUNT);
+
+        // a field of the wrong length is rejected, never truncated or padded
+        for len in &[0, 7, 9] {
+            assert_eq!(
+                Attnets::try_from(&vec![0x01; *len][..]),
+                Err(FieldError::InvalidLength {
+                    key: "attnets".into(),
+                    expected: 8,
+                    got: *len,
+                })
+            );
+        }
+        // the 4 sync committee subnets are the low bits of a single byte
+        let vectors: &[(u8, &[u8])] = &[
+            (0x00, &[]),
+            (0x01, &[0]),
+            (0x05, &[0, 2]),
+            (0x0f, &[0, 1, 2, 3]),
+        ];
+        for (byte, subnets) in vectors {
+            let syncnets = Syncnets::try_from(&[*byte][..]).unwrap();
+            assert_eq!(syncnets.iter_set().collect::<Vec<_>>(), *subnets);
+            assert_eq!(syncnets.count(), subnets.len());
+            for subnet in (0_u8..).take(SYNC_COMMITTEE_SUBNET_COUNT + 1) {
+                assert_eq!(syncnets.is_set(subnet), subnets.contains(&subnet));
+            }
+        }
+        assert!(Syncnets::try_from(&[0x05, 0x00][..]).is_err());
+
+        let key = MockSigner(1);
+        let mut enr = EnrBuilder::new("v4")
+            .attnets(Attnets([0, 0, 0x02, 0, 0, 0, 0, 0]))
+            .build(&key)
+            .unwrap();
+        assert!(enr.attnets_bitfield().unwrap().unwrap().is_set(17));
+        enr.insert_value("attnets", &vec![0, 0, 0x02, 0, 0, 0, 0, 0, 0], &key)
+            .unwrap();
+        assert_eq!(
+            enr.attnets_bitfield(),
+            Err(FieldError::InvalidLength {
+                key: "attnets".into(),
+                expected: 8,
+                got: 9,
+            })
+        );
+    }
+
+    #[test]
+    fn test_eth2_fields() {
+        let key = MockSigner(1);
+        let fork_id = hex::decode("b5303f2a010000000022010000000000").unwrap();
+        let mut attnets = Attnets::default();
+        attnets.set(5, true);
+        let mut enr = EnrBuilder::new("v4")
+            .eth2(&fork_id)
+            .attnets(attnets)
+            .syncnets(Syncnets(0x01))
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.eth2(), Some(fork_id));
+        assert_eq!(enr.attnets(), Some(vec![0x20, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(enr.attnets_bitfield(), Ok(Some(attnets)));
+        assert_eq!(enr.syncnets_bitfield(), Ok(Some(Syncnets(0x01))));
+
+        attnets.set(6, true);
+        assert_eq!(
+            enr.set_attnets(attnets, &key).unwrap(),
+            Some(Attnets([0x20, 0, 0, 0, 0, 0, 0, 0]))
+        );
+        assert_eq!(
+            enr.set_syncnets(Syncnets(0x03), &key).unwrap(),
+            Some(Syncnets(0x01))
+        );
+        assert_eq!(enr.attnets_bitfield(), Ok(Some(attnets)));
+        assert_eq!(enr.syncnets(), Some(vec![0x03]));
+        assert_eq!(enr.seq(), 3);
+
+        // the raw getters accept any length, the typed getters do not
+        enr.insert_value("attnets", &vec![0xff_u8; 4], &key)
+            .unwrap();
+        assert_eq!(enr.attnets(), Some(vec![0xff; 4]));
+        assert_eq!(
+            enr.attnets_bitfield(),
+            Err(FieldError::InvalidLength {
+                key: "attnets".into(),
+                expected: 8,
+                got: 4
+            })
+        );
+        assert_eq!(enr.set_attnets(attnets, &key).unwrap(), None);
+
+        let enr = EnrBuilder::new("v4").build(&key).unwrap();
+        assert_eq!(enr.eth2(), None);
+        assert_eq!(enr.attnets_bitfield(), Ok(None));
+        assert_eq!(enr.syncnets_bitfield(), Ok(None));
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "libsecp256k1")]
+mod mainnet_tests {
+    use super::*;
+    use crate::secp256k1;
+
+    type DefaultEnr = Enr<secp256k1::SecretKey>;
+
+    /// A mainnet boot node of Lighthouse, after the Altair fork was scheduled.
+    const LIGHTHOUSE_BOOT_ENR: &str = "enr:-Le4QPUXJS2BTORXxyx2Ia-9ae4YqA_JWX3ssj4E_J-3z1A-HmFGrU8BpvpqhNabayXeOZ2Nq_sbeDgtzMJpLLnXFgAChGV0aDKQtTA_KgEAAAAAIgEAAAAAAIJpZIJ2NIJpcISsaa0Zg2lwNpAkAIkHAAAAAPA8kv_-awoTiXNlY3AyNTZrMaEDHAD2JKYevx89W0CcFJFiskdcEzkH_Wdv9iW42qLK79ODdWRwgiMohHVkcDaCI4I";
+
+    /// A mainnet boot node of Prysm, at genesis.
+    const PRYSM_BOOT_ENR: &str = "enr:-Ku4QImhMc1z8yCiNJ1TyUxdcfNucje3BGwEHzodEZUan8PherEo4sF7pPHPSIB1NNuSg5fZy7qFsjmUKs2ea1Whi0EBh2F0dG5ldHOIAAAAAAAAAACEZXRoMpD1pf1CAAAAAP__________gmlkgnY0gmlwhBLf22SJc2VjcDI1NmsxoQOVphkDqal4QzPMksc5wnpuC3gvSC8AfbFOnZY_On34wIN1ZHCCIyg";
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_lighthouse_boot_node() {
+        let enr = LIGHTHOUSE_BOOT_ENR.parse::<DefaultEnr>().unwrap();
+        assert!(enr.verify());
+        // the mainnet genesis fork digest, and the Altair fork version and epoch
+        assert_eq!(
+            enr.eth2().map(hex::encode).as_deref(),
+            Some("b5303f2a010000000022010000000000")
+        );
+        assert_eq!(enr.attnets(), None);
+        assert_eq!(enr.attnets_bitfield(), Ok(None));
+        assert_eq!(enr.syncnets_bitfield(), Ok(None));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_prysm_boot_node() {
+        let enr = PRYSM_BOOT_ENR.parse::<DefaultEnr>().unwrap();
+        assert!(enr.verify());
+        // a fork digest with no fork scheduled
+        assert_eq!(
+            enr.eth2().map(hex::encode).as_deref(),
+            Some("f5a5fd4200000000ffffffffffffffff")
+        );
+        // boot nodes do not subscribe to subnets
+        let attnets = enr.attnets_bitfield().unwrap().unwrap();
+        assert_eq!(attnets, Attnets::default());
+        assert_eq!(attnets.iter_set().count(), 0);
+        assert_eq!(enr.syncnets(), None);
+    }
+}
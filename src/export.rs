@@ -0,0 +1,457 @@
+//! Exports and imports of node databases.
+//!
+//! [`EnrWriter`] writes records in one of the formats of [`EnrFormat`], for configuration files,
+//! bootnode lists or debugging, and [`EnrReader`] reads them back. The CSV format spells out the
+//! main fields of each record next to its text form, which is the column read back.
+
+use crate::{CombinedKey, Enr, EnrKey, EnrPublicKey};
+use std::{
+    io::{self, Read, Write},
+    mem,
+};
+
+/// The header of the CSV format.
+const CSV_HEADER: &str = "node_id_hex,ip4,ip6,tcp,udp,quic,seq,key_type,enr";
+
+/// The format of an export of records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnrFormat {
+    /// The text form of each record on a line of its own.
+    TextOnePerLine,
+
+    /// A JSON array of the text forms of the records. Requires the `json` feature.
+    #[cfg(feature = "json")]
+    JsonArray,
+
+    /// CSV with a header and a row per record, of the columns `node_id_hex`, `ip4`, `ip6`,
+    /// `tcp`, `udp`, `quic`, `seq`, `key_type` and `enr`, the text form of the record. Missing
+    /// fields are left empty.
+    CsvWithFields,
+}
+
+/// Writes records in an [`EnrFormat`].
+///
+/// The writer must be finished with [`EnrWriter::finish`], which completes the JSON array.
+pub struct EnrWriter<W: Write> {
+    /// The destination of the export.
+    writer: W,
+
+    /// The format of the export.
+    format: EnrFormat,
+
+    /// The number of records written so far.
+    written: usize,
+}
+
+impl<W: Write> EnrWriter<W> {
+    /// Constructs a writer of records in the given format.
+    pub const fn new(writer: W, format: EnrFormat) -> Self {
+        Self {
+            writer,
+            format,
+            written: 0,
+        }
+    }
+
+    /// Writes a record.
+    pub fn write<K: EnrKey>(&mut self, enr: &Enr<K>) -> io::Result<()> {
+        let first = self.written == 0;
+        match self.format {
+            EnrFormat::TextOnePerLine => writeln!(self.writer, "{}", enr.to_base64())?,
+            #[cfg(feature = "json")]
+            EnrFormat::JsonArray => {
+                let separator = if first { "[" } else { "," };
+                let text = serde_json::Value::from(enr.to_base64());
+                write!(self.writer, "{separator}\n  {text}")?;
+            }
+            EnrFormat::CsvWithFields => {
+                if first {
+                    writeln!(self.writer, "{CSV_HEADER}")?;
+                }
+                writeln!(
+                    self.writer,
+                    "{},{},{},{},{},{},{},{},{}",
+                    enr.node_id().to_hex(),
+                    cell(enr.ip()),
+                    cell(enr.ip6()),
+                    cell(enr.tcp4().or_else(|| enr.tcp6())),
+                    cell(enr.udp4().or_else(|| enr.udp6())),
+                    cell(enr.quic().or_else(|| enr.quic6())),
+                    enr.seq(),
+                    enr.public_key().enr_key(),
+                    enr.to_base64(),
+                )?;
+            }
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Writes each of the records.
+    pub fn write_all<'a, K: EnrKey + 'a>(
+        &mut self,
+        enrs: impl IntoIterator<Item = &'a Enr<K>>,
+    ) -> io::Result<()> {
+        enrs.into_iter().try_for_each(|enr| self.write(enr))
+    }
+
+    /// Completes the export, writing the header of an empty CSV export and the end of the JSON
+    /// array, and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        match (self.format, self.written) {
+            #[cfg(feature = "json")]
+            (EnrFormat::JsonArray, 0) => writeln!(self.writer, "[]")?,
+            #[cfg(feature = "json")]
+            (EnrFormat::JsonArray, _) => writeln!(self.writer, "\n]")?,
+            (EnrFormat::CsvWithFields, 0) => writeln!(self.writer, "{CSV_HEADER}")?,
+            (EnrFormat::TextOnePerLine | EnrFormat::CsvWithFields, _) => {}
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads records written by an [`EnrWriter`] in the same [`EnrFormat`].
+pub struct EnrReader<R: Read> {
+    /// The source of the export.
+    reader: R,
+
+    /// The format of the export.
+    format: EnrFormat,
+}
+
+impl<R: Read> EnrReader<R> {
+    /// Constructs a reader of records in the given format.
+    pub const fn new(reader: R, format: EnrFormat) -> Self {
+        Self { reader, format }
+    }
+
+    /// Reads all the records, verifying their signatures.
+    ///
+    /// Blank lines of the text and CSV formats are skipped. CSV cells may be quoted, see
+    /// [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180). Malformed input fails with
+    /// [`io::ErrorKind::InvalidData`], naming the offending line or JSON element.
+    pub fn read_all(mut self) -> io::Result<Vec<Enr<CombinedKey>>> {
+        let mut input = String::new();
+        self.reader.read_to_string(&mut input)?;
+        match self.format {
+            EnrFormat::TextOnePerLine => lines(&input)
+                .map(|(line, text)| parse(text).map_err(|e| invalid(format!("line {line}: {e}"))))
+                .collect(),
+            #[cfg(feature = "json")]
+            EnrFormat::JsonArray => parse_json_array(&input),
+            EnrFormat::CsvWithFields => {
+                let mut rows = csv_rows(&input)?.into_iter();
+                let column = rows
+                    .next()
+                    .and_then(|(_, header)| header.iter().position(|name| name == "enr"))
+                    .ok_or_else(|| invalid("missing the enr column of the CSV header".into()))?;
+                rows.map(|(line, row)| {
+                    let text = row.get(column).map_or("", String::as_str);
+                    parse(text).map_err(|e| invalid(format!("line {line}: {e}")))
+                })
+                .collect()
+            }
+        }
+    }
+}
+
+/// A CSV cell of an optional field, empty if the field is missing.
+fn cell<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// The trimmed, non-blank lines of the input, numbered from 1.
+fn lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+}
+
+/// The rows of CSV input, numbered by the line each starts on, of trimmed cells. Quoted cells may
+/// hold commas, line breaks and quotes, which are doubled. Blank lines are skipped.
+fn csv_rows(input: &str) -> io::Result<Vec<(usize, Vec<String>)>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let (mut line, mut row_line) = (1, 1);
+    let mut quoted = false;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    cell.push('"');
+                }
+                '"' => quoted = false,
+                _ => {
+                    line += usize::from(c == '\n');
+                    cell.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if cell.trim().is_empty() => {
+                cell.clear();
+                quoted = true;
+            }
+            ',' => row.push(take_cell(&mut cell)),
+            '\n' => {
+                if !row.is_empty() || !cell.trim().is_empty() {
+                    row.push(take_cell(&mut cell));
+                    rows.push((row_line, mem::take(&mut row)));
+                }
+                cell.clear();
+                line += 1;
+                row_line = line;
+            }
+            _ => cell.push(c),
+        }
+    }
+    if quoted {
+        return Err(invalid(format!(
+            "line {row_line}: unterminated quoted cell"
+        )));
+    }
+    if !row.is_empty() || !cell.trim().is_empty() {
+        row.push(take_cell(&mut cell));
+        rows.push((row_line, row));
+    }
+    Ok(rows)
+}
+
+/// Takes the trimmed content of a CSV cell, leaving it empty for the next cell.
+fn take_cell(cell: &mut String) -> String {
+    let content = cell.trim().to_string();
+    cell.clear();
+    content
+}
+
+/// Parses a JSON array of text forms of records.
+#[cfg(feature = "json")]
+fn parse_json_array(json: &str) -> io::Result<Vec<Enr<CombinedKey>>> {
+    let texts: Vec<String> = serde_json::from_str(json)
+        .map_err(|e| invalid(format!("expected a JSON array of strings: {e}")))?;
+    texts
+        .iter()
+        .enumerate()
+        .map(|(index, text)| parse(text).map_err(|e| invalid(format!("element {index}: {e}"))))
+        .collect()
+}
+
+/// Parses the text form of a record.
+fn parse(text: &str) -> Result<Enr<CombinedKey>, String> {
+    text.parse()
+}
+
+/// An error of malformed input.
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EnrBuilder;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    fn records() -> Vec<Enr<CombinedKey>> {
+        let secp256k1 = CombinedKey::generate_secp256k1();
+        let ed25519 = CombinedKey::generate_ed25519();
+        vec![
+            EnrBuilder::new("v4")
+                .ip4(Ipv4Addr::new(192, 0, 2, 1))
+                .tcp(30303)
+                .udp(30304)
+                .build(&secp256k1)
+                .unwrap(),
+            EnrBuilder::new("v4")
+                .ip6(Ipv6Addr::LOCALHOST)
+                .udp6(9000)
+                .quic6(9001)
+                .build(&ed25519)
+                .unwrap(),
+            EnrBuilder::new("v4").build(&secp256k1).unwrap(),
+        ]
+    }
+
+    fn export(format: EnrFormat, enrs: &[Enr<CombinedKey>]) -> String {
+        let mut writer = EnrWriter::new(Vec::new(), format);
+        writer.write_all(enrs).unwrap();
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    fn import(format: EnrFormat, text: &str) -> io::Result<Vec<Enr<CombinedKey>>> {
+        EnrReader::new(text.as_bytes(), format).read_all()
+    }
+
+    /// The formats available with the enabled features.
+    fn formats() -> Vec<EnrFormat> {
+        vec![
+            EnrFormat::TextOnePerLine,
+            #[cfg(feature = "json")]
+            EnrFormat::JsonArray,
+            EnrFormat::CsvWithFields,
+        ]
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_export_roundtrip() {
+        let enrs = records();
+        for format in formats() {
+            let text = export(format, &enrs);
+            assert_eq!(import(format, &text).unwrap(), enrs, "{format:?}");
+            assert_eq!(import(format, &export(format, &[])).unwrap(), vec![]);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_export_formats() {
+        let enrs = records();
+        let text = export(EnrFormat::TextOnePerLine, &enrs);
+        assert_eq!(
+            text.lines().collect::<Vec<_>>(),
+            [
+                enrs[0].to_base64(),
+                enrs[1].to_base64(),
+                enrs[2].to_base64()
+            ]
+        );
+
+        let csv = export(EnrFormat::CsvWithFields, &enrs);
+        let rows: Vec<_> = csv.lines().collect();
+        assert_eq!(rows[0], CSV_HEADER);
+        assert_eq!(
+            rows[1],
+            format!(
+                "{},192.0.2.1,,30303,30304,,1,secp256k1,{}",
+                enrs[0].node_id().to_hex(),
+                enrs[0].to_base64()
+            )
+        );
+        assert_eq!(
+            rows[2],
+            format!(
+                "{},,::1,,9000,9001,1,ed25519,{}",
+                enrs[1].node_id().to_hex(),
+                enrs[1].to_base64()
+            )
+        );
+        assert_eq!(
+            export(EnrFormat::CsvWithFields, &[]),
+            format!("{CSV_HEADER}\n")
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_json_array() {
+        let enrs = records();
+        let json = export(EnrFormat::JsonArray, &enrs[..2]);
+        assert_eq!(
+            json,
+            format!(
+                "[\n  \"{}\",\n  \"{}\"\n]\n",
+                enrs[0].to_base64(),
+                enrs[1].to_base64()
+            )
+        );
+        assert_eq!(export(EnrFormat::JsonArray, &[]), "[]\n");
+
+        // any JSON array of the text forms is read, however it is laid out or escaped
+        let text = enrs[0].to_base64();
+        let escaped = text.replace('-', "\\u002d").replace('_', "\\u005F");
+        assert_ne!(escaped, text);
+        let json = format!("[\"{escaped}\" ,\"{}\"]", enrs[1].to_base64());
+        assert_eq!(import(EnrFormat::JsonArray, &json).unwrap(), enrs[..2]);
+
+        let enr = &text;
+        let error = |json: &str| import(EnrFormat::JsonArray, json).unwrap_err();
+        assert_eq!(error(enr).kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            error(&format!("[\"{enr}\", 1]")).kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            error(&format!("[\"{enr}\"")).kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert!(error(&format!("[\"{enr}\", \"enr:-xyz\"]"))
+            .to_string()
+            .starts_with("element 1: "));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_csv_quoting() {
+        let enrs = records();
+        let csv = format!(
+            "\"note, with a comma\",\"enr\"\r\n\"a \"\"quoted\"\"\nnote\",\"{}\"\r\n\n,{}\n",
+            enrs[0].to_base64(),
+            enrs[1].to_base64()
+        );
+        assert_eq!(
+            csv_rows(&csv).unwrap(),
+            vec![
+                (1, vec!["note, with a comma".into(), "enr".into()]),
+                (2, vec!["a \"quoted\"\nnote".into(), enrs[0].to_base64()]),
+                (5, vec![String::new(), enrs[1].to_base64()]),
+            ]
+        );
+        assert_eq!(import(EnrFormat::CsvWithFields, &csv).unwrap(), enrs[..2]);
+
+        assert!(import(EnrFormat::CsvWithFields, "enr\n\"enr:-xyz")
+            .unwrap_err()
+            .to_string()
+            .starts_with("line 2: "));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_import_errors() {
+        let enr = records().remove(0).to_base64();
+        let kind = |format, text: &str| import(format, text).unwrap_err().kind();
+        assert_eq!(
+            kind(EnrFormat::TextOnePerLine, "enr:-xyz"),
+            io::ErrorKind::InvalidData
+        );
+        assert!(
+            import(EnrFormat::TextOnePerLine, &format!("\n{enr}\nenr:-xyz"))
+                .unwrap_err()
+                .to_string()
+                .starts_with("line 3: ")
+        );
+
+        assert_eq!(
+            kind(EnrFormat::CsvWithFields, &enr),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            kind(EnrFormat::CsvWithFields, &format!("enr\n{enr}\n,")),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            import(EnrFormat::CsvWithFields, &format!("seq,enr\n1,{enr}\n")).unwrap()[0]
+                .to_base64(),
+            enr
+        );
+
+        // records of unknown schemes are not imported
+        let forged = crate::forge_unknown_scheme(&records()[0]);
+        for format in formats() {
+            let text = export(format, std::slice::from_ref(&forged));
+            assert_eq!(
+                kind(format, &text),
+                io::ErrorKind::InvalidData,
+                "{format:?}"
+            );
+        }
+    }
+}
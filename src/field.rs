@@ -0,0 +1,603 @@
+//! Typed getters for the values of a record.
+//!
+//! EIP-778 requires integers to be RLP-encoded without leading zero bytes. The typed getters
+//! enforce this, and distinguish an absent key, `Ok(None)`, from a value that is present but
+//! malformed, `Err(FieldError)`.
+
+use crate::{Enr, EnrError, EnrKey};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::convert::TryFrom;
+use rlp::{DecoderError, Rlp};
+
+/// An error decoding the value of a record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldError {
+    /// The value is not a well-formed RLP byte string.
+    InvalidRlp(DecoderError),
+    /// The integer is encoded with leading zero bytes.
+    NonMinimalInteger,
+    /// The integer does not fit the requested type.
+    IntegerOverflow,
+    /// The value is not valid UTF-8.
+    InvalidUtf8(core::str::Utf8Error),
+    /// The `id` is not the name of a known identity scheme.
+    UnknownIdentityScheme(String),
+    /// The value of a well-known key has the wrong length.
+    InvalidLength {
+        /// The key of the value.
+        key: String,
+        /// The expected length, or maximum length for integers, in bytes.
+        expected: usize,
+        /// The length of the value in bytes.
+        got: usize,
+    },
+}
+
+/// The identity schemes known to the crate. Records of a custom scheme, see
+/// [`crate::IdentityScheme`], may carry its name as well.
+const IDENTITY_SCHEMES: [&str; 1] = ["v4"];
+
+/// A sequence number within this distance of `u64::MAX` raises
+/// [`EnrWarning::SequenceNumberNearMax`].
+pub const SEQ_WARNING_MARGIN: u64 = 1000;
+
+/// A property of a valid record that may surprise its peers, see [`Enr::validate`] and
+/// [`Enr::rotate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnrWarning {
+    /// The sequence number is within [`SEQ_WARNING_MARGIN`] of `u64::MAX`, the node updates its
+    /// record far too often. Updates fail with [`EnrError::SequenceNumberTooHigh`] once
+    /// `u64::MAX` is reached.
+    ///
+    /// [`EnrError::SequenceNumberTooHigh`]: crate::EnrError::SequenceNumberTooHigh
+    SequenceNumberNearMax(u64),
+    /// The record was re-signed with a key of a different type. Peers see a new node identity,
+    /// signed by a different signature algorithm.
+    PublicKeyTypeChanged {
+        /// The public key field of the previous record, e.g. `secp256k1`.
+        previous: String,
+        /// The public key field of the new record, e.g. `ed25519`.
+        new: String,
+    },
+    /// A port is in the range 1-1023 reserved for well-known services, which usually requires
+    /// elevated privileges to listen on.
+    PrivilegedPort {
+        /// The key of the port field, e.g. `tcp`.
+        key: String,
+        /// The port.
+        port: u16,
+    },
+}
+
+/// The keys of the port fields.
+pub const PORT_KEYS: [&str; 6] = ["tcp", "tcp6", "udp", "udp6", "quic", "quic6"];
+
+/// The highest port of the range reserved for well-known services.
+const MAX_PRIVILEGED_PORT: u16 = 1023;
+
+/// A field with a fixed key and a typed value, read and written with [`Enr::get_field`],
+/// [`Enr::set_field`] and [`EnrBuilder::field`]. The well-known fields are defined in
+/// [`crate::fields`].
+///
+/// An application defines its own field by implementing the trait for the type of its value:
+///
+/// ```rust
+/// use enr::{rlp, EnrField};
+///
+/// /// The shard the node serves.
+/// struct Shard(u16);
+///
+/// impl EnrField for Shard {
+///     const KEY: &'static str = "shard";
+///     type Error = rlp::DecoderError;
+///
+///     fn encode(&self) -> Vec<u8> {
+///         rlp::encode(&self.0).into()
+///     }
+///
+///     fn decode(raw: &[u8]) -> Result<Self, Self::Error> {
+///         rlp::decode(raw).map(Shard)
+///     }
+/// }
+/// ```
+///
+/// [`EnrBuilder::field`]: crate::EnrBuilder::field
+pub trait EnrField: Sized {
+    /// The key of the field.
+    const KEY: &'static str;
+
+    /// The error decoding a malformed value.
+    type Error;
+
+    /// Encodes the value as a single RLP item, which may be a list.
+    fn encode(&self) -> Vec<u8>;
+
+    /// Decodes the RLP-encoded value of the field.
+    fn decode(raw: &[u8]) -> Result<Self, Self::Error>;
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// Reads a typed field. Returns `None` if the field is not set, and an error if its value is
+    /// malformed.
+    #[must_use]
+    pub fn get_field<F: EnrField>(&self) -> Option<Result<F, F::Error>> {
+        self.get_raw_rlp(F::KEY).map(F::decode)
+    }
+
+    /// Sets a typed field. Returns the previous value, if any was well-formed.
+    ///
+    /// # Errors
+    /// Fails if the encoded value is not a single well-formed RLP item, or is a malformed value
+    /// of a well-known key, see [`Enr::insert_raw_rlp_list`].
+    pub fn set_field<F: EnrField>(&mut self, value: &F, key: &K) -> Result<Option<F>, EnrError> {
+        let previous = self.get_field::<F>().and_then(Result::ok);
+        self.insert_raw_rlp_list(F::KEY, value.encode(), key)?;
+        Ok(previous)
+    }
+
+    /// Validates the values of the well-known keys of the record, see [`Enr::validate_fields`],
+    /// and returns the warnings about the record, a sequence number close to overflowing or a
+    /// privileged port.
+    ///
+    /// # Errors
+    /// Fails if the value of a well-known key is malformed.
+    pub fn validate(&self) -> Result<Vec<EnrWarning>, FieldError> {
+        self.validate_fields()?;
+        let mut warnings = Vec::new();
+        if self.seq() >= u64::MAX - SEQ_WARNING_MARGIN {
+            warnings.push(EnrWarning::SequenceNumberNearMax(self.seq()));
+        }
+        for key in &PORT_KEYS {
+            if let Some(port) = self.get_u16(key)? {
+                if port <= MAX_PRIVILEGED_PORT {
+                    warnings.push(EnrWarning::PrivilegedPort {
+                        key: (*key).to_string(),
+                        port,
+                    });
+                }
+            }
+        }
+        Ok(warnings)
+    }
+
+    /// Validates the values of the well-known keys of the record, see [`EnrDecodeOptions::strict`].
+    /// The `id` of a record may also name its custom identity scheme.
+    ///
+    /// [`EnrDecodeOptions::strict`]: crate::EnrDecodeOptions::strict
+    pub fn validate_fields(&self) -> Result<(), FieldError> {
+        let scheme = self.identity_scheme();
+        self.content.iter().try_for_each(|(key, raw)| match scheme {
+            Some(scheme) if key == "id" && rlp_bytes(raw) == Ok(scheme.as_bytes()) => Ok(()),
+            _ => validate(key, raw),
+        })
+    }
+
+    /// Reads a minimally encoded unsigned integer of at most 2 bytes.
+    pub fn get_u16(&self, key: impl AsRef<str>) -> Result<Option<u16>, FieldError> {
+        self.get_uint(key.as_ref(), 2)?
+            .map(|value| u16::try_from(value).map_err(|_| FieldError::IntegerOverflow))
+            .transpose()
+    }
+
+    /// Reads a minimally encoded unsigned integer of at most 4 bytes.
+    pub fn get_u32(&self, key: impl AsRef<str>) -> Result<Option<u32>, FieldError> {
+        self.get_uint(key.as_ref(), 4)?
+            .map(|value| u32::try_from(value).map_err(|_| FieldError::IntegerOverflow))
+            .transpose()
+    }
+
+    /// Reads a minimally encoded unsigned integer of at most 8 bytes.
+    pub fn get_u64(&self, key: impl AsRef<str>) -> Result<Option<u64>, FieldError> {
+        self.get_uint(key.as_ref(), 8)
+    }
+
+    /// Reads a port, a minimally encoded integer of at most 2 bytes.
+    ///
+    /// Unless `strict` is set, leading zero bytes, which some implementations emit, are
+    /// accepted. The port getters, such as [`Enr::udp4`], are always strict.
+    pub fn get_port(&self, key: impl AsRef<str>, strict: bool) -> Result<Option<u16>, FieldError> {
+        if strict {
+            return self.get_u16(key);
+        }
+        self.get_raw_rlp(key)
+            .map(|raw| {
+                let bytes = rlp_bytes(raw)?;
+                let significant = &bytes[bytes.iter().take_while(|byte| **byte == 0).count()..];
+                match *significant {
+                    [] => Ok(0),
+                    [low] => Ok(u16::from(low)),
+                    [high, low] => Ok(u16::from_be_bytes([high, low])),
+                    _ => Err(FieldError::IntegerOverflow),
+                }
+            })
+            .transpose()
+    }
+
+    /// Reads a UTF-8 encoded string.
+    pub fn get_utf8(&self, key: impl AsRef<str>) -> Result<Option<&str>, FieldError> {
+        self.get_raw_rlp(key)
+            .map(|raw| core::str::from_utf8(rlp_bytes(raw)?).map_err(FieldError::InvalidUtf8))
+            .transpose()
+    }
+
+    /// Reads a minimally encoded unsigned integer of at most `size` bytes.
+    fn get_uint(&self, key: &str, size: usize) -> Result<Option<u64>, FieldError> {
+        self.get_raw_rlp(key)
+            .map(|raw| decode_uint(raw, size))
+            .transpose()
+    }
+}
+
+/// Validates the RLP-encoded value of a well-known key: `ip` and `ip6` addresses are 4 and 16
+/// bytes, ports are minimally encoded integers of at most 2 bytes, `id` names a known identity
+/// scheme and the `secp256k1` and `ed25519` public keys are 33 and 32 bytes. Other keys are not
+/// validated.
+pub fn validate(key: &str, raw: &[u8]) -> Result<(), FieldError> {
+    let expected = match key {
+        "ip" => 4,
+        "ip6" => 16,
+        "secp256k1" => 33,
+        "ed25519" => 32,
+        "tcp" | "udp" | "tcp6" | "udp6" | "quic" | "quic6" => {
+            return match decode_uint(raw, 2) {
+                Err(FieldError::IntegerOverflow) => Err(FieldError::InvalidLength {
+                    key: key.into(),
+                    expected: 2,
+                    got: rlp_bytes(raw)?.len(),
+                }),
+                result => result.map(|_| ()),
+            };
+        }
+        "id" => {
+            let id = core::str::from_utf8(rlp_bytes(raw)?).map_err(FieldError::InvalidUtf8)?;
+            if !IDENTITY_SCHEMES.contains(&id) {
+                return Err(FieldError::UnknownIdentityScheme(id.into()));
+            }
+            return Ok(());
+        }
+        _ => return Ok(()),
+    };
+    let got = rlp_bytes(raw)?.len();
+    if got != expected {
+        return Err(FieldError::InvalidLength {
+            key: key.into(),
+            expected,
+            got,
+        });
+    }
+    Ok(())
+}
+
+/// Decodes a minimally encoded, RLP-encoded unsigned integer of at most `size` bytes.
+pub fn decode_uint(raw: &[u8], size: usize) -> Result<u64, FieldError> {
+    let bytes = rlp_bytes(raw)?;
+    if bytes.first() == Some(&0) {
+        return Err(FieldError::NonMinimalInteger);
+    }
+    if bytes.len() > size {
+        return Err(FieldError::IntegerOverflow);
+    }
+    Ok(bytes
+        .iter()
+        .fold(0, |value, byte| (value << 8) | u64::from(*byte)))
+}
+
+/// Returns the payload of an RLP-encoded byte string.
+pub fn rlp_bytes(raw: &[u8]) -> Result<&[u8], FieldError> {
+    let rlp = Rlp::new(raw);
+    if rlp.is_list() {
+        return Err(FieldError::InvalidRlp(DecoderError::RlpExpectedToBeData));
+    }
+    rlp.data().map_err(FieldError::InvalidRlp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder, EnrError};
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    #[test]
+    fn test_typed_getters() {
+        let key = MockSigner(1);
+        let mut enr = EnrBuilder::new("v4").udp(80).build(&key).unwrap();
+        enr.insert_raw_rlp("tcp", rlp::encode(&0_u16).to_vec(), &key)
+            .unwrap();
+
+        // the builder encodes ports minimally
+        assert_eq!(enr.get_raw_rlp("udp"), Some(&[0x50][..]));
+        assert_eq!(enr.get_raw_rlp("tcp"), Some(&[0x80][..]));
+        assert_eq!(enr.get_u16("udp"), Ok(Some(80)));
+        assert_eq!(enr.get_u16("tcp"), Ok(Some(0)));
+        assert_eq!(enr.get_u16("udp6"), Ok(None));
+        assert_eq!(enr.udp(), Some(80));
+        assert_eq!(enr.get_utf8("id"), Ok(Some("v4")));
+
+        // non-minimal encodings are rejected, also when inserted
+        assert!(matches!(
+            enr.insert_raw_rlp("udp", vec![0x82, 0x00, 0x50], &key),
+            Err(EnrError::InvalidField(FieldError::NonMinimalInteger))
+        ));
+        enr.content
+            .insert("udp".into(), vec![0x82, 0x00, 0x50].into());
+        assert_eq!(enr.get_u16("udp"), Err(FieldError::NonMinimalInteger));
+        assert_eq!(enr.udp(), None);
+
+        enr.insert_raw_rlp("big", vec![0x83, 0x01, 0x02, 0x03], &key)
+            .unwrap();
+        assert_eq!(enr.get_u16("big"), Err(FieldError::IntegerOverflow));
+        assert_eq!(enr.get_u32("big"), Ok(Some(0x0001_0203)));
+        assert_eq!(enr.get_u64("big"), Ok(Some(0x0001_0203)));
+
+        enr.insert_raw_rlp_list("list", vec![0xc1, 0x01], &key)
+            .unwrap();
+        assert!(matches!(
+            enr.get_u64("list"),
+            Err(FieldError::InvalidRlp(_))
+        ));
+
+        enr.insert_value("name", &vec![0xff_u8, 0xfe], &key)
+            .unwrap();
+        assert!(matches!(
+            enr.get_utf8("name"),
+            Err(FieldError::InvalidUtf8(_))
+        ));
+    }
+
+    /// An application field holding a list.
+    #[derive(Debug, PartialEq, Eq)]
+    struct Shards(Vec<u16>);
+
+    impl EnrField for Shards {
+        const KEY: &'static str = "shards";
+        type Error = DecoderError;
+
+        fn encode(&self) -> Vec<u8> {
+            rlp::encode_list(&self.0).to_vec()
+        }
+
+        fn decode(raw: &[u8]) -> Result<Self, Self::Error> {
+            let rlp = Rlp::new(raw);
+            if !rlp.is_list() {
+                return Err(DecoderError::RlpExpectedToBeList);
+            }
+            rlp.as_list().map(Shards)
+        }
+    }
+
+    #[test]
+    fn test_application_field() {
+        let key = MockSigner(1);
+        let mut enr = EnrBuilder::new("v4")
+            .field(&Shards(vec![1, 300]))
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.get_field(), Some(Ok(Shards(vec![1, 300]))));
+        assert_eq!(
+            enr.get_raw_rlp("shards"),
+            Some(&[0xc4, 0x01, 0x82, 0x01, 0x2c][..])
+        );
+
+        let previous = enr.set_field(&Shards(vec![]), &key).unwrap();
+        assert_eq!(previous, Some(Shards(vec![1, 300])));
+        assert_eq!(enr.get_field(), Some(Ok(Shards(vec![]))));
+        assert!(enr.verify());
+
+        // a malformed value is reported with the error type of the field
+        enr.insert_value("shards", &1_u8, &key).unwrap();
+        assert_eq!(
+            enr.get_field::<Shards>(),
+            Some(Err(DecoderError::RlpExpectedToBeList))
+        );
+    }
+
+    #[test]
+    fn test_minimal_port_encoding() {
+        let key = MockSigner(1);
+        let vectors: &[(u16, &[u8])] = &[
+            (1, &[0x01]),
+            (255, &[0x81, 0xff]),
+            (256, &[0x82, 0x01, 0x00]),
+            (65535, &[0x82, 0xff, 0xff]),
+        ];
+        for (port, encoded) in vectors {
+            let socket = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), *port);
+            let mut records = vec![
+                EnrBuilder::new("v4").udp(*port).build(&key).unwrap(),
+                EnrBuilder::new("v4")
+                    .field(&crate::fields::Udp(*port))
+                    .build(&key)
+                    .unwrap(),
+            ];
+            let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+            enr.set_udp(*port, &key).unwrap();
+            records.push(enr.clone());
+            enr.set_udp_socket(socket, &key).unwrap();
+            records.push(enr.clone());
+            enr.insert_value("udp", port, &key).unwrap();
+            records.push(enr);
+
+            for enr in &records {
+                assert_eq!(enr.get_raw_rlp("udp"), Some(*encoded), "{port}");
+                assert_eq!(enr.udp4(), Some(*port));
+                assert_eq!(enr.get_port("udp", true), Ok(Some(*port)));
+                assert_eq!(enr.get_port("udp", false), Ok(Some(*port)));
+            }
+        }
+
+        // a padded port is only read leniently
+        let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+        enr.content
+            .insert("udp".into(), vec![0x82, 0x00, 0x01].into());
+        assert_eq!(enr.udp4(), None);
+        assert_eq!(
+            enr.get_port("udp", true),
+            Err(FieldError::NonMinimalInteger)
+        );
+        assert_eq!(enr.get_port("udp", false), Ok(Some(1)));
+        enr.content
+            .insert("udp".into(), vec![0x83, 0x00, 0x01, 0x00].into());
+        assert_eq!(enr.get_port("udp", false), Ok(Some(256)));
+        enr.content
+            .insert("udp".into(), vec![0x83, 0x01, 0x00, 0x00].into());
+        assert_eq!(enr.get_port("udp", false), Err(FieldError::IntegerOverflow));
+        assert_eq!(enr.get_port("tcp", false), Ok(None));
+    }
+
+    #[test]
+    fn test_minimal_seq_encoding() {
+        let key = MockSigner(1);
+        let vectors: &[(u64, &[u8])] = &[
+            (0, &[0x80]),
+            (1, &[0x01]),
+            (1 << 32, &[0x85, 0x01, 0x00, 0x00, 0x00, 0x00]),
+        ];
+        for (seq, encoded) in vectors {
+            let enr = EnrBuilder::new("v4").seq(*seq).build(&key).unwrap();
+            let signed = enr.signed_content();
+            assert_eq!(Rlp::new(&signed).at(0).unwrap().as_raw(), *encoded);
+            let record = enr.encode();
+            assert_eq!(Rlp::new(&record).at(1).unwrap().as_raw(), *encoded);
+            assert_eq!(Enr::<MockSigner>::decode_strict(&record).unwrap(), enr);
+        }
+
+        // incrementing from 0 to 1
+        let mut enr = EnrBuilder::new("v4").seq(0).build(&key).unwrap();
+        enr.set_udp(9000, &key).unwrap();
+        assert_eq!(
+            Rlp::new(&enr.signed_content()).at(0).unwrap().as_raw(),
+            &[0x01]
+        );
+    }
+
+    #[test]
+    fn test_utf8_fields() {
+        let key = MockSigner(1);
+        let mut enr = EnrBuilder::new("v4")
+            .add_utf8("name".into(), "ノード")
+            .add_utf8("contact".into(), "")
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.get_utf8("name"), Ok(Some("ノード")));
+        assert_eq!(enr.get_utf8("contact"), Ok(Some("")));
+
+        let previous = enr.insert_utf8("name", "nœud", &key).unwrap();
+        assert_eq!(previous, Some(rlp::encode(&"ノード").to_vec()));
+        assert_eq!(enr.get_utf8("name"), Ok(Some("nœud")));
+        assert!(enr.verify());
+
+        let decoded = rlp::decode::<Enr<MockSigner>>(&enr.encode()).unwrap();
+        assert_eq!(decoded.get_utf8("name"), Ok(Some("nœud")));
+        assert_eq!(decoded.get_utf8("contact"), Ok(Some("")));
+
+        // the value is a plain RLP byte string, as read by other RLP implementations
+        let mut raw = enr.get_raw_rlp("name").unwrap();
+        assert_eq!(
+            alloy_rlp::Header::decode(&mut &raw[..]).unwrap(),
+            alloy_rlp::Header {
+                list: false,
+                payload_length: "nœud".len()
+            }
+        );
+        let name: String = alloy_rlp::Decodable::decode(&mut raw).unwrap();
+        assert_eq!(name, "nœud");
+        assert_eq!(enr.get_raw_rlp("contact"), Some(&[0x80][..]));
+
+        // the record size limit is enforced
+        let long = "ß".repeat(150);
+        assert!(matches!(
+            enr.insert_utf8("name", &long, &key),
+            Err(EnrError::ExceedsMaxSize)
+        ));
+        assert_eq!(enr.get_utf8("name"), Ok(Some("nœud")));
+        assert!(matches!(
+            EnrBuilder::new("v4")
+                .add_utf8("name".into(), &long)
+                .build(&key),
+            Err(EnrError::ExceedsMaxSize)
+        ));
+    }
+
+    #[cfg(feature = "libsecp256k1")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_field_validation() {
+        use crate::secp256k1;
+        use rlp::RlpStream;
+
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let invalid_ip = |error| {
+            matches!(
+                error,
+                EnrError::InvalidField(FieldError::InvalidLength { ref key, expected: 4, got: 5 })
+                    if key == "ip"
+            )
+        };
+
+        // on insert
+        let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+        let error = enr
+            .insert_value("ip", &vec![1_u8, 2, 3, 4, 5], &key)
+            .unwrap_err();
+        assert!(invalid_ip(error));
+        assert_eq!(enr.seq(), 1);
+        assert!(matches!(
+            enr.insert_value("udp", &vec![1_u8, 2, 3], &key),
+            Err(EnrError::InvalidField(FieldError::InvalidLength {
+                expected: 2,
+                got: 3,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            enr.insert_raw_rlp_list("ip6", vec![0xc1, 1], &key),
+            Err(EnrError::InvalidField(FieldError::InvalidRlp(_)))
+        ));
+        enr.insert_value("custom", &vec![1_u8, 2, 3, 4, 5], &key)
+            .unwrap();
+
+        // the `id` names a known identity scheme
+        assert_eq!(validate("id", &rlp::encode(&"v4")), Ok(()));
+        assert_eq!(
+            validate("id", &rlp::encode(&"v5")),
+            Err(FieldError::UnknownIdentityScheme("v5".into()))
+        );
+
+        // in the builder
+        let error = EnrBuilder::new("v4")
+            .add_value("ip".into(), vec![1, 2, 3, 4, 5])
+            .build(&key)
+            .unwrap_err();
+        assert!(invalid_ip(error));
+
+        // a validly signed record with a 5 byte `ip` is only decoded leniently
+        let mut content = enr.content.clone();
+        content.insert("ip".into(), rlp::encode(&vec![1_u8, 2, 3, 4, 5]).into());
+        let encode = |signature: Option<&[u8]>| {
+            let mut stream =
+                RlpStream::new_list(content.len() * 2 + 1 + usize::from(signature.is_some()));
+            if let Some(signature) = signature {
+                stream.append(&signature);
+            }
+            stream.append(&1_u64);
+            for (k, v) in &content {
+                stream.append(k);
+                stream.append_raw(v, 1);
+            }
+            stream.out()
+        };
+        let signature = key.sign_v4(&encode(None)).unwrap();
+        let bytes = encode(Some(&signature));
+
+        let lenient = rlp::decode::<Enr<secp256k1::SecretKey>>(&bytes).unwrap();
+        assert_eq!(lenient.ip(), None);
+        assert!(invalid_ip(EnrError::InvalidField(
+            lenient.validate_fields().unwrap_err()
+        )));
+        let error = Enr::<secp256k1::SecretKey>::decode_strict(&bytes).unwrap_err();
+        assert!(invalid_ip(error));
+    }
+}
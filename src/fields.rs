@@ -0,0 +1,253 @@
+//! The well-known fields of EIP-778 as [`EnrField`]s, read and written with [`Enr::get_field`],
+//! [`Enr::set_field`] and [`EnrBuilder::field`].
+//!
+//! [`Enr::get_field`]: crate::Enr::get_field
+//! [`Enr::set_field`]: crate::Enr::set_field
+//! [`EnrBuilder::field`]: crate::EnrBuilder::field
+
+use crate::{
+    field::{decode_uint, rlp_bytes, validate},
+    EnrField, FieldError,
+};
+use alloc::vec::Vec;
+use core::{
+    convert::TryFrom,
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+/// The `ip` field, the IPv4 address of the node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Ip4(pub Ipv4Addr);
+
+impl EnrField for Ip4 {
+    const KEY: &'static str = "ip";
+    type Error = FieldError;
+
+    fn encode(&self) -> Vec<u8> {
+        rlp::encode(&self.0.octets().to_vec()).into()
+    }
+
+    fn decode(raw: &[u8]) -> Result<Self, Self::Error> {
+        decode_octets(Self::KEY, raw).map(|octets: [u8; 4]| Self(octets.into()))
+    }
+}
+
+/// The `ip6` field, the IPv6 address of the node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Ip6(pub Ipv6Addr);
+
+impl EnrField for Ip6 {
+    const KEY: &'static str = "ip6";
+    type Error = FieldError;
+
+    fn encode(&self) -> Vec<u8> {
+        rlp::encode(&self.0.octets().to_vec()).into()
+    }
+
+    fn decode(raw: &[u8]) -> Result<Self, Self::Error> {
+        decode_octets(Self::KEY, raw).map(|octets: [u8; 16]| Self(octets.into()))
+    }
+}
+
+/// Defines a port field, a minimally encoded integer of at most 2 bytes.
+macro_rules! port_field {
+    ($(#[$doc:meta])* $name:ident, $key:literal) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub struct $name(pub u16);
+
+        impl EnrField for $name {
+            const KEY: &'static str = $key;
+            type Error = FieldError;
+
+            fn encode(&self) -> Vec<u8> {
+                rlp::encode(&self.0).into()
+            }
+
+            fn decode(raw: &[u8]) -> Result<Self, Self::Error> {
+                decode_port(Self::KEY, raw).map(Self)
+            }
+        }
+    };
+}
+
+port_field!(
+    /// The `tcp` field, the IPv4 TCP port of the node.
+    Tcp,
+    "tcp"
+);
+port_field!(
+    /// The `tcp6` field, the IPv6 TCP port of the node.
+    Tcp6,
+    "tcp6"
+);
+port_field!(
+    /// The `udp` field, the IPv4 UDP port of the node.
+    Udp,
+    "udp"
+);
+port_field!(
+    /// The `udp6` field, the IPv6 UDP port of the node.
+    Udp6,
+    "udp6"
+);
+port_field!(
+    /// The `quic` field, the IPv4 QUIC port of the node.
+    Quic,
+    "quic"
+);
+port_field!(
+    /// The `quic6` field, the IPv6 QUIC port of the node.
+    Quic6,
+    "quic6"
+);
+
+/// Decodes an address of `N` bytes.
+fn decode_octets<const N: usize>(key: &str, raw: &[u8]) -> Result<[u8; N], FieldError> {
+    validate(key, raw)?;
+    let bytes = rlp_bytes(raw)?;
+    <[u8; N]>::try_from(bytes).map_err(|_| FieldError::InvalidLength {
+        key: key.into(),
+        expected: N,
+        got: bytes.len(),
+    })
+}
+
+/// Decodes a port.
+fn decode_port(key: &str, raw: &[u8]) -> Result<u16, FieldError> {
+    validate(key, raw)?;
+    u16::try_from(decode_uint(raw, 2)?).map_err(|_| FieldError::IntegerOverflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder, EnrError};
+
+    /// A field writing a malformed value of a well-known key.
+    struct ShortIp;
+
+    impl EnrField for ShortIp {
+        const KEY: &'static str = "ip";
+        type Error = FieldError;
+
+        fn encode(&self) -> Vec<u8> {
+            rlp::encode(&vec![192_u8, 0, 2]).to_vec()
+        }
+
+        fn decode(_raw: &[u8]) -> Result<Self, Self::Error> {
+            Ok(Self)
+        }
+    }
+
+    #[test]
+    fn test_well_known_fields() {
+        let key = MockSigner(1);
+        let ip4 = Ipv4Addr::new(192, 0, 2, 1);
+        let ip6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+        let mut enr = EnrBuilder::new("v4")
+            .field(&Ip4(ip4))
+            .field(&Ip6(ip6))
+            .field(&Tcp(30303))
+            .field(&Udp(30304))
+            .field(&Tcp6(30305))
+            .field(&Udp6(30306))
+            .field(&Quic(30307))
+            .field(&Quic6(30308))
+            .build(&key)
+            .unwrap();
+
+        // the typed fields agree with the dedicated getters
+        assert_eq!(enr.ip(), Some(ip4));
+        assert_eq!(enr.ip6(), Some(ip6));
+        assert_eq!(enr.tcp4(), Some(30303));
+        assert_eq!(enr.udp4(), Some(30304));
+        assert_eq!(enr.tcp6(), Some(30305));
+        assert_eq!(enr.udp6(), Some(30306));
+        assert_eq!(enr.quic(), Some(30307));
+        assert_eq!(enr.quic6(), Some(30308));
+        assert_eq!(enr.get_field(), Some(Ok(Ip4(ip4))));
+        assert_eq!(enr.get_field(), Some(Ok(Quic6(30308))));
+
+        assert_eq!(enr.set_field(&Udp(9000), &key).unwrap(), Some(Udp(30304)));
+        assert_eq!(enr.get_field(), Some(Ok(Udp(9000))));
+        assert_eq!(enr.udp4(), Some(9000));
+        assert!(enr.verify());
+
+        let enr = EnrBuilder::new("v4").build(&key).unwrap();
+        assert_eq!(enr.get_field::<Udp>(), None);
+    }
+
+    #[test]
+    fn test_builder_checks_typed_fields() {
+        let key = MockSigner(1);
+        assert!(matches!(
+            EnrBuilder::new("v4").field(&Udp(0)).build(&key),
+            Err(EnrError::InvalidPort(ref port)) if port == "udp"
+        ));
+        assert!(matches!(
+            EnrBuilder::new("v4").field(&Quic6(0)).build(&key),
+            Err(EnrError::InvalidPort(ref port)) if port == "quic6"
+        ));
+        let mapped = Ipv4Addr::new(192, 0, 2, 1).to_ipv6_mapped();
+        assert!(matches!(
+            EnrBuilder::new("v4").field(&Ip6(mapped)).build(&key),
+            Err(EnrError::Ipv4MappedAddress(ip)) if ip == mapped
+        ));
+        assert!(matches!(
+            EnrBuilder::new("v4")
+                .field(&Ip4(Ipv4Addr::LOCALHOST))
+                .with_validation(true)
+                .build(&key),
+            Err(EnrError::NonPublicAddress(..))
+        ));
+
+        // like the named setters, a field set again replaces the previous value, but a
+        // later `add_value` of the same key is a duplicate
+        let enr = EnrBuilder::new("v4")
+            .udp(9000)
+            .field(&Udp(9001))
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.udp4(), Some(9001));
+        assert!(matches!(
+            EnrBuilder::new("v4")
+                .field(&Udp(9000))
+                .add_value("udp".into(), vec![0x23, 0x29])
+                .build(&key),
+            Err(EnrError::DuplicateKey(ref k)) if k == "udp"
+        ));
+    }
+
+    #[test]
+    fn test_malformed_well_known_fields() {
+        let key = MockSigner(1);
+        let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+        enr.content
+            .insert("udp".into(), vec![0x82, 0x00, 0x50].into());
+        enr.content
+            .insert("ip".into(), rlp::encode(&vec![192_u8, 0, 2]).into());
+        assert_eq!(
+            enr.get_field::<Udp>(),
+            Some(Err(FieldError::NonMinimalInteger))
+        );
+        assert_eq!(
+            enr.get_field::<Ip4>(),
+            Some(Err(FieldError::InvalidLength {
+                key: "ip".into(),
+                expected: 4,
+                got: 3,
+            }))
+        );
+
+        // an application field cannot bypass the validation of a well-known key
+        assert!(matches!(
+            EnrBuilder::new("v4").field(&ShortIp).build(&key),
+            Err(EnrError::InvalidField(FieldError::InvalidLength { .. }))
+        ));
+        assert!(matches!(
+            enr.set_field(&ShortIp, &key),
+            Err(EnrError::InvalidField(FieldError::InvalidLength { .. }))
+        ));
+    }
+}
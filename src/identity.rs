@@ -0,0 +1,52 @@
+//! Identity schemes of ENR records.
+//!
+//! The identity scheme of a record, stored under the `id` key, defines how the record is signed
+//! and verified and how its `NodeId` is derived from the public key. EIP-778 only defines the
+//! "v4" scheme, which is provided by [`V4Scheme`] and used by default. Experimental or
+//! application-specific schemes can be supported by implementing [`IdentityScheme`] and passing it
+//! to [`EnrBuilder::identity_scheme`] or [`Enr::with_identity_scheme`].
+//!
+//! [`EnrBuilder::identity_scheme`]: crate::EnrBuilder::identity_scheme
+//! [`Enr::with_identity_scheme`]: crate::Enr::with_identity_scheme
+
+use crate::{digest, keys::SigningError, EnrKey, EnrPublicKey};
+use alloc::vec::Vec;
+
+/// An identity scheme, defining the signature and node id derivation of an ENR record signed
+/// with keys of type `K`.
+pub trait IdentityScheme<K: EnrKey>: Send + Sync {
+    /// The name of the identity scheme, stored under the `id` key of the record.
+    fn scheme_id(&self) -> &'static str;
+
+    /// Signs the RLP-encoded content of a record.
+    fn sign(&self, key: &K, content: &[u8]) -> Result<Vec<u8>, SigningError>;
+
+    /// Verifies the signature of the RLP-encoded content of a record.
+    fn verify(&self, public_key: &K::PublicKey, content: &[u8], sig: &[u8]) -> bool;
+
+    /// Derives the raw `NodeId` of a record from its public key.
+    fn node_id(&self, public_key: &K::PublicKey) -> [u8; 32];
+}
+
+/// The "v4" identity scheme of EIP-778. The node id is the keccak256 hash of the uncompressed
+/// public key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct V4Scheme;
+
+impl<K: EnrKey> IdentityScheme<K> for V4Scheme {
+    fn scheme_id(&self) -> &'static str {
+        "v4"
+    }
+
+    fn sign(&self, key: &K, content: &[u8]) -> Result<Vec<u8>, SigningError> {
+        key.sign_v4(content)
+    }
+
+    fn verify(&self, public_key: &K::PublicKey, content: &[u8], sig: &[u8]) -> bool {
+        public_key.verify_v4(content, sig)
+    }
+
+    fn node_id(&self, public_key: &K::PublicKey) -> [u8; 32] {
+        digest(&public_key.encode_uncompressed())
+    }
+}
@@ -0,0 +1,310 @@
+//! An expanded JSON representation of records, with the well-known fields decoded, provided by
+//! the `json` feature.
+//!
+//! ```json
+//! {
+//!   "node_id": "0xa448f24c6d18e575453db13171562b71999873db5b286df957af199ec94617f7",
+//!   "seq": 1,
+//!   "signature": "0x7098ad86...",
+//!   "public_key": "0x03ca634c...",
+//!   "id": "v4",
+//!   "ip": "127.0.0.1",
+//!   "udp": 30303,
+//!   "fields": { "eth2": "0x84..." }
+//! }
+//! ```
+//!
+//! The `id`, `ip`, `ip6` and port fields are decoded. The values of all other fields, and of
+//! well-known fields that are not canonically encoded, are kept under `fields` as the hex of their
+//! RLP encoding, so that a record survives [`Enr::to_json`] and [`Enr::from_json`] byte for byte.
+
+use crate::{
+    field::rlp_bytes,
+    fields::{Ip4, Ip6, Quic, Quic6, Tcp, Tcp6, Udp, Udp6},
+    Enr, EnrError, EnrField, EnrKey, EnrParts, EnrPublicKey,
+};
+use serde_json::{Map, Value};
+use std::{collections::BTreeMap, convert::TryFrom, str::FromStr};
+
+/// Encodes bytes as `0x`-prefixed hex.
+fn to_hex(bytes: &[u8]) -> Value {
+    Value::String(format!("0x{}", hex::encode(bytes)))
+}
+
+/// Decodes hex, with or without the `0x` prefix.
+fn from_hex(value: &Value) -> Result<Vec<u8>, EnrError> {
+    let text = value
+        .as_str()
+        .ok_or(EnrError::InvalidJson("expected hex"))?;
+    hex::decode(text.strip_prefix("0x").unwrap_or(text))
+        .map_err(|_| EnrError::InvalidJson("invalid hex"))
+}
+
+/// Returns the decoded value of a well-known field, or `None` if the key is not decoded or the
+/// value would not be re-encoded byte for byte.
+fn decoded_field(key: &str, raw: &[u8]) -> Option<Value> {
+    fn canonical<F: EnrField>(raw: &[u8], value: impl Fn(F) -> Value) -> Option<Value> {
+        F::decode(raw)
+            .ok()
+            .filter(|field| field.encode() == raw)
+            .map(value)
+    }
+    let port = |port: u16| Value::from(port);
+    match key {
+        "id" => rlp_bytes(raw)
+            .ok()
+            .and_then(|id| std::str::from_utf8(id).ok())
+            .filter(|id| rlp::encode(id) == raw)
+            .map(Value::from),
+        Ip4::KEY => canonical(raw, |Ip4(ip)| Value::from(ip.to_string())),
+        Ip6::KEY => canonical(raw, |Ip6(ip)| Value::from(ip.to_string())),
+        Tcp::KEY => canonical(raw, |Tcp(p)| port(p)),
+        Tcp6::KEY => canonical(raw, |Tcp6(p)| port(p)),
+        Udp::KEY => canonical(raw, |Udp(p)| port(p)),
+        Udp6::KEY => canonical(raw, |Udp6(p)| port(p)),
+        Quic::KEY => canonical(raw, |Quic(p)| port(p)),
+        Quic6::KEY => canonical(raw, |Quic6(p)| port(p)),
+        _ => None,
+    }
+}
+
+/// Encodes a decoded well-known field, or returns `None` if the key is not decoded.
+fn encode_field(key: &str, value: &Value) -> Option<Result<Vec<u8>, EnrError>> {
+    fn ip<T: FromStr>(value: &Value) -> Result<T, EnrError> {
+        value
+            .as_str()
+            .and_then(|ip| ip.parse().ok())
+            .ok_or(EnrError::InvalidJson("invalid ip"))
+    }
+    let port = |value: &Value| {
+        value
+            .as_u64()
+            .and_then(|port| u16::try_from(port).ok())
+            .ok_or(EnrError::InvalidJson("invalid port"))
+    };
+    Some(match key {
+        "id" => value
+            .as_str()
+            .map(|id| rlp::encode(&id).into())
+            .ok_or(EnrError::InvalidJson("invalid id")),
+        Ip4::KEY => ip(value).map(|ip| Ip4(ip).encode()),
+        Ip6::KEY => ip(value).map(|ip| Ip6(ip).encode()),
+        Tcp::KEY => port(value).map(|p| Tcp(p).encode()),
+        Tcp6::KEY => port(value).map(|p| Tcp6(p).encode()),
+        Udp::KEY => port(value).map(|p| Udp(p).encode()),
+        Udp6::KEY => port(value).map(|p| Udp6(p).encode()),
+        Quic::KEY => port(value).map(|p| Quic(p).encode()),
+        Quic6::KEY => port(value).map(|p| Quic6(p).encode()),
+        _ => return None,
+    })
+}
+
+/// The key of a public key of the given length, when no hint is given.
+const fn public_key_name(len: usize) -> Result<&'static str, EnrError> {
+    match len {
+        33 => Ok("secp256k1"),
+        32 => Ok("ed25519"),
+        _ => Err(EnrError::InvalidJson("unknown public key type")),
+    }
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// The record as a JSON object with its well-known fields decoded, see the [`json`](self)
+    /// module.
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        let public_key_name = self.public_key().enr_key();
+        let mut json = Map::new();
+        let mut fields = Map::new();
+        json.insert("node_id".into(), to_hex(&self.node_id.raw()));
+        json.insert("seq".into(), self.seq.into());
+        json.insert("signature".into(), to_hex(&self.signature));
+        for (key, raw) in &self.content {
+            if *key == public_key_name {
+                if let Ok(public_key) = rlp_bytes(raw) {
+                    json.insert("public_key".into(), to_hex(public_key));
+                    continue;
+                }
+            }
+            decoded_field(key, raw).map_or_else(
+                || fields.insert(key.clone(), to_hex(raw)),
+                |value| json.insert(key.clone(), value),
+            );
+        }
+        json.insert("fields".into(), Value::Object(fields));
+        Value::Object(json)
+    }
+
+    /// Reconstructs a record from its JSON object, see [`Enr::to_json`], verifying its signature.
+    ///
+    /// `key_hint` is the key of the public key, such as `"secp256k1"`. If `None`, it is inferred
+    /// from the length of the public key. The `node_id` is optional, and checked if present.
+    /// Other unknown members are ignored.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidJson`] if the signature, public key or sequence number is
+    /// missing or a member is malformed, with [`EnrError::NodeIdMismatch`] if the `node_id` is
+    /// not that of the public key, and as [`Enr::from_parts`] otherwise.
+    pub fn from_json(json: &Value, key_hint: Option<&str>) -> Result<Self, EnrError> {
+        let json = json
+            .as_object()
+            .ok_or(EnrError::InvalidJson("expected an object"))?;
+        let signature = from_hex(
+            json.get("signature")
+                .ok_or(EnrError::InvalidJson("missing signature"))?,
+        )?;
+        let seq = json
+            .get("seq")
+            .and_then(Value::as_u64)
+            .ok_or(EnrError::InvalidJson("missing seq"))?;
+        let public_key = from_hex(
+            json.get("public_key")
+                .ok_or(EnrError::InvalidJson("missing public key"))?,
+        )?;
+        let public_key_name = match key_hint {
+            Some(name) => name,
+            None => public_key_name(public_key.len())?,
+        };
+
+        let mut content = BTreeMap::new();
+        content.insert(public_key_name.to_string(), rlp::encode(&public_key).into());
+        for (key, value) in json {
+            if let Some(raw) = encode_field(key, value) {
+                content.insert(key.clone(), raw?);
+            }
+        }
+        if let Some(fields) = json.get("fields") {
+            let fields = fields
+                .as_object()
+                .ok_or(EnrError::InvalidJson("expected an object of fields"))?;
+            for (key, value) in fields {
+                content.insert(key.clone(), from_hex(value)?);
+            }
+        }
+
+        let enr = Self::from_parts(EnrParts {
+            seq,
+            signature,
+            pairs: content
+                .into_iter()
+                .map(|(key, value)| (key.into_bytes(), value))
+                .collect(),
+        })?;
+        if let Some(node_id) = json.get("node_id") {
+            if from_hex(node_id)? != enr.node_id.raw() {
+                return Err(EnrError::NodeIdMismatch);
+            }
+        }
+        Ok(enr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rlp::DecoderError;
+    use serde_json::json;
+
+    /// The record of the EIP-778 test vector.
+    #[cfg(feature = "libsecp256k1")]
+    const EIP_778: &str = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+
+    #[cfg(feature = "libsecp256k1")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_json_secp256k1_fixture() {
+        type DefaultEnr = Enr<crate::secp256k1::SecretKey>;
+        let enr: DefaultEnr = EIP_778.parse().unwrap();
+        let expected = json!({
+            "node_id": "0xa448f24c6d18e575453db13171562b71999873db5b286df957af199ec94617f7",
+            "seq": 1,
+            "signature": "0x7098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c",
+            "public_key": "0x03ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd3138",
+            "id": "v4",
+            "ip": "127.0.0.1",
+            "udp": 30303,
+            "fields": {},
+        });
+        assert_eq!(enr.to_json(), expected);
+        assert_eq!(DefaultEnr::from_json(&expected, None).unwrap(), enr);
+        assert_eq!(
+            DefaultEnr::from_json(&expected, Some("secp256k1")).unwrap(),
+            enr
+        );
+
+        // the signature and public key are required, and the signature is verified
+        for member in &["signature", "public_key", "seq"] {
+            let mut incomplete = expected.clone();
+            incomplete.as_object_mut().unwrap().remove(*member);
+            assert!(matches!(
+                DefaultEnr::from_json(&incomplete, None),
+                Err(EnrError::InvalidJson(_))
+            ));
+        }
+        let mut tampered = expected.clone();
+        tampered["udp"] = 30304.into();
+        assert!(matches!(
+            DefaultEnr::from_json(&tampered, None),
+            Err(EnrError::InvalidRlpData(DecoderError::Custom(
+                "Invalid Signature"
+            )))
+        ));
+        let mut unknown_scheme = expected.clone();
+        unknown_scheme["id"] = "v5".into();
+        assert!(matches!(
+            DefaultEnr::from_json(&unknown_scheme, None),
+            Err(EnrError::UnsupportedIdentityScheme)
+        ));
+        let mut other_node = expected;
+        other_node["node_id"] = to_hex(&[0; 32]);
+        assert!(matches!(
+            DefaultEnr::from_json(&other_node, None),
+            Err(EnrError::NodeIdMismatch)
+        ));
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_json_ed25519_fixture() {
+        use crate::{CombinedKey, EnrBuilder};
+        use std::net::Ipv6Addr;
+
+        let key = CombinedKey::ed25519_from_bytes(&mut [7_u8; 32]).unwrap();
+        let mut enr = EnrBuilder::new("v4")
+            .ip6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1))
+            .udp6(9000)
+            .add_value("eth2".into(), vec![0xab_u8; 16])
+            .build(&key)
+            .unwrap();
+        // a non-minimal port is kept verbatim, under the fields
+        enr.content
+            .insert("tcp".into(), vec![0x82, 0x00, 0x50].into());
+        enr.sign(&key).unwrap();
+
+        // ed25519 signatures are deterministic
+        let expected = json!({
+            "node_id": "0x0791868d8f29ea735f26a17a9aea038cd4255baac26eac5a74e58a07ed2f1975",
+            "seq": 1,
+            "signature": "0xc3bebff9b2809510980e2b0a69060c47c9c3e7c5795fc02d0b23a970d554d2351c0e66beaef93276040f9c42c7a8bf059d4c9b6fd56ede47f9f51e0b87593f0d",
+            "public_key": "0xea4a6c63e29c520abef5507b132ec5f9954776aebebe7b92421eea691446d22c",
+            "id": "v4",
+            "ip6": "2001:db8::1",
+            "udp6": 9000,
+            "fields": {
+                "eth2": "0x90abababababababababababababababab",
+                "tcp": "0x820050",
+            },
+        });
+        assert_eq!(enr.to_json(), expected);
+
+        let decoded = Enr::<CombinedKey>::from_json(&expected, None).unwrap();
+        assert_eq!(decoded.encode(), enr.encode());
+        let decoded = Enr::<CombinedKey>::from_json(&expected, Some("ed25519")).unwrap();
+        assert_eq!(decoded.encode(), enr.encode());
+        assert!(matches!(
+            Enr::<CombinedKey>::from_json(&expected, Some("secp256k1")),
+            Err(EnrError::InvalidRlpData(_))
+        ));
+    }
+}
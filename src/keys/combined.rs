@@ -1,13 +1,22 @@
 //! An implementation that combines the currently supported key types. This
 //! facilitates and ENR type than can decode and read ENR's of all supported key types.
 //!
-//! Currently only `secp256k1` and `ed25519` key types are supported.
+//! Currently only `secp256k1` and `ed25519` key types are supported. The `ed25519` keys are those
+//! of version 1 of `ed25519_dalek`, or of version 2 with the `ed25519-v2` feature.
 
+#[cfg(feature = "ed25519-v2")]
+use super::ed25519_dalek_v2::{SigningKey as Ed25519Key, VerifyingKey as Ed25519PublicKey};
 use super::{ed25519_dalek as ed25519, EnrKey, EnrPublicKey, SigningError};
-use rand::RngCore;
+use crate::{digest, EnrError};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use bytes::Bytes;
+#[cfg(feature = "ed25519-v2")]
+use core::convert::TryFrom;
+#[cfg(not(feature = "ed25519-v2"))]
+use ed25519::{Keypair as Ed25519Key, PublicKey as Ed25519PublicKey};
 use rlp::DecoderError;
 pub use secp256k1;
-use std::collections::BTreeMap;
+use subtle::{Choice, ConstantTimeEq};
 use zeroize::Zeroize;
 
 /// A standard implementation of the `EnrKey` trait used to sign and modify ENR records. The variants here represent the currently
@@ -15,8 +24,9 @@ use zeroize::Zeroize;
 pub enum CombinedKey {
     /// An `secp256k1` keypair.
     Secp256k1(secp256k1::SecretKey),
-    /// An `Ed25519` keypair.
-    Ed25519(ed25519::Keypair),
+    /// An `Ed25519` keypair, an `ed25519_dalek::SigningKey` of version 2 with the `ed25519-v2`
+    /// feature.
+    Ed25519(Ed25519Key),
 }
 
 impl From<secp256k1::SecretKey> for CombinedKey {
@@ -25,18 +35,33 @@ impl From<secp256k1::SecretKey> for CombinedKey {
     }
 }
 
-impl From<ed25519::Keypair> for CombinedKey {
-    fn from(keypair: ed25519_dalek::Keypair) -> CombinedKey {
+impl From<Ed25519Key> for CombinedKey {
+    fn from(keypair: Ed25519Key) -> CombinedKey {
         CombinedKey::Ed25519(keypair)
     }
 }
 
+/// Converts a keypair of version 1 of `ed25519_dalek`, for code that has not migrated to
+/// version 2 yet.
+#[cfg(feature = "ed25519-v2")]
+impl From<ed25519::Keypair> for CombinedKey {
+    fn from(keypair: ed25519::Keypair) -> CombinedKey {
+        CombinedKey::from(keypair.secret)
+    }
+}
+
 /// Promote an Ed25519 secret key into a keypair.
 impl From<ed25519::SecretKey> for CombinedKey {
+    #[cfg(not(feature = "ed25519-v2"))]
     fn from(secret: ed25519::SecretKey) -> CombinedKey {
         let public = ed25519::PublicKey::from(&secret);
         CombinedKey::Ed25519(ed25519::Keypair { secret, public })
     }
+
+    #[cfg(feature = "ed25519-v2")]
+    fn from(secret: ed25519::SecretKey) -> CombinedKey {
+        CombinedKey::Ed25519(Ed25519Key::from_bytes(secret.as_bytes()))
+    }
 }
 
 impl EnrKey for CombinedKey {
@@ -64,41 +89,14 @@ impl EnrKey for CombinedKey {
     }
 
     /// Decodes the raw bytes of an ENR's content into a public key if possible.
-    fn enr_to_public(content: &BTreeMap<String, Vec<u8>>) -> Result<Self::PublicKey, DecoderError> {
+    fn enr_to_public(content: &BTreeMap<String, Bytes>) -> Result<Self::PublicKey, DecoderError> {
         secp256k1::SecretKey::enr_to_public(content)
             .map(CombinedPublicKey::Secp256k1)
-            .or_else(|_| ed25519::Keypair::enr_to_public(content).map(CombinedPublicKey::from))
+            .or_else(|_| Ed25519Key::enr_to_public(content).map(CombinedPublicKey::from))
     }
 }
 
 impl CombinedKey {
-    /// Generates a new secp256k1 key.
-    pub fn generate_secp256k1() -> Self {
-        let mut r = rand::thread_rng();
-        let mut b = [0; secp256k1::util::SECRET_KEY_SIZE];
-        // This is how it is done in `secp256k1::SecretKey::random` which
-        // we do not use here because it uses `rand::Rng` from rand-0.4.
-        loop {
-            r.fill_bytes(&mut b);
-            if let Ok(k) = secp256k1::SecretKey::parse(&b) {
-                b.zeroize();
-                return CombinedKey::Secp256k1(k);
-            }
-        }
-    }
-
-    /// Generates a new ed25510 key.
-    pub fn generate_ed25519() -> Self {
-        let mut bytes = [0u8; 32];
-        rand::thread_rng().fill_bytes(&mut bytes);
-        let key =
-            CombinedKey::from(ed25519::SecretKey::from_bytes(&bytes).expect(
-                "this returns `Err` only if the length is wrong; the length is correct; qed",
-            ));
-        bytes.zeroize();
-        key
-    }
-
     /// Imports a secp256k1 from raw bytes in any format.
     pub fn secp256k1_from_bytes(bytes: &mut [u8]) -> Result<Self, DecoderError> {
         let key = secp256k1::SecretKey::parse_slice(bytes)
@@ -110,8 +108,8 @@ impl CombinedKey {
 
     /// Imports an ed25519 key from raw 32 bytes.
     pub fn ed25519_from_bytes(bytes: &mut [u8]) -> Result<Self, DecoderError> {
-        let key = ed25519::SecretKey::from_bytes(bytes)
-            .map_err(|_| DecoderError::Custom("Invalid ed25519 secret key"))
+        let key = ed25519_key_from_bytes(bytes)
+            .ok_or(DecoderError::Custom("Invalid ed25519 secret key"))
             .map(CombinedKey::from)?;
         bytes.zeroize();
         Ok(key)
@@ -121,19 +119,53 @@ impl CombinedKey {
     pub fn encode(&self) -> Vec<u8> {
         match self {
             CombinedKey::Secp256k1(key) => key.serialize().to_vec(),
-            CombinedKey::Ed25519(key) => key.secret.as_bytes().to_vec(),
+            CombinedKey::Ed25519(key) => ed25519_secret(key).to_vec(),
         }
     }
+
+    /// Returns a stable, non-secret identifier for the key. This is the keccak256 hash of the
+    /// uncompressed public key and is therefore identical to the raw `NodeId` of any ENR signed
+    /// with this key.
+    #[must_use]
+    pub fn public_key_fingerprint(&self) -> [u8; 32] {
+        digest(&self.public().encode_uncompressed())
+    }
 }
 
+/// Compares the secret key material in constant time. Keys of differing types are never equal.
+impl ConstantTimeEq for CombinedKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        match (self, other) {
+            (Self::Secp256k1(a), Self::Secp256k1(b)) => {
+                let (mut a, mut b) = (a.serialize(), b.serialize());
+                let eq = a.ct_eq(&b);
+                a.zeroize();
+                b.zeroize();
+                eq
+            }
+            (Self::Ed25519(a), Self::Ed25519(b)) => ed25519_secret(a).ct_eq(ed25519_secret(b)),
+            _ => Choice::from(0),
+        }
+    }
+}
+
+impl PartialEq for CombinedKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for CombinedKey {}
+
 /// A combined implementation of `EnrPublicKey` which has support for `Secp256k1`
 /// and `Ed25519` for ENR signature verification.
 #[derive(Clone, Debug, PartialEq)]
 pub enum CombinedPublicKey {
     /// An `Secp256k1` public key.
     Secp256k1(secp256k1::PublicKey),
-    /// An `Ed25519` public key.
-    Ed25519(ed25519::PublicKey),
+    /// An `Ed25519` public key, an `ed25519_dalek::VerifyingKey` of version 2 with the
+    /// `ed25519-v2` feature.
+    Ed25519(Ed25519PublicKey),
 }
 
 impl From<secp256k1::PublicKey> for CombinedPublicKey {
@@ -142,12 +174,71 @@ impl From<secp256k1::PublicKey> for CombinedPublicKey {
     }
 }
 
-impl From<ed25519::PublicKey> for CombinedPublicKey {
-    fn from(public_key: ed25519::PublicKey) -> CombinedPublicKey {
+impl From<Ed25519PublicKey> for CombinedPublicKey {
+    fn from(public_key: Ed25519PublicKey) -> CombinedPublicKey {
         CombinedPublicKey::Ed25519(public_key)
     }
 }
 
+/// Converts a public key of version 1 of `ed25519_dalek`. Keys that version 2 does not accept
+/// fail with [`EnrError::InvalidPublicKey`].
+#[cfg(feature = "ed25519-v2")]
+impl TryFrom<ed25519::PublicKey> for CombinedPublicKey {
+    type Error = EnrError;
+
+    fn try_from(public_key: ed25519::PublicKey) -> Result<Self, Self::Error> {
+        Self::from_compressed_ed25519(public_key.as_bytes())
+    }
+}
+
+impl CombinedPublicKey {
+    /// Decodes a compressed public key, as returned by [`EnrPublicKey::encode`], detecting the
+    /// key type from its length: 33 bytes for `secp256k1` and 32 bytes for `ed25519`.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidPublicKey`] for any other length, or if the bytes are not
+    /// a valid key.
+    pub fn from_compressed(bytes: &[u8]) -> Result<Self, EnrError> {
+        match bytes.len() {
+            secp256k1::util::COMPRESSED_PUBLIC_KEY_SIZE => Self::from_compressed_secp256k1(bytes),
+            ed25519::PUBLIC_KEY_LENGTH => Self::from_compressed_ed25519(bytes),
+            _ => Err(EnrError::InvalidPublicKey("Invalid public key length")),
+        }
+    }
+
+    /// Decodes a 33 byte compressed `secp256k1` public key.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidPublicKey`] if the bytes are not a compressed `secp256k1`
+    /// key.
+    pub fn from_compressed_secp256k1(bytes: &[u8]) -> Result<Self, EnrError> {
+        secp256k1::PublicKey::parse_slice(bytes, Some(secp256k1::PublicKeyFormat::Compressed))
+            .map(Self::Secp256k1)
+            .map_err(|_| EnrError::InvalidPublicKey("Invalid secp256k1 public key"))
+    }
+
+    /// Decodes a 32 byte `ed25519` public key.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidPublicKey`] if the bytes are not an `ed25519` key.
+    pub fn from_compressed_ed25519(bytes: &[u8]) -> Result<Self, EnrError> {
+        ed25519_public_key_from_bytes(bytes)
+            .map(Self::Ed25519)
+            .ok_or(EnrError::InvalidPublicKey("Invalid ed25519 public key"))
+    }
+}
+
+#[cfg(feature = "rust-secp256k1")]
+impl From<c_secp256k1::PublicKey> for CombinedPublicKey {
+    fn from(public_key: c_secp256k1::PublicKey) -> Self {
+        // a valid public key of either secp256k1 library is valid in the other
+        Self::Secp256k1(
+            secp256k1::PublicKey::parse(&public_key.serialize_uncompressed())
+                .expect("secp256k1 public keys are interchangeable"),
+        )
+    }
+}
+
 impl EnrPublicKey for CombinedPublicKey {
     /// Verify a raw message, given a public key for the v4 identity scheme.
     fn verify_v4(&self, msg: &[u8], sig: &[u8]) -> bool {
@@ -182,3 +273,121 @@ impl EnrPublicKey for CombinedPublicKey {
         }
     }
 }
+
+/// The secret key of an ed25519 keypair.
+#[cfg(not(feature = "ed25519-v2"))]
+fn ed25519_secret(key: &Ed25519Key) -> &[u8; 32] {
+    key.secret.as_bytes()
+}
+
+/// The secret key of an ed25519 keypair.
+#[cfg(feature = "ed25519-v2")]
+fn ed25519_secret(key: &Ed25519Key) -> &[u8; 32] {
+    key.as_bytes()
+}
+
+/// Decodes a 32 byte ed25519 secret key into a keypair.
+#[cfg(not(feature = "ed25519-v2"))]
+fn ed25519_key_from_bytes(bytes: &[u8]) -> Option<Ed25519Key> {
+    ed25519::SecretKey::from_bytes(bytes).ok().map(|secret| {
+        let public = ed25519::PublicKey::from(&secret);
+        Ed25519Key { secret, public }
+    })
+}
+
+/// Decodes a 32 byte ed25519 secret key into a keypair.
+#[cfg(feature = "ed25519-v2")]
+fn ed25519_key_from_bytes(bytes: &[u8]) -> Option<Ed25519Key> {
+    Ed25519Key::try_from(bytes).ok()
+}
+
+/// Decodes a 32 byte ed25519 public key.
+#[cfg(not(feature = "ed25519-v2"))]
+fn ed25519_public_key_from_bytes(bytes: &[u8]) -> Option<Ed25519PublicKey> {
+    Ed25519PublicKey::from_bytes(bytes).ok()
+}
+
+/// Decodes a 32 byte ed25519 public key.
+#[cfg(feature = "ed25519-v2")]
+fn ed25519_public_key_from_bytes(bytes: &[u8]) -> Option<Ed25519PublicKey> {
+    Ed25519PublicKey::try_from(bytes).ok()
+}
+
+#[cfg(feature = "msgpack")]
+impl serde::Serialize for CombinedPublicKey {
+    /// Serializes the public key as a map of its type, `secp256k1` or `ed25519`, and its
+    /// compressed bytes.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        /// Serializes a slice as a byte array rather than a sequence.
+        struct Bytes<'a>(&'a [u8]);
+
+        impl serde::Serialize for Bytes<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(self.0)
+            }
+        }
+
+        let mut state = serializer.serialize_struct("CombinedPublicKey", 2)?;
+        state.serialize_field("type", &self.enr_key())?;
+        state.serialize_field("bytes", &Bytes(&self.encode()))?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl<'de> serde::Deserialize<'de> for CombinedPublicKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{self, MapAccess, Visitor};
+
+        /// The raw bytes of the public key.
+        struct Bytes(Vec<u8>);
+
+        impl<'de> serde::Deserialize<'de> for Bytes {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer
+                    .deserialize_byte_buf(crate::BytesVisitor)
+                    .map(Bytes)
+            }
+        }
+
+        struct PublicKeyVisitor;
+
+        impl<'de> Visitor<'de> for PublicKeyVisitor {
+            type Value = CombinedPublicKey;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                write!(f, "a map with type and bytes fields")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut key_type: Option<String> = None;
+                let mut bytes: Option<Bytes> = None;
+                while let Some(field) = map.next_key::<String>()? {
+                    match field.as_str() {
+                        "type" => key_type = Some(map.next_value()?),
+                        "bytes" => bytes = Some(map.next_value()?),
+                        _ => return Err(de::Error::unknown_field(&field, &["type", "bytes"])),
+                    }
+                }
+                let key_type = key_type.ok_or_else(|| de::Error::missing_field("type"))?;
+                let Bytes(bytes) = bytes.ok_or_else(|| de::Error::missing_field("bytes"))?;
+                match key_type.as_str() {
+                    "secp256k1" => secp256k1::PublicKey::parse_slice(&bytes, None)
+                        .map(CombinedPublicKey::Secp256k1)
+                        .map_err(|_| de::Error::custom("Invalid secp256k1 public key")),
+                    "ed25519" => ed25519_public_key_from_bytes(&bytes)
+                        .map(CombinedPublicKey::Ed25519)
+                        .ok_or_else(|| de::Error::custom("Invalid ed25519 public key")),
+                    _ => Err(de::Error::unknown_variant(
+                        &key_type,
+                        &["secp256k1", "ed25519"],
+                    )),
+                }
+            }
+        }
+
+        deserializer.deserialize_struct("CombinedPublicKey", &["type", "bytes"], PublicKeyVisitor)
+    }
+}
@@ -1,6 +1,7 @@
 use super::{ed25519_dalek as ed25519, EnrKey, EnrPublicKey, SigningError};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use bytes::Bytes;
 use rlp::DecoderError;
-use std::collections::BTreeMap;
 
 /// The ENR key that stores the public key in the ENR record.
 pub const ENR_KEY: &str = "ed25519";
@@ -22,8 +23,10 @@ impl EnrKey for ed25519::Keypair {
     }
 
     /// Decodes the raw bytes of an ENR's content into a public key if possible.
-    fn enr_to_public(content: &BTreeMap<String, Vec<u8>>) -> Result<Self::PublicKey, DecoderError> {
+    fn enr_to_public(content: &BTreeMap<String, Bytes>) -> Result<Self::PublicKey, DecoderError> {
         if let Some(pubkey_bytes) = content.get(ENR_KEY) {
+            // the value is stored RLP-encoded
+            let pubkey_bytes = rlp::Rlp::new(pubkey_bytes).data()?;
             ed25519::PublicKey::from_bytes(pubkey_bytes)
                 .map_err(|_| DecoderError::Custom("Invalid ed25519 Signature"))
         } else {
@@ -0,0 +1,60 @@
+use super::{ed25519::ENR_KEY, ed25519_dalek_v2 as ed25519, EnrKey, EnrPublicKey, SigningError};
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use bytes::Bytes;
+use core::convert::TryFrom;
+use ed25519::{Signer, Verifier};
+use rlp::DecoderError;
+
+impl EnrKey for ed25519::SigningKey {
+    type PublicKey = ed25519::VerifyingKey;
+
+    /// Performs ENR-specific signing.
+    ///
+    /// Using `ed25519` keys do not currently follow the `v4` identity scheme, which dictates
+    /// `secp256k1` keys should be used.
+    fn sign_v4(&self, msg: &[u8]) -> Result<Vec<u8>, SigningError> {
+        Ok(self.sign(msg).to_bytes().to_vec())
+    }
+
+    /// Returns the public key associated with the private key.
+    fn public(&self) -> Self::PublicKey {
+        self.verifying_key()
+    }
+
+    /// Decodes the raw bytes of an ENR's content into a public key if possible.
+    fn enr_to_public(content: &BTreeMap<String, Bytes>) -> Result<Self::PublicKey, DecoderError> {
+        if let Some(pubkey_bytes) = content.get(ENR_KEY) {
+            // the value is stored RLP-encoded
+            let pubkey_bytes = rlp::Rlp::new(pubkey_bytes).data()?;
+            ed25519::VerifyingKey::try_from(pubkey_bytes)
+                .map_err(|_| DecoderError::Custom("Invalid ed25519 Signature"))
+        } else {
+            Err(DecoderError::Custom("Unknown signature"))
+        }
+    }
+}
+
+impl EnrPublicKey for ed25519::VerifyingKey {
+    /// Verify a raw message, given a public key for the v4 identity scheme.
+    fn verify_v4(&self, msg: &[u8], sig: &[u8]) -> bool {
+        ed25519::Signature::from_slice(sig)
+            .and_then(|s| self.verify(msg, &s))
+            .is_ok()
+    }
+
+    /// Encodes the public key into compressed form, if possible.
+    fn encode(&self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+
+    /// Encodes the public key in uncompressed form. This is the same as the compressed form for
+    /// ed25519 keys
+    fn encode_uncompressed(&self) -> Vec<u8> {
+        self.encode()
+    }
+
+    /// Generates the ENR public key string associated with the ed25519 key type.
+    fn enr_key(&self) -> String {
+        ENR_KEY.into()
+    }
+}
@@ -2,8 +2,9 @@
 
 use super::{secp256k1, EnrKey, EnrPublicKey, SigningError};
 use crate::digest;
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use bytes::Bytes;
 use rlp::DecoderError;
-use std::collections::BTreeMap;
 
 /// The ENR key that stores the public key in the ENR record.
 pub const ENR_KEY: &str = "secp256k1";
@@ -15,13 +16,17 @@ impl EnrKey for secp256k1::SecretKey {
     ///
     /// currently lists the `v4` identity scheme which requires the `secp256k1` signing algorithm.
     /// Using `secp256k1` keys follow the `v4` identity scheme.
+    ///
+    /// Signatures are normalized to low-s form, see [`EnrPublicKey::verify_v4`].
     fn sign_v4(&self, msg: &[u8]) -> Result<Vec<u8>, SigningError> {
         // take a keccak256 hash then sign.
         let hash = digest(msg);
         let m = secp256k1::Message::parse_slice(&hash)
             .map_err(|_| SigningError::new("failed to parse secp256k1 digest"))?;
+        let mut signature = secp256k1::sign(&m, self).0;
+        signature.normalize_s();
         // serialize to an uncompressed 64 byte vector
-        Ok(secp256k1::sign(&m, self).0.serialize().to_vec())
+        Ok(signature.serialize().to_vec())
     }
 
     /// Returns the public key associated with the private key.
@@ -30,8 +35,10 @@ impl EnrKey for secp256k1::SecretKey {
     }
 
     /// Decodes the raw bytes of an ENR's content into a public key if possible.
-    fn enr_to_public(content: &BTreeMap<String, Vec<u8>>) -> Result<Self::PublicKey, DecoderError> {
+    fn enr_to_public(content: &BTreeMap<String, Bytes>) -> Result<Self::PublicKey, DecoderError> {
         if let Some(pubkey_bytes) = content.get(ENR_KEY) {
+            // the value is stored RLP-encoded
+            let pubkey_bytes = rlp::Rlp::new(pubkey_bytes).data()?;
             // should be encoded in compressed form, i.e 33 byte raw secp256k1 public key
             secp256k1::PublicKey::parse_slice(
                 pubkey_bytes,
@@ -46,13 +53,18 @@ impl EnrKey for secp256k1::SecretKey {
 
 impl EnrPublicKey for secp256k1::PublicKey {
     /// Verify a raw message, given a public key for the v4 identity scheme.
+    ///
+    /// Signatures with a high s value are rejected. For every valid signature `(r, s)`,
+    /// `(r, n - s)` is valid as well, and accepting both would let anyone alter the signature of
+    /// a record without invalidating it.
     fn verify_v4(&self, msg: &[u8], sig: &[u8]) -> bool {
         let msg = digest(msg);
         secp256k1::Signature::parse_slice(sig)
             .and_then(|sig| {
-                secp256k1::Message::parse_slice(&msg).map(|m| secp256k1::verify(&m, &sig, self))
+                secp256k1::Message::parse_slice(&msg)
+                    .map(|m| !sig.s.is_high() && secp256k1::verify(&m, &sig, self))
             })
-            .is_ok()
+            .unwrap_or(false)
     }
 
     /// Encodes the public key into compressed form, if possible.
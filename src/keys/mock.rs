@@ -0,0 +1,82 @@
+//! A signer for tests that do not exercise cryptography. Only compiled for the crate's own tests.
+
+use super::{EnrKey, EnrPublicKey, SigningError};
+use alloc::collections::BTreeMap;
+use bytes::Bytes;
+use rlp::DecoderError;
+
+/// The ENR key of the public key of a [`MockSigner`].
+pub const ENR_KEY: &str = "mock";
+
+/// A key whose signatures are a fixed 64 bytes, and whose public key accepts any signature.
+///
+/// The byte identifies the signer, records of signers with different bytes have different node
+/// ids.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MockSigner(pub u8);
+
+/// The public key of a [`MockSigner`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MockPublicKey(pub u8);
+
+impl EnrKey for MockSigner {
+    type PublicKey = MockPublicKey;
+
+    fn sign_v4(&self, _msg: &[u8]) -> Result<Vec<u8>, SigningError> {
+        Ok(vec![self.0; 64])
+    }
+
+    fn public(&self) -> Self::PublicKey {
+        MockPublicKey(self.0)
+    }
+
+    fn enr_to_public(content: &BTreeMap<String, Bytes>) -> Result<Self::PublicKey, DecoderError> {
+        let raw = content
+            .get(ENR_KEY)
+            .ok_or(DecoderError::Custom("Unknown signature"))?;
+        match rlp::Rlp::new(raw).data()? {
+            [byte] => Ok(MockPublicKey(*byte)),
+            _ => Err(DecoderError::Custom("Invalid mock public key")),
+        }
+    }
+}
+
+impl EnrPublicKey for MockPublicKey {
+    fn verify_v4(&self, _msg: &[u8], _sig: &[u8]) -> bool {
+        true
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        vec![self.0]
+    }
+
+    fn encode_uncompressed(&self) -> Vec<u8> {
+        self.encode()
+    }
+
+    fn enr_key(&self) -> String {
+        ENR_KEY.into()
+    }
+}
+
+mod tests {
+    use super::*;
+    use crate::{Enr, EnrBuilder};
+
+    #[test]
+    fn test_mock_signer() {
+        let enr = EnrBuilder::new("v4")
+            .udp(30303)
+            .build(&MockSigner(1))
+            .unwrap();
+        assert_eq!(enr.signature(), &[1; 64][..]);
+        assert_eq!(enr.public_key(), MockPublicKey(1));
+        assert!(enr.verify());
+
+        let decoded = rlp::decode::<Enr<MockSigner>>(&enr.encode()).unwrap();
+        assert_eq!(decoded, enr);
+
+        let other = EnrBuilder::new("v4").build(&MockSigner(2)).unwrap();
+        assert_ne!(other.node_id(), enr.node_id());
+    }
+}
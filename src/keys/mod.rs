@@ -2,7 +2,8 @@
 //! own signing schemes can implement these traits and apply them to a [`Enr`].
 //!
 //! This module contains implementations for the `libsecp256k1` and `ed25519_dalek`
-//! secret key libraries, provided the `libsecp256k1` and `ed25519` features are set.
+//! secret key libraries, provided the `libsecp256k1` and `ed25519` features are set, and for
+//! version 2 of `ed25519_dalek` as `ed25519_dalek_v2`, provided the `ed25519-v2` feature is set.
 //!
 //! [`EnrKey`]: crate::EnrKey
 //! [`EnrPublicKey`]: crate::EnrPublicKey
@@ -12,8 +13,14 @@
 mod combined;
 #[cfg(feature = "ed25519")]
 mod ed25519;
+#[cfg(feature = "ed25519-v2")]
+mod ed25519_v2;
 #[cfg(any(feature = "libsecp256k1", doc))]
 mod libsecp256k1;
+#[cfg(test)]
+mod mock;
+#[cfg(feature = "ed25519")]
+mod pem;
 #[cfg(feature = "rust-secp256k1")]
 mod rust_secp256k1;
 
@@ -23,15 +30,25 @@ pub use c_secp256k1;
 pub use combined::{CombinedKey, CombinedPublicKey};
 #[cfg(feature = "ed25519")]
 pub use ed25519_dalek;
+#[cfg(feature = "ed25519-v2")]
+pub use ed25519_dalek_v2;
+#[cfg(test)]
+pub use mock::MockSigner;
 #[cfg(any(feature = "libsecp256k1", doc))]
 pub use secp256k1;
 
-use rlp::DecoderError;
-use std::{
+use alloc::{
+    boxed::Box,
     collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use bytes::Bytes;
+use core::{
     error::Error,
     fmt::{self, Display},
 };
+use rlp::DecoderError;
 
 /// The trait required for a key to sign and modify an ENR record.
 pub trait EnrKey {
@@ -46,10 +63,11 @@ pub trait EnrKey {
     /// Provides a method to decode a raw public key from an ENR `BTreeMap` to a useable public key.
     ///
     /// This method allows a key type to decode the raw bytes in an ENR to a useable
-    /// `EnrPublicKey`. It takes the ENR's `BTreeMap` and returns a public key.
+    /// `EnrPublicKey`. It takes the ENR's `BTreeMap` and returns a public key. The values of the
+    /// map are RLP-encoded.
     ///
     /// Note: This specifies the supported key schemes for an ENR.
-    fn enr_to_public(content: &BTreeMap<String, Vec<u8>>) -> Result<Self::PublicKey, DecoderError>;
+    fn enr_to_public(content: &BTreeMap<String, Bytes>) -> Result<Self::PublicKey, DecoderError>;
 }
 
 /// The trait required for a `PublicKey` to verify an ENR record.
@@ -79,7 +97,7 @@ pub struct SigningError {
 /// An error during encoding of key material.
 #[allow(dead_code)]
 impl SigningError {
-    pub(crate) fn new<S: Display>(msg: S) -> Self {
+    pub fn new<S: Display>(msg: S) -> Self {
         Self {
             msg: msg.to_string(),
             source: None,
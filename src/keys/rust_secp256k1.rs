@@ -1,7 +1,8 @@
 use super::{EnrKey, EnrPublicKey, SigningError};
 use crate::digest;
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use bytes::Bytes;
 use rlp::DecoderError;
-use std::collections::BTreeMap;
 
 /// The ENR key that stores the public key in the ENR record.
 pub const ENR_KEY: &str = "secp256k1";
@@ -14,19 +15,22 @@ impl EnrKey for c_secp256k1::SecretKey {
         let hash = digest(msg);
         let m = c_secp256k1::Message::from_slice(&hash)
             .map_err(|_| SigningError::new("failed to parse secp256k1 digest"))?;
+        let mut signature = c_secp256k1::Secp256k1::new().sign(&m, self);
+        // libsecp256k1 signs in low-s form, normalized regardless as high-s signatures do not
+        // verify
+        signature.normalize_s();
         // serialize to an uncompressed 64 byte vector
-        Ok(c_secp256k1::Secp256k1::new()
-            .sign(&m, self)
-            .serialize_compact()
-            .to_vec())
+        Ok(signature.serialize_compact().to_vec())
     }
 
     fn public(&self) -> Self::PublicKey {
         Self::PublicKey::from_secret_key(&c_secp256k1::Secp256k1::new(), self)
     }
 
-    fn enr_to_public(content: &BTreeMap<String, Vec<u8>>) -> Result<Self::PublicKey, DecoderError> {
+    fn enr_to_public(content: &BTreeMap<String, Bytes>) -> Result<Self::PublicKey, DecoderError> {
         if let Some(pubkey_bytes) = content.get(ENR_KEY) {
+            // the value is stored RLP-encoded
+            let pubkey_bytes = rlp::Rlp::new(pubkey_bytes).data()?;
             // should be encoded in compressed form, i.e 33 byte raw secp256k1 public key
             c_secp256k1::PublicKey::from_slice(pubkey_bytes)
                 .map_err(|_| DecoderError::Custom("Invalid Secp256k1 Signature"))
@@ -37,12 +41,14 @@ impl EnrKey for c_secp256k1::SecretKey {
 }
 
 impl EnrPublicKey for c_secp256k1::PublicKey {
+    /// Signatures with a high s value are rejected by libsecp256k1, which prevents malleated
+    /// signatures.
     fn verify_v4(&self, msg: &[u8], sig: &[u8]) -> bool {
         let msg = digest(msg);
         c_secp256k1::Signature::from_compact(sig)
             .and_then(|sig| {
                 c_secp256k1::Message::from_slice(&msg)
-                    .map(|m| c_secp256k1::Secp256k1::new().verify(&m, &sig, self))
+                    .and_then(|m| c_secp256k1::Secp256k1::new().verify(&m, &sig, self))
             })
             .is_ok()
     }
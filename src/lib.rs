@@ -10,8 +10,9 @@
 //! ENR's are identified by their sequence number. When updating an ENR, the sequence number is
 //! increased.
 //!
-//! Different identity schemes can be used to define the node id and signatures. Currently only the
-//! "v4" identity is supported and is set by default.
+//! Different identity schemes can be used to define the node id and signatures. The "v4" identity
+//! is supported and is set by default. Custom identity schemes can be used by implementing the
+//! [`IdentityScheme`] trait.
 //!
 //! ## Signing Algorithms
 //!
@@ -24,15 +25,57 @@
 //!
 //! Furthermore, a [`CombinedKey`] is provided if the `ed25519` feature flag is set, which provides an
 //! ENR type that can support both `secp256k1` and `ed25519` signed ENR records. Examples of the
-//! use of each of these key types is given below.
+//! use of each of these key types is given below. A `CombinedKey` can be exported to and imported
+//! from PEM with `to_pem` and `from_pem`.
 //!
 //! ## Features
 //!
 //! This crate supports a number of features.
 //!
-//! - `serde`: Allows for serde serialization and deserialization for ENRs.
-//! - `ed25519`: Provides support for `ed25519_dalek` keypair types.
+//! - `std`: Enabled by default. Provides the parts of the crate that need the standard library:
+//!   the collections of records such as [`EnrSet`], [`NodeId::random`], the
+//!   `CombinedKey::generate_*` functions and [`EnrBuilder::set_ip_from_socket`]. Without it the
+//!   crate is `no_std` and only needs `alloc`. Most other features enable it.
+//! - `libsecp256k1`: Enabled by default. Implements [`EnrKey`] for the secp256k1 keys of
+//!   `libsecp256k1`, re-exported as `secp256k1`, and provides the parsing and verification of
+//!   EIP-1459 DNS tree entries for records of any key type, see [`dns_tree`], and the
+//!   construction and signing of such trees, see [`dns_tree::TreeBuilder`].
+//! - `js`: Provides [`NodeId::random`] and the `CombinedKey::generate_*` functions on
+//!   `wasm32-unknown-unknown`, drawing entropy from the JavaScript host. Decoding, verifying and
+//!   signing with imported keys need no extra feature on that target.
+//! - `serde`: Allows for serde serialization and deserialization for ENRs, as their base64 text
+//!   form in human-readable formats, see [`text`], and as their RLP encoding in binary formats.
+//! - `ed25519`: Provides support for `ed25519_dalek` keypair types, Merkle inclusion proofs of
+//!   records, see [`MerkleTree`], and with `libsecp256k1` the deprecated [`dns`] module. With
+//!   `std`, also provides exports and imports of node databases, see [`EnrWriter`] and
+//!   [`EnrReader`].
+//! - `ed25519-v2`: Implements [`EnrKey`] for the `SigningKey` of version 2 of `ed25519_dalek`,
+//!   re-exported as `ed25519_dalek_v2`, and makes it the key of [`CombinedKey::Ed25519`]. Keys of
+//!   version 1 still convert into a [`CombinedKey`]. This will become the default once the
+//!   ecosystem has moved to version 2, and the version 1 keys will be deprecated.
 //! - `rust-secp256k1`: Uses `c-secp256k1` for secp256k1 keys.
+//! - `lru`: Provides [`EnrCache`], a bounded least-recently-used cache of ENR records, and
+//!   [`SeenEnrs`], a bounded set of seen records.
+//! - `msgpack`: Provides `MessagePack` serialization of ENRs, see [`msgpack`].
+//! - `proto`: Provides protobuf encoding of ENRs using `prost`, see [`enr_proto`].
+//! - `test-utils`: Provides assertions and `proptest` strategies for tests of records, see
+//!   [`test_utils`]. With `ed25519`, also provides networks of records for simulations, see
+//!   [`test_utils::EnrTopologyBuilder`].
+//! - `eth`: Provides accessors for the `eth`, `snap`, `les` and `client` fields of Ethereum
+//!   execution clients, see [`EthEnrExt`] and [`EthEnrBuilderExt`].
+//! - `eth2`: Provides accessors for the `eth2`, `attnets` and `syncnets` fields of Ethereum
+//!   consensus clients, and the subnet bitfields [`Attnets`] and [`Syncnets`]. The docs of
+//!   [`Attnets`] show how to read and write the subnets of a record.
+//! - `ffi`: Provides a C interface to decode, encode and verify records, see [`enr_ffi`].
+//! - `async`: Provides [`EnrBuilder::set_ip_from_stun`], discovering the public address of a
+//!   node with a STUN request, using `tokio`.
+//! - `json`: Provides an expanded JSON representation of records with their fields decoded, see
+//!   [`Enr::to_json`] and [`Enr::from_json`], and the JSON array format of [`EnrWriter`] and
+//!   [`EnrReader`].
+//! - `ssz`: Implements the SSZ `Encode` and `Decode` traits of `ethereum_ssz` for records, as a
+//!   `List[byte, 300]` of their RLP encoding, and provides [`Enr::tree_hash_root`].
+//! - `rlp-legacy`: Provides conversions between the [`DecoderError`] of rlp 0.5 and that of rlp
+//!   0.4, which earlier versions exposed, see [`rlp_legacy`].
 //!
 //! These can be enabled via adding the feature flag in your `Cargo.toml`
 //!
@@ -48,7 +91,7 @@
 //!
 //! ```rust
 //! use enr::{EnrBuilder, secp256k1};
-//! use std::net::Ipv4Addr;
+//! use core::net::Ipv4Addr;
 //! use rand::thread_rng;
 //!
 //! // generate a random secp256k1 key
@@ -56,7 +99,7 @@
 //! let key = secp256k1::SecretKey::random(&mut rng);
 //!
 //! let ip = Ipv4Addr::new(192,168,0,1);
-//! let enr = EnrBuilder::new("v4").ip(ip.into()).tcp(8000).build(&key).unwrap();
+//! let enr = EnrBuilder::new("v4").ip4(ip).tcp(8000).build(&key).unwrap();
 //!
 //! assert_eq!(enr.ip(), Some("192.168.0.1".parse().unwrap()));
 //! assert_eq!(enr.id(), Some("v4".into()));
@@ -70,7 +113,7 @@
 //! ```rust
 //! # #[cfg(feature = "ed25519")] {
 //! use enr::{EnrBuilder, CombinedKey};
-//! use std::net::Ipv4Addr;
+//! use core::net::Ipv4Addr;
 //!
 //! // create a new secp256k1 key
 //! let key = CombinedKey::generate_secp256k1();
@@ -79,7 +122,7 @@
 //! let key = CombinedKey::generate_ed25519();
 //!
 //! let ip = Ipv4Addr::new(192,168,0,1);
-//! let enr = EnrBuilder::new("v4").ip(ip.into()).tcp(8000).build(&key).unwrap();
+//! let enr = EnrBuilder::new("v4").ip4(ip).tcp(8000).build(&key).unwrap();
 //!
 //! assert_eq!(enr.ip(), Some("192.168.0.1".parse().unwrap()));
 //! assert_eq!(enr.id(), Some("v4".into()));
@@ -89,11 +132,11 @@
 //! ### Modifying an [`Enr`]
 //!
 //! ENR fields can be added and modified using the getters/setters on [`Enr`]. A custom field
-//! can be added using [`insert`] and retrieved with [`get`].
+//! can be added using [`insert_value`] and retrieved with [`get`].
 //!
 //! ```rust
 //! use enr::{EnrBuilder, secp256k1::SecretKey, Enr};
-//! use std::net::Ipv4Addr;
+//! use core::net::Ipv4Addr;
 //! use rand::thread_rng;
 //!
 //! // specify the type of ENR
@@ -104,11 +147,11 @@
 //! let key = SecretKey::random(&mut rng);
 //!
 //! let ip = Ipv4Addr::new(192,168,0,1);
-//! let mut enr = EnrBuilder::new("v4").ip(ip.into()).tcp(8000).build(&key).unwrap();
+//! let mut enr = EnrBuilder::new("v4").ip4(ip).tcp(8000).build(&key).unwrap();
 //!
 //! enr.set_tcp(8001, &key);
 //! // set a custom key
-//! enr.insert("custom_key", vec![0,0,1], &key);
+//! enr.insert_value("custom_key", &vec![0_u8,0,1], &key);
 //!
 //! // encode to base64
 //! let base_64_string = enr.to_base64();
@@ -119,7 +162,7 @@
 //! assert_eq!(decoded_enr.ip(), Some("192.168.0.1".parse().unwrap()));
 //! assert_eq!(decoded_enr.id(), Some("v4".into()));
 //! assert_eq!(decoded_enr.tcp(), Some(8001));
-//! assert_eq!(decoded_enr.get("custom_key"), Some(&vec![0,0,1]));
+//! assert_eq!(decoded_enr.get("custom_key"), Some(&[0,0,1][..]));
 //! ```
 //!
 //! ### Encoding/Decoding ENR's of various key types
@@ -127,7 +170,7 @@
 //! ```rust
 //! # #[cfg(feature = "ed25519")] {
 //! use enr::{EnrBuilder, secp256k1::SecretKey, Enr, ed25519_dalek::Keypair, CombinedKey};
-//! use std::net::Ipv4Addr;
+//! use core::net::Ipv4Addr;
 //! use rand::thread_rng;
 //! use rand::Rng;
 //!
@@ -135,14 +178,14 @@
 //! let mut rng = thread_rng();
 //! let key = SecretKey::random(&mut rng);
 //! let ip = Ipv4Addr::new(192,168,0,1);
-//! let enr_secp256k1 = EnrBuilder::new("v4").ip(ip.into()).tcp(8000).build(&key).unwrap();
+//! let enr_secp256k1 = EnrBuilder::new("v4").ip4(ip).tcp(8000).build(&key).unwrap();
 //!
 //! // encode to base64
 //! let base64_string_secp256k1 = enr_secp256k1.to_base64();
 //!
 //! // generate a random ed25519 key
 //! let key = Keypair::generate(&mut rng);
-//! let enr_ed25519 = EnrBuilder::new("v4").ip(ip.into()).tcp(8000).build(&key).unwrap();
+//! let enr_ed25519 = EnrBuilder::new("v4").ip4(ip).tcp(8000).build(&key).unwrap();
 //!
 //! // encode to base64
 //! let base64_string_ed25519 = enr_ed25519.to_base64();
@@ -161,43 +204,175 @@
 //!
 //!
 //! [`CombinedKey`]: enum.CombinedKey.html
+//! [`EnrCache`]: struct.EnrCache.html
 //! [`EnrKey`]: trait.EnrKey.html
 //! [`Enr`]: struct.EnrBase.html
 //! [`EnrBuilder`]: struct.EnrBuilderBase.html
 //! [`NodeId`]: struct.NodeId.html
-//! [`insert`]: struct.Enr.html#method.insert
+//! [`insert_value`]: struct.Enr.html#method.insert_value
 //! [`get`]: struct.Enr.html#method.get
 
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 #![warn(clippy::all, clippy::pedantic, clippy::nursery)]
 #![allow(clippy::missing_errors_doc, clippy::module_name_repetitions)]
 
+extern crate alloc;
+
+mod address;
+mod base64_options;
+#[cfg(feature = "std")]
+mod bloom;
 mod builder;
+#[cfg(feature = "lru")]
+mod cache;
+mod client;
+mod connection;
+mod decode;
+mod diff;
+#[cfg(all(feature = "ed25519", feature = "libsecp256k1"))]
+// the test harness refers to the tests of the module through its deprecated path
+#[cfg_attr(
+    not(test),
+    deprecated(
+        since = "0.1.0-alpha.7",
+        note = "use `dns_tree`, whose `Entry` parses the same entries and whose `TreeBuilder` builds the same trees"
+    )
+)]
+pub mod dns;
+#[cfg(feature = "libsecp256k1")]
+pub mod dns_tree;
+#[cfg(feature = "ffi")]
+pub mod enr_ffi;
+#[cfg(feature = "proto")]
+pub mod enr_proto;
+#[cfg(feature = "ssz")]
+mod enr_ssz;
+#[cfg(feature = "eth")]
+mod eth;
+#[cfg(feature = "eth2")]
+mod eth2;
+#[cfg(all(feature = "std", feature = "ed25519"))]
+mod export;
+mod field;
+pub mod fields;
+mod identity;
+#[cfg(feature = "json")]
+mod json;
 mod keys;
+mod list;
+#[cfg(feature = "ed25519")]
+mod merkle;
+mod meta;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
 mod node_id;
-
+mod parts;
+mod pretty;
+mod protocol;
+mod qr;
+#[cfg(all(
+    feature = "std",
+    any(
+        feature = "js",
+        not(all(target_arch = "wasm32", target_os = "unknown"))
+    )
+))]
+mod random;
+#[cfg(feature = "rlp-legacy")]
+pub mod rlp_legacy;
+mod routing;
+#[cfg(feature = "std")]
+mod scored;
+#[cfg(feature = "lru")]
+mod seen;
+#[cfg(feature = "std")]
+mod set;
+mod socket;
+#[cfg(feature = "std")]
+mod store;
+#[cfg(feature = "async")]
+mod stun;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+#[cfg(feature = "serde")]
+pub mod text;
+#[cfg(feature = "std")]
+mod timed;
+pub mod validators;
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
 use log::debug;
-use rlp::{DecoderError, Rlp, RlpStream};
-use std::collections::BTreeMap;
+use rlp::{Rlp, RlpStream};
 use tiny_keccak::{Hasher, Keccak};
 
-#[cfg(feature = "serde")]
-use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
-use std::{
-    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+use core::{
+    convert::TryFrom,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
     str::FromStr,
 };
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub use address::{AddressFamily, AddressRange};
+pub use base64_options::Base64Options;
+#[cfg(feature = "std")]
+pub use bloom::EnrBloomFilter;
+pub use builder::{EnrBuilder, Transport};
+pub use bytes::Bytes;
+#[cfg(feature = "lru")]
+pub use cache::EnrCache;
+pub use client::ClientInfo;
+#[cfg(feature = "ed25519")]
+pub use decode::decode_with_options;
+pub use decode::EnrDecodeOptions;
+pub use diff::EnrDiff;
+#[cfg(feature = "eth")]
+pub use eth::{EthEnrBuilderExt, EthEnrExt, ForkId};
+#[cfg(feature = "eth2")]
+pub use eth2::{
+    AttestationBitfield, Attnets, Syncnets, ATTESTATION_SUBNET_COUNT, SYNC_COMMITTEE_SUBNET_COUNT,
+};
+#[cfg(all(feature = "std", feature = "ed25519"))]
+pub use export::{EnrFormat, EnrReader, EnrWriter};
+pub use field::{EnrField, EnrWarning, FieldError, SEQ_WARNING_MARGIN};
+pub use identity::{IdentityScheme, V4Scheme};
+pub use list::DecodeListError;
+#[cfg(feature = "ed25519")]
+pub use merkle::{MerkleProof, MerkleTree};
+pub use meta::{AnnotatedEnr, EnrWithMeta};
+pub use rlp::{self, DecoderError};
 
-pub use builder::EnrBuilder;
-
+use core::marker::PhantomData;
 #[cfg(feature = "rust-secp256k1")]
 pub use keys::c_secp256k1;
+#[cfg(feature = "ed25519-v2")]
+pub use keys::ed25519_dalek_v2;
 #[cfg(feature = "libsecp256k1")]
 pub use keys::secp256k1;
 #[cfg(feature = "ed25519")]
 pub use keys::{ed25519_dalek, CombinedKey, CombinedPublicKey};
-pub use keys::{EnrKey, EnrPublicKey};
-pub use node_id::NodeId;
-use std::marker::PhantomData;
+pub use keys::{EnrKey, EnrPublicKey, SigningError};
+pub use node_id::{NodeId, NodeIdParseError};
+pub use parts::EnrParts;
+pub use protocol::ProtocolField;
+#[cfg(feature = "std")]
+pub use scored::{ScorePolicy, ScoredEnr};
+#[cfg(feature = "lru")]
+pub use seen::SeenEnrs;
+#[cfg(feature = "std")]
+pub use set::{EnrSet, TimedEnrSet};
+pub use socket::IpPreference;
+#[cfg(feature = "std")]
+pub use store::{EnrStore, HashMapEnrStore};
+#[cfg(feature = "std")]
+pub use timed::TimedEnr;
+pub use validators::{FieldValidationError, FieldValidator, FieldValidators};
 
 const MAX_ENR_SIZE: usize = 300;
 
@@ -205,7 +380,6 @@ const MAX_ENR_SIZE: usize = 300;
 /// `secp256k1` using the `libsecp256k1` library.
 ///
 /// This struct will always have a valid signature, known public key type, sequence number and `NodeId`. All other parameters are variable/optional.
-#[derive(Eq)]
 pub struct Enr<K: EnrKey> {
     /// ENR sequence number.
     seq: u64,
@@ -214,17 +388,46 @@ pub struct Enr<K: EnrKey> {
     node_id: NodeId,
 
     /// Key-value contents of the ENR. A BTreeMap is used to get the keys in sorted order, which is
-    /// important for verifying the signature of the ENR.
-    content: BTreeMap<String, Vec<u8>>,
+    /// important for verifying the signature of the ENR. Values are stored in their RLP-encoded
+    /// form. Fields are kept verbatim, including those unknown to the accessors, until removed
+    /// with [`Enr::remove`].
+    content: BTreeMap<String, Bytes>,
 
     /// The signature of the ENR record, stored as bytes.
     signature: Vec<u8>,
 
+    /// The identity scheme of the record. This is `None` for decoded records of an unknown
+    /// identity scheme, which can neither be verified nor modified.
+    scheme: Option<Arc<dyn IdentityScheme<K>>>,
+
     /// Marker to pin the generic.
     phantom: PhantomData<K>,
 }
 
 impl<K: EnrKey> Enr<K> {
+    /// Constructs a builder for a record of the "v4" identity scheme, equivalent to
+    /// `EnrBuilder::new("v4")`.
+    #[must_use]
+    pub fn builder() -> EnrBuilder<K> {
+        EnrBuilder::new("v4")
+    }
+
+    /// Constructs a signed record without any endpoint fields, holding only the `id` and public
+    /// key at sequence number 1.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "libsecp256k1")] {
+    /// use enr::{secp256k1::SecretKey, Enr};
+    ///
+    /// let key = SecretKey::random(&mut rand::thread_rng());
+    /// let enr = Enr::empty(&key).unwrap();
+    /// assert!(enr.verify());
+    /// # }
+    /// ```
+    pub fn empty(enr_key: &K) -> Result<Self, EnrError> {
+        Self::builder().build(enr_key)
+    }
+
     // getters //
 
     /// The `NodeId` for the record.
@@ -239,20 +442,50 @@ impl<K: EnrKey> Enr<K> {
         self.seq
     }
 
-    /// Reads a custom key from the record if it exists.
-    pub fn get(&self, key: impl Into<String>) -> Option<&Vec<u8>> {
-        self.content.get(&key.into())
+    /// Reads a custom key from the record if it exists, returning the RLP-decoded value.
+    ///
+    /// The value is borrowed from the record, no allocation is performed.
+    pub fn get(&self, key: impl AsRef<str>) -> Option<&[u8]> {
+        self.get_raw_rlp(key)
+            .and_then(|raw| Rlp::new(raw).data().ok())
+    }
+
+    /// Reads a custom key from the record if it exists, returning the raw RLP-encoded value.
+    pub fn get_raw_rlp(&self, key: impl AsRef<str>) -> Option<&[u8]> {
+        self.content.get(key.as_ref()).map(|value| &value[..])
+    }
+
+    /// Reads a custom key whose value is an RLP list, decoding each item as a `T`.
+    ///
+    /// Returns `None` if the key is not in the record, and an error if the value is not a list
+    /// or an item cannot be decoded.
+    pub fn get_list<T: rlp::Decodable>(
+        &self,
+        key: impl AsRef<str>,
+    ) -> Option<Result<Vec<T>, DecoderError>> {
+        self.get_raw_rlp(key).map(|raw| {
+            let rlp = Rlp::new(raw);
+            if !rlp.is_list() {
+                return Err(DecoderError::RlpExpectedToBeList);
+            }
+            rlp.as_list()
+        })
+    }
+
+    /// Reads a custom key from the record if it exists, returning an owned copy of the value.
+    pub fn get_owned(&self, key: impl AsRef<str>) -> Option<Vec<u8>> {
+        self.get(key).map(<[u8]>::to_vec)
     }
 
-    /// Returns an iterator over all key/value pairs in the ENR.
-    pub fn iter(&self) -> impl Iterator<Item = (&String, &Vec<u8>)> {
-        self.content.iter()
+    /// Returns an iterator over all key/value pairs in the ENR. The values are RLP-encoded.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &[u8])> {
+        self.content.iter().map(|(key, value)| (key, &value[..]))
     }
 
     /// Returns the IPv4 address of the ENR record if it is defined.
     #[must_use]
     pub fn ip(&self) -> Option<Ipv4Addr> {
-        if let Some(ip_bytes) = self.content.get("ip") {
+        if let Some(ip_bytes) = self.get("ip") {
             return match ip_bytes.len() {
                 4 => {
                     let mut ip = [0_u8; 4];
@@ -268,7 +501,7 @@ impl<K: EnrKey> Enr<K> {
     /// Returns the IPv6 address of the ENR record if it is defined.
     #[must_use]
     pub fn ip6(&self) -> Option<Ipv6Addr> {
-        if let Some(ip_bytes) = self.content.get("ip6") {
+        if let Some(ip_bytes) = self.get("ip6") {
             return match ip_bytes.len() {
                 16 => {
                     let mut ip = [0_u8; 16];
@@ -281,97 +514,269 @@ impl<K: EnrKey> Enr<K> {
         None
     }
 
+    /// Returns the IPv4 address of the `ip` field.
+    ///
+    /// Unlike [`Enr::ip`], a value that is not exactly 4 bytes long is reported as
+    /// [`FieldError::InvalidLength`] rather than treated as absent.
+    pub fn ip4_checked(&self) -> Result<Option<IpAddr>, FieldError> {
+        self.get_field::<fields::Ip4>()
+            .transpose()
+            .map(|ip| ip.map(|ip| IpAddr::V4(ip.0)))
+    }
+
+    /// Returns the IPv6 address of the `ip6` field.
+    ///
+    /// Unlike [`Enr::ip6`], a value that is not exactly 16 bytes long is reported as
+    /// [`FieldError::InvalidLength`] rather than treated as absent.
+    pub fn ip6_checked(&self) -> Result<Option<IpAddr>, FieldError> {
+        self.get_field::<fields::Ip6>()
+            .transpose()
+            .map(|ip| ip.map(|ip| IpAddr::V6(ip.0)))
+    }
+
+    /// Returns the address of the `ip6` field, converting IPv4-mapped (`::ffff:a.b.c.d`) and
+    /// IPv4-compatible (`::a.b.c.d`) addresses to the IPv4 address they embed.
+    ///
+    /// Some nodes advertise their IPv4 address in the `ip6` field, which cannot be dialed from an
+    /// IPv6-only socket. Only the returned view is normalized, the record is left untouched.
+    #[must_use]
+    pub fn ip6_normalized(&self) -> Option<IpAddr> {
+        self.ip6()
+            .map(|ip6| address::embedded_ipv4(ip6).map_or(IpAddr::V6(ip6), IpAddr::V4))
+    }
+
     /// The `id` of ENR record if it is defined.
     #[must_use]
     pub fn id(&self) -> Option<String> {
-        if let Some(id_bytes) = self.content.get("id") {
+        if let Some(id_bytes) = self.get("id") {
             return Some(String::from_utf8_lossy(id_bytes).to_string());
         }
         None
     }
 
-    /// The TCP port of ENR record if it is defined.
+    /// The TCP port of ENR record if it is defined. An alias of [`Enr::tcp4`], the IPv6-specific
+    /// port is never returned.
     #[must_use]
     pub fn tcp(&self) -> Option<u16> {
-        if let Some(tcp_bytes) = self.content.get("tcp") {
-            if tcp_bytes.len() <= 2 {
-                let mut tcp = [0_u8; 2];
-                tcp[2 - tcp_bytes.len()..].copy_from_slice(tcp_bytes);
-                return Some(u16::from_be_bytes(tcp));
-            }
-        }
-        None
+        self.tcp4()
+    }
+
+    /// The IPv4 TCP port, the `tcp` field, of ENR record if it is defined.
+    #[must_use]
+    pub fn tcp4(&self) -> Option<u16> {
+        self.get_u16("tcp").ok().flatten()
     }
 
     /// The IPv6-specific TCP port of ENR record if it is defined.
     #[must_use]
     pub fn tcp6(&self) -> Option<u16> {
-        if let Some(tcp_bytes) = self.content.get("tcp6") {
-            if tcp_bytes.len() <= 2 {
-                let mut tcp = [0_u8; 2];
-                tcp[2 - tcp_bytes.len()..].copy_from_slice(tcp_bytes);
-                return Some(u16::from_be_bytes(tcp));
-            }
-        }
-        None
+        self.get_u16("tcp6").ok().flatten()
     }
 
-    /// The UDP port of ENR record if it is defined.
+    /// The UDP port of ENR record if it is defined. An alias of [`Enr::udp4`], the IPv6-specific
+    /// port is never returned.
     #[must_use]
     pub fn udp(&self) -> Option<u16> {
-        if let Some(udp_bytes) = self.content.get("udp") {
-            if udp_bytes.len() <= 2 {
-                let mut udp = [0_u8; 2];
-                udp[2 - udp_bytes.len()..].copy_from_slice(udp_bytes);
-                return Some(u16::from_be_bytes(udp));
-            }
-        }
-        None
+        self.udp4()
+    }
+
+    /// The IPv4 UDP port, the `udp` field, of ENR record if it is defined.
+    #[must_use]
+    pub fn udp4(&self) -> Option<u16> {
+        self.get_u16("udp").ok().flatten()
     }
 
     /// The IPv6-specific UDP port of ENR record if it is defined.
     #[must_use]
     pub fn udp6(&self) -> Option<u16> {
-        if let Some(udp_bytes) = self.content.get("udp6") {
-            if udp_bytes.len() <= 2 {
-                let mut udp = [0_u8; 2];
-                udp[2 - udp_bytes.len()..].copy_from_slice(udp_bytes);
-                return Some(u16::from_be_bytes(udp));
-            }
-        }
-        None
+        self.get_u16("udp6").ok().flatten()
+    }
+
+    /// The IPv4 QUIC port, the `quic` field, of ENR record if it is defined.
+    #[must_use]
+    pub fn quic(&self) -> Option<u16> {
+        self.get_u16("quic").ok().flatten()
+    }
+
+    /// The IPv6-specific QUIC port of ENR record if it is defined.
+    #[must_use]
+    pub fn quic6(&self) -> Option<u16> {
+        self.get_u16("quic6").ok().flatten()
     }
 
     /// Provides a socket (based on the UDP port), if the IP and UDP fields are specified.
+    ///
+    /// The IPv4 socket is preferred, falling back to the IPv6 socket. Use
+    /// [`Enr::udp4_socket`] or [`Enr::udp6_socket`] for a socket of a specific family.
     #[must_use]
     pub fn udp_socket(&self) -> Option<SocketAddr> {
-        if let Some(ip) = self.ip() {
-            if let Some(udp) = self.udp() {
-                return Some(SocketAddr::new(IpAddr::V4(ip), udp));
-            }
-        }
-        if let Some(ip6) = self.ip6() {
-            if let Some(udp6) = self.udp6() {
-                return Some(SocketAddr::new(IpAddr::V6(ip6), udp6));
-            }
-        }
-        None
+        self.udp4_socket()
+            .map(SocketAddr::V4)
+            .or_else(|| self.udp6_socket().map(SocketAddr::V6))
     }
 
-    /// Provides a socket (based on the TCP port), if the IP and UDP fields are specified.
+    /// Provides a socket (based on the TCP port), if the IP and TCP fields are specified.
+    ///
+    /// The IPv4 socket is preferred, falling back to the IPv6 socket. Use
+    /// [`Enr::tcp4_socket`] or [`Enr::tcp6_socket`] for a socket of a specific family.
     #[must_use]
     pub fn tcp_socket(&self) -> Option<SocketAddr> {
-        if let Some(ip) = self.ip() {
-            if let Some(tcp) = self.tcp() {
-                return Some(SocketAddr::new(IpAddr::V4(ip), tcp));
-            }
-        }
-        if let Some(ip6) = self.ip6() {
-            if let Some(tcp6) = self.tcp6() {
-                return Some(SocketAddr::new(IpAddr::V6(ip6), tcp6));
-            }
+        self.tcp4_socket()
+            .map(SocketAddr::V4)
+            .or_else(|| self.tcp6_socket().map(SocketAddr::V6))
+    }
+
+    /// Provides a socket (based on the UDP port) like [`Enr::udp_socket`], reporting a malformed
+    /// address or port of a family that has a UDP port instead of skipping that family.
+    pub fn udp_socket_checked(&self) -> Result<Option<SocketAddr>, FieldError> {
+        Ok(self
+            .socket_checked(Self::ip4_checked, "udp")?
+            .or(self.socket_checked(Self::ip6_checked, "udp6")?))
+    }
+
+    /// Provides a socket (based on the TCP port) like [`Enr::tcp_socket`], reporting a malformed
+    /// address or port of a family that has a TCP port instead of skipping that family.
+    pub fn tcp_socket_checked(&self) -> Result<Option<SocketAddr>, FieldError> {
+        Ok(self
+            .socket_checked(Self::ip4_checked, "tcp")?
+            .or(self.socket_checked(Self::ip6_checked, "tcp6")?))
+    }
+
+    /// The socket of the port `port_key` and the address read by `ip`, if the port is set.
+    fn socket_checked(
+        &self,
+        ip: fn(&Self) -> Result<Option<IpAddr>, FieldError>,
+        port_key: &str,
+    ) -> Result<Option<SocketAddr>, FieldError> {
+        match self.get_u16(port_key)? {
+            Some(port) => Ok(ip(self)?.map(|ip| SocketAddr::new(ip, port))),
+            None => Ok(None),
         }
-        None
+    }
+
+    /// Provides the IPv4 UDP socket, if the `ip` and `udp` fields are specified.
+    #[must_use]
+    pub fn udp4_socket(&self) -> Option<SocketAddrV4> {
+        Some(SocketAddrV4::new(self.ip()?, self.udp4()?))
+    }
+
+    /// Provides the IPv4 TCP socket, if the `ip` and `tcp` fields are specified.
+    #[must_use]
+    pub fn tcp4_socket(&self) -> Option<SocketAddrV4> {
+        Some(SocketAddrV4::new(self.ip()?, self.tcp4()?))
+    }
+
+    /// Provides the IPv6 UDP socket, if the `ip6` and `udp6` fields are specified.
+    #[must_use]
+    pub fn udp6_socket(&self) -> Option<SocketAddrV6> {
+        Some(SocketAddrV6::new(self.ip6()?, self.udp6()?, 0, 0))
+    }
+
+    /// Provides the IPv6 TCP socket, if the `ip6` and `tcp6` fields are specified.
+    #[must_use]
+    pub fn tcp6_socket(&self) -> Option<SocketAddrV6> {
+        Some(SocketAddrV6::new(self.ip6()?, self.tcp6()?, 0, 0))
+    }
+
+    /// Provides the IPv4 QUIC socket, if the `ip` and `quic` fields are specified.
+    #[must_use]
+    pub fn quic_socket(&self) -> Option<SocketAddrV4> {
+        Some(SocketAddrV4::new(self.ip()?, self.quic()?))
+    }
+
+    /// Provides the IPv6 QUIC socket, if the `ip6` and `quic6` fields are specified.
+    #[must_use]
+    pub fn quic6_socket(&self) -> Option<SocketAddrV6> {
+        Some(SocketAddrV6::new(self.ip6()?, self.quic6()?, 0, 0))
+    }
+
+    /// Provides the UDP socket of the `ip6` and `udp6` fields, with an IPv4 address embedded in
+    /// the `ip6` field normalized to an IPv4 socket, see [`Enr::ip6_normalized`].
+    #[must_use]
+    pub fn udp6_socket_normalized(&self) -> Option<SocketAddr> {
+        Some(SocketAddr::new(self.ip6_normalized()?, self.udp6()?))
+    }
+
+    /// Provides the TCP socket of the `ip6` and `tcp6` fields, with an IPv4 address embedded in
+    /// the `ip6` field normalized to an IPv4 socket, see [`Enr::ip6_normalized`].
+    #[must_use]
+    pub fn tcp6_socket_normalized(&self) -> Option<SocketAddr> {
+        Some(SocketAddr::new(self.ip6_normalized()?, self.tcp6()?))
+    }
+
+    /// The address families of the `ip` and `ip6` fields. A malformed address is not
+    /// advertised.
+    #[must_use]
+    pub fn address_family(&self) -> AddressFamily {
+        AddressFamily::from_presence(self.ip().is_some(), self.ip6().is_some())
+    }
+
+    /// Provides the IPv4 and IPv6 UDP sockets, see [`Enr::udp4_socket`] and
+    /// [`Enr::udp6_socket`].
+    #[must_use]
+    pub fn dual_socket_addrs(&self) -> (Option<SocketAddr>, Option<SocketAddr>) {
+        (
+            self.udp4_socket().map(SocketAddr::V4),
+            self.udp6_socket().map(SocketAddr::V6),
+        )
+    }
+
+    /// Provides all advertised UDP sockets, the IPv4 socket (`ip` and `udp`) followed by the IPv6
+    /// socket (`ip6` and `udp6`).
+    pub fn udp_sockets(&self) -> impl Iterator<Item = SocketAddr> {
+        let udp4 = self
+            .ip()
+            .zip(self.udp())
+            .map(|(ip, udp)| SocketAddr::new(IpAddr::V4(ip), udp));
+        let udp6 = self
+            .ip6()
+            .zip(self.udp6())
+            .map(|(ip6, udp6)| SocketAddr::new(IpAddr::V6(ip6), udp6));
+        udp4.into_iter().chain(udp6)
+    }
+
+    /// Returns `true` if the record advertises a UDP endpoint, that is, `ip` and `udp` or `ip6`
+    /// and `udp6`. An address without a matching port, or a port without an address, is not an
+    /// endpoint.
+    #[must_use]
+    pub fn udp_reachable(&self) -> bool {
+        (self.ip().is_some() && self.udp().is_some())
+            || (self.ip6().is_some() && self.udp6().is_some())
+    }
+
+    /// Returns `true` if the record advertises a TCP endpoint, that is, `ip` and `tcp` or `ip6`
+    /// and `tcp6`. An address without a matching port, or a port without an address, is not an
+    /// endpoint.
+    #[must_use]
+    pub fn tcp_reachable(&self) -> bool {
+        (self.ip().is_some() && self.tcp().is_some())
+            || (self.ip6().is_some() && self.tcp6().is_some())
+    }
+
+    /// Returns `true` if the record advertises an IPv4 endpoint, that is, `ip` along with `udp`
+    /// or `tcp`.
+    #[must_use]
+    pub fn supports_ipv4(&self) -> bool {
+        self.ip().is_some() && (self.udp().is_some() || self.tcp().is_some())
+    }
+
+    /// Returns `true` if the record advertises an IPv6 endpoint, that is, `ip6` along with `udp6`
+    /// or `tcp6`.
+    ///
+    /// An IPv4-mapped (`::ffff:a.b.c.d`) or IPv4-compatible (`::a.b.c.d`) address in the `ip6`
+    /// field does not count, as the node is only reachable over IPv4. Such an endpoint is still reported by
+    /// [`Enr::udp_reachable`], [`Enr::tcp_reachable`] and [`Enr::udp_sockets`].
+    #[must_use]
+    pub fn supports_ipv6(&self) -> bool {
+        self.ip6()
+            .is_some_and(|ip6| address::embedded_ipv4(ip6).is_none())
+            && (self.udp6().is_some() || self.tcp6().is_some())
+    }
+
+    /// Returns `true` if the record advertises both an IPv4 and an IPv6 endpoint.
+    #[must_use]
+    pub fn is_dual_stack(&self) -> bool {
+        self.supports_ipv4() && self.supports_ipv6()
     }
 
     /// The signature of the ENR record.
@@ -387,14 +792,155 @@ impl<K: EnrKey> Enr<K> {
     }
 
     /// Verify the signature of the ENR record.
+    ///
+    /// Records of an unknown identity scheme, or whose `id` is not that of their scheme, never
+    /// verify.
     #[must_use]
     pub fn verify(&self) -> bool {
-        let pubkey = self.public_key();
-        match self.id() {
-            Some(ref id) if id == "v4" => pubkey.verify_v4(&self.rlp_content(), &self.signature),
-            // unsupported identity schemes
-            _ => false,
+        self.scheme.as_ref().is_some_and(|scheme| {
+            self.id().as_deref() == Some(scheme.scheme_id())
+                && scheme.verify(&self.public_key(), &self.rlp_content(), &self.signature)
+        })
+    }
+
+    /// Verify the signature of the ENR record against an arbitrary public key under the `v4`
+    /// identity scheme, rather than the key embedded in the record.
+    #[must_use]
+    pub fn verify_with(&self, public_key: &impl EnrPublicKey) -> bool {
+        self.id().as_deref() == Some("v4")
+            && public_key.verify_v4(&self.signed_content(), &self.signature)
+    }
+
+    /// The identity scheme of the record, if it is known. Records decoded with an unknown
+    /// identity scheme return `None`, the scheme name is still available via [`Enr::id`].
+    #[must_use]
+    pub fn identity_scheme(&self) -> Option<&'static str> {
+        self.scheme.as_ref().map(|scheme| scheme.scheme_id())
+    }
+
+    /// Associates a custom identity scheme with a decoded record, verifying the record under it.
+    ///
+    /// Records with an identity scheme other than "v4" are only decoded, without verification,
+    /// with [`EnrDecodeOptions::allow_unknown_schemes`]. This attaches the scheme the record was
+    /// created with and recomputes the `NodeId` accordingly.
+    ///
+    /// # Errors
+    /// Fails if the `id` of the record does not match the scheme, or the signature does not
+    /// verify.
+    pub fn with_identity_scheme(
+        mut self,
+        scheme: impl IdentityScheme<K> + 'static,
+    ) -> Result<Self, EnrError> {
+        if self.id().as_deref() != Some(scheme.scheme_id()) {
+            return Err(EnrError::UnsupportedIdentityScheme);
+        }
+        self.node_id = NodeId::new(&scheme.node_id(&self.public_key()));
+        self.scheme = Some(Arc::new(scheme));
+        if !self.verify() {
+            return Err(EnrError::InvalidSignature);
+        }
+        Ok(self)
+    }
+
+    /// Converts the record into a record of key type `N`, without re-signing it. The content,
+    /// sequence number and signature are carried over unchanged.
+    ///
+    /// Records of a custom identity scheme become records of an unknown identity scheme, see
+    /// [`Enr::with_identity_scheme`].
+    ///
+    /// # Errors
+    /// Returns the record unchanged if its public key cannot be read by `N`, or it does not
+    /// verify with `N`.
+    pub fn try_map_key<N: EnrKey>(self) -> Result<Enr<N>, Self> {
+        let Ok(public_key) = N::enr_to_public(&self.content) else {
+            return Err(self);
+        };
+        let scheme: Option<Arc<dyn IdentityScheme<N>>> = match self.identity_scheme() {
+            Some("v4") => {
+                if !public_key.verify_v4(&self.rlp_content(), &self.signature) {
+                    return Err(self);
+                }
+                Some(Arc::new(V4Scheme))
+            }
+            _ => None,
+        };
+        Ok(Enr {
+            seq: self.seq,
+            node_id: self.node_id,
+            content: self.content,
+            signature: self.signature,
+            scheme,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Converts the record into a record of the [`CombinedKey`] type, without re-signing it. The
+    /// content, sequence number and signature are carried over unchanged.
+    ///
+    /// Records of a custom identity scheme become records of an unknown identity scheme, see
+    /// [`Enr::with_identity_scheme`].
+    #[cfg(feature = "ed25519")]
+    #[must_use]
+    pub fn into_combined(self) -> Enr<CombinedKey>
+    where
+        K::PublicKey: core::convert::TryInto<CombinedPublicKey>,
+    {
+        let scheme: Option<Arc<dyn IdentityScheme<CombinedKey>>> = match self.identity_scheme() {
+            Some("v4") => Some(Arc::new(V4Scheme)),
+            _ => None,
+        };
+        Enr {
+            seq: self.seq,
+            node_id: self.node_id,
+            content: self.content,
+            signature: self.signature,
+            scheme,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Republishes the content of the record under another key, leaving this record untouched.
+    ///
+    /// All fields except the `id` and the public key of this record are copied to a new `v4`
+    /// record holding the public key of `key`, signed by `key` with sequence number `seq`, or 1
+    /// if not given.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::ExceedsMaxSizeWithKey`] if the content no longer fits the record
+    /// with the new public key.
+    pub fn clone_with_key<N: EnrKey>(&self, key: &N, seq: Option<u64>) -> Result<Enr<N>, EnrError> {
+        let own_key = K::enr_to_public(&self.content)
+            .ok()
+            .map(|public_key| public_key.enr_key());
+        let mut content: BTreeMap<String, Bytes> = self
+            .content
+            .iter()
+            .filter(|(k, _)| k.as_str() != "id" && Some(k.as_str()) != own_key.as_deref())
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        let public_key = key.public();
+        content.insert("id".into(), rlp::encode(&"v4").into());
+        content.insert(
+            public_key.enr_key(),
+            rlp::encode(&public_key.encode()).into(),
+        );
+
+        let mut enr = Enr {
+            seq: seq.unwrap_or(1),
+            node_id: NodeId::new(&IdentityScheme::<N>::node_id(&V4Scheme, &public_key)),
+            signature: Vec::new(),
+            content,
+            scheme: Some(Arc::new(V4Scheme)),
+            phantom: PhantomData,
+        };
+        enr.sign(key)?;
+
+        let size = enr.encode().len();
+        if size > MAX_ENR_SIZE {
+            return Err(EnrError::ExceedsMaxSizeWithKey(size));
         }
+        Ok(enr)
     }
 
     /// RLP encodes the ENR into a byte array.
@@ -402,7 +948,7 @@ impl<K: EnrKey> Enr<K> {
     pub fn encode(&self) -> Vec<u8> {
         let mut s = RlpStream::new();
         s.append(self);
-        s.drain()
+        s.out().into()
     }
 
     /// Provides the URL-safe base64 encoded "text" version of the ENR prefixed by "enr:".
@@ -412,23 +958,176 @@ impl<K: EnrKey> Enr<K> {
         format!("enr:{}", hex)
     }
 
+    /// Decodes the text form of a record exactly as specified by EIP-778, `enr:` followed by
+    /// unpadded URL-safe base64, verifying its signature.
+    ///
+    /// Parsing with [`str::parse`] also accepts surrounding whitespace, a missing prefix, the
+    /// standard base64 alphabet and padding.
+    pub fn from_str_strict(text: &str) -> Result<Self, String> {
+        let encoded = text
+            .strip_prefix("enr:")
+            .ok_or_else(|| "Invalid ENR string: missing the enr: prefix".to_string())?;
+        if encoded.contains('=') {
+            return Err("Invalid base64 encoding: padding".to_string());
+        }
+        let bytes = base64::decode_config(encoded, base64::URL_SAFE_NO_PAD)
+            .map_err(|e| format!("Invalid base64 encoding: {e:?}"))?;
+        Self::decode_bytes(&bytes.into()).map_err(|e| format!("Invalid ENR: {e:?}"))
+    }
+
+    /// Provides the 0x-prefixed, lower case hex encoding of the RLP-encoded record, as exchanged
+    /// by some JSON-RPC interfaces.
+    #[must_use]
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.encode()))
+    }
+
+    /// Decodes the hex encoding of an RLP-encoded record, with or without a `0x` prefix,
+    /// verifying its signature. Surrounding whitespace is ignored.
+    pub fn from_hex(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let encoded = text.strip_prefix("0x").unwrap_or(text);
+        if !encoded.len().is_multiple_of(2) {
+            return Err("Invalid hex encoding: odd length".to_string());
+        }
+        let bytes = hex::decode(encoded).map_err(|e| format!("Invalid hex encoding: {e:?}"))?;
+        Self::decode_bytes(&bytes.into()).map_err(|e| format!("Invalid ENR: {e:?}"))
+    }
+
+    /// Decodes a record from either its base64 text form, as by [`str::parse`], or its hex
+    /// encoding, as by [`Enr::from_hex`]. Text starting with `0x` is decoded as hex, and other
+    /// text is decoded as hex only if it is not a valid base64 record.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if text.trim_start().starts_with("0x") {
+            return Self::from_hex(text);
+        }
+        text.parse()
+            .or_else(|e| Self::from_hex(text).map_err(|_| e))
+    }
+
+    /// The exact payload that is signed, the RLP list `[seq, k, v, ...]` with the key-value pairs
+    /// sorted by key. This is the message passed to [`EnrKey::sign_v4`].
+    #[must_use]
+    pub fn signed_content(&self) -> Vec<u8> {
+        self.rlp_content()
+    }
+
     /// Returns the current size of the ENR.
     #[must_use]
     pub fn size(&self) -> usize {
         self.rlp_content().len()
     }
 
+    /// Returns `true` if both records hold identical signed content, that is, the same sequence
+    /// number and the same key-value pairs. The signatures are not compared.
+    ///
+    /// This does not re-encode either record and performs no allocations.
+    #[must_use]
+    pub fn content_eq(&self, other: &Self) -> bool {
+        self.seq == other.seq && self.content == other.content
+    }
+
+    /// Returns `true` if both records belong to the same node, regardless of their sequence
+    /// numbers and contents.
+    #[must_use]
+    pub fn same_identity(&self, other: &Self) -> bool {
+        self.node_id == other.node_id
+    }
+
+    /// Returns `true` if both records belong to the same node and this record has a higher
+    /// sequence number.
+    #[must_use]
+    pub fn is_newer_than(&self, other: &Self) -> bool {
+        self.same_identity(other) && self.seq > other.seq
+    }
+
+    /// Compares the freshness of two records of the same node by their sequence numbers. Returns
+    /// `None` for records of different nodes.
+    #[must_use]
+    pub fn cmp_freshness(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.same_identity(other).then(|| self.seq.cmp(&other.seq))
+    }
+
+    /// The keccak256 digest of the signed content of the record. The preimage is the RLP list
+    /// `[seq, k, v, ...]` with the key-value pairs sorted by key, which is exactly the message
+    /// passed to [`EnrKey::sign_v4`].
+    ///
+    /// Two transmissions of the same record have the same content hash regardless of their
+    /// signatures.
+    #[must_use]
+    pub fn content_hash(&self) -> [u8; 32] {
+        digest(&self.rlp_content())
+    }
+
+    /// The keccak256 digest of the full record. The preimage is the RLP list
+    /// `[signature, seq, k, v, ...]` as returned by [`Enr::encode`].
+    #[must_use]
+    pub fn record_hash(&self) -> [u8; 32] {
+        digest(&self.encode())
+    }
+
     // Setters //
 
+    /// Replaces this record with a newer version of it received from a remote peer. No private key
+    /// is required as the signed content of the incoming record is adopted verbatim.
+    ///
+    /// An incoming record identical to the current one is accepted without modification.
+    ///
+    /// # Errors
+    /// The incoming record is rejected if its signature does not verify, it belongs to a
+    /// different node or its sequence number is not strictly greater than the current one.
+    pub fn update_from(&mut self, incoming: Self) -> Result<Updated, EnrError> {
+        let old_seq = self.seq;
+        if !incoming.verify() {
+            return Err(EnrError::InvalidSignature);
+        }
+        if incoming.node_id != self.node_id {
+            return Err(EnrError::NodeIdMismatch);
+        }
+        if incoming == *self {
+            return Ok(Updated {
+                updated: false,
+                old_seq,
+            });
+        }
+        if incoming.seq <= self.seq {
+            return Err(EnrError::StaleSequenceNumber);
+        }
+
+        *self = incoming;
+        Ok(Updated {
+            updated: true,
+            old_seq,
+        })
+    }
+
+    /// Clones the record with its sequence number incremented by one, without re-signing it. The
+    /// sequence number saturates at `u64::MAX`.
+    ///
+    /// The clone keeps the signature of this record and therefore has an invalid signature until
+    /// it is re-signed, for example with `set_seq(clone.seq(), key)`. It must not be published
+    /// before then.
+    #[must_use]
+    pub fn clone_with_incremented_seq(&self) -> Self {
+        let mut enr = self.clone();
+        enr.seq = self.seq.saturating_add(1);
+        enr
+    }
+
     /// Allows setting the sequence number to an arbitrary value.
     pub fn set_seq(&mut self, seq: u64, key: &K) -> Result<(), EnrError> {
+        // records of unknown identity schemes cannot be re-signed
+        if self.scheme.is_none() {
+            return Err(EnrError::UnsupportedIdentityScheme);
+        }
+
         self.seq = seq;
 
         // sign the record
         self.sign(key)?;
 
         // update the node id
-        self.node_id = NodeId::from(key.public());
+        self.update_node_id();
 
         // check the size of the record
         if self.size() > MAX_ENR_SIZE {
@@ -439,138 +1138,339 @@ impl<K: EnrKey> Enr<K> {
     }
 
     /// Adds or modifies a key/value to the ENR record. A `EnrKey` is required to re-sign the record once
-    /// modified.
+    /// modified. The value is RLP-encoded as a byte string.
     ///
     /// Returns the previous value in the record if it exists.
+    #[deprecated(
+        since = "0.1.0-alpha.7",
+        note = "use `insert_value` to RLP-encode a value or `insert_raw_rlp` for pre-encoded values"
+    )]
+    #[allow(clippy::needless_pass_by_value)]
     pub fn insert(
         &mut self,
         key: &str,
         value: Vec<u8>,
         enr_key: &K,
     ) -> Result<Option<Vec<u8>>, EnrError> {
-        // currently only support "v4" identity schemes
-        if key == "id" && value != b"v4" {
-            return Err(EnrError::UnsupportedIdentityScheme);
-        }
+        Ok(self
+            .insert_raw(key, rlp::encode(&value).into(), enr_key)?
+            .and_then(|raw| Rlp::new(&raw).data().ok().map(<[u8]>::to_vec)))
+    }
 
-        let previous_value = self.content.insert(key.into(), value);
-        // add the new public key
-        let public_key = enr_key.public();
-        let previous_key = self
-            .content
-            .insert(public_key.enr_key(), public_key.encode());
+    /// Adds or modifies a key/value to the ENR record, RLP-encoding the value. A `EnrKey` is
+    /// required to re-sign the record once modified.
+    ///
+    /// Malformed values of well-known keys, such as an `ip` that is not 4 bytes, are rejected
+    /// with [`EnrError::InvalidField`].
+    ///
+    /// Returns the previous RLP-encoded value in the record if it exists.
+    pub fn insert_value<T: rlp::Encodable>(
+        &mut self,
+        key: &str,
+        value: &T,
+        enr_key: &K,
+    ) -> Result<Option<Vec<u8>>, EnrError> {
+        self.insert_raw(key, rlp::encode(value).into(), enr_key)
+    }
 
-        // check the size of the record
-        if self.size() > MAX_ENR_SIZE {
-            // if the size of the record is too large, revert and error
-            // revert the public key
-            if let Some(key) = previous_key {
-                self.content.insert(public_key.enr_key(), key);
-            } else {
-                self.content.remove(&public_key.enr_key());
-            }
-            // revert the content
-            if let Some(prev_value) = previous_value {
+    /// Adds or modifies a key/value to the ENR record, RLP-encoding the items as a list. A
+    /// `EnrKey` is required to re-sign the record once modified. The value is read back with
+    /// [`get_list`](#method.get_list).
+    ///
+    /// Returns the previous RLP-encoded value in the record if it exists.
+    pub fn insert_list<T: rlp::Encodable>(
+        &mut self,
+        key: &str,
+        items: &[T],
+        enr_key: &K,
+    ) -> Result<Option<Vec<u8>>, EnrError> {
+        self.insert_raw(key, rlp::encode_list::<T, T>(items).into(), enr_key)
+    }
+
+    /// Adds or modifies a key/value to the ENR record, encoding the UTF-8 bytes of the string as
+    /// an RLP byte string. A `EnrKey` is required to re-sign the record once modified. The value
+    /// is read back with [`get_utf8`](#method.get_utf8).
+    ///
+    /// Returns the previous RLP-encoded value in the record if it exists.
+    pub fn insert_utf8(
+        &mut self,
+        key: &str,
+        value: &str,
+        enr_key: &K,
+    ) -> Result<Option<Vec<u8>>, EnrError> {
+        self.insert_raw(key, rlp::encode(&value.as_bytes()).into(), enr_key)
+    }
+
+    /// Adds or modifies a key/value to the ENR record where the value is already RLP-encoded. A
+    /// `EnrKey` is required to re-sign the record once modified.
+    ///
+    /// The value must be a single, well-formed RLP byte string with no trailing bytes. Use
+    /// [`insert_raw_rlp_list`](#method.insert_raw_rlp_list) to insert an RLP list.
+    ///
+    /// Malformed values of well-known keys, such as an `ip` that is not 4 bytes, are rejected
+    /// with [`EnrError::InvalidField`].
+    ///
+    /// Returns the previous RLP-encoded value in the record if it exists.
+    pub fn insert_raw_rlp(
+        &mut self,
+        key: &str,
+        value: Vec<u8>,
+        enr_key: &K,
+    ) -> Result<Option<Vec<u8>>, EnrError> {
+        validate_raw_rlp(&value, false)?;
+        self.insert_raw(key, value, enr_key)
+    }
+
+    /// Adds or modifies a key/value to the ENR record where the value is already RLP-encoded and
+    /// may be an RLP list. A `EnrKey` is required to re-sign the record once modified.
+    ///
+    /// The value must be a single, well-formed RLP item with no trailing bytes.
+    ///
+    /// Malformed values of well-known keys, such as an `ip` that is not 4 bytes, are rejected
+    /// with [`EnrError::InvalidField`].
+    ///
+    /// Returns the previous RLP-encoded value in the record if it exists.
+    pub fn insert_raw_rlp_list(
+        &mut self,
+        key: &str,
+        value: Vec<u8>,
+        enr_key: &K,
+    ) -> Result<Option<Vec<u8>>, EnrError> {
+        validate_raw_rlp(&value, true)?;
+        self.insert_raw(key, value, enr_key)
+    }
+
+    /// Inserts an RLP-encoded value into the record and re-signs it. The value is assumed to be
+    /// well-formed.
+    fn insert_raw(
+        &mut self,
+        key: &str,
+        value: Vec<u8>,
+        enr_key: &K,
+    ) -> Result<Option<Vec<u8>>, EnrError> {
+        // the identity scheme of a record cannot be changed, and records of unknown identity
+        // schemes cannot be re-signed
+        match self.scheme {
+            Some(ref scheme) if key != "id" || value == rlp::encode(&scheme.scheme_id()) => {}
+            _ => return Err(EnrError::UnsupportedIdentityScheme),
+        }
+
+        // the `id` was checked against the identity scheme of the record
+        if key != "id" {
+            field::validate(key, &value).map_err(EnrError::InvalidField)?;
+        }
+        let seq = self.next_seq()?;
+
+        let previous_value = self.content.insert(key.into(), value.into());
+        // add the new public key
+        let public_key = enr_key.public();
+        let previous_key = self.content.insert(
+            public_key.enr_key(),
+            rlp::encode(&public_key.encode()).into(),
+        );
+
+        // check the size of the record
+        if self.size() > MAX_ENR_SIZE {
+            // if the size of the record is too large, revert and error
+            // revert the public key
+            if let Some(key) = previous_key {
+                self.content.insert(public_key.enr_key(), key);
+            } else {
+                self.content.remove(&public_key.enr_key());
+            }
+            // revert the content
+            if let Some(prev_value) = previous_value {
                 self.content.insert(key.into(), prev_value);
             } else {
                 self.content.remove(key);
             }
             return Err(EnrError::ExceedsMaxSize);
         }
-        // increment the sequence number
-        self.seq = self
-            .seq
-            .checked_add(1)
-            .ok_or_else(|| EnrError::SequenceNumberTooHigh)?;
+        self.seq = seq;
+
+        // sign the record
+        self.sign(enr_key)?;
+
+        // update the node id
+        self.update_node_id();
+
+        if self.size() > MAX_ENR_SIZE {
+            // incase the signature size changes, inform the user the size has exceeded the maximum
+            return Err(EnrError::ExceedsMaxSize);
+        }
+
+        Ok(previous_value.map(Vec::from))
+    }
+
+    /// Removes a key from the ENR record. A `EnrKey` is required to re-sign the record once
+    /// modified. The `id` and public key fields cannot be removed.
+    ///
+    /// Returns the previous RLP-encoded value in the record if it existed. If the key is not
+    /// present, the record is left unmodified.
+    pub fn remove(&mut self, key: &str, enr_key: &K) -> Result<Option<Vec<u8>>, EnrError> {
+        let public_key = enr_key.public();
+        if key == "id" || key == public_key.enr_key() {
+            return Err(EnrError::ReservedKey(key.into()));
+        }
+
+        if !self.content.contains_key(key) {
+            return Ok(None);
+        }
+
+        // records of unknown identity schemes cannot be re-signed
+        if self.scheme.is_none() {
+            return Err(EnrError::UnsupportedIdentityScheme);
+        }
+
+        let seq = self.next_seq()?;
+
+        let previous_value = self.content.remove(key);
+        // add the new public key
+        self.content.insert(
+            public_key.enr_key(),
+            rlp::encode(&public_key.encode()).into(),
+        );
+        self.seq = seq;
 
         // sign the record
         self.sign(enr_key)?;
 
         // update the node id
-        self.node_id = NodeId::from(enr_key.public());
+        self.update_node_id();
 
         if self.size() > MAX_ENR_SIZE {
             // incase the signature size changes, inform the user the size has exceeded the maximum
             return Err(EnrError::ExceedsMaxSize);
         }
 
-        Ok(previous_value)
+        Ok(previous_value.map(Vec::from))
     }
 
-    /// Sets the `ip` field of the ENR. Returns any pre-existing IP address in the record.
+    /// Sets the IP address of the ENR, writing IPv4 addresses to the `ip` field and IPv6
+    /// addresses to the `ip6` field. The other field is left untouched. Returns any pre-existing
+    /// IP address of the same family in the record.
+    ///
+    /// IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`) are not converted and are written to the
+    /// `ip6` field as given. Use [`Enr::set_ip6`] to reject them.
     pub fn set_ip(&mut self, ip: IpAddr, key: &K) -> Result<Option<IpAddr>, EnrError> {
         match ip {
-            IpAddr::V4(addr) => {
-                let prev_value = self.insert("ip", addr.octets().to_vec(), key)?;
-                if let Some(bytes) = prev_value {
-                    if bytes.len() == 4 {
-                        let mut v = [0_u8; 4];
-                        v.copy_from_slice(&bytes);
-                        return Ok(Some(IpAddr::V4(Ipv4Addr::from(v))));
-                    }
-                }
-            }
-            IpAddr::V6(addr) => {
-                let prev_value = self.insert("ip6", addr.octets().to_vec(), key)?;
-                if let Some(bytes) = prev_value {
-                    if bytes.len() == 16 {
-                        let mut v = [0_u8; 16];
-                        v.copy_from_slice(&bytes);
-                        return Ok(Some(IpAddr::V6(Ipv6Addr::from(v))));
-                    }
-                }
-            }
+            IpAddr::V4(addr) => Ok(self.set_ip4(addr, key)?.map(IpAddr::V4)),
+            IpAddr::V6(addr) => Ok(self.insert_ip6(addr, key)?.map(IpAddr::V6)),
         }
+    }
 
+    /// Sets the `ip` field of the ENR. Returns any pre-existing IPv4 address in the record.
+    pub fn set_ip4(&mut self, ip: Ipv4Addr, key: &K) -> Result<Option<Ipv4Addr>, EnrError> {
+        let prev_value = self.insert_value("ip", &ip.octets().to_vec(), key)?;
+        if let Some(bytes) = prev_value.as_deref().and_then(rlp_data) {
+            if bytes.len() == 4 {
+                let mut v = [0_u8; 4];
+                v.copy_from_slice(bytes);
+                return Ok(Some(Ipv4Addr::from(v)));
+            }
+        }
         Ok(None)
     }
 
-    /// Sets the `udp` field of the ENR. Returns any pre-existing UDP port in the record.
-    pub fn set_udp(&mut self, udp: u16, key: &K) -> Result<Option<u16>, EnrError> {
-        if let Some(udp_bytes) = self.insert("udp", udp.to_be_bytes().to_vec(), key)? {
-            if udp_bytes.len() <= 2 {
-                let mut v = [0_u8; 2];
-                v[2 - udp_bytes.len()..].copy_from_slice(&udp_bytes);
-                return Ok(Some(u16::from_be_bytes(v)));
+    /// Sets the `ip6` field of the ENR. Returns any pre-existing IPv6 address in the record.
+    ///
+    /// # Errors
+    /// IPv4-mapped (`::ffff:a.b.c.d`) and IPv4-compatible (`::a.b.c.d`) IPv6 addresses are
+    /// rejected, as the node is only reachable over IPv4. These should be set with
+    /// [`Enr::set_ip4`].
+    pub fn set_ip6(&mut self, ip: Ipv6Addr, key: &K) -> Result<Option<Ipv6Addr>, EnrError> {
+        if address::embedded_ipv4(ip).is_some() {
+            return Err(EnrError::Ipv4MappedAddress(ip));
+        }
+        self.insert_ip6(ip, key)
+    }
+
+    /// Sets the `ip6` field of the ENR without validation.
+    fn insert_ip6(&mut self, ip: Ipv6Addr, key: &K) -> Result<Option<Ipv6Addr>, EnrError> {
+        let prev_value = self.insert_value("ip6", &ip.octets().to_vec(), key)?;
+        if let Some(bytes) = prev_value.as_deref().and_then(rlp_data) {
+            if bytes.len() == 16 {
+                let mut v = [0_u8; 16];
+                v.copy_from_slice(bytes);
+                return Ok(Some(Ipv6Addr::from(v)));
             }
         }
         Ok(None)
     }
 
+    /// Sets the `udp` field of the ENR. Returns any pre-existing UDP port in the record. An alias
+    /// of [`Enr::set_udp4`].
+    pub fn set_udp(&mut self, udp: u16, key: &K) -> Result<Option<u16>, EnrError> {
+        self.set_udp4(udp, key)
+    }
+
+    /// Sets the IPv4 UDP port, the `udp` field, of the ENR. Returns any pre-existing UDP port in
+    /// the record.
+    ///
+    /// # Errors
+    /// Port 0 is rejected with [`EnrError::InvalidPort`].
+    pub fn set_udp4(&mut self, udp: u16, key: &K) -> Result<Option<u16>, EnrError> {
+        check_port("udp", udp)?;
+        let prev_value = self.insert_value("udp", &udp, key)?;
+        Ok(prev_value.and_then(|raw| decode_port(&raw)))
+    }
+
     /// Sets the `udp6` field of the ENR. Returns any pre-existing UDP port in the record.
+    ///
+    /// # Errors
+    /// Port 0 is rejected with [`EnrError::InvalidPort`].
     pub fn set_udp6(&mut self, udp: u16, key: &K) -> Result<Option<u16>, EnrError> {
-        if let Some(udp_bytes) = self.insert("udp6", udp.to_be_bytes().to_vec(), key)? {
-            if udp_bytes.len() <= 2 {
-                let mut v = [0_u8; 2];
-                v[2 - udp_bytes.len()..].copy_from_slice(&udp_bytes);
-                return Ok(Some(u16::from_be_bytes(v)));
-            }
-        }
-        Ok(None)
+        check_port("udp6", udp)?;
+        let prev_value = self.insert_value("udp6", &udp, key)?;
+        Ok(prev_value.and_then(|raw| decode_port(&raw)))
     }
 
-    /// Sets the `tcp` field of the ENR. Returns any pre-existing tcp port in the record.
+    /// Sets the `tcp` field of the ENR. Returns any pre-existing tcp port in the record. An alias
+    /// of [`Enr::set_tcp4`].
     pub fn set_tcp(&mut self, tcp: u16, key: &K) -> Result<Option<u16>, EnrError> {
-        if let Some(tcp_bytes) = self.insert("tcp", tcp.to_be_bytes().to_vec(), key)? {
-            if tcp_bytes.len() <= 2 {
-                let mut v = [0_u8; 2];
-                v[2 - tcp_bytes.len()..].copy_from_slice(&tcp_bytes);
-                return Ok(Some(u16::from_be_bytes(v)));
-            }
-        }
-        Ok(None)
+        self.set_tcp4(tcp, key)
+    }
+
+    /// Sets the IPv4 TCP port, the `tcp` field, of the ENR. Returns any pre-existing tcp port in
+    /// the record.
+    ///
+    /// # Errors
+    /// Port 0 is rejected with [`EnrError::InvalidPort`].
+    pub fn set_tcp4(&mut self, tcp: u16, key: &K) -> Result<Option<u16>, EnrError> {
+        check_port("tcp", tcp)?;
+        let prev_value = self.insert_value("tcp", &tcp, key)?;
+        Ok(prev_value.and_then(|raw| decode_port(&raw)))
     }
 
     /// Sets the `tcp6` field of the ENR. Returns any pre-existing tcp6 port in the record.
+    ///
+    /// # Errors
+    /// Port 0 is rejected with [`EnrError::InvalidPort`].
     pub fn set_tcp6(&mut self, tcp: u16, key: &K) -> Result<Option<u16>, EnrError> {
-        if let Some(tcp_bytes) = self.insert("tcp6", tcp.to_be_bytes().to_vec(), key)? {
-            if tcp_bytes.len() <= 2 {
-                let mut v = [0_u8; 2];
-                v[2 - tcp_bytes.len()..].copy_from_slice(&tcp_bytes);
-                return Ok(Some(u16::from_be_bytes(v)));
-            }
-        }
-        Ok(None)
+        check_port("tcp6", tcp)?;
+        let prev_value = self.insert_value("tcp6", &tcp, key)?;
+        Ok(prev_value.and_then(|raw| decode_port(&raw)))
+    }
+
+    /// Sets the IPv4 QUIC port, the `quic` field, of the ENR. Returns any pre-existing QUIC port
+    /// in the record.
+    ///
+    /// # Errors
+    /// Port 0 is rejected with [`EnrError::InvalidPort`].
+    pub fn set_quic(&mut self, quic: u16, key: &K) -> Result<Option<u16>, EnrError> {
+        check_port("quic", quic)?;
+        let prev_value = self.insert_value("quic", &quic, key)?;
+        Ok(prev_value.and_then(|raw| decode_port(&raw)))
+    }
+
+    /// Sets the `quic6` field of the ENR. Returns any pre-existing quic6 port in the record.
+    ///
+    /// # Errors
+    /// Port 0 is rejected with [`EnrError::InvalidPort`].
+    pub fn set_quic6(&mut self, quic: u16, key: &K) -> Result<Option<u16>, EnrError> {
+        check_port("quic6", quic)?;
+        let prev_value = self.insert_value("quic6", &quic, key)?;
+        Ok(prev_value.and_then(|raw| decode_port(&raw)))
     }
 
     /// Sets the IP and UDP port in a single update with a single increment in sequence number.
@@ -591,23 +1491,37 @@ impl<K: EnrKey> Enr<K> {
             ("udp".into(), "udp6".into())
         };
 
+        if socket.is_ipv4() {
+            check_port(&port_string, socket.port())?;
+        } else {
+            check_port(&port_v6_string, socket.port())?;
+        }
+        // records of unknown identity schemes cannot be re-signed
+        if self.scheme.is_none() {
+            return Err(EnrError::UnsupportedIdentityScheme);
+        }
+        let seq = self.next_seq()?;
+
         let (prev_ip, prev_port) = match socket.ip() {
             IpAddr::V4(addr) => (
-                self.content.insert("ip".into(), addr.octets().to_vec()),
                 self.content
-                    .insert(port_string.clone(), socket.port().to_be_bytes().to_vec()),
+                    .insert("ip".into(), rlp::encode(&addr.octets().to_vec()).into()),
+                self.content
+                    .insert(port_string.clone(), rlp::encode(&socket.port()).into()),
             ),
             IpAddr::V6(addr) => (
-                self.content.insert("ip6".into(), addr.octets().to_vec()),
                 self.content
-                    .insert(port_v6_string.clone(), socket.port().to_be_bytes().to_vec()),
+                    .insert("ip6".into(), rlp::encode(&addr.octets().to_vec()).into()),
+                self.content
+                    .insert(port_v6_string.clone(), rlp::encode(&socket.port()).into()),
             ),
         };
 
         let public_key = key.public();
-        let previous_key = self
-            .content
-            .insert(public_key.enr_key(), public_key.encode());
+        let previous_key = self.content.insert(
+            public_key.enr_key(),
+            rlp::encode(&public_key.encode()).into(),
+        );
 
         // check the size and revert on failure
         if self.size() > MAX_ENR_SIZE {
@@ -648,29 +1562,33 @@ impl<K: EnrKey> Enr<K> {
             return Err(EnrError::ExceedsMaxSize);
         }
 
-        // increment the sequence number
-        self.seq = self
-            .seq
-            .checked_add(1)
-            .ok_or_else(|| EnrError::SequenceNumberTooHigh)?;
+        self.seq = seq;
 
         // sign the record
         self.sign(key)?;
 
         // update the node id
-        self.node_id = NodeId::from(key.public());
+        self.update_node_id();
 
         Ok(())
     }
 
     /// Sets a new public key for the record.
     pub fn set_public_key(&mut self, public_key: &K::PublicKey, key: &K) -> Result<(), EnrError> {
-        self.insert(&public_key.enr_key(), public_key.encode(), key)
+        self.insert_value(&public_key.enr_key(), &public_key.encode(), key)
             .map(|_| {})
     }
 
     // Private Functions //
 
+    /// The sequence number of the next update of the record. Checked before the record is
+    /// modified, so that a failed update leaves it unchanged.
+    fn next_seq(&self) -> Result<u64, EnrError> {
+        self.seq
+            .checked_add(1)
+            .ok_or(EnrError::SequenceNumberTooHigh)
+    }
+
     /// Evaluates the RLP-encoding of the content of the ENR record.
     fn rlp_content(&self) -> Vec<u8> {
         let mut stream = RlpStream::new();
@@ -678,24 +1596,41 @@ impl<K: EnrKey> Enr<K> {
         stream.append(&self.seq);
         for (k, v) in &self.content {
             stream.append(k);
-            stream.append(v);
+            // values are stored RLP-encoded
+            stream.append_raw(v, 1);
         }
-        stream.drain()
+        stream.out().into()
     }
 
-    /// Signs the ENR record based on the identity scheme. Currently only "v4" is supported.
+    /// Signs the ENR record based on its identity scheme.
     fn sign(&mut self, key: &K) -> Result<(), EnrError> {
-        self.signature = {
-            match self.id() {
-                Some(ref id) if id == "v4" => key
-                    .sign_v4(&self.rlp_content())
-                    .map_err(|_| EnrError::SigningError)?,
-                // other identity schemes are unsupported
-                _ => return Err(EnrError::SigningError),
-            }
+        self.signature = match self.scheme {
+            Some(ref scheme) => scheme
+                .sign(key, &self.rlp_content())
+                .map_err(|_| EnrError::SigningError)?,
+            // records of unknown identity schemes cannot be re-signed
+            None => return Err(EnrError::UnsupportedIdentityScheme),
         };
         Ok(())
     }
+
+    /// Derives the `NodeId` of the record from its public key according to its identity scheme.
+    fn update_node_id(&mut self) {
+        let public_key = self.public_key();
+        self.node_id = match self.scheme {
+            Some(ref scheme) => NodeId::new(&scheme.node_id(&public_key)),
+            None => NodeId::from(public_key),
+        };
+    }
+}
+
+/// The outcome of a successful [`Enr::update_from`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Updated {
+    /// Whether the record was replaced.
+    pub updated: bool,
+    /// The sequence number of the record prior to the update.
+    pub old_seq: u64,
 }
 
 // traits //
@@ -707,11 +1642,15 @@ impl<K: EnrKey> Clone for Enr<K> {
             node_id: self.node_id,
             content: self.content.clone(),
             signature: self.signature.clone(),
+            scheme: self.scheme.clone(),
             phantom: self.phantom,
         }
     }
 }
 
+/// Two records are equal if they are byte-identical, that is, they hold the same signed content
+/// and signature. Two records of the same node at different sequence numbers are not equal, see
+/// [`Enr::same_identity`].
 impl<K: EnrKey> PartialEq for Enr<K> {
     fn eq(&self, other: &Self) -> bool {
         self.seq == other.seq
@@ -721,8 +1660,54 @@ impl<K: EnrKey> PartialEq for Enr<K> {
     }
 }
 
-impl<K: EnrKey> std::fmt::Display for Enr<K> {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl<K: EnrKey> Eq for Enr<K> {}
+
+/// Orders records by `NodeId`, then sequence number, then their RLP encoding. This is a total
+/// order consistent with `PartialEq`, even for distinct records of a node with the same sequence
+/// number.
+impl<K: EnrKey> Ord for Enr<K> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.node_id
+            .cmp(&other.node_id)
+            .then(self.seq.cmp(&other.seq))
+            .then_with(|| {
+                if self == other {
+                    core::cmp::Ordering::Equal
+                } else {
+                    self.encode().cmp(&other.encode())
+                }
+            })
+    }
+}
+
+impl<K: EnrKey> PartialOrd for Enr<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Hashes the signed content and signature of the record, consistently with `PartialEq`.
+impl<K: EnrKey> core::hash::Hash for Enr<K> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.seq.hash(state);
+        self.content.hash(state);
+        self.signature.hash(state);
+    }
+}
+
+impl<K: EnrKey> AsRef<Self> for Enr<K> {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+/// Displays a short summary of the record. The alternate form, `{:#}`, displays every field of
+/// the record, see [`Enr::pretty`].
+impl<K: EnrKey> core::fmt::Display for Enr<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if f.alternate() {
+            return write!(f, "{}", self.pretty());
+        }
         write!(
             f,
             "ENR: NodeId: {}, Socket: {:?}",
@@ -732,51 +1717,142 @@ impl<K: EnrKey> std::fmt::Display for Enr<K> {
     }
 }
 
-impl<K: EnrKey> std::fmt::Debug for Enr<K> {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl<K: EnrKey> core::fmt::Debug for Enr<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         write!(f, "{}", self.to_base64())
     }
 }
 
-/// Convert a URL-SAFE base64 encoded ENR into an ENR.
+/// Decodes an RLP-encoded record, verifying its signature. Trailing bytes and records of unknown
+/// identity schemes are rejected.
+impl<K: EnrKey> TryFrom<&[u8]> for Enr<K> {
+    type Error = EnrError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let rlp = Rlp::new(bytes);
+        let info = rlp.payload_info().map_err(EnrError::InvalidRlpData)?;
+        if info.header_len + info.value_len != bytes.len() {
+            return Err(EnrError::InvalidRlpData(
+                DecoderError::RlpInconsistentLengthAndData,
+            ));
+        }
+        rlp.as_val().map_err(EnrError::from_decoder_error)
+    }
+}
+
+/// Decodes an RLP-encoded record, verifying its signature. Trailing bytes and records of unknown
+/// identity schemes are rejected.
+impl<K: EnrKey> TryFrom<Vec<u8>> for Enr<K> {
+    type Error = EnrError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from(bytes.as_slice())
+    }
+}
+
+/// The signed RLP encoding of the record, see [`Enr::encode`].
+impl<K: EnrKey> From<&Enr<K>> for Vec<u8> {
+    fn from(enr: &Enr<K>) -> Self {
+        enr.encode()
+    }
+}
+
+/// Decodes the base64 text form of a record, tolerating surrounding whitespace, a missing `enr:`
+/// prefix, the standard base64 alphabet and padding.
+fn decode_text(text: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    let text = text.trim();
+    let text = text.strip_prefix("enr:").unwrap_or(text);
+    let unpadded = text.trim_end_matches('=');
+    if text.len() - unpadded.len() > 2 {
+        return Err(base64::DecodeError::InvalidByte(unpadded.len() + 2, b'='));
+    }
+    let url_safe: String = unpadded
+        .chars()
+        .map(|c| match c {
+            '+' => '-',
+            '/' => '_',
+            c => c,
+        })
+        .collect();
+    base64::decode_config(url_safe, base64::URL_SAFE_NO_PAD)
+}
+
+/// Decodes the text form of a record, verifying its signature. Besides the `enr:` prefixed,
+/// unpadded URL-safe base64 of EIP-778, surrounding whitespace, a missing prefix, the standard
+/// base64 alphabet and padding are accepted. See [`Enr::from_str_strict`] for the exact form.
 impl<K: EnrKey> FromStr for Enr<K> {
     type Err = String;
 
     fn from_str(base64_string: &str) -> Result<Self, Self::Err> {
-        if base64_string.len() < 4 {
+        if base64_string.trim().is_empty() {
             return Err("Invalid ENR string".to_string());
         }
-        // support both enr prefix and not
-        let mut decode_string = base64_string;
-        if base64_string.starts_with("enr:") {
-            decode_string = decode_string
-                .get(4..)
-                .ok_or_else(|| "Invalid ENR string".to_string())?;
-        }
-        let bytes = base64::decode_config(decode_string, base64::URL_SAFE_NO_PAD)
-            .map_err(|e| format!("Invalid base64 encoding: {:?}", e))?;
-        rlp::decode(&bytes).map_err(|e| format!("Invalid ENR: {:?}", e))
+        let bytes =
+            decode_text(base64_string).map_err(|e| format!("Invalid base64 encoding: {e:?}"))?;
+        Self::decode_bytes(&bytes.into()).map_err(|e| format!("Invalid ENR: {e:?}"))
     }
 }
 
+/// Records are serialized as their base64 text form in human-readable formats, see [`text`], and
+/// as the bytes of their RLP encoding in binary formats.
 #[cfg(any(feature = "serde", doc))]
 impl<K: EnrKey> Serialize for Enr<K> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.to_base64())
+        if serializer.is_human_readable() {
+            text::serialize(self, serializer)
+        } else {
+            serde_bytes::Bytes::new(&self.encode()).serialize(serializer)
+        }
     }
 }
 
+/// Records are deserialized from their base64 text form in human-readable formats and from the
+/// bytes of their RLP encoding in binary formats. The signature is verified either way.
 #[cfg(any(feature = "serde", doc))]
 impl<'de, K: EnrKey> Deserialize<'de> for Enr<K> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let s: String = Deserialize::deserialize(deserializer)?;
-        Self::from_str(&s).map_err(D::Error::custom)
+        if deserializer.is_human_readable() {
+            text::deserialize(deserializer)
+        } else {
+            let bytes = serde_bytes::ByteBuf::deserialize(deserializer)?;
+            Self::decode_bytes(&bytes.into_vec().into())
+                .map_err(|e| serde::de::Error::custom(format!("Invalid ENR: {e:?}")))
+        }
+    }
+}
+
+/// Reads a byte buffer, accepting both byte arrays and sequences of bytes.
+#[cfg(feature = "msgpack")]
+pub(crate) struct BytesVisitor;
+
+#[cfg(feature = "msgpack")]
+impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "a byte array")
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
     }
 }
 
@@ -788,30 +1864,57 @@ impl<K: EnrKey> rlp::Encodable for Enr<K> {
         // must use rlp_content to preserve ordering.
         for (k, v) in &self.content {
             s.append(k);
-            s.append(v);
+            // values are stored RLP-encoded
+            s.append_raw(v, 1);
         }
     }
 }
 
+/// The decoder only borrows its input, so the record is copied once into a buffer its values
+/// share. [`Enr::decode_bytes`] decodes an owned buffer without copying, as do the text, hex and
+/// serde decoders.
 impl<K: EnrKey> rlp::Decodable for Enr<K> {
     fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        // the values share a single copy of the record
+        let buffer = Bytes::copy_from_slice(rlp.as_raw());
+        Self::decode_rlp(&Rlp::new(&buffer), &buffer, false)
+    }
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// Decodes an RLP-encoded record without copying its values, which share the buffer of
+    /// `bytes` until they are replaced. Decoding with [`rlp::decode`] copies the record once.
+    pub fn decode_bytes(bytes: &Bytes) -> Result<Self, DecoderError> {
+        Self::decode_rlp(&Rlp::new(bytes), bytes, false)
+    }
+
+    /// Decodes a record from `rlp`, a view of `buffer`. Records of identity schemes other than
+    /// "v4" cannot be verified and are only accepted if `allow_unknown_schemes` is set.
+    fn decode_rlp(
+        rlp: &Rlp,
+        buffer: &Bytes,
+        allow_unknown_schemes: bool,
+    ) -> Result<Self, DecoderError> {
         if !rlp.is_list() {
             debug!("Failed to decode ENR. Not an RLP list: {}", rlp);
             return Err(DecoderError::RlpExpectedToBeList);
         }
 
-        let mut decoded_list = rlp.as_list::<Vec<u8>>().map_err(|_| {
-            debug!("Could not decode content: {}", rlp);
-            DecoderError::Custom("List decode fail")
-        })?;
-
-        if decoded_list.is_empty() || decoded_list.len() % 2 != 0 {
+        let item_count = rlp.item_count()?;
+        if item_count == 0 || item_count % 2 != 0 {
             debug!("Failed to decode ENR. List size is not a multiple of 2.");
             return Err(DecoderError::Custom("List not a multiple of two"));
         }
 
-        let signature = decoded_list.remove(0);
-        let seq_bytes = decoded_list.remove(0);
+        let mut rlp_iter = rlp.iter();
+        let signature = rlp_iter
+            .next()
+            .ok_or(DecoderError::Custom("List decode fail"))?
+            .as_val::<Vec<u8>>()?;
+        let seq_bytes = rlp_iter
+            .next()
+            .ok_or(DecoderError::Custom("List decode fail"))?
+            .as_val::<Vec<u8>>()?;
 
         if seq_bytes.len() > 8 {
             debug!("Failed to decode ENR. Sequence number is not a u64.");
@@ -824,18 +1927,23 @@ impl<K: EnrKey> rlp::Decodable for Enr<K> {
         let seq = u64::from_be_bytes(seq);
 
         let mut content = BTreeMap::new();
-        let mut prev: Option<String> = None;
-        for _ in 0..decoded_list.len() / 2 {
-            let key = decoded_list.remove(0);
-            let value = decoded_list.remove(0);
-
-            let key = String::from_utf8_lossy(&key);
+        while let Some(key) = rlp_iter.next() {
+            let key = key
+                .decoder()
+                .decode_value(|key| Ok(String::from_utf8_lossy(key).into_owned()))?;
+            let value = rlp_iter
+                .next()
+                .ok_or(DecoderError::Custom("List not a multiple of two"))?;
             // TODO: add tests for this error case
-            if prev.is_some() && prev >= Some(key.to_string()) {
+            if content
+                .keys()
+                .next_back()
+                .is_some_and(|prev: &String| *prev >= key)
+            {
                 return Err(DecoderError::Custom("Unsorted keys"));
             }
-            prev = Some(key.to_string());
-            content.insert(key.to_string(), value);
+            // values are stored in their RLP-encoded form
+            content.insert(key, buffer.slice_ref(value.as_raw()));
         }
 
         // verify we know the signature type
@@ -844,19 +1952,39 @@ impl<K: EnrKey> rlp::Decodable for Enr<K> {
         // calculate the node id
         let node_id = NodeId::from(public_key);
 
-        let enr = Self {
+        let mut enr = Self {
             seq,
             node_id,
             signature,
             content,
+            scheme: Some(Arc::new(V4Scheme)),
             phantom: PhantomData,
         };
 
-        // verify the signature before returning
-        // if the public key is of an unknown type, this will fail.
-        // An ENR record will always have a valid public-key and therefore node-id
-        if !enr.verify() {
-            return Err(DecoderError::Custom("Invalid Signature"));
+        match enr.id() {
+            None => {
+                debug!("Failed to decode ENR. Missing identity scheme");
+                return Err(DecoderError::Custom("Missing identity scheme"));
+            }
+            // records of unknown identity schemes are preserved on request, but cannot be verified
+            Some(ref id) if id != "v4" => {
+                if !allow_unknown_schemes {
+                    debug!("Failed to decode ENR. Unknown identity scheme: {id}");
+                    return Err(DecoderError::Custom("Unsupported identity scheme"));
+                }
+                debug!(
+                    "Decoded ENR with unknown identity scheme: {id}. The record is not verified"
+                );
+                enr.scheme = None;
+            }
+            // verify the signature before returning
+            // if the public key is of an unknown type, this will fail.
+            // An ENR record will always have a valid public-key and therefore node-id
+            _ => {
+                if !enr.verify() {
+                    return Err(DecoderError::Custom("Invalid Signature"));
+                }
+            }
         }
         Ok(enr)
     }
@@ -867,12 +1995,139 @@ impl<K: EnrKey> rlp::Decodable for Enr<K> {
 pub enum EnrError {
     /// The ENR is too large.
     ExceedsMaxSize,
-    /// The sequence number is too large.
+    /// The content of a record does not fit the maximum size with a new public key, see
+    /// [`Enr::clone_with_key`]. Holds the size the record would have.
+    ExceedsMaxSizeWithKey(usize),
+    /// The sequence number cannot be incremented, it is `u64::MAX`.
     SequenceNumberTooHigh,
     /// There was an error with signing an ENR record.
     SigningError,
     /// The identity scheme is not supported.
     UnsupportedIdentityScheme,
+    /// The value to be inserted is not well-formed RLP.
+    InvalidRlpData(DecoderError),
+    /// The key is reserved for the identity scheme or public key and cannot be modified directly.
+    ReservedKey(String),
+    /// The records belong to different nodes.
+    NodeIdMismatch,
+    /// The signature of the record is invalid.
+    InvalidSignature,
+    /// The sequence number is not greater than the current sequence number.
+    StaleSequenceNumber,
+    /// An IPv4-mapped or IPv4-compatible IPv6 address was given where a genuine IPv6 address is
+    /// required.
+    Ipv4MappedAddress(Ipv6Addr),
+    /// A public key could not be decoded.
+    InvalidPublicKey(&'static str),
+    /// A PEM encoded key could not be decoded.
+    InvalidPem(&'static str),
+    /// The address is not publicly routable, it belongs to the given range.
+    NonPublicAddress(IpAddr, AddressRange),
+    /// The value of a well-known key is malformed.
+    InvalidField(FieldError),
+    /// The key was added to the builder more than once, see [`EnrBuilder::add_value`].
+    DuplicateKey(String),
+    /// The IPv6 port field is set without an `ip6` field, see [`EnrBuilder::allow_orphan_ports`].
+    OrphanPort(&'static str),
+    /// The port field, of the given key, is set to 0, on which a node cannot be reached.
+    InvalidPort(String),
+    /// Field values were rejected by the validators of [`Enr::decode_with_validators`].
+    InvalidFields(Vec<FieldValidationError>),
+    /// A text record is not valid URL-safe base64.
+    InvalidBase64,
+    /// A connection string is malformed, see [`Enr::from_connection_string`].
+    InvalidConnectionString(&'static str),
+    /// A TXT record is not a valid EIP-1459 tree entry, see [`dns_tree`].
+    #[cfg(feature = "libsecp256k1")]
+    InvalidDnsEntry(&'static str),
+    /// An EIP-1459 tree was updated before it was ever signed, see
+    /// [`dns_tree::TreeBuilder::update`].
+    #[cfg(feature = "libsecp256k1")]
+    UnsignedDnsTree,
+    /// A JSON record is malformed or incomplete, see [`Enr::from_json`].
+    #[cfg(feature = "json")]
+    InvalidJson(&'static str),
+}
+
+impl EnrError {
+    /// Converts an error decoding a record, separating signature and identity scheme failures
+    /// from malformed RLP.
+    pub(crate) fn from_decoder_error(error: DecoderError) -> Self {
+        match error {
+            DecoderError::Custom("Invalid Signature") => Self::InvalidSignature,
+            DecoderError::Custom("Unsupported identity scheme") => Self::UnsupportedIdentityScheme,
+            error => Self::InvalidRlpData(error),
+        }
+    }
+}
+
+/// Selects the record with the highest sequence number from records of the same node. The first
+/// such record is returned if several share the highest sequence number.
+///
+/// Returns `None` if there are no records, or the records belong to different nodes.
+#[must_use]
+pub fn most_recent<K: EnrKey>(records: &[Enr<K>]) -> Option<&Enr<K>> {
+    let (first, rest) = records.split_first()?;
+    rest.iter().try_fold(first, |newest, enr| {
+        if !enr.same_identity(newest) {
+            None
+        } else if enr.is_newer_than(newest) {
+            Some(enr)
+        } else {
+            Some(newest)
+        }
+    })
+}
+
+/// Rejects port 0 for the port field `key`, a node cannot be reached on it.
+fn check_port(key: &str, port: u16) -> Result<(), EnrError> {
+    if port == 0 {
+        return Err(EnrError::InvalidPort(key.into()));
+    }
+    Ok(())
+}
+
+/// Decodes a minimally encoded, RLP-encoded port.
+fn decode_port(raw: &[u8]) -> Option<u16> {
+    field::decode_uint(raw, 2)
+        .ok()
+        .and_then(|port| u16::try_from(port).ok())
+}
+
+/// Returns the payload of an RLP-encoded byte string.
+fn rlp_data(raw: &[u8]) -> Option<&[u8]> {
+    Rlp::new(raw).data().ok()
+}
+
+/// Checks that `raw` is a single well-formed RLP item with no trailing bytes. Lists (and their
+/// nested items) are only accepted if `allow_list` is set.
+fn validate_raw_rlp(raw: &[u8], allow_list: bool) -> Result<(), EnrError> {
+    fn validate(rlp: &Rlp, allow_list: bool) -> Result<(), DecoderError> {
+        if rlp.payload_info()?.total() != rlp.as_raw().len() {
+            return Err(DecoderError::RlpInconsistentLengthAndData);
+        }
+        if rlp.is_list() {
+            if !allow_list {
+                return Err(DecoderError::RlpExpectedToBeData);
+            }
+            for item in rlp {
+                validate(&item, allow_list)?;
+            }
+            // the items must account for the entire payload
+            let items_len: usize = rlp.iter().map(|item| item.as_raw().len()).sum();
+            if items_len != rlp.payload_info()?.value_len {
+                return Err(DecoderError::RlpInconsistentLengthAndData);
+            }
+        } else {
+            rlp.data()?;
+        }
+        Ok(())
+    }
+
+    if raw.is_empty() {
+        return Err(EnrError::InvalidRlpData(DecoderError::RlpIsTooShort));
+    }
+    validate(&Rlp::new(raw), allow_list).map_err(EnrError::InvalidRlpData)
 }
 
 pub(crate) fn digest(b: &[u8]) -> [u8; 32] {
@@ -883,24 +2138,874 @@ pub(crate) fn digest(b: &[u8]) -> [u8; 32] {
     output
 }
 
+/// A copy of the record claiming the unknown identity scheme `v5`, keeping the signature, which no
+/// longer verifies.
 #[cfg(test)]
-#[cfg(feature = "libsecp256k1")]
-mod tests {
+pub(crate) fn forge_unknown_scheme<K: EnrKey>(enr: &Enr<K>) -> Enr<K> {
+    let mut forged = enr.clone();
+    forged
+        .content
+        .insert("id".into(), rlp::encode(&"v5").into());
+    forged.seq += 1;
+    forged
+}
+
+/// Tests of the record as a data structure. They sign with the `MockSigner`, which performs no
+/// cryptography, so they also run under miri, see `miri.sh`.
+#[cfg(test)]
+mod data_tests {
     use super::*;
+    use keys::MockSigner;
     use std::net::Ipv4Addr;
 
-    type DefaultEnr = Enr<secp256k1::SecretKey>;
+    type MockEnr = Enr<MockSigner>;
 
-    #[cfg(feature = "libsecp256k1")]
     #[test]
-    fn check_test_vector() {
-        let valid_record = hex::decode("f884b8407098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c01826964827634826970847f00000189736563703235366b31a103ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd31388375647082765f").unwrap();
-        let signature = hex::decode("7098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c").unwrap();
-        let expected_pubkey =
-            hex::decode("03ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd3138")
-                .unwrap();
+    fn test_content_operations() {
+        let key = MockSigner(1);
+        let mut enr = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::new(192, 0, 2, 1))
+            .udp(30303)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.seq(), 1);
+        assert_eq!(enr.ip(), Some(Ipv4Addr::new(192, 0, 2, 1)));
+        assert_eq!(enr.udp(), Some(30303));
+        assert_eq!(enr.tcp(), None);
 
-        let enr = rlp::decode::<DefaultEnr>(&valid_record).unwrap();
+        enr.insert_value("b", &vec![2_u8], &key).unwrap();
+        enr.insert_raw_rlp("a", rlp::encode(&1_u8).to_vec(), &key)
+            .unwrap();
+        enr.insert_list("c", &[1_u16, 2], &key).unwrap();
+        assert_eq!(enr.seq(), 4);
+        let keys: Vec<&str> = enr.content.keys().map(String::as_str).collect();
+        assert_eq!(keys, vec!["a", "b", "c", "id", "ip", "mock", "udp"]);
+        assert_eq!(enr.get("b"), Some(&[2][..]));
+        assert_eq!(enr.get_list::<u16>("c"), Some(Ok(vec![1, 2])));
+
+        assert_eq!(
+            enr.remove("a", &key).unwrap(),
+            Some(rlp::encode(&1_u8).to_vec())
+        );
+        assert_eq!(enr.remove("a", &key).unwrap(), None);
+        assert!(matches!(
+            enr.remove("id", &key),
+            Err(EnrError::ReservedKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_encoding_roundtrip() {
+        let key = MockSigner(1);
+        let enr = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::new(192, 0, 2, 1))
+            .tcp(30303)
+            .add_utf8("name".into(), "node")
+            .seq(300)
+            .build(&key)
+            .unwrap();
+
+        let decoded = rlp::decode::<MockEnr>(&enr.encode()).unwrap();
+        assert_eq!(decoded, enr);
+        assert_eq!(decoded.encode(), enr.encode());
+        assert_eq!(enr.to_base64().parse::<MockEnr>().unwrap(), enr);
+        test_utils::assert_enr_roundtrip(&enr);
+
+        let parts = enr.clone().into_parts();
+        assert_eq!(MockEnr::from_parts(parts).unwrap(), enr);
+
+        // malformed encodings
+        assert!(rlp::decode::<MockEnr>(&[]).is_err());
+        assert!(rlp::decode::<MockEnr>(&[0xc0]).is_err());
+        let encoded = enr.encode();
+        assert!(rlp::decode::<MockEnr>(&encoded[..encoded.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_unknown_fields_survive_updates() {
+        let key = MockSigner(1);
+        // a record of a newer version, with fields unknown to this one
+        let mut newer = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::new(192, 0, 2, 1))
+            .udp(30303)
+            .add_value("ext".into(), vec![0xde, 0xad])
+            .build(&key)
+            .unwrap();
+        let nested = {
+            let mut stream = rlp::RlpStream::new_list(2);
+            stream.append(&1_u8).begin_list(1).append(&"sub");
+            stream.out().to_vec()
+        };
+        newer
+            .insert_raw_rlp_list("future", nested.clone(), &key)
+            .unwrap();
+        let unknown = |enr: &MockEnr| {
+            (
+                enr.get_raw_rlp("ext").map(<[u8]>::to_vec),
+                enr.get_raw_rlp("future").map(<[u8]>::to_vec),
+            )
+        };
+        let expected = (
+            Some(rlp::encode(&vec![0xde_u8, 0xad]).to_vec()),
+            Some(nested),
+        );
+        assert_eq!(unknown(&newer), expected);
+
+        let mut enr = rlp::decode::<MockEnr>(&newer.encode()).unwrap();
+        enr.set_ip(Ipv4Addr::new(198, 51, 100, 1).into(), &key)
+            .unwrap();
+        enr.set_udp6(9000, &key).unwrap();
+        enr.set_seq(10, &key).unwrap();
+        assert!(enr.verify());
+        assert_eq!(unknown(&enr), expected);
+
+        let enr = rlp::decode::<MockEnr>(&enr.encode()).unwrap();
+        assert_eq!(enr.ip(), Some(Ipv4Addr::new(198, 51, 100, 1)));
+        assert_eq!(unknown(&enr), expected);
+
+        // only removal drops a field
+        let mut enr = enr;
+        enr.remove("future", &key).unwrap();
+        let enr = rlp::decode::<MockEnr>(&enr.encode()).unwrap();
+        assert_eq!(unknown(&enr), (expected.0, None));
+    }
+
+    #[test]
+    fn test_compare_and_update() {
+        let key = MockSigner(1);
+        let mut enr = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::new(192, 0, 2, 1))
+            .add_value("ext".into(), vec![1])
+            .build(&key)
+            .unwrap();
+
+        // two tasks read the record, the first to update wins
+        let seq = enr.seq();
+        assert!(enr
+            .compare_and_update(
+                seq,
+                |builder| {
+                    builder.udp(9000);
+                },
+                &key
+            )
+            .unwrap());
+        assert!(!enr
+            .compare_and_update(
+                seq,
+                |builder| {
+                    builder.tcp(9001);
+                },
+                &key
+            )
+            .unwrap());
+        assert_eq!(enr.seq(), 2);
+        assert_eq!(enr.tcp(), None);
+
+        // the second task retries with the current sequence number
+        assert!(enr
+            .compare_and_update(
+                enr.seq(),
+                |builder| {
+                    builder.tcp(9001);
+                },
+                &key
+            )
+            .unwrap());
+        assert_eq!(enr.seq(), 3);
+        assert_eq!(enr.ip(), Some(Ipv4Addr::new(192, 0, 2, 1)));
+        assert_eq!(enr.udp(), Some(9000));
+        assert_eq!(enr.tcp(), Some(9001));
+        assert_eq!(enr.get("ext"), Some(&[1][..]));
+        assert!(enr.verify());
+
+        // failed updates leave the record unchanged
+        let before = enr.clone();
+        assert!(matches!(
+            enr.compare_and_update(
+                3,
+                |builder| {
+                    builder.seq(3);
+                },
+                &key
+            ),
+            Err(EnrError::StaleSequenceNumber)
+        ));
+        assert!(matches!(
+            enr.compare_and_update(
+                3,
+                |builder| {
+                    builder.udp6(9000);
+                },
+                &key
+            ),
+            Err(EnrError::OrphanPort("udp6"))
+        ));
+        assert_eq!(enr, before);
+    }
+
+    #[test]
+    fn test_quic_fields() {
+        let key = MockSigner(1);
+        let ip4 = Ipv4Addr::new(192, 0, 2, 1);
+        let ip6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+        let mut enr = EnrBuilder::new("v4")
+            .ip4(ip4)
+            .ip6(ip6)
+            .quic(9001)
+            .quic6(9101)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.get_raw_rlp("quic"), Some(&[0x82, 0x23, 0x29][..]));
+        assert_eq!(enr.quic(), Some(9001));
+        assert_eq!(enr.quic6(), Some(9101));
+        assert_eq!(enr.quic_socket(), Some(SocketAddrV4::new(ip4, 9001)));
+        assert_eq!(enr.quic6_socket(), Some(SocketAddrV6::new(ip6, 9101, 0, 0)));
+        assert_eq!(enr.udp_socket(), None);
+
+        assert_eq!(enr.set_quic(80, &key).unwrap(), Some(9001));
+        assert_eq!(enr.get_raw_rlp("quic"), Some(&[0x50][..]));
+        assert_eq!(enr.set_quic6(81, &key).unwrap(), Some(9101));
+        assert_eq!(enr.quic_socket(), Some(SocketAddrV4::new(ip4, 80)));
+        assert_eq!(enr.quic6_socket(), Some(SocketAddrV6::new(ip6, 81, 0, 0)));
+        assert!(matches!(
+            enr.insert_raw_rlp("quic", vec![0x82, 0x00, 0x50], &key),
+            Err(EnrError::InvalidField(FieldError::NonMinimalInteger))
+        ));
+
+        // a port without its address
+        let enr = EnrBuilder::new("v4").quic(9001).build(&key).unwrap();
+        assert_eq!(enr.quic(), Some(9001));
+        assert_eq!(enr.quic_socket(), None);
+        assert!(matches!(
+            EnrBuilder::new("v4").ip4(ip4).quic6(9101).build(&key),
+            Err(EnrError::OrphanPort("quic6"))
+        ));
+    }
+
+    #[test]
+    fn test_builder_duplicate_and_reserved_keys() {
+        let key = MockSigner(1);
+        assert!(matches!(
+            EnrBuilder::new("v4")
+                .add_value("ext".into(), vec![1])
+                .add_value("ext".into(), vec![2])
+                .build(&key),
+            Err(EnrError::DuplicateKey(ref k)) if k == "ext"
+        ));
+        assert!(matches!(
+            EnrBuilder::new("v4")
+                .udp(9000)
+                .add_value("udp".into(), vec![0x23, 0x29])
+                .build(&key),
+            Err(EnrError::DuplicateKey(ref k)) if k == "udp"
+        ));
+        assert!(matches!(
+            EnrBuilder::new("v4")
+                .add_utf8("name".into(), "a")
+                .add_utf8("name".into(), "b")
+                .build(&key),
+            Err(EnrError::DuplicateKey(ref k)) if k == "name"
+        ));
+
+        // the typed setters replace earlier values
+        let enr = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::new(10, 0, 0, 1))
+            .ip4(Ipv4Addr::new(10, 0, 0, 2))
+            .ip6(Ipv6Addr::LOCALHOST)
+            .ip6(Ipv6Addr::UNSPECIFIED)
+            .udp(9000)
+            .udp(9001)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.ip(), Some(Ipv4Addr::new(10, 0, 0, 2)));
+        assert_eq!(enr.ip6(), Some(Ipv6Addr::UNSPECIFIED));
+        assert_eq!(enr.udp4(), Some(9001));
+        assert!(matches!(
+            EnrBuilder::new("v4")
+                .ip4(Ipv4Addr::LOCALHOST)
+                .add_value("ip".into(), vec![127, 0, 0, 2])
+                .build(&key),
+            Err(EnrError::DuplicateKey(ref k)) if k == "ip"
+        ));
+
+        // `id` is written by `build`
+        assert!(matches!(
+            EnrBuilder::new("v4")
+                .add_value("id".into(), b"v4".to_vec())
+                .build(&key),
+            Err(EnrError::ReservedKey(ref k)) if k == "id"
+        ));
+        let enr = EnrBuilder::new("v4")
+            .override_reserved()
+            .add_value("id".into(), b"v5".to_vec())
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.id().as_deref(), Some("v5"));
+
+        // a builder may build several records
+        let mut builder = EnrBuilder::new("v4");
+        builder.udp(9000);
+        assert_eq!(builder.build(&key).unwrap(), builder.build(&key).unwrap());
+    }
+
+    #[test]
+    fn test_from_socket() {
+        let key = MockSigner(1);
+        let socket4: SocketAddr = "203.0.113.5:9000".parse().unwrap();
+        let socket6: SocketAddr = "[2001:db8::1]:9000".parse().unwrap();
+
+        let enr = MockEnr::from_socket(socket4, Transport::Udp, &key).unwrap();
+        assert_eq!(enr.seq(), 1);
+        assert!(enr.verify());
+        assert_eq!(enr.udp_socket(), Some(socket4));
+        assert_eq!(enr.tcp(), None);
+        assert_eq!(enr.iter().count(), 4);
+
+        let enr = MockEnr::from_socket(socket4, Transport::Tcp, &key).unwrap();
+        assert_eq!(enr.tcp_socket(), Some(socket4));
+        assert_eq!(enr.udp(), None);
+
+        let enr = MockEnr::from_socket(socket6, Transport::Both, &key).unwrap();
+        assert_eq!(enr.ip(), None);
+        assert_eq!(enr.udp_socket(), Some(socket6));
+        assert_eq!(enr.tcp_socket(), Some(socket6));
+
+        let enr = MockEnr::from_socket(socket4, Transport::Quic, &key).unwrap();
+        assert_eq!(enr.get_u16("quic"), Ok(Some(9000)));
+        assert_eq!(enr.udp(), None);
+        let enr = MockEnr::from_socket(socket6, Transport::Quic, &key).unwrap();
+        assert_eq!(enr.get_u16("quic6"), Ok(Some(9000)));
+
+        let mapped: SocketAddr = "[::ffff:203.0.113.5]:9000".parse().unwrap();
+        assert!(matches!(
+            MockEnr::from_socket(mapped, Transport::Udp, &key),
+            Err(EnrError::Ipv4MappedAddress(_))
+        ));
+
+        // port 0 is rejected as by the builder
+        let unbound: SocketAddr = "203.0.113.5:0".parse().unwrap();
+        assert!(matches!(
+            MockEnr::from_socket(unbound, Transport::Udp, &key),
+            Err(EnrError::InvalidPort(ref key)) if key == "udp"
+        ));
+        let unbound: SocketAddr = "[2001:db8::1]:0".parse().unwrap();
+        assert!(matches!(
+            MockEnr::from_socket(unbound, Transport::Both, &key),
+            Err(EnrError::InvalidPort(_))
+        ));
+    }
+
+    #[test]
+    fn test_ip6_normalization() {
+        let key = MockSigner(1);
+        let cases: &[(&str, IpAddr)] = &[
+            ("::ffff:1.2.3.4", Ipv4Addr::new(1, 2, 3, 4).into()),
+            ("::1.2.3.4", Ipv4Addr::new(1, 2, 3, 4).into()),
+            ("2606:4700::1111", "2606:4700::1111".parse().unwrap()),
+        ];
+        for (ip6, normalized) in cases {
+            let ip6: Ipv6Addr = ip6.parse().unwrap();
+            let mut enr = EnrBuilder::new("v4")
+                .udp6(9000)
+                .tcp6(9001)
+                .allow_orphan_ports(true)
+                .build(&key)
+                .unwrap();
+            // peers may publish such addresses, bypassing the checks of `set_ip6`
+            enr.insert_value("ip6", &ip6.octets().to_vec(), &key)
+                .unwrap();
+            let raw = enr.get_raw_rlp("ip6").unwrap().to_vec();
+
+            assert_eq!(enr.ip6(), Some(ip6));
+            assert_eq!(enr.ip6_normalized(), Some(*normalized));
+            assert_eq!(
+                enr.udp6_socket_normalized(),
+                Some(SocketAddr::new(*normalized, 9000))
+            );
+            assert_eq!(
+                enr.tcp6_socket_normalized(),
+                Some(SocketAddr::new(*normalized, 9001))
+            );
+            // the record is unchanged
+            assert_eq!(enr.get_raw_rlp("ip6"), Some(&raw[..]));
+            assert!(enr.verify());
+
+            let is_ipv4 = normalized.is_ipv4();
+            assert_eq!(
+                matches!(enr.set_ip6(ip6, &key), Err(EnrError::Ipv4MappedAddress(_))),
+                is_ipv4
+            );
+            assert_eq!(
+                matches!(
+                    EnrBuilder::new("v4").ip6(ip6).build(&key),
+                    Err(EnrError::Ipv4MappedAddress(_))
+                ),
+                is_ipv4
+            );
+        }
+    }
+
+    #[test]
+    fn test_sequence_number_overflow() {
+        let key = MockSigner(1);
+        let mut enr = EnrBuilder::new("v4")
+            .udp(9000)
+            .seq(u64::MAX - 1)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.set_udp(9001, &key).unwrap(), Some(9000));
+        assert_eq!(enr.seq(), u64::MAX);
+
+        // at `u64::MAX` every update fails and leaves the record untouched
+        let socket: SocketAddr = "192.0.2.1:9002".parse().unwrap();
+        let original = enr.clone();
+        assert!(matches!(
+            enr.set_udp(9002, &key),
+            Err(EnrError::SequenceNumberTooHigh)
+        ));
+        assert!(matches!(
+            enr.insert_value("foo", &1_u8, &key),
+            Err(EnrError::SequenceNumberTooHigh)
+        ));
+        assert!(matches!(
+            enr.remove("udp", &key),
+            Err(EnrError::SequenceNumberTooHigh)
+        ));
+        assert!(matches!(
+            enr.set_udp_socket(socket, &key),
+            Err(EnrError::SequenceNumberTooHigh)
+        ));
+        assert!(matches!(
+            enr.compare_and_update(
+                u64::MAX,
+                |builder| {
+                    builder.udp(9002);
+                },
+                &key
+            ),
+            Err(EnrError::SequenceNumberTooHigh)
+        ));
+        assert_eq!(enr, original);
+        assert_eq!(enr.udp(), Some(9001));
+        assert!(enr.verify());
+
+        // a record may still be built at `u64::MAX`
+        let enr = EnrBuilder::new("v4").seq(u64::MAX).build(&key).unwrap();
+        assert_eq!(enr.seq(), u64::MAX);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let key = MockSigner(1);
+        let enr = EnrBuilder::new("v4")
+            .udp(9000)
+            .udp6(9006)
+            .allow_orphan_ports(true)
+            .add_value("foo".into(), vec![1])
+            .seq(5)
+            .build(&key)
+            .unwrap();
+
+        let new_key = MockSigner(2);
+        let (rotated, warnings) = enr.rotate(&new_key).unwrap();
+        assert_eq!(warnings, vec![]);
+        assert_eq!(rotated.seq(), 6);
+        assert_eq!(rotated.public_key(), new_key.public());
+        assert_ne!(rotated.node_id(), enr.node_id());
+        for field in &["id", "udp", "udp6", "foo"] {
+            assert_eq!(rotated.get_raw_rlp(field), enr.get_raw_rlp(field));
+        }
+        assert_eq!(rotated.iter().count(), enr.iter().count());
+        assert!(rotated.verify());
+
+        let enr = EnrBuilder::new("v4").seq(u64::MAX).build(&key).unwrap();
+        assert!(matches!(
+            enr.rotate(&new_key),
+            Err(EnrError::SequenceNumberTooHigh)
+        ));
+    }
+
+    #[test]
+    fn test_sequence_number_warning() {
+        let key = MockSigner(1);
+        let warnings = |seq| {
+            let enr = EnrBuilder::new("v4").seq(seq).build(&key).unwrap();
+            enr.validate().unwrap()
+        };
+        assert_eq!(warnings(1), vec![]);
+        assert_eq!(warnings(u64::MAX - SEQ_WARNING_MARGIN - 1), vec![]);
+        for seq in &[u64::MAX - SEQ_WARNING_MARGIN, u64::MAX] {
+            assert_eq!(
+                warnings(*seq),
+                vec![EnrWarning::SequenceNumberNearMax(*seq)]
+            );
+        }
+    }
+
+    #[test]
+    fn test_port_validation() {
+        let key = MockSigner(1);
+        for builder in &mut [
+            EnrBuilder::new("v4").tcp(0),
+            EnrBuilder::new("v4").udp(0),
+            EnrBuilder::new("v4").quic(0),
+            EnrBuilder::new("v4").ip6(Ipv6Addr::LOCALHOST).udp6(0),
+        ] {
+            assert!(matches!(builder.build(&key), Err(EnrError::InvalidPort(_))));
+        }
+
+        let mut enr = EnrBuilder::new("v4").udp(30303).build(&key).unwrap();
+        assert!(matches!(
+            enr.set_tcp(0, &key),
+            Err(EnrError::InvalidPort(ref port)) if port == "tcp"
+        ));
+        assert!(matches!(
+            enr.set_quic6(0, &key),
+            Err(EnrError::InvalidPort(ref port)) if port == "quic6"
+        ));
+        assert!(matches!(
+            enr.set_udp_socket(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0), &key),
+            Err(EnrError::InvalidPort(ref port)) if port == "udp"
+        ));
+        assert_eq!(enr.seq(), 1);
+        assert_eq!(enr.validate().unwrap(), vec![]);
+
+        // privileged ports are accepted with a warning
+        enr.set_tcp(443, &key).unwrap();
+        enr.set_udp6(1024, &key).unwrap();
+        assert_eq!(
+            enr.validate().unwrap(),
+            vec![EnrWarning::PrivilegedPort {
+                key: "tcp".into(),
+                port: 443
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decode_bytes_shares_buffer() {
+        let key = MockSigner(1);
+        let enr = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::new(192, 0, 2, 1))
+            .udp(30303)
+            .build(&key)
+            .unwrap();
+        let bytes = Bytes::from(enr.encode());
+        let within = |value: &[u8]| bytes.as_ptr_range().contains(&value.as_ptr());
+
+        let mut decoded = Enr::<MockSigner>::decode_bytes(&bytes).unwrap();
+        assert_eq!(decoded, enr);
+        assert_eq!(rlp::decode::<Enr<MockSigner>>(&bytes), Ok(enr));
+        assert!(decoded.iter().all(|(_, value)| within(value)));
+
+        // only the replaced values are copied
+        decoded.set_udp(30304, &key).unwrap();
+        assert!(!within(decoded.get_raw_rlp("udp").unwrap()));
+        assert!(within(decoded.get_raw_rlp("ip").unwrap()));
+
+        assert_eq!(
+            Enr::<MockSigner>::decode_bytes(&Bytes::from_static(&[0x80])),
+            Err(DecoderError::RlpExpectedToBeList)
+        );
+    }
+
+    #[test]
+    fn test_family_specific_ports_and_sockets() {
+        let key = MockSigner(1);
+        let ip4 = Ipv4Addr::new(192, 0, 2, 1);
+        let ip6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+        let mut enr = EnrBuilder::new("v4")
+            .ip4(ip4)
+            .udp(9000)
+            .tcp(9001)
+            .ip6(ip6)
+            .udp6(9100)
+            .tcp6(9101)
+            .build(&key)
+            .unwrap();
+
+        assert_eq!(enr.udp4(), Some(9000));
+        assert_eq!(enr.tcp4(), Some(9001));
+        assert_eq!(enr.udp6(), Some(9100));
+        assert_eq!(enr.tcp6(), Some(9101));
+        assert_eq!(enr.udp4_socket(), Some(SocketAddrV4::new(ip4, 9000)));
+        assert_eq!(enr.tcp4_socket(), Some(SocketAddrV4::new(ip4, 9001)));
+        assert_eq!(enr.udp6_socket(), Some(SocketAddrV6::new(ip6, 9100, 0, 0)));
+        assert_eq!(enr.tcp6_socket(), Some(SocketAddrV6::new(ip6, 9101, 0, 0)));
+
+        // the aliases
+        assert_eq!(enr.udp(), enr.udp4());
+        assert_eq!(enr.tcp(), enr.tcp4());
+        assert_eq!(enr.set_udp4(9002, &key).unwrap(), Some(9000));
+        assert_eq!(enr.set_tcp4(9003, &key).unwrap(), Some(9001));
+        assert_eq!(enr.udp(), Some(9002));
+        assert_eq!(enr.tcp(), Some(9003));
+        assert_eq!(enr.udp6(), Some(9100));
+        assert_eq!(enr.tcp6(), Some(9101));
+
+        // an IPv6-only record has no IPv4 ports or sockets
+        let enr = EnrBuilder::new("v4")
+            .ip6(ip6)
+            .udp6(9100)
+            .tcp6(9101)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.udp4(), None);
+        assert_eq!(enr.tcp4(), None);
+        assert_eq!(enr.udp4_socket(), None);
+        assert_eq!(enr.tcp4_socket(), None);
+        assert_eq!(enr.udp_socket(), Some(SocketAddr::new(ip6.into(), 9100)));
+        assert_eq!(enr.tcp_socket(), Some(SocketAddr::new(ip6.into(), 9101)));
+
+        // and an IPv4-only record no IPv6 ones
+        let enr = EnrBuilder::new("v4")
+            .ip4(ip4)
+            .udp(9000)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.udp6(), None);
+        assert_eq!(enr.udp6_socket(), None);
+        assert_eq!(enr.tcp6_socket(), None);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_set_ip_from_socket() {
+        let key = MockSigner(1);
+        let socket = std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let enr = EnrBuilder::new("v4")
+            .set_ip_from_socket(&socket)
+            .unwrap()
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.ip(), Some(Ipv4Addr::LOCALHOST));
+
+        // an unconnected socket bound to the unspecified address has no address to advertise
+        let socket = std::net::UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).unwrap();
+        let mut builder = EnrBuilder::<MockSigner>::new("v4");
+        assert!(matches!(
+            builder.set_ip_from_socket(&socket),
+            Err(e) if e.kind() == std::io::ErrorKind::AddrNotAvailable
+        ));
+
+        // hosts without a route to the internet have no outbound address
+        if let Ok(builder) = EnrBuilder::new("v4").default_ip_from_os() {
+            let enr = builder.build(&key).unwrap();
+            assert!(enr.ip().is_some_and(|ip| !ip.is_unspecified()));
+        }
+    }
+
+    #[test]
+    fn test_address_family() {
+        let key = MockSigner(1);
+        let ip4 = Ipv4Addr::new(192, 0, 2, 1);
+        let ip6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+        let udp4 = SocketAddr::new(ip4.into(), 9000);
+        let udp6 = SocketAddr::new(ip6.into(), 9100);
+
+        let enr = EnrBuilder::new("v4")
+            .ip4(ip4)
+            .udp(9000)
+            .ip6(ip6)
+            .udp6(9100)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.address_family(), AddressFamily::DualStack);
+        assert_eq!(enr.dual_socket_addrs(), (Some(udp4), Some(udp6)));
+
+        let enr = EnrBuilder::new("v4")
+            .ip4(ip4)
+            .udp(9000)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.address_family(), AddressFamily::V4Only);
+        assert_eq!(enr.dual_socket_addrs(), (Some(udp4), None));
+
+        // an address without a UDP port has no socket
+        let enr = EnrBuilder::new("v4")
+            .ip6(ip6)
+            .tcp6(9101)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.address_family(), AddressFamily::V6Only);
+        assert_eq!(enr.dual_socket_addrs(), (None, None));
+
+        let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+        assert_eq!(enr.address_family(), AddressFamily::None);
+        enr.content
+            .insert("ip".into(), rlp::encode(&vec![192_u8, 0, 2]).into());
+        assert_eq!(enr.address_family(), AddressFamily::None);
+
+        assert!(AddressFamily::DualStack.has_ipv4() && AddressFamily::DualStack.has_ipv6());
+        assert!(!AddressFamily::V6Only.has_ipv4());
+        assert!(!AddressFamily::V4Only.has_ipv6());
+    }
+
+    #[test]
+    fn test_checked_ip_fields() {
+        let key = MockSigner(1);
+        for len in [0, 3, 4, 15, 16, 17] {
+            let octets: Vec<u8> = (1..=len).collect();
+            let mut enr = EnrBuilder::new("v4")
+                .allow_orphan_ports(true)
+                .udp(9000)
+                .udp6(9100)
+                .build(&key)
+                .unwrap();
+            enr.content.insert("ip".into(), rlp::encode(&octets).into());
+            enr.content
+                .insert("ip6".into(), rlp::encode(&octets).into());
+
+            let ip4 = <[u8; 4]>::try_from(&octets[..]).ok().map(Ipv4Addr::from);
+            let ip6 = <[u8; 16]>::try_from(&octets[..]).ok().map(Ipv6Addr::from);
+            let invalid = |key: &str, expected| FieldError::InvalidLength {
+                key: key.into(),
+                expected,
+                got: usize::from(len),
+            };
+            assert_eq!(enr.ip(), ip4);
+            assert_eq!(enr.ip6(), ip6);
+            assert_eq!(
+                enr.ip4_checked(),
+                ip4.map(IpAddr::V4)
+                    .map(Some)
+                    .ok_or_else(|| invalid("ip", 4))
+            );
+            assert_eq!(
+                enr.ip6_checked(),
+                ip6.map(IpAddr::V6)
+                    .map(Some)
+                    .ok_or_else(|| invalid("ip6", 16))
+            );
+
+            // a malformed address with a valid port is reported, not skipped, even if the
+            // other family has a valid socket
+            let udp = if ip4.is_some() {
+                invalid("ip6", 16)
+            } else {
+                invalid("ip", 4)
+            };
+            assert_eq!(enr.udp_socket_checked(), Err(udp), "{len}");
+            assert_eq!(enr.udp_socket().is_some(), ip4.is_some() || ip6.is_some());
+            // without a TCP port, the addresses are not needed
+            assert_eq!(enr.tcp_socket_checked(), Ok(None));
+        }
+
+        // an IPv6-only record with a malformed address
+        let mut enr = EnrBuilder::new("v4")
+            .allow_orphan_ports(true)
+            .tcp6(9101)
+            .build(&key)
+            .unwrap();
+        enr.content
+            .insert("ip6".into(), rlp::encode(&vec![0_u8; 4]).into());
+        assert_eq!(enr.tcp_socket(), None);
+        assert_eq!(
+            enr.tcp_socket_checked(),
+            Err(FieldError::InvalidLength {
+                key: "ip6".into(),
+                expected: 16,
+                got: 4,
+            })
+        );
+
+        // absent addresses are not an error
+        let enr = EnrBuilder::new("v4")
+            .allow_orphan_ports(true)
+            .udp(9000)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.ip4_checked(), Ok(None));
+        assert_eq!(enr.ip6_checked(), Ok(None));
+        assert_eq!(enr.udp_socket_checked(), Ok(None));
+
+        let ip6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+        let enr = EnrBuilder::new("v4")
+            .ip6(ip6)
+            .udp6(9100)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.ip6_checked(), Ok(Some(ip6.into())));
+        assert_eq!(
+            enr.udp_socket_checked(),
+            Ok(Some(SocketAddr::new(ip6.into(), 9100)))
+        );
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "libsecp256k1")]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    type DefaultEnr = Enr<secp256k1::SecretKey>;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_serde_binary_formats() {
+        let valid_record = hex::decode("f884b8407098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c01826964827634826970847f00000189736563703235366b31a103ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd31388375647082765f").unwrap();
+        let enr = rlp::decode::<DefaultEnr>(&valid_record).unwrap();
+        let records = vec![enr.clone(), enr.clone()];
+
+        // binary formats hold the RLP encoding, after a length prefix
+        let bincode = bincode::serialize(&enr).unwrap();
+        assert_eq!(bincode[8..], valid_record[..]);
+        let postcard = postcard::to_allocvec(&enr).unwrap();
+        assert_eq!(postcard[2..], valid_record[..]);
+        let json = serde_json::to_string(&enr).unwrap();
+        assert_eq!(json, format!("\"{}\"", enr.to_base64()));
+
+        assert_eq!(bincode::deserialize::<DefaultEnr>(&bincode).unwrap(), enr);
+        assert_eq!(postcard::from_bytes::<DefaultEnr>(&postcard).unwrap(), enr);
+        assert_eq!(serde_json::from_str::<DefaultEnr>(&json).unwrap(), enr);
+        for decoded in [
+            bincode::deserialize::<Vec<DefaultEnr>>(&bincode::serialize(&records).unwrap())
+                .unwrap(),
+            postcard::from_bytes(&postcard::to_allocvec(&records).unwrap()).unwrap(),
+            serde_json::from_str(&serde_json::to_string(&records).unwrap()).unwrap(),
+        ] {
+            assert_eq!(decoded, records);
+            assert_eq!(decoded[0].encode(), valid_record);
+        }
+
+        // the signature is verified in binary formats as well
+        let mut tampered = postcard;
+        *tampered.last_mut().unwrap() += 1;
+        assert!(postcard::from_bytes::<DefaultEnr>(&tampered).is_err());
+        let mut tampered = bincode;
+        *tampered.last_mut().unwrap() += 1;
+        assert!(bincode::deserialize::<DefaultEnr>(&tampered)
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid Signature"));
+
+        // and records of unknown schemes are rejected
+        let forged = forge_unknown_scheme(&enr);
+        assert!(
+            bincode::deserialize::<DefaultEnr>(&bincode::serialize(&forged).unwrap())
+                .unwrap_err()
+                .to_string()
+                .contains("Unsupported identity scheme")
+        );
+        assert!(
+            postcard::from_bytes::<DefaultEnr>(&postcard::to_allocvec(&forged).unwrap()).is_err()
+        );
+    }
+
+    #[cfg(feature = "libsecp256k1")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn check_test_vector() {
+        let valid_record = hex::decode("f884b8407098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c01826964827634826970847f00000189736563703235366b31a103ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd31388375647082765f").unwrap();
+        let signature = hex::decode("7098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c").unwrap();
+        let expected_pubkey =
+            hex::decode("03ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd3138")
+                .unwrap();
+
+        let enr = rlp::decode::<DefaultEnr>(&valid_record).unwrap();
 
         let pubkey = enr.public_key().encode();
 
@@ -915,6 +3020,7 @@ mod tests {
 
     #[cfg(feature = "libsecp256k1")]
     #[test]
+    #[cfg_attr(miri, ignore)]
     fn check_test_vector_2() {
         let text = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
         let signature = hex::decode("7098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c").unwrap();
@@ -942,12 +3048,94 @@ mod tests {
         assert_eq!(pubkey, expected_pubkey);
         dbg!("here3");
         assert_eq!(enr.node_id().raw().to_vec(), expected_node_id);
+        assert_eq!(
+            enr.node_id().to_string(),
+            "a448f24c6d18e575453db13171562b71999873db5b286df957af199ec94617f7"
+        );
+        assert_eq!(
+            "0xA448F24C6D18E575453DB13171562B71999873DB5B286DF957AF199EC94617F7".parse(),
+            Ok(enr.node_id())
+        );
 
         assert!(enr.verify());
     }
 
     #[cfg(feature = "libsecp256k1")]
     #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_text_variants() {
+        const TEXT: &str = "-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+        let expected = format!("enr:{TEXT}").parse::<DefaultEnr>().unwrap();
+        let standard = TEXT.replace('-', "+").replace('_', "/");
+
+        // prefix, alphabet, padding and whitespace
+        for prefix in &["enr:", ""] {
+            for alphabet in &[TEXT, &standard] {
+                for padding in &["", "="] {
+                    for whitespace in &["", " \n\t"] {
+                        let text = format!("{whitespace}{prefix}{alphabet}{padding}{whitespace}");
+                        assert_eq!(text.parse::<DefaultEnr>(), Ok(expected.clone()), "{text:?}");
+                        let exact = prefix.len() == 4
+                            && *alphabet == TEXT
+                            && padding.is_empty()
+                            && whitespace.is_empty();
+                        assert_eq!(
+                            DefaultEnr::from_str_strict(&text).is_ok(),
+                            exact,
+                            "{text:?}"
+                        );
+                    }
+                }
+            }
+        }
+
+        // anything else is still rejected
+        for text in &[
+            "",
+            "  ",
+            "enr:",
+            "enr: -IS4Q",
+            &format!("{TEXT}==="),
+            &format!("{TEXT}!"),
+            &format!("enr:{}", TEXT.replace("Bgmlk", "Bgmll")),
+        ] {
+            assert!(text.parse::<DefaultEnr>().is_err(), "{:?}", text);
+        }
+        let tampered = format!("enr:{}", &TEXT[..TEXT.len() - 1]);
+        assert!(tampered.parse::<DefaultEnr>().is_err());
+    }
+
+    #[cfg(feature = "libsecp256k1")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_hex_encoding() {
+        const TEXT: &str = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+        const HEX: &str = "0xf884b8407098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c01826964827634826970847f00000189736563703235366b31a103ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd31388375647082765f";
+        let expected = TEXT.parse::<DefaultEnr>().unwrap();
+        assert_eq!(expected.to_hex(), HEX);
+
+        for hex in &[HEX, &HEX[2..], &HEX.to_uppercase().replace("0X", "0x")] {
+            assert_eq!(DefaultEnr::from_hex(hex), Ok(expected.clone()), "{hex}");
+            assert_eq!(DefaultEnr::parse(hex), Ok(expected.clone()), "{hex}");
+        }
+        assert_eq!(DefaultEnr::parse(TEXT), Ok(expected));
+
+        assert_eq!(
+            DefaultEnr::from_hex(&HEX[..HEX.len() - 1]),
+            Err("Invalid hex encoding: odd length".to_string())
+        );
+        assert!(DefaultEnr::from_hex("0xzz").is_err());
+        // raise the udp port, the last byte of the record, without re-signing
+        let tampered = format!("{}60", &HEX[..HEX.len() - 2]);
+        assert!(DefaultEnr::from_hex(&tampered)
+            .unwrap_err()
+            .contains("Invalid Signature"));
+        assert!(DefaultEnr::parse(&tampered).is_err());
+    }
+
+    #[cfg(feature = "libsecp256k1")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
     fn test_read_enr() {
         let text = "-Iu4QM-YJF2RRpMcZkFiWzMf2kRd1A5F1GIekPa4Sfi_v0DCLTDBfOMTMMWJhhawr1YLUPb5008CpnBKrgjY3sstjfgCgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQP8u1uyQFyJYuQUTyA1raXKhSw1HhhxNUQ2VE52LNHWMIN0Y3CCIyiDdWRwgiMo";
         let enr = text.parse::<DefaultEnr>().unwrap();
@@ -958,6 +3146,7 @@ mod tests {
 
     #[cfg(feature = "libsecp256k1")]
     #[test]
+    #[cfg_attr(miri, ignore)]
     fn test_encode_test_vector_2() {
         let key = secp256k1::SecretKey::parse_slice(
             &hex::decode("b71c71a67e1177ad4e901695e1b4b9ee17ae16c6668d313eac2f96dbcda3f291")
@@ -972,7 +3161,7 @@ mod tests {
 
         let enr = {
             let mut builder = EnrBuilder::new("v4");
-            builder.ip(ip.into());
+            builder.ip4(ip);
             builder.udp(udp);
             builder.build(&key).unwrap()
         };
@@ -982,6 +3171,7 @@ mod tests {
 
     #[cfg(feature = "libsecp256k1")]
     #[test]
+    #[cfg_attr(miri, ignore)]
     fn test_encode_decode_secp256k1() {
         let mut rng = rand::thread_rng();
         let key = secp256k1::SecretKey::random(&mut rng);
@@ -990,12 +3180,12 @@ mod tests {
 
         let enr = {
             let mut builder = EnrBuilder::new("v4");
-            builder.ip(ip.into());
+            builder.ip4(ip);
             builder.tcp(tcp);
             builder.build(&key).unwrap()
         };
 
-        let encoded_enr = rlp::encode(&enr);
+        let encoded_enr = rlp::encode(&enr).to_vec();
 
         let decoded_enr = rlp::decode::<DefaultEnr>(&encoded_enr).unwrap();
 
@@ -1005,10 +3195,12 @@ mod tests {
         // Must compare encoding as the public key itself can be different
         assert_eq!(decoded_enr.public_key().encode(), key.public().encode());
         assert!(decoded_enr.verify());
+        test_utils::assert_enr_roundtrip(&enr);
     }
 
     #[cfg(feature = "rust-secp256k1")]
     #[test]
+    #[cfg_attr(miri, ignore)]
     fn test_encode_decode_c_secp256k1() {
         let mut rng = c_secp256k1::rand::thread_rng();
         let key = c_secp256k1::SecretKey::new(&mut rng);
@@ -1017,12 +3209,12 @@ mod tests {
 
         let enr = {
             let mut builder = EnrBuilder::new("v4");
-            builder.ip(ip.into());
+            builder.ip4(ip);
             builder.tcp(tcp);
             builder.build(&key).unwrap()
         };
 
-        let encoded_enr = rlp::encode(&enr);
+        let encoded_enr = rlp::encode(&enr).to_vec();
 
         let decoded_enr = rlp::decode::<Enr<c_secp256k1::SecretKey>>(&encoded_enr).unwrap();
 
@@ -1032,10 +3224,37 @@ mod tests {
         // Must compare encoding as the public key itself can be different
         assert_eq!(decoded_enr.public_key().encode(), key.public().encode());
         assert!(decoded_enr.verify());
+        test_utils::assert_enr_roundtrip(&enr);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_verify_v4_checks_signature() {
+        let key = secp256k1::SecretKey::parse(&[1; 32]).unwrap();
+        let other = secp256k1::SecretKey::parse(&[2; 32]).unwrap();
+        let sig = key.sign_v4(b"message").unwrap();
+        assert!(key.public().verify_v4(b"message", &sig));
+        // a well-formed signature of another key or message does not verify
+        assert!(!other.public().verify_v4(b"message", &sig));
+        assert!(!key.public().verify_v4(b"another message", &sig));
+    }
+
+    #[cfg(feature = "rust-secp256k1")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_verify_v4_checks_signature_c_secp256k1() {
+        let key = c_secp256k1::SecretKey::from_slice(&[1; 32]).unwrap();
+        let other = c_secp256k1::SecretKey::from_slice(&[2; 32]).unwrap();
+        let sig = key.sign_v4(b"message").unwrap();
+        assert!(key.public().verify_v4(b"message", &sig));
+        // a well-formed signature of another key or message does not verify
+        assert!(!other.public().verify_v4(b"message", &sig));
+        assert!(!key.public().verify_v4(b"another message", &sig));
     }
 
     #[cfg(feature = "ed25519")]
     #[test]
+    #[cfg_attr(miri, ignore)]
     fn test_encode_decode_ed25519() {
         let mut rng = rand::thread_rng();
         let key = ed25519_dalek::Keypair::generate(&mut rng);
@@ -1044,12 +3263,12 @@ mod tests {
 
         let enr = {
             let mut builder = EnrBuilder::new("v4");
-            builder.ip(ip.into());
+            builder.ip4(ip);
             builder.tcp(tcp);
             builder.build(&key).unwrap()
         };
 
-        let encoded_enr = rlp::encode(&enr);
+        let encoded_enr = rlp::encode(&enr).to_vec();
         let decoded_enr = rlp::decode::<Enr<CombinedKey>>(&encoded_enr).unwrap();
 
         assert_eq!(decoded_enr.id(), Some("v4".into()));
@@ -1057,9 +3276,61 @@ mod tests {
         assert_eq!(decoded_enr.tcp(), Some(tcp));
         assert_eq!(decoded_enr.public_key().encode(), key.public().encode());
         assert!(decoded_enr.verify());
+        test_utils::assert_enr_roundtrip(&enr);
+    }
+
+    #[cfg(feature = "ed25519-v2")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_encode_decode_ed25519_v2() {
+        let secret = [7; 32];
+        let key = ed25519_dalek_v2::SigningKey::from_bytes(&secret);
+        let enr = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::new(10, 0, 0, 1))
+            .tcp(30303)
+            .build(&key)
+            .unwrap();
+        assert!(enr.verify());
+
+        // ed25519 signatures are deterministic, both versions sign the same record
+        let v1 = {
+            let secret = ed25519_dalek::SecretKey::from_bytes(&secret).unwrap();
+            let public = ed25519_dalek::PublicKey::from(&secret);
+            ed25519_dalek::Keypair { secret, public }
+        };
+        let v1_enr = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::new(10, 0, 0, 1))
+            .tcp(30303)
+            .build(&v1)
+            .unwrap();
+        assert_eq!(v1_enr.encode(), enr.encode());
+
+        let decoded = rlp::decode::<Enr<CombinedKey>>(&enr.encode()).unwrap();
+        assert_eq!(
+            decoded.public_key(),
+            CombinedPublicKey::Ed25519(key.verifying_key())
+        );
+        assert_eq!(v1_enr.into_combined(), decoded);
+
+        let public = CombinedPublicKey::try_from(v1.public).unwrap();
+        assert_eq!(public, CombinedPublicKey::Ed25519(key.verifying_key()));
+
+        let combined = CombinedKey::from(v1);
+        assert!(matches!(&combined, CombinedKey::Ed25519(inner) if inner == &key));
+        assert_eq!(combined.encode(), secret);
+        assert_eq!(
+            EnrBuilder::new("v4")
+                .ip4(Ipv4Addr::new(10, 0, 0, 1))
+                .tcp(30303)
+                .build(&combined)
+                .unwrap()
+                .encode(),
+            enr.encode()
+        );
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)]
     fn test_add_key() {
         let mut rng = rand::thread_rng();
         let key = secp256k1::SecretKey::random(&mut rng);
@@ -1068,16 +3339,17 @@ mod tests {
 
         let mut enr = {
             let mut builder = EnrBuilder::new("v4");
-            builder.ip(ip.into());
+            builder.ip4(ip);
             builder.tcp(tcp);
             builder.build(&key).unwrap()
         };
 
-        assert!(enr.insert("random", Vec::new(), &key).is_ok());
+        assert!(enr.insert_value("random", &Vec::<u8>::new(), &key).is_ok());
         assert!(enr.verify());
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)]
     fn test_set_ip() {
         let mut rng = rand::thread_rng();
         let key = secp256k1::SecretKey::random(&mut rng);
@@ -1101,6 +3373,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(miri, ignore)]
     fn ip_mutation_static_node_id() {
         let mut rng = rand::thread_rng();
         let key = secp256k1::SecretKey::random(&mut rng);
@@ -1110,7 +3383,7 @@ mod tests {
 
         let mut enr = {
             let mut builder = EnrBuilder::new("v4");
-            builder.ip(ip.into());
+            builder.ip4(ip);
             builder.tcp(tcp);
             builder.udp(udp);
             builder.build(&key).unwrap()
@@ -1127,29 +3400,1276 @@ mod tests {
         );
     }
 
-    #[cfg(feature = "ed25519")]
     #[test]
-    fn combined_key_can_decode_all() {
-        // generate a random secp256k1 key
+    #[cfg_attr(miri, ignore)]
+    fn test_insert_value_and_raw_rlp_match_test_vector() {
+        let key = secp256k1::SecretKey::parse_slice(
+            &hex::decode("b71c71a67e1177ad4e901695e1b4b9ee17ae16c6668d313eac2f96dbcda3f291")
+                .unwrap(),
+        )
+        .unwrap();
+        let expected = hex::decode("f884b8407098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c01826964827634826970847f00000189736563703235366b31a103ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd31388375647082765f").unwrap();
+
+        // value insertion encodes for the caller
+        let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+        enr.insert_value("ip", &Ipv4Addr::LOCALHOST.octets().to_vec(), &key)
+            .unwrap();
+        enr.insert_value("udp", &30303_u16, &key).unwrap();
+        enr.set_seq(1, &key).unwrap();
+        assert_eq!(enr.encode(), expected);
+
+        // raw insertion takes the pre-encoded value
+        let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+        enr.insert_raw_rlp("ip", hex::decode("847f000001").unwrap(), &key)
+            .unwrap();
+        enr.insert_raw_rlp("udp", hex::decode("82765f").unwrap(), &key)
+            .unwrap();
+        enr.set_seq(1, &key).unwrap();
+        assert_eq!(enr.encode(), expected);
+
+        // both paths agree on custom byte fields
+        let mut value_enr = EnrBuilder::new("v4").build(&key).unwrap();
+        let mut raw_enr = value_enr.clone();
+        value_enr
+            .insert_value("custom", &vec![0_u8, 1, 2], &key)
+            .unwrap();
+        raw_enr
+            .insert_raw_rlp("custom", rlp::encode(&vec![0_u8, 1, 2]).to_vec(), &key)
+            .unwrap();
+        assert_eq!(value_enr.encode(), raw_enr.encode());
+        assert_eq!(value_enr.get("custom"), Some(&[0_u8, 1, 2][..]));
+        assert_eq!(
+            value_enr.get_raw_rlp("custom"),
+            Some(&[0x83_u8, 0, 1, 2][..])
+        );
+        let decoded = rlp::decode::<DefaultEnr>(&value_enr.encode()).unwrap();
+        assert_eq!(decoded.get("custom"), Some(&[0_u8, 1, 2][..]));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_insert_raw_rlp_validation() {
         let mut rng = rand::thread_rng();
         let key = secp256k1::SecretKey::random(&mut rng);
-        let ip = Ipv4Addr::new(192, 168, 0, 1);
-        let enr_secp256k1 = EnrBuilder::new("v4")
-            .ip(ip.into())
-            .tcp(8000)
-            .build(&key)
+        let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+        let list = {
+            let mut s = RlpStream::new_list(2);
+            s.append(&1_u8).append(&"a");
+            s.out().to_vec()
+        };
+
+        // empty input, trailing bytes and truncated items are rejected
+        assert!(enr.insert_raw_rlp("custom", Vec::new(), &key).is_err());
+        assert!(enr
+            .insert_raw_rlp("custom", vec![0x01, 0x02], &key)
+            .is_err());
+        assert!(enr
+            .insert_raw_rlp("custom", vec![0x82, 0x01], &key)
+            .is_err());
+        let mut trailing = list.clone();
+        trailing.push(0x01);
+        assert!(enr.insert_raw_rlp_list("custom", trailing, &key).is_err());
+
+        // lists are only accepted if requested
+        assert!(enr.insert_raw_rlp("custom", list.clone(), &key).is_err());
+        assert_eq!(enr.get("custom"), None);
+        assert_eq!(enr.seq(), 1);
+        enr.insert_raw_rlp_list("custom", list.clone(), &key)
             .unwrap();
+        assert_eq!(enr.get_raw_rlp("custom"), Some(&list[..]));
 
-        // encode to base64
-        let base64_string_secp256k1 = enr_secp256k1.to_base64();
+        let decoded = rlp::decode::<DefaultEnr>(&enr.encode()).unwrap();
+        assert_eq!(decoded.get_raw_rlp("custom"), Some(&list[..]));
+        assert!(decoded.verify());
+    }
 
-        // generate a random ed25519 key
-        let key = ed25519_dalek::Keypair::generate(&mut rng);
-        let enr_ed25519 = EnrBuilder::new("v4")
-            .ip(ip.into())
-            .tcp(8000)
-            .build(&key)
-            .unwrap();
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    #[allow(deprecated)]
+    fn test_deprecated_insert_encodes_bytes() {
+        let mut rng = rand::thread_rng();
+        let key = secp256k1::SecretKey::random(&mut rng);
+        let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+
+        assert_eq!(enr.insert("custom", vec![1, 2], &key).unwrap(), None);
+        assert_eq!(
+            enr.insert("custom", vec![3], &key).unwrap(),
+            Some(vec![1, 2])
+        );
+        assert_eq!(enr.get("custom"), Some(&[3_u8][..]));
+        assert_eq!(enr.get_raw_rlp("custom"), Some(&[3_u8][..]));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_insert_and_remove_return_previous_values() {
+        let mut rng = rand::thread_rng();
+        let key = secp256k1::SecretKey::random(&mut rng);
+        let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+
+        // first insert
+        assert_eq!(enr.insert_value("custom", &1_u8, &key).unwrap(), None);
+        // replacement
+        assert_eq!(
+            enr.insert_value("custom", &2_u8, &key).unwrap(),
+            Some(rlp::encode(&1_u8).to_vec())
+        );
+        assert_eq!(enr.seq(), 3);
+
+        // removal
+        assert_eq!(
+            enr.remove("custom", &key).unwrap(),
+            Some(rlp::encode(&2_u8).to_vec())
+        );
+        assert_eq!(enr.get("custom"), None);
+        assert_eq!(enr.seq(), 4);
+        assert!(enr.verify());
+
+        // removing a missing key leaves the record untouched
+        assert_eq!(enr.remove("custom", &key).unwrap(), None);
+        assert_eq!(enr.seq(), 4);
+
+        // reserved keys cannot be removed
+        assert!(enr.remove("id", &key).is_err());
+        assert!(enr.remove("secp256k1", &key).is_err());
+
+        // typed setters
+        assert_eq!(enr.set_udp(30303, &key).unwrap(), None);
+        assert_eq!(enr.set_udp(30304, &key).unwrap(), Some(30303));
+        let ip: IpAddr = Ipv4Addr::new(10, 0, 0, 1).into();
+        assert_eq!(enr.set_ip(ip, &key).unwrap(), None);
+        assert_eq!(
+            enr.set_ip(Ipv4Addr::new(10, 0, 0, 2).into(), &key).unwrap(),
+            Some(ip)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_update_from() {
+        let mut rng = rand::thread_rng();
+        let key = secp256k1::SecretKey::random(&mut rng);
+        let mut local = EnrBuilder::new("v4").udp(30303).build(&key).unwrap();
+
+        let mut remote = local.clone();
+        remote.set_udp(30304, &key).unwrap();
+
+        // identical records are accepted as a no-op
+        let updated = local.update_from(local.clone()).unwrap();
+        assert!(!updated.updated);
+
+        // a newer record replaces the current one
+        let updated = local.update_from(remote.clone()).unwrap();
+        assert_eq!(
+            updated,
+            Updated {
+                updated: true,
+                old_seq: 1
+            }
+        );
+        assert_eq!(local, remote);
+
+        // stale records
+        let stale = EnrBuilder::new("v4").udp(1).build(&key).unwrap();
+        assert!(matches!(
+            local.update_from(stale),
+            Err(EnrError::StaleSequenceNumber)
+        ));
+
+        // other nodes
+        let other = EnrBuilder::new("v4")
+            .seq(10)
+            .build(&secp256k1::SecretKey::random(&mut rng))
+            .unwrap();
+        assert!(matches!(
+            local.update_from(other),
+            Err(EnrError::NodeIdMismatch)
+        ));
+
+        // invalid signatures
+        let mut forged = remote;
+        forged.seq = 10;
+        assert!(matches!(
+            local.update_from(forged),
+            Err(EnrError::InvalidSignature)
+        ));
+        assert_eq!(local.udp(), Some(30304));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_get() {
+        let mut rng = rand::thread_rng();
+        let key = secp256k1::SecretKey::random(&mut rng);
+
+        let enr = EnrBuilder::new("v4")
+            .add_value("custom".into(), vec![1, 2, 3])
+            .build(&key)
+            .unwrap();
+
+        assert_eq!(enr.get("custom"), Some(&[1_u8, 2, 3][..]));
+        assert_eq!(enr.get(String::from("custom")), Some(&[1_u8, 2, 3][..]));
+        assert_eq!(enr.get_owned("custom"), Some(vec![1, 2, 3]));
+        assert_eq!(enr.get("missing"), None);
+        assert_eq!(enr.get_owned("missing"), None);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_dual_stack_predicates() {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let ip4 = Ipv4Addr::new(10, 0, 0, 1);
+        let ip6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+
+        // no endpoints, ports and addresses alone do not count
+        for enr in &[
+            EnrBuilder::new("v4").build(&key).unwrap(),
+            EnrBuilder::new("v4")
+                .udp(30303)
+                .tcp6(30303)
+                .allow_orphan_ports(true)
+                .build(&key)
+                .unwrap(),
+            EnrBuilder::new("v4")
+                .ip4(ip4)
+                .udp6(30303)
+                .allow_orphan_ports(true)
+                .build(&key)
+                .unwrap(),
+            EnrBuilder::new("v4")
+                .ip6(ip6)
+                .tcp(30303)
+                .build(&key)
+                .unwrap(),
+        ] {
+            assert!(!enr.supports_ipv4());
+            assert!(!enr.supports_ipv6());
+            assert!(!enr.is_dual_stack());
+            assert!(!enr.udp_reachable());
+            assert!(!enr.tcp_reachable());
+            assert_eq!(enr.udp_sockets().count(), 0);
+        }
+
+        // IPv4 only
+        let enr = EnrBuilder::new("v4")
+            .ip4(ip4)
+            .udp(30303)
+            .build(&key)
+            .unwrap();
+        assert!(enr.supports_ipv4());
+        assert!(!enr.supports_ipv6());
+        assert!(!enr.is_dual_stack());
+        assert!(enr.udp_reachable());
+        assert!(!enr.tcp_reachable());
+        assert_eq!(
+            enr.udp_sockets().collect::<Vec<_>>(),
+            vec![SocketAddr::new(ip4.into(), 30303)]
+        );
+
+        // IPv6 only
+        let enr = EnrBuilder::new("v4")
+            .ip6(ip6)
+            .tcp6(30304)
+            .build(&key)
+            .unwrap();
+        assert!(!enr.supports_ipv4());
+        assert!(enr.supports_ipv6());
+        assert!(!enr.udp_reachable());
+        assert!(enr.tcp_reachable());
+        assert_eq!(enr.udp_sockets().count(), 0);
+
+        // dual stack
+        let enr = EnrBuilder::new("v4")
+            .ip4(ip4)
+            .ip6(ip6)
+            .tcp(30303)
+            .udp(30303)
+            .udp6(30304)
+            .build(&key)
+            .unwrap();
+        assert!(enr.is_dual_stack());
+        assert!(enr.udp_reachable());
+        assert!(enr.tcp_reachable());
+        assert_eq!(
+            enr.udp_sockets().collect::<Vec<_>>(),
+            vec![
+                SocketAddr::new(ip4.into(), 30303),
+                SocketAddr::new(ip6.into(), 30304)
+            ]
+        );
+
+        // an IPv4-mapped `ip6` is reachable, but does not count as IPv6 support
+        let mapped = ip4.to_ipv6_mapped();
+        let enr = EnrBuilder::new("v4")
+            .add_value("ip6".into(), mapped.octets().to_vec())
+            .udp6(30303)
+            .build(&key)
+            .unwrap();
+        assert!(!enr.supports_ipv6());
+        assert!(enr.udp_reachable());
+        assert_eq!(
+            enr.udp_sockets().collect::<Vec<_>>(),
+            vec![SocketAddr::new(mapped.into(), 30303)]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_set_ip_dispatch() {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let ip4 = Ipv4Addr::new(10, 0, 0, 1);
+        let ip6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+        let mut enr = EnrBuilder::new("v4").ip4(ip4).build(&key).unwrap();
+
+        // setting an IPv6 address does not clobber the IPv4 address
+        assert_eq!(enr.set_ip(ip6.into(), &key).unwrap(), None);
+        assert_eq!(enr.ip(), Some(ip4));
+        assert_eq!(enr.ip6(), Some(ip6));
+
+        let new_ip4 = Ipv4Addr::new(10, 0, 0, 2);
+        assert_eq!(
+            enr.set_ip(new_ip4.into(), &key).unwrap(),
+            Some(IpAddr::V4(ip4))
+        );
+        assert_eq!(enr.set_ip4(ip4, &key).unwrap(), Some(new_ip4));
+        assert_eq!(enr.ip6(), Some(ip6));
+
+        // IPv4-mapped addresses are written to `ip6` unchanged by `set_ip`...
+        let mapped = Ipv4Addr::new(1, 2, 3, 4).to_ipv6_mapped();
+        assert_eq!(
+            enr.set_ip(mapped.into(), &key).unwrap(),
+            Some(IpAddr::V6(ip6))
+        );
+        assert_eq!(enr.ip(), Some(ip4));
+        assert_eq!(enr.ip6(), Some(mapped));
+
+        // ...and rejected by `set_ip6`
+        let seq = enr.seq();
+        assert!(matches!(
+            enr.set_ip6(mapped, &key),
+            Err(EnrError::Ipv4MappedAddress(ip)) if ip == mapped
+        ));
+        assert_eq!(enr.seq(), seq);
+        assert_eq!(enr.set_ip6(ip6, &key).unwrap(), Some(mapped));
+
+        // the deprecated builder `ip` dispatches in the same way
+        #[allow(deprecated)]
+        let enr = EnrBuilder::new("v4")
+            .ip(ip4.into())
+            .ip(mapped.into())
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.ip(), Some(ip4));
+        assert_eq!(enr.ip6(), Some(mapped));
+
+        // while the typed builder setters reject mapped addresses
+        let enr = EnrBuilder::new("v4").ip4(ip4).ip6(ip6).build(&key).unwrap();
+        assert_eq!(enr.ip(), Some(ip4));
+        assert_eq!(enr.ip6(), Some(ip6));
+        assert!(matches!(
+            EnrBuilder::new("v4").ip4(ip4).ip6(mapped).build(&key),
+            Err(EnrError::Ipv4MappedAddress(ip)) if ip == mapped
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_content_eq() {
+        let mut rng = rand::thread_rng();
+        let key = secp256k1::SecretKey::random(&mut rng);
+
+        let mut enr = EnrBuilder::new("v4").tcp(30303).build(&key).unwrap();
+        let decoded = rlp::decode::<DefaultEnr>(&enr.encode()).unwrap();
+        assert!(enr.content_eq(&decoded));
+
+        // same content, but a different sequence number
+        enr.set_tcp(30303, &key).unwrap();
+        assert!(!enr.content_eq(&decoded));
+
+        // same sequence number, but different content
+        let other = EnrBuilder::new("v4")
+            .seq(enr.seq())
+            .tcp(30304)
+            .build(&key)
+            .unwrap();
+        assert!(!enr.content_eq(&other));
+    }
+
+    /// A "v4"-like identity scheme deriving node ids from the compressed public key.
+    struct CompressedScheme;
+
+    impl IdentityScheme<secp256k1::SecretKey> for CompressedScheme {
+        fn scheme_id(&self) -> &'static str {
+            "compressed"
+        }
+
+        fn sign(
+            &self,
+            key: &secp256k1::SecretKey,
+            content: &[u8],
+        ) -> Result<Vec<u8>, SigningError> {
+            key.sign_v4(content)
+        }
+
+        fn verify(&self, public_key: &secp256k1::PublicKey, content: &[u8], sig: &[u8]) -> bool {
+            public_key.verify_v4(content, sig)
+        }
+
+        fn node_id(&self, public_key: &secp256k1::PublicKey) -> [u8; 32] {
+            digest(&public_key.encode())
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_custom_identity_scheme() {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let mut enr = EnrBuilder::new("v4")
+            .identity_scheme(CompressedScheme)
+            .tcp(30303)
+            .build(&key)
+            .unwrap();
+
+        assert_eq!(enr.id(), Some("compressed".into()));
+        assert_eq!(enr.identity_scheme(), Some("compressed"));
+        assert_eq!(enr.validate_fields(), Ok(()));
+        assert_eq!(enr.node_id().raw(), digest(&key.public().encode()));
+        assert!(enr.verify());
+        enr.set_udp(30303, &key).unwrap();
+        assert!(enr.verify());
+        assert!(matches!(
+            enr.insert_value("id", &"v4", &key),
+            Err(EnrError::UnsupportedIdentityScheme)
+        ));
+
+        // the record is refused by default, and only preserved on request when decoded without
+        // knowledge of the scheme
+        assert!(rlp::decode::<DefaultEnr>(&enr.encode()).is_err());
+        assert!(enr.to_base64().parse::<DefaultEnr>().is_err());
+        let mut decoded = DefaultEnr::decode_with_options(
+            &enr.encode(),
+            EnrDecodeOptions::new().allow_unknown_schemes(true),
+        )
+        .unwrap();
+        assert_eq!(decoded.encode(), enr.encode());
+        assert_eq!(decoded.id(), Some("compressed".into()));
+        assert_eq!(decoded.identity_scheme(), None);
+        assert!(!decoded.verify());
+        assert!(matches!(
+            decoded.set_tcp(30304, &key),
+            Err(EnrError::UnsupportedIdentityScheme)
+        ));
+
+        assert!(matches!(
+            decoded.clone().with_identity_scheme(V4Scheme),
+            Err(EnrError::UnsupportedIdentityScheme)
+        ));
+        let decoded = decoded.with_identity_scheme(CompressedScheme).unwrap();
+        assert!(decoded.verify());
+        assert_eq!(decoded.node_id(), enr.node_id());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_forged_unknown_scheme_is_rejected() {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        // claims the public key of `key` under an unknown scheme, without a valid signature
+        let forged = forge_unknown_scheme(&EnrBuilder::new("v4").udp(30303).build(&key).unwrap());
+        let bytes = forged.encode();
+
+        assert!(rlp::decode::<DefaultEnr>(&bytes).is_err());
+        assert!(DefaultEnr::decode_bytes(&bytes::Bytes::copy_from_slice(&bytes)).is_err());
+        assert!(forged.to_base64().parse::<DefaultEnr>().is_err());
+        assert!(DefaultEnr::from_str_strict(&forged.to_base64()).is_err());
+        assert!(DefaultEnr::from_hex(&hex::encode(&bytes)).is_err());
+        assert!(DefaultEnr::parse(&forged.to_base64()).is_err());
+        assert!(DefaultEnr::parse(&hex::encode(&bytes)).is_err());
+        assert!(matches!(
+            DefaultEnr::decode_with_options(&bytes, &EnrDecodeOptions::new()),
+            Err(EnrError::UnsupportedIdentityScheme)
+        ));
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&forged.to_base64()).unwrap();
+            assert!(serde_json::from_str::<DefaultEnr>(&json).is_err());
+        }
+
+        // kept on request, but without a scheme and unverified
+        let kept = DefaultEnr::decode_with_options(
+            &bytes,
+            EnrDecodeOptions::new().allow_unknown_schemes(true),
+        )
+        .unwrap();
+        assert_eq!(kept.identity_scheme(), None);
+        assert!(!kept.verify());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_missing_identity_scheme_is_rejected() {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let public_key = key.public().encode();
+        // a record signed under the v4 scheme, without an `id` key
+        let mut content = RlpStream::new_list(3);
+        content
+            .append(&1_u64)
+            .append(&"secp256k1")
+            .append(&public_key.as_slice());
+        let sig = key.sign_v4(&content.out()).unwrap();
+        let mut record = RlpStream::new_list(4);
+        record
+            .append(&sig)
+            .append(&1_u64)
+            .append(&"secp256k1")
+            .append(&public_key.as_slice());
+        let bytes = record.out();
+
+        assert!(rlp::decode::<DefaultEnr>(&bytes).is_err());
+        assert!(DefaultEnr::decode_with_options(
+            &bytes,
+            EnrDecodeOptions::new().allow_unknown_schemes(true),
+        )
+        .is_err());
+        assert!(DefaultEnr::from_hex(&hex::encode(&bytes)).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_content_and_record_hash() {
+        // the EIP-778 test vector
+        let text = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+        let mut enr = text.parse::<DefaultEnr>().unwrap();
+
+        // the preimage of the content hash is the signed content `[seq, k, v, ...]`
+        let content = hex::decode("f84201826964827634826970847f00000189736563703235366b31a103ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd31388375647082765f").unwrap();
+        assert_eq!(enr.rlp_content(), content);
+        assert_eq!(enr.content_hash(), digest(&content));
+        assert_eq!(
+            hex::encode(enr.content_hash()),
+            "bc218268b018aecb5d4c5afd5feeb3b920f56eddf09bdf83df9d3de868e1cb95"
+        );
+        assert_eq!(
+            hex::encode(enr.record_hash()),
+            "76f6af98a1a2060c86a676b92d21f89fc7d1ce14a14e6bcfad6da1af3345b6ce"
+        );
+
+        // a differing signature only changes the record hash
+        let content_hash = enr.content_hash();
+        let record_hash = enr.record_hash();
+        enr.signature[0] ^= 1;
+        assert_eq!(enr.content_hash(), content_hash);
+        assert_ne!(enr.record_hash(), record_hash);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_eq_and_hash() {
+        use std::collections::HashSet;
+
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let mut enr = EnrBuilder::new("v4").udp(30303).build(&key).unwrap();
+        let text = enr.to_base64();
+
+        // re-decoded copies are equal and deduplicated
+        let first = text.parse::<DefaultEnr>().unwrap();
+        let second = text.parse::<DefaultEnr>().unwrap();
+        assert_eq!(first, second);
+        let set: HashSet<_> = vec![first, second, enr.clone()].into_iter().collect();
+        assert_eq!(set.len(), 1);
+
+        // a newer version of the record is a distinct record of the same node
+        let old = enr.clone();
+        enr.set_udp(30304, &key).unwrap();
+        assert_ne!(enr, old);
+        assert!(enr.same_identity(&old));
+        assert!(!set.contains(&enr));
+
+        let other = EnrBuilder::new("v4")
+            .udp(30303)
+            .build(&secp256k1::SecretKey::random(&mut rand::thread_rng()))
+            .unwrap();
+        assert!(!other.same_identity(&old));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_ordering() {
+        use std::cmp::Ordering;
+
+        let mut records = Vec::new();
+        for byte in 1..=3 {
+            let key = secp256k1::SecretKey::parse(&[byte; 32]).unwrap();
+            for seq in 1..=2 {
+                records.push(EnrBuilder::new("v4").seq(seq).build(&key).unwrap());
+            }
+        }
+        let summary = |records: &[DefaultEnr]| {
+            records
+                .iter()
+                .map(|enr| (hex::encode(&enr.node_id().raw()[..2]), enr.seq()))
+                .collect::<Vec<_>>()
+        };
+
+        let mut sorted = vec![
+            records[3].clone(),
+            records[0].clone(),
+            records[5].clone(),
+            records[2].clone(),
+            records[1].clone(),
+            records[4].clone(),
+        ];
+        sorted.sort();
+        assert_eq!(
+            summary(&sorted),
+            vec![
+                ("3056".to_string(), 1),
+                ("3056".to_string(), 2),
+                ("a959".to_string(), 1),
+                ("a959".to_string(), 2),
+                ("b8a0".to_string(), 1),
+                ("b8a0".to_string(), 2),
+            ]
+        );
+
+        // consistent with `Eq`
+        let copy = records[0].to_base64().parse::<DefaultEnr>().unwrap();
+        assert_eq!(copy.cmp(&records[0]), Ordering::Equal);
+
+        // distinct records of the same node and sequence number are ordered
+        let key = secp256k1::SecretKey::parse(&[1; 32]).unwrap();
+        let other = EnrBuilder::new("v4").udp(30303).build(&key).unwrap();
+        assert_ne!(other.cmp(&records[0]), Ordering::Equal);
+        assert_eq!(other.cmp(&records[0]), records[0].cmp(&other).reverse());
+
+        assert_eq!(
+            records[1].cmp_freshness(&records[0]),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(other.cmp_freshness(&records[0]), Some(Ordering::Equal));
+        assert_eq!(records[0].cmp_freshness(&records[2]), None);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_clone_with_incremented_seq() {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let enr = EnrBuilder::new("v4").udp(30303).build(&key).unwrap();
+
+        let mut clone = enr.clone_with_incremented_seq();
+        assert_eq!(clone.seq(), enr.seq() + 1);
+        assert_eq!(clone.node_id(), enr.node_id());
+        assert_eq!(clone.udp(), enr.udp());
+        assert_eq!(clone.signature(), enr.signature());
+        assert!(!clone.verify());
+
+        // re-signing at the incremented sequence number
+        clone.set_seq(clone.seq(), &key).unwrap();
+        assert!(clone.verify());
+        assert_eq!(clone.seq(), enr.seq() + 1);
+        assert!(clone.to_base64().parse::<DefaultEnr>().is_ok());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_byte_conversions() {
+        // the EIP-778 test vector
+        let text = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+        let bytes = base64::decode_config(&text[4..], base64::URL_SAFE_NO_PAD).unwrap();
+        let expected = text.parse::<DefaultEnr>().unwrap();
+
+        let enr = DefaultEnr::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(enr, expected);
+        assert_eq!(DefaultEnr::try_from(bytes.clone()).unwrap(), expected);
+        assert_eq!(Vec::from(&enr), bytes);
+
+        // trailing bytes
+        let mut trailing = bytes.clone();
+        trailing.push(0x80);
+        assert!(matches!(
+            DefaultEnr::try_from(trailing),
+            Err(EnrError::InvalidRlpData(_))
+        ));
+
+        // an invalid signature
+        let mut tampered = bytes.clone();
+        tampered[10] ^= 1;
+        assert!(matches!(
+            DefaultEnr::try_from(tampered),
+            Err(EnrError::InvalidSignature)
+        ));
+        assert!(DefaultEnr::try_from(&bytes[..bytes.len() - 1]).is_err());
+
+        // an unverifiable record of an unknown identity scheme
+        let mut unknown = expected;
+        unknown
+            .content
+            .insert("id".into(), rlp::encode(&"v5").into());
+        assert!(matches!(
+            DefaultEnr::try_from(unknown.encode()),
+            Err(EnrError::UnsupportedIdentityScheme)
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_builder_ip_validation() {
+        let key = keys::MockSigner(1);
+        let public4 = Ipv4Addr::new(8, 8, 8, 8);
+        let public6 = Ipv6Addr::new(0x2606, 0x4700, 0, 0, 0, 0, 0, 0x1111);
+
+        // permissive by default
+        let enr = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::LOCALHOST)
+            .ip6(Ipv6Addr::LOCALHOST)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.ip(), Some(Ipv4Addr::LOCALHOST));
+
+        let enr = EnrBuilder::new("v4")
+            .with_validation(true)
+            .ip4(public4)
+            .ip6(public6)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.ip(), Some(public4));
+        assert_eq!(enr.ip6(), Some(public6));
+
+        let private = Ipv4Addr::new(192, 168, 0, 1);
+        assert!(matches!(
+            EnrBuilder::new("v4")
+                .with_validation(true)
+                .ip4(private)
+                .ip6(public6)
+                .build(&key),
+            Err(EnrError::NonPublicAddress(ip, AddressRange::Private)) if ip == IpAddr::from(private)
+        ));
+        let link_local = Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1);
+        assert!(matches!(
+            EnrBuilder::new("v4")
+                .with_validation(true)
+                .ip4(public4)
+                .ip6(link_local)
+                .build(&key),
+            Err(EnrError::NonPublicAddress(ip, AddressRange::LinkLocal)) if ip == IpAddr::from(link_local)
+        ));
+
+        // the validation applies to addresses set before it is enabled, and to any setter
+        assert!(matches!(
+            EnrBuilder::new("v4")
+                .ip4(private)
+                .with_validation(true)
+                .build(&key),
+            Err(EnrError::NonPublicAddress(ip, AddressRange::Private)) if ip == IpAddr::from(private)
+        ));
+        assert!(matches!(
+            EnrBuilder::new("v4")
+                .add_value("ip6".into(), link_local.octets().to_vec())
+                .with_validation(true)
+                .build(&key),
+            Err(EnrError::NonPublicAddress(ip, AddressRange::LinkLocal)) if ip == IpAddr::from(link_local)
+        ));
+        // and is lifted if disabled again
+        assert!(EnrBuilder::new("v4")
+            .with_validation(true)
+            .ip4(private)
+            .with_validation(false)
+            .build(&key)
+            .is_ok());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_builder_ipv6_fields() {
+        // the EIP-778 example key, advertising `[2001:db8::1]:30303` over UDP and port 80 over TCP.
+        // The fixture was built by this crate, signing is deterministic so it pins the encoding
+        let key = secp256k1::SecretKey::parse_slice(
+            &hex::decode("b71c71a67e1177ad4e901695e1b4b9ee17ae16c6668d313eac2f96dbcda3f291")
+                .unwrap(),
+        )
+        .unwrap();
+        let fixture = "enr:-Ji4QOglLRLk8IWCcZIWWJxxWFZO_vZOm0g-fcp2hipkUpq1ADIWrdpAkNmvc2iAx9OpiRaWVHrmtRxuf4AYVY2t7oMBgmlkgnY0g2lwNpAgAQ24AAAAAAAAAAAAAAABiXNlY3AyNTZrMaEDymNMrg1JrLQB2KTGtv6MVbcNEVv0AHacwUAPMljNMTiEdGNwNlCEdWRwNoJ2Xw";
+        let ip6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+
+        let enr = EnrBuilder::new("v4")
+            .ip6(ip6)
+            .udp6(30303)
+            .tcp6(80)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.to_base64(), fixture);
+
+        // the entries as EIP-778 specifies them, a 16 byte address and ports as minimal big-endian
+        // integers
+        let decoded = fixture.parse::<DefaultEnr>().unwrap();
+        assert_eq!(
+            hex::encode(decoded.get_raw_rlp("ip6").unwrap()),
+            "9020010db8000000000000000000000001"
+        );
+        assert_eq!(hex::encode(decoded.get_raw_rlp("udp6").unwrap()), "82765f");
+        assert_eq!(hex::encode(decoded.get_raw_rlp("tcp6").unwrap()), "50");
+        assert_eq!(
+            decoded.udp6_socket(),
+            Some(SocketAddrV6::new(ip6, 30303, 0, 0))
+        );
+        assert_eq!(
+            decoded.tcp6_socket(),
+            Some(SocketAddrV6::new(ip6, 80, 0, 0))
+        );
+
+        // ports without an address
+        assert!(matches!(
+            EnrBuilder::new("v4").udp6(30303).build(&key),
+            Err(EnrError::OrphanPort("udp6"))
+        ));
+        assert!(matches!(
+            EnrBuilder::new("v4")
+                .ip4(Ipv4Addr::new(192, 0, 2, 1))
+                .tcp6(30303)
+                .build(&key),
+            Err(EnrError::OrphanPort("tcp6"))
+        ));
+        let enr = EnrBuilder::new("v4")
+            .udp6(30303)
+            .allow_orphan_ports(true)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.udp6(), Some(30303));
+        assert_eq!(enr.udp6_socket(), None);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_consensus_client_record() {
+        // a mainnet boot node of the Lighthouse team, as published in the `boot_enr.yaml` of
+        // Lighthouse
+        let text = "enr:-Le4QPUXJS2BTORXxyx2Ia-9ae4YqA_JWX3ssj4E_J-3z1A-HmFGrU8BpvpqhNabayXeOZ2Nq_sbeDgtzMJpLLnXFgAChGV0aDKQtTA_KgEAAAAAIgEAAAAAAIJpZIJ2NIJpcISsaa0Zg2lwNpAkAIkHAAAAAPA8kv_-awoTiXNlY3AyNTZrMaEDHAD2JKYevx89W0CcFJFiskdcEzkH_Wdv9iW42qLK79ODdWRwgiMohHVkcDaCI4I";
+        let enr = text.parse::<DefaultEnr>().unwrap();
+        assert!(enr.verify());
+        assert_eq!(enr.seq(), 2);
+        let ip = Ipv4Addr::new(172, 105, 173, 25);
+        let ip6 = Ipv6Addr::new(0x2400, 0x8907, 0, 0, 0xf03c, 0x92ff, 0xfe6b, 0x0a13);
+        assert_eq!(enr.udp4_socket(), Some(SocketAddrV4::new(ip, 9000)));
+        assert_eq!(enr.udp6_socket(), Some(SocketAddrV6::new(ip6, 9090, 0, 0)));
+        assert_eq!(enr.tcp4_socket(), None);
+        assert_eq!(enr.quic(), None);
+        assert_eq!(enr.quic_socket(), None);
+        // the phase0 fork digest, with the Altair fork scheduled at epoch 74240
+        assert_eq!(
+            hex::encode(enr.get("eth2").unwrap()),
+            "b5303f2a010000000022010000000000"
+        );
+        assert_eq!(enr.to_base64(), text);
+
+        // no genuine record advertising QUIC is at hand, the accessors are checked on a record
+        // built with a fixed key
+        let key = secp256k1::SecretKey::parse_slice(&[0x42; 32]).unwrap();
+        let enr = EnrBuilder::new("v4")
+            .ip4(ip)
+            .tcp(9000)
+            .udp(9000)
+            .quic(9001)
+            .build(&key)
+            .unwrap();
+        let decoded = enr.to_base64().parse::<DefaultEnr>().unwrap();
+        assert_eq!(decoded.quic(), Some(9001));
+        assert_eq!(decoded.quic_socket(), Some(SocketAddrV4::new(ip, 9001)));
+        assert_eq!(decoded.quic6(), None);
+        assert_eq!(decoded.quic6_socket(), None);
+        assert_eq!(decoded.tcp4_socket(), Some(SocketAddrV4::new(ip, 9000)));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_builder_public_key_field() {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let other = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let other_public = other.public().encode();
+
+        assert!(matches!(
+            EnrBuilder::new("v4")
+                .add_value("secp256k1".into(), other_public.clone())
+                .build(&key),
+            Err(EnrError::ReservedKey(ref k)) if k == "secp256k1"
+        ));
+
+        // the manual public key is kept, and does not match the signature
+        let enr = EnrBuilder::new("v4")
+            .override_reserved()
+            .add_value("secp256k1".into(), other_public)
+            .build(&key)
+            .unwrap();
+        assert_eq!(enr.public_key(), other.public());
+        assert_eq!(
+            enr.node_id(),
+            EnrBuilder::new("v4").build(&other).unwrap().node_id()
+        );
+        assert!(!enr.verify());
+        assert!(DefaultEnr::from_str(&enr.to_base64()).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_signature_malleability() {
+        let record = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+        let enr = record.parse::<DefaultEnr>().unwrap();
+        // the EIP-778 signature, and the same signature with s negated modulo the curve order
+        let low_s = hex::decode("7098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c").unwrap();
+        let high_s = hex::decode("7098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b890d9ca0b1dcb8c70cf7ec576146ec807c70e7c0410e8129e05f719bbbd975a5").unwrap();
+        assert_eq!(enr.signature(), &low_s[..]);
+
+        let content = enr.signed_content();
+        let public_key = enr.public_key();
+        assert!(public_key.verify_v4(&content, &low_s));
+        assert!(!public_key.verify_v4(&content, &high_s));
+
+        #[cfg(feature = "rust-secp256k1")]
+        {
+            let public_key =
+                c_secp256k1::PublicKey::from_slice(&enr.public_key().encode()).unwrap();
+            assert!(public_key.verify_v4(&content, &low_s));
+            assert!(!public_key.verify_v4(&content, &high_s));
+        }
+
+        // the malleated record does not decode
+        let mut malleated = enr;
+        malleated.signature = high_s;
+        assert!(matches!(
+            DefaultEnr::from_str(&malleated.to_base64()),
+            Err(e) if e.contains("Invalid Signature")
+        ));
+
+        // signatures are always produced in low-s form
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        for i in 0..32_u8 {
+            let signature = key.sign_v4(&[i]).unwrap();
+            let signature = secp256k1::Signature::parse_slice(&signature).unwrap();
+            assert!(!signature.s.is_high());
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_clone_with_key() {
+        let mock = keys::MockSigner(1);
+        let source = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::new(192, 0, 2, 1))
+            .udp(30303)
+            .seq(7)
+            .build(&mock)
+            .unwrap();
+        let bytes = source.encode();
+
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let enr = source.clone_with_key(&key, None).unwrap();
+        assert_eq!(source.encode(), bytes);
+        assert_eq!(enr.seq(), 1);
+        assert_eq!(enr.id().as_deref(), Some("v4"));
+        assert_eq!(enr.ip(), source.ip());
+        assert_eq!(enr.udp(), Some(30303));
+        assert_eq!(enr.get_raw_rlp("mock"), None);
+        assert!(enr.verify_with(&key.public()));
+        assert_ne!(enr.node_id(), source.node_id());
+        assert_eq!(enr.node_id(), NodeId::from(key.public()));
+        assert_eq!(rlp::decode::<DefaultEnr>(&enr.encode()).unwrap(), enr);
+
+        let enr = source.clone_with_key(&key, Some(9)).unwrap();
+        assert_eq!(enr.seq(), 9);
+
+        // content that only fits the record with the smaller public key
+        let filler = vec![0; MAX_ENR_SIZE - source.encode().len() - 20];
+        let source = EnrBuilder::new("v4")
+            .add_value("filler".into(), filler)
+            .build(&mock)
+            .unwrap();
+        assert!(source.encode().len() <= MAX_ENR_SIZE);
+        assert!(matches!(
+            source.clone_with_key(&key, None),
+            Err(EnrError::ExceedsMaxSizeWithKey(size)) if size > MAX_ENR_SIZE
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_empty() {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let enr = DefaultEnr::empty(&key).unwrap();
+        assert_eq!(enr.seq(), 1);
+        assert_eq!(enr.id().as_deref(), Some("v4"));
+        assert_eq!(enr.iter().count(), 2);
+        assert!(enr.verify());
+        assert_eq!(enr.to_base64().parse::<DefaultEnr>().unwrap(), enr);
+        // the signature, sequence number, `id` and `secp256k1` pairs and the list header
+        assert_eq!(enr.encode().len(), 119);
+
+        let enr = DefaultEnr::builder().udp(30303).build(&key).unwrap();
+        assert_eq!(enr.id().as_deref(), Some("v4"));
+        assert_eq!(enr.udp(), Some(30303));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_most_recent() {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let first = EnrBuilder::new("v4").seq(1).build(&key).unwrap();
+        let second = EnrBuilder::new("v4").seq(2).build(&key).unwrap();
+        let third = EnrBuilder::new("v4").seq(3).build(&key).unwrap();
+
+        assert!(third.is_newer_than(&second));
+        assert!(!second.is_newer_than(&third));
+        assert!(!second.is_newer_than(&second));
+
+        let records = vec![second.clone(), third.clone(), first];
+        assert_eq!(most_recent(&records), Some(&third));
+        assert_eq!(most_recent(&records[..1]), Some(&second));
+        assert_eq!(most_recent::<secp256k1::SecretKey>(&[]), None);
+
+        // records of different nodes
+        let other_key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let other = EnrBuilder::new("v4").seq(4).build(&other_key).unwrap();
+        assert!(!other.is_newer_than(&third));
+        assert_eq!(most_recent(&[second, third, other]), None);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_signed_content_and_verify_with() {
+        // the EIP-778 test vector
+        let text = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+        let enr = text.parse::<DefaultEnr>().unwrap();
+
+        assert_eq!(
+            hex::encode(enr.signed_content()),
+            "f84201826964827634826970847f00000189736563703235366b31a103ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd31388375647082765f"
+        );
+        assert_eq!(
+            hex::encode(enr.signature()),
+            "7098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c"
+        );
+
+        assert!(enr.verify_with(&enr.public_key()));
+        let other = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        assert!(!enr.verify_with(&other.public()));
+    }
+
+    /// The fork id of the `eth` entry: a list of the fork hash and the next fork block.
+    #[derive(Debug, PartialEq)]
+    struct ForkId {
+        hash: Vec<u8>,
+        next: u64,
+    }
+
+    impl rlp::Encodable for ForkId {
+        fn rlp_append(&self, s: &mut RlpStream) {
+            s.begin_list(2);
+            s.append(&self.hash);
+            s.append(&self.next);
+        }
+    }
+
+    impl rlp::Decodable for ForkId {
+        fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+            Ok(Self {
+                hash: rlp.val_at(0)?,
+                next: rlp.val_at(1)?,
+            })
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_list_values() {
+        // a mainnet record with the list-valued `eth` entry of geth
+        let text = "enr:-Je4QH0uN2HkMRmscUp6yvyTOPGtOg9U6lCxBFvCGynyystnDNRJbfz5GhXXY2lcu9tsghMxRiYHoznBwG46GQ7dfm0og2V0aMfGhMvbiDiAgmlkgnY0gmlwhA6hJmuJc2VjcDI1NmsxoQJBP4kg9GNBurV3uVXgR72u1n-XIABibUZLT1WvJLKwvIN0Y3CCdyeDdWRwgncn";
+        let enr = text.parse::<DefaultEnr>().unwrap();
+        let fork_id = ForkId {
+            hash: hex::decode("cbdb8838").unwrap(),
+            next: 0,
+        };
+
+        assert_eq!(enr.seq(), 40);
+        assert_eq!(enr.ip(), Some(Ipv4Addr::new(14, 161, 38, 107)));
+        assert_eq!(
+            enr.get_raw_rlp("eth"),
+            Some(&hex::decode("c7c684cbdb883880").unwrap()[..])
+        );
+        assert_eq!(enr.get_list::<ForkId>("eth"), Some(Ok(vec![fork_id])));
+        assert_eq!(enr.get_list::<ForkId>("missing"), None);
+        assert_eq!(
+            enr.get_list::<u16>("udp"),
+            Some(Err(DecoderError::RlpExpectedToBeList))
+        );
+        assert_eq!(enr.to_base64(), text);
+
+        // the list is embedded in the signed content exactly once
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let mut built = EnrBuilder::new("v4").build(&key).unwrap();
+        let fork_ids = enr.get_list::<ForkId>("eth").unwrap().unwrap();
+        built.insert_list("eth", &fork_ids, &key).unwrap();
+        assert_eq!(built.get_raw_rlp("eth"), enr.get_raw_rlp("eth"));
+        let signed_content = built.signed_content();
+        let signed = Rlp::new(&signed_content);
+        assert_eq!(signed.at(1).unwrap().as_raw(), b"\x83eth");
+        assert_eq!(
+            signed.at(2).unwrap().as_raw(),
+            enr.get_raw_rlp("eth").unwrap()
+        );
+
+        let decoded = rlp::decode::<DefaultEnr>(&built.encode()).unwrap();
+        assert_eq!(decoded.get_list::<ForkId>("eth"), Some(Ok(fork_ids)));
+
+        built.insert_list::<u16>("empty", &[], &key).unwrap();
+        assert_eq!(built.get_raw_rlp("empty"), Some(&[0xc0][..]));
+        assert_eq!(built.get_list::<u16>("empty"), Some(Ok(vec![])));
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_rotate_key_type() {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let enr = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::LOCALHOST)
+            .udp(30303)
+            .build(&key)
+            .unwrap();
+
+        let new_key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let (rotated, warnings) = enr.rotate(&new_key).unwrap();
+        assert!(warnings.is_empty());
+        assert!(rotated.verify());
+        assert_eq!(rotated.seq(), enr.seq() + 1);
+
+        let new_key = CombinedKey::generate_ed25519();
+        let (rotated, warnings) = enr.rotate(&new_key).unwrap();
+        assert_eq!(
+            warnings,
+            vec![EnrWarning::PublicKeyTypeChanged {
+                previous: "secp256k1".into(),
+                new: "ed25519".into(),
+            }]
+        );
+        assert!(rotated.verify());
+        assert_eq!(rotated.id(), Some("v4".into()));
+        assert_eq!(rotated.get("secp256k1"), None);
+        assert_eq!(rotated.public_key(), new_key.public());
+        assert_eq!(rotated.udp4_socket(), enr.udp4_socket());
+        // the rotated record is read by peers like any other record
+        let decoded = rotated.to_base64().parse::<Enr<CombinedKey>>().unwrap();
+        assert_eq!(decoded, rotated);
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_key_type_conversion() {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let enr = EnrBuilder::new("v4").udp(30303).build(&key).unwrap();
+
+        let combined = enr.clone().into_combined();
+        assert!(combined.verify());
+        assert_eq!(combined.node_id(), enr.node_id());
+        assert_eq!(combined.encode(), enr.encode());
+        assert_eq!(
+            combined.clone().try_map_key::<CombinedKey>().unwrap(),
+            combined
+        );
+
+        let back = combined.try_map_key::<secp256k1::SecretKey>().unwrap();
+        assert!(back.verify());
+        assert_eq!(back.encode(), enr.encode());
+        assert_eq!(back, enr);
+
+        // an ed25519 record cannot be read as a secp256k1 record
+        let key = CombinedKey::generate_ed25519();
+        let enr = EnrBuilder::new("v4").udp(30303).build(&key).unwrap();
+        let rejected = enr
+            .clone()
+            .try_map_key::<secp256k1::SecretKey>()
+            .unwrap_err();
+        assert_eq!(rejected, enr);
+        let keypair = enr.try_map_key::<ed25519_dalek::Keypair>().unwrap();
+        assert!(keypair.verify());
+        assert_eq!(keypair.into_combined().udp(), Some(30303));
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_combined_key_comparison_and_fingerprint() {
+        for key in &[
+            CombinedKey::generate_secp256k1(),
+            CombinedKey::generate_ed25519(),
+        ] {
+            let mut bytes = key.encode();
+            let copy = match key {
+                CombinedKey::Secp256k1(_) => CombinedKey::secp256k1_from_bytes(&mut bytes),
+                CombinedKey::Ed25519(_) => CombinedKey::ed25519_from_bytes(&mut bytes),
+            }
+            .unwrap();
+            assert!(key == &copy);
+
+            let enr = EnrBuilder::new("v4").build(key).unwrap();
+            assert_eq!(key.public_key_fingerprint(), enr.node_id().raw());
+        }
+
+        assert!(CombinedKey::generate_secp256k1() != CombinedKey::generate_secp256k1());
+        assert!(CombinedKey::generate_ed25519() != CombinedKey::generate_secp256k1());
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_combined_public_key_from_compressed() {
+        for key in &[
+            CombinedKey::generate_secp256k1(),
+            CombinedKey::generate_ed25519(),
+        ] {
+            let public_key = key.public();
+            let bytes = public_key.encode();
+            assert_eq!(
+                CombinedPublicKey::from_compressed(&bytes).unwrap(),
+                public_key
+            );
+        }
+
+        let secp256k1 = CombinedKey::generate_secp256k1().public().encode();
+        let ed25519 = CombinedKey::generate_ed25519().public().encode();
+        assert!(CombinedPublicKey::from_compressed_secp256k1(&secp256k1).is_ok());
+        assert!(CombinedPublicKey::from_compressed_ed25519(&ed25519).is_ok());
+        assert!(CombinedPublicKey::from_compressed_secp256k1(&ed25519).is_err());
+        assert!(CombinedPublicKey::from_compressed_ed25519(&secp256k1).is_err());
+
+        // an uncompressed secp256k1 key, and a 33 byte string that is not a curve point
+        let uncompressed = CombinedKey::generate_secp256k1()
+            .public()
+            .encode_uncompressed();
+        assert!(matches!(
+            CombinedPublicKey::from_compressed(&uncompressed),
+            Err(EnrError::InvalidPublicKey(_))
+        ));
+        assert!(matches!(
+            CombinedPublicKey::from_compressed(&[0x05; 33]),
+            Err(EnrError::InvalidPublicKey("Invalid secp256k1 public key"))
+        ));
+        assert!(CombinedPublicKey::from_compressed(&[]).is_err());
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn combined_key_can_decode_all() {
+        // generate a random secp256k1 key
+        let mut rng = rand::thread_rng();
+        let key = secp256k1::SecretKey::random(&mut rng);
+        let ip = Ipv4Addr::new(192, 168, 0, 1);
+        let enr_secp256k1 = EnrBuilder::new("v4").ip4(ip).tcp(8000).build(&key).unwrap();
+
+        // encode to base64
+        let base64_string_secp256k1 = enr_secp256k1.to_base64();
+
+        // generate a random ed25519 key
+        let key = ed25519_dalek::Keypair::generate(&mut rng);
+        let enr_ed25519 = EnrBuilder::new("v4").ip4(ip).tcp(8000).build(&key).unwrap();
 
         // encode to base64
         let base64_string_ed25519 = enr_ed25519.to_base64();
@@ -0,0 +1,219 @@
+//! Lists of records in a single RLP list, as delivered by wire protocols such as the discv5
+//! `NODES` response.
+
+use crate::{Enr, EnrKey};
+use alloc::vec::Vec;
+use bytes::Bytes;
+use core::fmt;
+use rlp::{DecoderError, Rlp, RlpStream};
+
+/// An error decoding a list of records with [`Enr::decode_list`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeListError {
+    /// The input is not a single, well-formed RLP list, or has trailing bytes.
+    InvalidList(DecoderError),
+
+    /// A record of the list is invalid.
+    InvalidRecord {
+        /// The index of the record in the list.
+        index: usize,
+        /// The reason the record is invalid.
+        error: DecoderError,
+    },
+}
+
+impl fmt::Display for DecodeListError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidList(error) => write!(f, "invalid list of records: {error}"),
+            Self::InvalidRecord { index, error } => {
+                write!(f, "invalid record at index {index} of the list: {error}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for DecodeListError {}
+
+impl<K: EnrKey> Enr<K> {
+    /// RLP-encodes the records as a single list, the inverse of [`Enr::decode_list`].
+    #[must_use]
+    pub fn encode_list(records: &[Self]) -> Vec<u8> {
+        let mut s = RlpStream::new_list(records.len());
+        for enr in records {
+            s.append(enr);
+        }
+        s.out().into()
+    }
+
+    /// Decodes a single RLP list of records, verifying their signatures. The values of the records
+    /// share a single copy of `bytes`.
+    ///
+    /// # Errors
+    /// Fails with [`DecodeListError::InvalidList`] if `bytes` is not a single list, and with
+    /// [`DecodeListError::InvalidRecord`] on the first invalid record. See
+    /// [`Enr::decode_list_lenient`] to keep the valid records of a list.
+    pub fn decode_list(bytes: &[u8]) -> Result<Vec<Self>, DecodeListError> {
+        let buffer = Bytes::copy_from_slice(bytes);
+        list_items(&buffer)?
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                Self::decode_rlp(item, &buffer, false)
+                    .map_err(|error| DecodeListError::InvalidRecord { index, error })
+            })
+            .collect()
+    }
+
+    /// Decodes a single RLP list of records like [`Enr::decode_list`], skipping invalid records.
+    /// Returns the valid records, in order, and the index of each invalid record in the list with
+    /// the reason it is invalid.
+    ///
+    /// # Errors
+    /// Fails with [`DecodeListError::InvalidList`] if `bytes` is not a single list.
+    #[allow(clippy::type_complexity)]
+    pub fn decode_list_lenient(
+        bytes: &[u8],
+    ) -> Result<(Vec<Self>, Vec<(usize, DecoderError)>), DecodeListError> {
+        let buffer = Bytes::copy_from_slice(bytes);
+        let mut records = Vec::new();
+        let mut failures = Vec::new();
+        for (index, item) in list_items(&buffer)?.iter().enumerate() {
+            match Self::decode_rlp(item, &buffer, false) {
+                Ok(enr) => records.push(enr),
+                Err(error) => failures.push((index, error)),
+            }
+        }
+        Ok((records, failures))
+    }
+}
+
+/// The items of the single RLP list in `buffer`. The items must account for all of `buffer`.
+fn list_items(buffer: &[u8]) -> Result<Vec<Rlp<'_>>, DecodeListError> {
+    let rlp = Rlp::new(buffer);
+    if !rlp.is_list() {
+        return Err(DecodeListError::InvalidList(
+            DecoderError::RlpExpectedToBeList,
+        ));
+    }
+    let info = rlp.payload_info().map_err(DecodeListError::InvalidList)?;
+    if info.total() != buffer.len() {
+        return Err(DecodeListError::InvalidList(
+            DecoderError::RlpInconsistentLengthAndData,
+        ));
+    }
+    let items: Vec<_> = rlp.iter().collect();
+    // a malformed item ends the iteration early
+    if items.iter().map(|item| item.as_raw().len()).sum::<usize>() != info.value_len {
+        return Err(DecodeListError::InvalidList(
+            DecoderError::RlpInconsistentLengthAndData,
+        ));
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder};
+
+    fn records(n: u8) -> Vec<Enr<MockSigner>> {
+        (1..=n)
+            .map(|i| {
+                EnrBuilder::new("v4")
+                    .udp(9000 + u16::from(i))
+                    .build(&MockSigner(i))
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_list_roundtrip() {
+        let enrs = records(3);
+        let encoded = Enr::encode_list(&enrs);
+        assert_eq!(Enr::decode_list(&encoded).unwrap(), enrs);
+        assert_eq!(
+            Enr::decode_list_lenient(&encoded).unwrap(),
+            (enrs, Vec::new())
+        );
+
+        // an empty list
+        let empty = Enr::<MockSigner>::encode_list(&[]);
+        assert_eq!(empty, [0xc0]);
+        assert_eq!(Enr::<MockSigner>::decode_list(&empty).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_list_corrupt_record() {
+        let enrs = records(3);
+        // replace the middle record by a well-framed list that is not a record
+        let mut s = RlpStream::new_list(3);
+        s.append(&enrs[0]);
+        s.begin_list(1).append(&"not a record");
+        s.append(&enrs[2]);
+        let encoded = s.out().to_vec();
+
+        assert_eq!(
+            Enr::<MockSigner>::decode_list(&encoded),
+            Err(DecodeListError::InvalidRecord {
+                index: 1,
+                error: DecoderError::Custom("List not a multiple of two"),
+            })
+        );
+        let (valid, failures) = Enr::<MockSigner>::decode_list_lenient(&encoded).unwrap();
+        assert_eq!(valid, [enrs[0].clone(), enrs[2].clone()]);
+        assert_eq!(
+            failures,
+            [(1, DecoderError::Custom("List not a multiple of two"))]
+        );
+    }
+
+    #[test]
+    fn test_list_invalid() {
+        let enrs = records(2);
+        let mut encoded = Enr::encode_list(&enrs);
+        encoded.push(0x80);
+        assert_eq!(
+            Enr::<MockSigner>::decode_list(&encoded),
+            Err(DecodeListError::InvalidList(
+                DecoderError::RlpInconsistentLengthAndData
+            ))
+        );
+        assert!(Enr::<MockSigner>::decode_list_lenient(&encoded).is_err());
+
+        // a single record is not a list of records
+        assert!(matches!(
+            Enr::<MockSigner>::decode_list(&enrs[0].encode()),
+            Err(DecodeListError::InvalidRecord { index: 0, .. })
+        ));
+        assert_eq!(
+            Enr::<MockSigner>::decode_list(&[0x80]),
+            Err(DecodeListError::InvalidList(
+                DecoderError::RlpExpectedToBeList
+            ))
+        );
+        assert!(Enr::<MockSigner>::decode_list(&[]).is_err());
+
+        // records of unknown schemes are invalid
+        let forged = crate::forge_unknown_scheme(&enrs[1]);
+        let encoded = Enr::encode_list(&[enrs[0].clone(), forged]);
+        assert_eq!(
+            Enr::<MockSigner>::decode_list(&encoded),
+            Err(DecodeListError::InvalidRecord {
+                index: 1,
+                error: DecoderError::Custom("Unsupported identity scheme"),
+            })
+        );
+        let (valid, failures) = Enr::<MockSigner>::decode_list_lenient(&encoded).unwrap();
+        assert_eq!(valid, [enrs[0].clone()]);
+        assert_eq!(failures.len(), 1);
+
+        // the list is shorter than its header claims
+        let truncated = &Enr::encode_list(&enrs)[..50];
+        assert!(matches!(
+            Enr::<MockSigner>::decode_list(truncated),
+            Err(DecodeListError::InvalidList(_))
+        ));
+    }
+}
@@ -0,0 +1,180 @@
+//! Merkle inclusion proofs of records in a list of records. Enabled by the `ed25519` feature.
+//!
+//! The leaves are the keccak256 hashes of the text forms of the records, as hashed by
+//! [EIP-1459](https://eips.ethereum.org/EIPS/eip-1459). The tree itself is binary, each node
+//! hashing the concatenation of its two children in ascending order, so a proof is the list of
+//! sibling hashes from the leaf to the root. A node without a sibling is carried up unchanged.
+//!
+//! The root is not the `e=` root of an EIP-1459 tree, whose branches hold up to 13 truncated
+//! hashes in a text entry, see [`DnsTree`](crate::dns::DnsTree). A root has to be distributed,
+//! or signed, on its own.
+
+use crate::{digest, CombinedKey, Enr};
+use alloc::{vec, vec::Vec};
+
+/// A binary Merkle tree over a list of records.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleTree {
+    /// The levels of the tree, from the leaves to the root.
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+/// A proof that a record is a leaf of a [`MerkleTree`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// The root of the tree the proof belongs to.
+    pub root_hash: [u8; 32],
+    /// The sibling hashes from the leaf to the root.
+    pub path: Vec<[u8; 32]>,
+}
+
+/// The leaf hash of a record, the keccak256 hash of its text form.
+fn leaf(record: &Enr<CombinedKey>) -> [u8; 32] {
+    digest(record.to_base64().as_bytes())
+}
+
+/// The hash of an inner node, of its children in ascending order.
+fn node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    let mut children = [0_u8; 64];
+    children[..32].copy_from_slice(low);
+    children[32..].copy_from_slice(high);
+    digest(&children)
+}
+
+impl MerkleTree {
+    /// Builds the tree of the records, in the given order.
+    #[must_use]
+    pub fn build(records: &[Enr<CombinedKey>]) -> Self {
+        let mut levels = vec![records.iter().map(leaf).collect::<Vec<_>>()];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let parents = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => node(a, b),
+                    [a] => *a,
+                    _ => unreachable!("chunks of 2"),
+                })
+                .collect();
+            levels.push(parents);
+        }
+        Self { levels }
+    }
+
+    /// The root hash of the tree, or `None` if it has no records.
+    #[must_use]
+    pub fn root(&self) -> Option<[u8; 32]> {
+        self.levels.last()?.first().copied()
+    }
+
+    /// The number of records in the tree.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Returns `true` if the tree has no records.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// Returns the inclusion proof of a record, or `None` if the record is not in the tree.
+    #[must_use]
+    pub fn proof(&self, record: &Enr<CombinedKey>) -> Option<MerkleProof> {
+        let leaf = leaf(record);
+        let mut index = self.levels[0].iter().position(|hash| *hash == leaf)?;
+        let mut path = Vec::with_capacity(self.levels.len());
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                path.push(*sibling);
+            }
+            index /= 2;
+        }
+        Some(MerkleProof {
+            root_hash: self.root()?,
+            path,
+        })
+    }
+}
+
+impl MerkleProof {
+    /// Verifies that the record is a leaf of the tree with the given root. Both the root of the
+    /// proof and the path have to match it.
+    #[must_use]
+    pub fn verify(&self, record: &Enr<CombinedKey>, root: &[u8; 32]) -> bool {
+        let computed = self
+            .path
+            .iter()
+            .fold(leaf(record), |hash, sibling| node(&hash, sibling));
+        self.root_hash == *root && computed == *root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EnrBuilder;
+
+    fn records(n: u16) -> Vec<Enr<CombinedKey>> {
+        (0..n)
+            .map(|i| {
+                let key = if i % 2 == 0 {
+                    CombinedKey::generate_secp256k1()
+                } else {
+                    CombinedKey::generate_ed25519()
+                };
+                EnrBuilder::new("v4").udp(30303 + i).build(&key).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_merkle_proofs() {
+        let records = records(13);
+        for n in 1..=records.len() {
+            let tree = MerkleTree::build(&records[..n]);
+            assert_eq!(tree.len(), n);
+            let root = tree.root().unwrap();
+            for record in &records[..n] {
+                let proof = tree.proof(record).unwrap();
+                assert_eq!(proof.root_hash, root);
+                assert!(proof.verify(record, &root), "{} of {}", record, n);
+            }
+            // records outside of the tree have no proof, and do not verify with another's
+            if let Some(outside) = records.get(n) {
+                assert_eq!(tree.proof(outside), None);
+                assert!(!tree.proof(&records[0]).unwrap().verify(outside, &root));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_merkle_proof_tampering() {
+        let records = records(5);
+        let tree = MerkleTree::build(&records);
+        let root = tree.root().unwrap();
+        let proof = tree.proof(&records[2]).unwrap();
+
+        // the leaf of a single record is the root
+        let single = MerkleTree::build(&records[..1]);
+        assert_eq!(single.root(), Some(leaf(&records[0])));
+        assert!(single.proof(&records[0]).unwrap().path.is_empty());
+
+        let mut wrong_sibling = proof.clone();
+        wrong_sibling.path[0][0] ^= 1;
+        assert!(!wrong_sibling.verify(&records[2], &root));
+
+        let mut wrong_root = proof.clone();
+        wrong_root.root_hash[0] ^= 1;
+        assert!(!wrong_root.verify(&records[2], &root));
+        assert!(!proof.verify(&records[2], &wrong_root.root_hash));
+
+        let empty = MerkleTree::build(&[]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.root(), None);
+        assert_eq!(empty.proof(&records[0]), None);
+    }
+}
@@ -0,0 +1,199 @@
+//! An ENR record paired with arbitrary, application-specific metadata.
+//!
+//! Node tables often need to track per-node state, such as the number of successful contacts,
+//! alongside the record of a node. [`EnrWithMeta`] stores both together while still giving
+//! direct access to the record through `Deref`.
+
+use crate::{Enr, EnrKey};
+use core::ops::Deref;
+
+/// An ENR record paired with metadata of type `M`.
+pub struct EnrWithMeta<K: EnrKey, M> {
+    /// The record.
+    enr: Enr<K>,
+
+    /// The metadata associated with the record.
+    meta: M,
+}
+
+/// An ENR record annotated with its origin.
+///
+/// The annotation is typically the `PeerId` or `SocketAddr` of the peer the record was received
+/// from. It is kept through clones, serde round-trips and [`EnrSet`](crate::EnrSet) insertions.
+pub type AnnotatedEnr<K, A> = EnrWithMeta<K, A>;
+
+impl<K: EnrKey, M> EnrWithMeta<K, M> {
+    /// Pairs a record with its metadata.
+    pub const fn new(enr: Enr<K>, meta: M) -> Self {
+        Self { enr, meta }
+    }
+
+    /// The record.
+    #[must_use]
+    pub const fn enr(&self) -> &Enr<K> {
+        &self.enr
+    }
+
+    /// The metadata associated with the record.
+    #[must_use]
+    pub const fn meta(&self) -> &M {
+        &self.meta
+    }
+
+    /// A mutable reference to the metadata associated with the record.
+    pub const fn meta_mut(&mut self) -> &mut M {
+        &mut self.meta
+    }
+
+    /// Replaces the record, keeping the metadata. Returns the previous record.
+    pub const fn replace_enr(&mut self, enr: Enr<K>) -> Enr<K> {
+        core::mem::replace(&mut self.enr, enr)
+    }
+
+    /// Splits into the record and its metadata.
+    pub fn into_parts(self) -> (Enr<K>, M) {
+        (self.enr, self.meta)
+    }
+}
+
+impl<K: EnrKey, M> Deref for EnrWithMeta<K, M> {
+    type Target = Enr<K>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.enr
+    }
+}
+
+impl<K: EnrKey, M> AsRef<Enr<K>> for EnrWithMeta<K, M> {
+    fn as_ref(&self) -> &Enr<K> {
+        &self.enr
+    }
+}
+
+impl<K: EnrKey, M: Clone> Clone for EnrWithMeta<K, M> {
+    fn clone(&self) -> Self {
+        Self {
+            enr: self.enr.clone(),
+            meta: self.meta.clone(),
+        }
+    }
+}
+
+impl<K: EnrKey, M: PartialEq> PartialEq for EnrWithMeta<K, M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.enr == other.enr && self.meta == other.meta
+    }
+}
+
+impl<K: EnrKey, M: core::fmt::Debug> core::fmt::Debug for EnrWithMeta<K, M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("EnrWithMeta")
+            .field("enr", &self.enr)
+            .field("meta", &self.meta)
+            .finish()
+    }
+}
+
+/// Serializes as a struct of the base64 encoded record, `enr`, and the metadata, `meta`.
+#[cfg(feature = "serde")]
+impl<K: EnrKey, M: serde::Serialize> serde::Serialize for EnrWithMeta<K, M> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("EnrWithMeta", 2)?;
+        state.serialize_field("enr", &self.enr)?;
+        state.serialize_field("meta", &self.meta)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: EnrKey, M: serde::Deserialize<'de>> serde::Deserialize<'de> for EnrWithMeta<K, M> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use core::marker::PhantomData;
+        use serde::de::{self, MapAccess, SeqAccess, Visitor};
+
+        const FIELDS: &[&str] = &["enr", "meta"];
+
+        struct EnrWithMetaVisitor<K, M>(PhantomData<(K, M)>);
+
+        impl<'de, K: EnrKey, M: serde::Deserialize<'de>> Visitor<'de> for EnrWithMetaVisitor<K, M> {
+            type Value = EnrWithMeta<K, M>;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                write!(f, "a struct with enr and meta fields")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let enr = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let meta = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                Ok(EnrWithMeta::new(enr, meta))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut enr = None;
+                let mut meta = None;
+                while let Some(field) = map.next_key::<String>()? {
+                    match field.as_str() {
+                        "enr" => enr = Some(map.next_value()?),
+                        "meta" => meta = Some(map.next_value()?),
+                        _ => return Err(de::Error::unknown_field(&field, FIELDS)),
+                    }
+                }
+                let enr = enr.ok_or_else(|| de::Error::missing_field("enr"))?;
+                let meta = meta.ok_or_else(|| de::Error::missing_field("meta"))?;
+                Ok(EnrWithMeta::new(enr, meta))
+            }
+        }
+
+        deserializer.deserialize_struct("EnrWithMeta", FIELDS, EnrWithMetaVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "libsecp256k1")]
+mod tests {
+    use super::*;
+    use crate::{secp256k1, EnrBuilder};
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_enr_with_meta() {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let enr = EnrBuilder::new("v4").udp(30303).build(&key).unwrap();
+
+        let mut node = EnrWithMeta::new(enr.clone(), 0_u32);
+        *node.meta_mut() += 1;
+        assert_eq!(*node.meta(), 1);
+        // the record is accessible directly
+        assert_eq!(node.udp(), Some(30303));
+        assert_eq!(node.node_id(), enr.node_id());
+
+        let mut updated = enr.clone();
+        updated.set_udp(30304, &key).unwrap();
+        assert_eq!(node.replace_enr(updated.clone()), enr);
+        assert_eq!(node.clone().into_parts(), (updated, 1));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_enr_with_meta_serde() {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let enr = EnrBuilder::new("v4").udp(30303).build(&key).unwrap();
+        let node = EnrWithMeta::new(enr.clone(), vec![1_u8, 2]);
+
+        let json = serde_json::to_string(&node).unwrap();
+        assert_eq!(
+            json,
+            format!("{{\"enr\":\"{}\",\"meta\":[1,2]}}", enr.to_base64())
+        );
+        let decoded: EnrWithMeta<secp256k1::SecretKey, Vec<u8>> =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, node);
+    }
+}
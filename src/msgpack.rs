@@ -0,0 +1,99 @@
+//! `MessagePack` serialization of ENR records, provided by the `msgpack` feature.
+//!
+//! The default `serde` implementation of [`Enr`] serializes a record as its base64 text form in
+//! human-readable formats and as its RLP encoding in binary formats, so in `MessagePack` a record
+//! is stored as a `bin` value. The functions of this module always use the `bin` value, and also
+//! read arrays of bytes. Fields of other types can opt into them with
+//! `#[serde(with = "enr::msgpack")]`.
+//!
+//! ```rust
+//! use enr::{secp256k1::SecretKey, Enr, EnrBuilder};
+//!
+//! let key = SecretKey::random(&mut rand::thread_rng());
+//! let enr = EnrBuilder::new("v4").udp(30303).build(&key).unwrap();
+//!
+//! let bytes = enr.to_msgpack().unwrap();
+//! let decoded = Enr::<SecretKey>::from_msgpack(&bytes).unwrap();
+//! assert_eq!(decoded, enr);
+//! ```
+
+use crate::{BytesVisitor, Enr, EnrKey};
+use serde::{de, Deserializer, Serializer};
+
+/// Serializes a record as the bytes of its RLP encoding.
+pub fn serialize<K: EnrKey, S: Serializer>(enr: &Enr<K>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bytes(&enr.encode())
+}
+
+/// Deserializes a record from the bytes of its RLP encoding. The signature of the record is
+/// verified.
+pub fn deserialize<'de, K: EnrKey, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Enr<K>, D::Error> {
+    let bytes = deserializer.deserialize_byte_buf(BytesVisitor)?;
+    Enr::decode_bytes(&bytes.into()).map_err(|e| de::Error::custom(format!("Invalid ENR: {e:?}")))
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// Encodes the record as a `MessagePack` `bin` value holding its RLP encoding.
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        let mut buf = Vec::new();
+        serialize(self, &mut rmp_serde::Serializer::new(&mut buf))?;
+        Ok(buf)
+    }
+
+    /// Decodes a record from a `MessagePack` `bin` value holding its RLP encoding, verifying its
+    /// signature.
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Self, rmp_serde::decode::Error> {
+        deserialize(&mut rmp_serde::Deserializer::new(bytes))
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "ed25519")]
+mod tests {
+    use crate::{CombinedKey, CombinedPublicKey, Enr, EnrBuilder, EnrKey};
+    use std::net::Ipv4Addr;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_msgpack_roundtrip() {
+        for key in &[
+            CombinedKey::generate_secp256k1(),
+            CombinedKey::generate_ed25519(),
+        ] {
+            let enr = EnrBuilder::new("v4")
+                .ip4(Ipv4Addr::LOCALHOST)
+                .udp(30303)
+                .build(key)
+                .unwrap();
+
+            let bytes = enr.to_msgpack().unwrap();
+            // a `bin 8` value holding the RLP encoding
+            let rlp = enr.encode();
+            assert_eq!(bytes[0], 0xc4);
+            assert_eq!(usize::from(bytes[1]), rlp.len());
+            assert_eq!(&bytes[2..], &rlp[..]);
+
+            let decoded = Enr::<CombinedKey>::from_msgpack(&bytes).unwrap();
+            assert_eq!(decoded, enr);
+            assert_eq!(decoded.ip(), Some(Ipv4Addr::LOCALHOST));
+            assert_eq!(decoded.udp(), Some(30303));
+            assert!(decoded.verify());
+
+            // tampering with the record fails signature verification
+            let mut tampered = bytes.clone();
+            let last = tampered.len() - 1;
+            tampered[last] ^= 1;
+            assert!(Enr::<CombinedKey>::from_msgpack(&tampered).is_err());
+
+            // the public key is a map of its type and compressed bytes
+            let public = key.public();
+            let encoded = rmp_serde::to_vec_named(&public).unwrap();
+            assert_eq!(
+                rmp_serde::from_slice::<CombinedPublicKey>(&encoded).unwrap(),
+                public
+            );
+        }
+    }
+}
@@ -3,10 +3,12 @@
 
 use crate::{digest, keys::EnrPublicKey};
 use crate::{Enr, EnrKey};
+use alloc::string::String;
+use core::{fmt, str::FromStr};
 
 type RawNodeId = [u8; 32];
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 /// The `NodeId` of an ENR (a 32 byte identifier).
 pub struct NodeId {
     raw: RawNodeId,
@@ -26,24 +28,77 @@ impl NodeId {
         }
 
         let mut raw: RawNodeId = [0_u8; 32];
-        raw[..std::cmp::min(32, raw_input.len())].copy_from_slice(raw_input);
+        raw[..core::cmp::min(32, raw_input.len())].copy_from_slice(raw_input);
 
         Ok(Self { raw })
     }
 
-    /// Generates a random `NodeId`.
-    #[must_use]
-    pub fn random() -> Self {
-        Self {
-            raw: rand::random(),
-        }
-    }
-
     /// Returns a `RawNodeId` which is a 32 byte list.
     #[must_use]
     pub const fn raw(&self) -> RawNodeId {
         self.raw
     }
+
+    /// The 64 lower case hex characters of the node id, without a prefix. This is also its
+    /// `Display` form.
+    #[must_use]
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.raw)
+    }
+}
+
+/// An error parsing the hex form of a [`NodeId`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NodeIdParseError {
+    /// The input does not have 64 hex characters after the optional `0x` prefix. Holds the
+    /// number of characters.
+    InvalidLength(usize),
+    /// The input has a character that is not a hex digit, at the given index after the optional
+    /// `0x` prefix.
+    InvalidCharacter {
+        /// The invalid character.
+        character: char,
+        /// The index of the character.
+        index: usize,
+    },
+}
+
+impl fmt::Display for NodeIdParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidLength(len) => {
+                write!(f, "expected 64 hex characters for a node id, got {len}")
+            }
+            Self::InvalidCharacter { character, index } => {
+                write!(
+                    f,
+                    "invalid hex character {character:?} at index {index} of a node id"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for NodeIdParseError {}
+
+/// Parses the 64 hex characters of a node id, in either case, with an optional `0x` prefix.
+impl FromStr for NodeId {
+    type Err = NodeIdParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix("0x").unwrap_or(s);
+        let mut raw = [0_u8; 32];
+        match hex::decode_to_slice(hex, &mut raw) {
+            Ok(()) => Ok(Self { raw }),
+            Err(hex::FromHexError::InvalidHexCharacter { c, index }) => {
+                Err(NodeIdParseError::InvalidCharacter {
+                    character: c,
+                    index,
+                })
+            }
+            Err(_) => Err(NodeIdParseError::InvalidLength(hex.chars().count())),
+        }
+    }
 }
 
 impl<T: EnrPublicKey> From<T> for NodeId {
@@ -65,14 +120,91 @@ impl<T: EnrKey> From<&Enr<T>> for NodeId {
     }
 }
 
-impl std::fmt::Display for NodeId {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let hex_encode = hex::encode(self.raw);
-        write!(
-            f,
-            "0x{}..{}",
-            &hex_encode[0..4],
-            &hex_encode[hex_encode.len() - 4..]
-        )
+/// The 64 lower case hex characters of the node id, see [`NodeId::to_hex`].
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+/// The truncated form of the node id, `0xa448…17f7`.
+impl fmt::Debug for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hex = self.to_hex();
+        write!(f, "0x{}\u{2026}{}", &hex[..4], &hex[hex.len() - 4..])
+    }
+}
+
+/// The lower case hex form of the node id, prefixed with `0x` in the alternate form, `{:#x}`.
+impl fmt::LowerHex for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        f.write_str(&hex::encode(self.raw))
+    }
+}
+
+/// The upper case hex form of the node id, prefixed with `0x` in the alternate form, `{:#X}`.
+impl fmt::UpperHex for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        f.write_str(&hex::encode_upper(self.raw))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The node id of the EIP-778 example record.
+    const EIP_778_NODE_ID: &str =
+        "a448f24c6d18e575453db13171562b71999873db5b286df957af199ec94617f7";
+
+    #[test]
+    fn test_node_id_formatting() {
+        let node_id: NodeId = EIP_778_NODE_ID.parse().unwrap();
+        assert_eq!(node_id.to_string(), EIP_778_NODE_ID);
+        assert_eq!(node_id.to_hex(), EIP_778_NODE_ID);
+        assert_eq!(format!("{node_id:?}"), "0xa448\u{2026}17f7");
+        assert_eq!(format!("{node_id:x}"), EIP_778_NODE_ID);
+        assert_eq!(format!("{node_id:#x}"), format!("0x{EIP_778_NODE_ID}"));
+        assert_eq!(
+            format!("{node_id:#X}"),
+            format!("0x{}", EIP_778_NODE_ID.to_uppercase())
+        );
+
+        for _ in 0..10 {
+            let node_id = NodeId::random();
+            assert_eq!(node_id.to_string().parse(), Ok(node_id));
+            assert_eq!(format!("{node_id:#X}").parse(), Ok(node_id));
+        }
+    }
+
+    #[test]
+    fn test_node_id_parse_errors() {
+        assert_eq!(
+            "0xa448".parse::<NodeId>(),
+            Err(NodeIdParseError::InvalidLength(4))
+        );
+        assert_eq!(
+            format!("{EIP_778_NODE_ID}00").parse::<NodeId>(),
+            Err(NodeIdParseError::InvalidLength(66))
+        );
+        let invalid = format!("0x{}g", &EIP_778_NODE_ID[..63]);
+        let error = invalid.parse::<NodeId>().unwrap_err();
+        assert_eq!(
+            error,
+            NodeIdParseError::InvalidCharacter {
+                character: 'g',
+                index: 63
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "invalid hex character 'g' at index 63 of a node id"
+        );
     }
 }
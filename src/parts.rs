@@ -0,0 +1,164 @@
+//! Decomposition of a record into its parts, for persistence layers that store records in their
+//! own schema rather than as an encoded blob.
+
+use crate::{validate_raw_rlp, Enr, EnrDecodeOptions, EnrError, EnrKey};
+use alloc::vec::Vec;
+use rlp::{DecoderError, RlpStream};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The parts of a record, see [`Enr::into_parts`] and [`Enr::from_parts`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EnrParts {
+    /// The sequence number of the record.
+    pub seq: u64,
+
+    /// The signature of the record.
+    pub signature: Vec<u8>,
+
+    /// The key-value pairs of the record, sorted by key. The values are RLP-encoded.
+    pub pairs: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// Decomposes the record into its sequence number, signature and key-value pairs.
+    #[must_use]
+    pub fn into_parts(self) -> EnrParts {
+        EnrParts {
+            seq: self.seq,
+            signature: self.signature,
+            pairs: self
+                .content
+                .into_iter()
+                .map(|(key, value)| (key.into_bytes(), value.into()))
+                .collect(),
+        }
+    }
+
+    /// Rebuilds a record from its parts, see [`Enr::into_parts`].
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidRlpData`] if the keys are unsorted, duplicated or not UTF-8,
+    /// a value is not well-formed RLP or the signature does not verify, with
+    /// [`EnrError::UnsupportedIdentityScheme`] if the identity scheme is unknown, as such records
+    /// cannot be verified, and with [`EnrError::ExceedsMaxSize`] if the record is too large.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn from_parts(parts: EnrParts) -> Result<Self, EnrError> {
+        for pair in parts.pairs.windows(2) {
+            if pair[0].0 == pair[1].0 {
+                return Err(EnrError::InvalidRlpData(DecoderError::Custom(
+                    "Duplicate keys",
+                )));
+            }
+            if pair[0].0 > pair[1].0 {
+                return Err(EnrError::InvalidRlpData(DecoderError::Custom(
+                    "Unsorted keys",
+                )));
+            }
+        }
+
+        let mut stream = RlpStream::new_list(parts.pairs.len() * 2 + 2);
+        stream.append(&parts.signature);
+        stream.append(&parts.seq);
+        for (key, value) in &parts.pairs {
+            if core::str::from_utf8(key).is_err() {
+                return Err(EnrError::InvalidRlpData(DecoderError::Custom(
+                    "Invalid key",
+                )));
+            }
+            validate_raw_rlp(value, true)?;
+            stream.append(key);
+            stream.append_raw(value, 1);
+        }
+
+        Self::decode_with_options(&stream.out(), &EnrDecodeOptions::new())
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "libsecp256k1")]
+mod tests {
+    use super::*;
+    use crate::{secp256k1, EnrBuilder};
+    use std::net::Ipv4Addr;
+
+    type DefaultEnr = Enr<secp256k1::SecretKey>;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_parts() {
+        let key = secp256k1::SecretKey::random(&mut rand::thread_rng());
+        let enr = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::new(192, 0, 2, 1))
+            .udp(30303)
+            .seq(3)
+            .build(&key)
+            .unwrap();
+        let bytes = enr.encode();
+
+        let parts = enr.clone().into_parts();
+        assert_eq!(parts.seq, 3);
+        assert_eq!(parts.signature, enr.signature());
+        let keys: Vec<&[u8]> = parts.pairs.iter().map(|(k, _)| k.as_slice()).collect();
+        assert_eq!(keys, vec![&b"id"[..], b"ip", b"secp256k1", b"udp"]);
+
+        // through a simulated database
+        #[cfg(feature = "serde")]
+        let parts: EnrParts =
+            serde_json::from_str(&serde_json::to_string(&parts).unwrap()).unwrap();
+        let rebuilt = DefaultEnr::from_parts(parts.clone()).unwrap();
+        assert_eq!(rebuilt, enr);
+        assert_eq!(rebuilt.encode(), bytes);
+
+        let mut unsorted = parts.clone();
+        unsorted.pairs.swap(0, 1);
+        assert!(matches!(
+            DefaultEnr::from_parts(unsorted),
+            Err(EnrError::InvalidRlpData(DecoderError::Custom(
+                "Unsorted keys"
+            )))
+        ));
+
+        let mut duplicate = parts.clone();
+        duplicate.pairs.insert(1, duplicate.pairs[1].clone());
+        assert!(matches!(
+            DefaultEnr::from_parts(duplicate),
+            Err(EnrError::InvalidRlpData(DecoderError::Custom(
+                "Duplicate keys"
+            )))
+        ));
+
+        let mut malformed = parts.clone();
+        malformed.pairs[1].1 = vec![0x85, 1, 2];
+        assert!(matches!(
+            DefaultEnr::from_parts(malformed),
+            Err(EnrError::InvalidRlpData(_))
+        ));
+
+        let mut tampered = parts.clone();
+        tampered.seq = 4;
+        assert!(matches!(
+            DefaultEnr::from_parts(tampered),
+            Err(EnrError::InvalidRlpData(DecoderError::Custom(
+                "Invalid Signature"
+            )))
+        ));
+
+        let mut unknown = parts.clone();
+        unknown.pairs[0].1 = rlp::encode(&"v5").to_vec();
+        assert!(matches!(
+            DefaultEnr::from_parts(unknown),
+            Err(EnrError::UnsupportedIdentityScheme)
+        ));
+
+        let mut oversized = parts;
+        oversized
+            .pairs
+            .push((b"z".to_vec(), rlp::encode(&vec![0_u8; 300]).to_vec()));
+        assert!(matches!(
+            DefaultEnr::from_parts(oversized),
+            Err(EnrError::ExceedsMaxSize)
+        ));
+    }
+}
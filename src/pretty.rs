@@ -0,0 +1,214 @@
+//! Human-readable renderings of an ENR record for logging and debugging.
+
+use crate::{Enr, EnrKey, EnrPublicKey};
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::{
+    fmt::Write,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+};
+use rlp::Rlp;
+
+impl<K: EnrKey> Enr<K> {
+    /// A compact, single line summary of the record of the form
+    /// `NodeId(a448…17f7) seq=1 udp=127.0.0.1:30303 tcp6=[::1]:9000`, suitable for log lines.
+    ///
+    /// The node id is truncated to its first and last 4 hex characters. The `udp`, `tcp`, `udp6`
+    /// and `tcp6` endpoints are listed in that order, omitting any that are not present.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let node_id = hex::encode(self.node_id().raw());
+        let mut output = format!(
+            "NodeId({}\u{2026}{}) seq={}",
+            &node_id[..4],
+            &node_id[node_id.len() - 4..],
+            self.seq()
+        );
+        let endpoints = [
+            ("udp", self.ip().map(IpAddr::V4), self.udp()),
+            ("tcp", self.ip().map(IpAddr::V4), self.tcp()),
+            ("udp6", self.ip6().map(IpAddr::V6), self.udp6()),
+            ("tcp6", self.ip6().map(IpAddr::V6), self.tcp6()),
+        ];
+        for (name, ip, port) in &endpoints {
+            if let (Some(ip), Some(port)) = (ip, port) {
+                let _ = write!(output, " {}={}", name, SocketAddr::new(*ip, *port));
+            }
+        }
+        output
+    }
+
+    /// Renders the record in a human-readable, multi-line form. This is also the alternate
+    /// `Display` form of the record, `{:#}`.
+    ///
+    /// The node id, sequence number and key type are printed first, followed by every key-value
+    /// pair in key order. Known fields are decoded: `id` as a string, `ip`/`ip6` as addresses and
+    /// `tcp`/`udp`/`tcp6`/`udp6` as port numbers. All other fields, including the public key, are
+    /// printed as hex. Values that fail to decode are printed as `<invalid ...>` along with their
+    /// raw RLP encoding.
+    #[must_use]
+    pub fn pretty(&self) -> String {
+        let key_type = self.public_key().enr_key();
+        let mut output = format!(
+            "ENR\n  node_id: 0x{}\n  seq: {}\n  key_type: {}\n",
+            hex::encode(self.node_id().raw()),
+            self.seq(),
+            key_type
+        );
+        for (key, raw) in &self.content {
+            let _ = match pretty_value(key, &Rlp::new(raw)) {
+                Ok(value) => writeln!(output, "  {key}: {value}"),
+                Err(reason) => writeln!(
+                    output,
+                    "  {}: <invalid {}: 0x{}>",
+                    key,
+                    reason,
+                    hex::encode(raw)
+                ),
+            };
+        }
+        output
+    }
+}
+
+/// Decodes the value of a known field, falling back to hex. Lists are printed in their raw RLP
+/// encoding.
+fn pretty_value(key: &str, rlp: &Rlp) -> Result<String, &'static str> {
+    if rlp.is_list() {
+        return match key {
+            "id" | "ip" | "ip6" | "tcp" | "udp" | "tcp6" | "udp6" => Err("rlp list"),
+            _ => Ok(format!("0x{} (rlp list)", hex::encode(rlp.as_raw()))),
+        };
+    }
+    let value = rlp.data().map_err(|_| "rlp")?;
+    match key {
+        "id" => core::str::from_utf8(value)
+            .map(String::from)
+            .map_err(|_| "utf8"),
+        "ip" => {
+            let mut ip = [0_u8; 4];
+            if value.len() != ip.len() {
+                return Err("ip");
+            }
+            ip.copy_from_slice(value);
+            Ok(Ipv4Addr::from(ip).to_string())
+        }
+        "ip6" => {
+            let mut ip = [0_u8; 16];
+            if value.len() != ip.len() {
+                return Err("ip6");
+            }
+            ip.copy_from_slice(value);
+            Ok(Ipv6Addr::from(ip).to_string())
+        }
+        "tcp" | "udp" | "tcp6" | "udp6" => {
+            let mut port = [0_u8; 2];
+            if value.len() > port.len() {
+                return Err("port");
+            }
+            port[2 - value.len()..].copy_from_slice(value);
+            Ok(u16::from_be_bytes(port).to_string())
+        }
+        _ => Ok(format!("0x{}", hex::encode(value))),
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "libsecp256k1")]
+mod tests {
+    use crate::{secp256k1, Enr, EnrBuilder};
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_pretty() {
+        let key = secp256k1::SecretKey::parse_slice(
+            &hex::decode("b71c71a67e1177ad4e901695e1b4b9ee17ae16c6668d313eac2f96dbcda3f291")
+                .unwrap(),
+        )
+        .unwrap();
+        let mut enr: Enr<secp256k1::SecretKey> = EnrBuilder::new("v4")
+            .ip6(Ipv6Addr::LOCALHOST)
+            .tcp6(9000)
+            .udp(30303)
+            .add_value("eth2".into(), vec![0xde, 0xad, 0xbe, 0xef])
+            .build(&key)
+            .unwrap();
+        enr.insert_raw_rlp_list("list", vec![0xc2, 1, 2], &key)
+            .unwrap();
+        // malformed well-known values can only be received from peers
+        enr.content
+            .insert("ip".into(), rlp::encode(&vec![127_u8, 0, 1]).into());
+        enr.content.insert("tcp".into(), vec![0xc1, 1].into());
+
+        assert_eq!(
+            enr.pretty(),
+            "ENR
+  node_id: 0xa448f24c6d18e575453db13171562b71999873db5b286df957af199ec94617f7
+  seq: 2
+  key_type: secp256k1
+  eth2: 0xdeadbeef
+  id: v4
+  ip: <invalid ip: 0x837f0001>
+  ip6: ::1
+  list: 0xc20102 (rlp list)
+  secp256k1: 0x03ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd3138
+  tcp: <invalid rlp list: 0xc101>
+  tcp6: 9000
+  udp: 30303
+"
+        );
+        assert_eq!(format!("{enr:#}"), enr.pretty());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_summary() {
+        let key = secp256k1::SecretKey::parse_slice(
+            &hex::decode("b71c71a67e1177ad4e901695e1b4b9ee17ae16c6668d313eac2f96dbcda3f291")
+                .unwrap(),
+        )
+        .unwrap();
+        let ip4 = Ipv4Addr::new(1, 2, 3, 4);
+
+        // a port without an address is not an endpoint
+        let enr: Enr<secp256k1::SecretKey> = EnrBuilder::new("v4").udp(9000).build(&key).unwrap();
+        assert_eq!(enr.summary(), "NodeId(a448\u{2026}17f7) seq=1");
+
+        let enr: Enr<secp256k1::SecretKey> = EnrBuilder::new("v4")
+            .seq(7)
+            .ip4(ip4)
+            .udp(9000)
+            .tcp(9001)
+            .build(&key)
+            .unwrap();
+        assert_eq!(
+            enr.summary(),
+            "NodeId(a448\u{2026}17f7) seq=7 udp=1.2.3.4:9000 tcp=1.2.3.4:9001"
+        );
+
+        let enr: Enr<secp256k1::SecretKey> = EnrBuilder::new("v4")
+            .ip6(Ipv6Addr::LOCALHOST)
+            .tcp6(9001)
+            .build(&key)
+            .unwrap();
+        assert_eq!(
+            enr.summary(),
+            "NodeId(a448\u{2026}17f7) seq=1 tcp6=[::1]:9001"
+        );
+
+        let enr: Enr<secp256k1::SecretKey> = EnrBuilder::new("v4")
+            .ip4(ip4)
+            .ip6(Ipv6Addr::LOCALHOST)
+            .udp(9000)
+            .tcp6(9001)
+            .build(&key)
+            .unwrap();
+        assert_eq!(
+            enr.summary(),
+            "NodeId(a448\u{2026}17f7) seq=1 udp=1.2.3.4:9000 tcp6=[::1]:9001"
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Fields of networks built on the Ethereum node discovery, such as rollups and sidechains, that
+//! share a common shape: an RLP list of a chain id, a version and any number of further items.
+//!
+//! Further items are kept as their raw RLP encoding, so a record is re-signed without losing
+//! items this crate does not understand.
+
+use crate::{Enr, EnrError, EnrKey};
+use alloc::vec::Vec;
+use rlp::{DecoderError, Rlp, RlpStream};
+
+/// The decoded value of a sub-protocol field, `[chain_id, version, extra...]`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ProtocolField {
+    /// The chain id of the network.
+    pub chain_id: u64,
+    /// The version of the network's protocol.
+    pub version: u64,
+    /// The raw RLP encoding of each item after the version.
+    pub extra: Vec<Vec<u8>>,
+}
+
+impl rlp::Encodable for ProtocolField {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2 + self.extra.len());
+        s.append(&self.chain_id);
+        s.append(&self.version);
+        for item in &self.extra {
+            s.append_raw(item, 1);
+        }
+    }
+}
+
+impl rlp::Decodable for ProtocolField {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        if !rlp.is_list() {
+            return Err(DecoderError::RlpExpectedToBeList);
+        }
+        if rlp.item_count()? < 2 {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+        Ok(Self {
+            chain_id: rlp.val_at(0)?,
+            version: rlp.val_at(1)?,
+            extra: rlp
+                .iter()
+                .skip(2)
+                .map(|item| item.as_raw().to_vec())
+                .collect(),
+        })
+    }
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// Reads a sub-protocol field.
+    ///
+    /// Returns `None` if the field is not set, and an error if it is not a list starting with
+    /// two integers.
+    #[must_use]
+    pub fn get_protocol_field(&self, key: &str) -> Option<Result<ProtocolField, DecoderError>> {
+        self.get_raw_rlp(key).map(|raw| Rlp::new(raw).as_val())
+    }
+
+    /// Sets a sub-protocol field. Returns the previous value, if any was well-formed.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidRlpData`] if an extra item is not a single well-formed RLP
+    /// item.
+    pub fn set_protocol_field(
+        &mut self,
+        key: &str,
+        value: &ProtocolField,
+        enr_key: &K,
+    ) -> Result<Option<ProtocolField>, EnrError> {
+        let previous = self.get_protocol_field(key).and_then(Result::ok);
+        self.insert_raw_rlp_list(key, rlp::encode(value).into(), enr_key)?;
+        Ok(previous)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder};
+
+    #[test]
+    fn test_protocol_field() {
+        let key = MockSigner(1);
+        let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+        assert_eq!(enr.get_protocol_field("opstack"), None);
+
+        let field = ProtocolField {
+            chain_id: 10,
+            version: 0,
+            extra: vec![],
+        };
+        assert_eq!(
+            enr.set_protocol_field("opstack", &field, &key).unwrap(),
+            None
+        );
+        assert_eq!(enr.get_raw_rlp("opstack"), Some(&[0xc2, 0x0a, 0x80][..]));
+        assert_eq!(enr.get_protocol_field("opstack"), Some(Ok(field)));
+        assert!(enr.verify());
+    }
+
+    #[test]
+    fn test_extra_items_survive_updates() {
+        let key = MockSigner(1);
+        // [56, 1, "peer", [0x01, [0x02]], 0xff]
+        let raw = hex::decode("cd38018470656572c301c10281ff").unwrap();
+        let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+        enr.insert_raw_rlp_list("bsc", raw.clone(), &key).unwrap();
+
+        let mut field = enr.get_protocol_field("bsc").unwrap().unwrap();
+        assert_eq!((field.chain_id, field.version), (56, 1));
+        assert_eq!(
+            field.extra,
+            vec![
+                hex::decode("8470656572").unwrap(),
+                hex::decode("c301c102").unwrap(),
+                hex::decode("81ff").unwrap(),
+            ]
+        );
+
+        // a read-modify-write cycle only changes the modified item
+        field.version = 2;
+        enr.set_protocol_field("bsc", &field, &key).unwrap();
+        let mut expected = raw;
+        expected[2] = 0x02;
+        assert_eq!(enr.get_raw_rlp("bsc"), Some(&expected[..]));
+        assert!(enr.verify());
+
+        let malformed = ProtocolField {
+            chain_id: 56,
+            version: 2,
+            extra: vec![vec![0x83, 0x01]],
+        };
+        assert!(matches!(
+            enr.set_protocol_field("bsc", &malformed, &key),
+            Err(EnrError::InvalidRlpData(_))
+        ));
+    }
+
+    #[test]
+    fn test_malformed_protocol_fields() {
+        let key = MockSigner(1);
+        let cases: &[(&str, DecoderError)] = &[
+            // a byte string
+            ("820a00", DecoderError::RlpExpectedToBeList),
+            // [10]
+            ("c10a", DecoderError::RlpIncorrectListLen),
+            // [["nested"], 0]
+            ("c9c7866e657374656480", DecoderError::RlpExpectedToBeData),
+        ];
+        for (raw, error) in cases {
+            let mut enr = EnrBuilder::new("v4").build(&key).unwrap();
+            enr.insert_raw_rlp_list("opstack", hex::decode(raw).unwrap(), &key)
+                .unwrap();
+            assert_eq!(
+                enr.get_protocol_field("opstack"),
+                Some(Err(error.clone())),
+                "{raw}"
+            );
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Payloads of QR codes sharing a record.
+//!
+//! A QR code encodes its payload in one of several modes. The text form of a record,
+//! `enr:-IS4Q...`, is case-sensitive base64 and needs the byte mode, 8 bits per character. Upper
+//! case hex, `ENR:F884B8...`, fits the alphanumeric mode, 11 bits per pair of characters.
+//! [`Enr::to_qr_payload`] returns whichever form takes fewer bits. Text costs about 10.7 bits per
+//! byte of the record and hex 11, so text is chosen for any record of more than 43 bytes, which
+//! includes every record with a 64-byte signature.
+//!
+//! Level `M` error correction is a good default for codes shown on a screen or printed. A typical
+//! secp256k1 record of around 180 characters then needs a version 10 code, and the largest
+//! record, 404 characters, a version 15 code. Use level `L` to keep the code small, and `Q` or `H`
+//! for codes that may be damaged.
+
+use crate::{Enr, EnrKey};
+use alloc::{format, string::String};
+
+/// The prefix of the hex payload, in the alphanumeric character set.
+const HEX_PREFIX: &str = "ENR:";
+
+/// The number of data bits of a string in the QR byte mode.
+const fn byte_mode_bits(len: usize) -> usize {
+    len * 8
+}
+
+/// The number of data bits of a string in the QR alphanumeric mode.
+const fn alphanumeric_mode_bits(len: usize) -> usize {
+    len / 2 * 11 + len % 2 * 6
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// The shortest QR code payload of the record, see the [`qr`](self) module. This is either
+    /// its text form, `enr:-IS4Q...`, or `ENR:` followed by its upper case hex encoding.
+    ///
+    /// Both forms are decoded by [`Enr::from_qr_payload`].
+    #[must_use]
+    pub fn to_qr_payload(&self) -> String {
+        let text = self.to_base64();
+        let hex_len = HEX_PREFIX.len() + 2 * self.encode().len();
+        if alphanumeric_mode_bits(hex_len) < byte_mode_bits(text.len()) {
+            format!("{}{}", HEX_PREFIX, hex::encode_upper(self.encode()))
+        } else {
+            text
+        }
+    }
+
+    /// Decodes a record from a QR code payload, see [`Enr::to_qr_payload`]. The text form is
+    /// decoded as by [`str::parse`].
+    pub fn from_qr_payload(payload: &str) -> Result<Self, String> {
+        payload
+            .strip_prefix(HEX_PREFIX)
+            .map_or_else(|| payload.parse(), Self::from_hex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder};
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_qr_mode_bits() {
+        assert_eq!(byte_mode_bits(10), 80);
+        assert_eq!(alphanumeric_mode_bits(10), 55);
+        assert_eq!(alphanumeric_mode_bits(11), 61);
+
+        // hex is only shorter for records of at most 43 bytes
+        let text_bits = |bytes: usize| byte_mode_bits(4 + (bytes * 4).div_ceil(3));
+        let hex_bits = |bytes: usize| alphanumeric_mode_bits(HEX_PREFIX.len() + 2 * bytes);
+        assert!(hex_bits(20) < text_bits(20));
+        assert!(hex_bits(43) < text_bits(43));
+        for bytes in 44..=300 {
+            assert!(hex_bits(bytes) >= text_bits(bytes), "{} bytes", bytes);
+        }
+    }
+
+    #[test]
+    fn test_qr_payload() {
+        let enr = EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::new(192, 0, 2, 1))
+            .udp(30303)
+            .build(&MockSigner(1))
+            .unwrap();
+        assert_eq!(enr.to_qr_payload(), enr.to_base64());
+        assert_eq!(Enr::from_qr_payload(&enr.to_qr_payload()), Ok(enr.clone()));
+
+        let hex = format!("{}{}", HEX_PREFIX, hex::encode_upper(enr.encode()));
+        assert_eq!(Enr::from_qr_payload(&hex), Ok(enr.clone()));
+        assert!(Enr::<MockSigner>::from_qr_payload("ENR:XYZ")
+            .unwrap_err()
+            .starts_with("Invalid hex encoding"));
+
+        let forged = crate::forge_unknown_scheme(&enr);
+        let hex = format!("{}{}", HEX_PREFIX, hex::encode_upper(forged.encode()));
+        assert!(Enr::<MockSigner>::from_qr_payload(&forged.to_qr_payload()).is_err());
+        assert!(Enr::<MockSigner>::from_qr_payload(&hex).is_err());
+    }
+}
@@ -0,0 +1,47 @@
+//! Generation of random keys and node ids.
+//!
+//! These draw on the entropy of the operating system through `rand::thread_rng`, which needs
+//! `std`. On `wasm32-unknown-unknown` there is no operating system to ask, and the entropy of the
+//! JavaScript host is only reachable with the `js` feature. Without it this module is not
+//! compiled for that target, rather than panicking at runtime.
+
+use crate::NodeId;
+#[cfg(feature = "ed25519")]
+use crate::{ed25519_dalek as ed25519, secp256k1, CombinedKey};
+#[cfg(feature = "ed25519")]
+use rand::RngCore;
+#[cfg(feature = "ed25519")]
+use zeroize::Zeroize;
+
+impl NodeId {
+    /// Generates a random `NodeId`.
+    #[must_use]
+    pub fn random() -> Self {
+        Self::new(&rand::random())
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl CombinedKey {
+    /// Generates a new secp256k1 key.
+    #[must_use]
+    pub fn generate_secp256k1() -> Self {
+        let mut r = rand::thread_rng();
+        let mut b = [0; secp256k1::util::SECRET_KEY_SIZE];
+        // This is how it is done in `secp256k1::SecretKey::random` which
+        // we do not use here because it uses `rand::Rng` from rand-0.4.
+        loop {
+            r.fill_bytes(&mut b);
+            if let Ok(k) = secp256k1::SecretKey::parse(&b) {
+                b.zeroize();
+                return Self::Secp256k1(k);
+            }
+        }
+    }
+
+    /// Generates a new ed25510 key.
+    #[must_use]
+    pub fn generate_ed25519() -> Self {
+        Self::from(ed25519::SecretKey::generate(&mut rand::thread_rng()))
+    }
+}
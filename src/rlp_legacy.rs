@@ -0,0 +1,91 @@
+//! Conversions between the [`DecoderError`] of rlp 0.5, which this crate decodes with, and the
+//! `DecoderError` of rlp 0.4, which earlier versions exposed. Provided by the `rlp-legacy`
+//! feature.
+//!
+//! Both versions have the same variants, so match arms written against rlp 0.4 keep compiling
+//! on the converted error:
+//!
+//! ```rust
+//! use enr::{rlp_legacy, DecoderError};
+//!
+//! let error = rlp_legacy::to_legacy(&DecoderError::RlpIsTooShort);
+//! assert!(matches!(error, rlp_legacy::LegacyDecoderError::RlpIsTooShort));
+//! ```
+
+use crate::DecoderError;
+
+/// The `DecoderError` of rlp 0.4.
+pub use rlp_legacy::DecoderError as LegacyDecoderError;
+
+/// Converts an error to the equivalent error of rlp 0.4.
+#[must_use]
+pub const fn to_legacy(error: &DecoderError) -> LegacyDecoderError {
+    match error {
+        DecoderError::RlpIsTooBig => LegacyDecoderError::RlpIsTooBig,
+        DecoderError::RlpIsTooShort => LegacyDecoderError::RlpIsTooShort,
+        DecoderError::RlpExpectedToBeList => LegacyDecoderError::RlpExpectedToBeList,
+        DecoderError::RlpExpectedToBeData => LegacyDecoderError::RlpExpectedToBeData,
+        DecoderError::RlpIncorrectListLen => LegacyDecoderError::RlpIncorrectListLen,
+        DecoderError::RlpDataLenWithZeroPrefix => LegacyDecoderError::RlpDataLenWithZeroPrefix,
+        DecoderError::RlpListLenWithZeroPrefix => LegacyDecoderError::RlpListLenWithZeroPrefix,
+        DecoderError::RlpInvalidIndirection => LegacyDecoderError::RlpInvalidIndirection,
+        DecoderError::RlpInconsistentLengthAndData => {
+            LegacyDecoderError::RlpInconsistentLengthAndData
+        }
+        DecoderError::RlpInvalidLength => LegacyDecoderError::RlpInvalidLength,
+        DecoderError::Custom(message) => LegacyDecoderError::Custom(message),
+    }
+}
+
+/// Converts an error of rlp 0.4 to the equivalent error.
+#[must_use]
+pub const fn from_legacy(error: &LegacyDecoderError) -> DecoderError {
+    match error {
+        LegacyDecoderError::RlpIsTooBig => DecoderError::RlpIsTooBig,
+        LegacyDecoderError::RlpIsTooShort => DecoderError::RlpIsTooShort,
+        LegacyDecoderError::RlpExpectedToBeList => DecoderError::RlpExpectedToBeList,
+        LegacyDecoderError::RlpExpectedToBeData => DecoderError::RlpExpectedToBeData,
+        LegacyDecoderError::RlpIncorrectListLen => DecoderError::RlpIncorrectListLen,
+        LegacyDecoderError::RlpDataLenWithZeroPrefix => DecoderError::RlpDataLenWithZeroPrefix,
+        LegacyDecoderError::RlpListLenWithZeroPrefix => DecoderError::RlpListLenWithZeroPrefix,
+        LegacyDecoderError::RlpInvalidIndirection => DecoderError::RlpInvalidIndirection,
+        LegacyDecoderError::RlpInconsistentLengthAndData => {
+            DecoderError::RlpInconsistentLengthAndData
+        }
+        LegacyDecoderError::RlpInvalidLength => DecoderError::RlpInvalidLength,
+        LegacyDecoderError::Custom(message) => DecoderError::Custom(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions_roundtrip() {
+        let errors = [
+            DecoderError::RlpIsTooBig,
+            DecoderError::RlpIsTooShort,
+            DecoderError::RlpExpectedToBeList,
+            DecoderError::RlpExpectedToBeData,
+            DecoderError::RlpIncorrectListLen,
+            DecoderError::RlpDataLenWithZeroPrefix,
+            DecoderError::RlpListLenWithZeroPrefix,
+            DecoderError::RlpInvalidIndirection,
+            DecoderError::RlpInconsistentLengthAndData,
+            DecoderError::RlpInvalidLength,
+            DecoderError::Custom("Invalid Signature"),
+        ];
+        for error in &errors {
+            let legacy = to_legacy(error);
+            // the variants print the same in both versions
+            assert_eq!(format!("{legacy:?}"), format!("{error:?}"));
+            assert_eq!(from_legacy(&legacy), *error);
+        }
+        // the errors of a decoding failure convert to those rlp 0.4 reports
+        assert_eq!(
+            to_legacy(&rlp::decode::<u16>(&[0x83, 1, 2, 3]).unwrap_err()),
+            rlp_legacy::decode::<u16>(&[0x83, 1, 2, 3]).unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Kademlia routing primitives for `NodeId`s.
+//!
+//! The distance functions match those of the go-ethereum discovery implementation, where the
+//! log-distance of two ids is the bit length of their XOR.
+
+use crate::NodeId;
+use core::convert::TryFrom;
+
+impl NodeId {
+    /// The XOR distance between two node ids, as a big-endian 32 byte array.
+    #[must_use]
+    pub fn xor_distance(&self, other: &Self) -> [u8; 32] {
+        let mut distance = [0_u8; 32];
+        for (d, (a, b)) in distance
+            .iter_mut()
+            .zip(self.raw().iter().zip(other.raw().iter()))
+        {
+            *d = a ^ b;
+        }
+        distance
+    }
+
+    /// The logarithmic distance between two node ids. This is the bit length of their XOR
+    /// distance, i.e `floor(log2(a ^ b)) + 1`, ranging from 1 to 256.
+    ///
+    /// Returns `None` if the node ids are identical. This is `LogDist` in go-ethereum's
+    /// discovery implementation.
+    #[must_use]
+    pub fn log_distance(&self, other: &Self) -> Option<u64> {
+        let mut leading_zeros = 0;
+        for byte in &self.xor_distance(other) {
+            if *byte != 0 {
+                leading_zeros += u64::from(byte.leading_zeros());
+                return Some(256 - leading_zeros);
+            }
+            leading_zeros += 8;
+        }
+        None
+    }
+
+    /// The index of the bucket `other` falls into in a routing table of `table_bits` buckets,
+    /// where each bucket covers a single log-distance and all nodes closer than the last bucket
+    /// share bucket 0.
+    ///
+    /// This matches go-ethereum's `bucketAtDistance`, where a table of 17 buckets covers the
+    /// log-distances 240 to 256 and closer nodes are placed in bucket 0.
+    #[must_use]
+    pub fn bucket_index(&self, other: &Self, table_bits: u8) -> usize {
+        let min_distance = 256 - u64::from(table_bits);
+        match self.log_distance(other) {
+            // the index is below 256, so the conversion never fails
+            Some(distance) if distance > min_distance => {
+                usize::try_from(distance - min_distance - 1).unwrap_or_default()
+            }
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node_id(hex: &str) -> NodeId {
+        NodeId::parse(&hex::decode(hex).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_xor_and_log_distance() {
+        let zero = NodeId::new(&[0; 32]);
+        let a = node_id("0000000000000000000000000000000000000000000000000000000000000001");
+        let b = node_id("8000000000000000000000000000000000000000000000000000000000000000");
+        let c = node_id("00000000000000000000000000000000000000000000000000000000000000ff");
+        let d = node_id("0000000000000000000000000000000000000000000000000000000000000100");
+
+        assert_eq!(zero.log_distance(&zero), None);
+        assert_eq!(zero.log_distance(&a), Some(1));
+        assert_eq!(zero.log_distance(&b), Some(256));
+        assert_eq!(zero.log_distance(&c), Some(8));
+        assert_eq!(zero.log_distance(&d), Some(9));
+        assert_eq!(c.log_distance(&d), Some(9));
+        assert_eq!(a.log_distance(&c), Some(8));
+
+        assert_eq!(
+            c.xor_distance(&d),
+            node_id("00000000000000000000000000000000000000000000000000000000000001ff").raw()
+        );
+        assert_eq!(a.xor_distance(&b), b.xor_distance(&a));
+    }
+
+    #[test]
+    fn test_log_distance_symmetry() {
+        let a = node_id("0000000000000000000000000000000000000000000000000000000000000000");
+        let b = node_id("00ff000000000000000000000000000000000000000000000000000000000000");
+        assert_eq!(a.log_distance(&b), Some(248));
+
+        let random = NodeId::random();
+        assert_eq!(random.log_distance(&random), None);
+        assert_eq!(random.log_distance(&a), a.log_distance(&random));
+    }
+
+    #[test]
+    fn test_bucket_index() {
+        let zero = NodeId::new(&[0; 32]);
+        let far = node_id("8000000000000000000000000000000000000000000000000000000000000000");
+        let near = node_id("0000000000000000000000000000000000000000000000000000000000000001");
+        let edge = node_id("0000800000000000000000000000000000000000000000000000000000000000");
+
+        // go-ethereum's v4 table: 17 buckets
+        assert_eq!(zero.bucket_index(&far, 17), 16);
+        assert_eq!(zero.bucket_index(&edge, 17), 0);
+        assert_eq!(zero.bucket_index(&near, 17), 0);
+        assert_eq!(zero.bucket_index(&zero, 17), 0);
+
+        // a table with a bucket per log-distance
+        assert_eq!(zero.bucket_index(&far, 255), 254);
+        assert_eq!(zero.bucket_index(&near, 255), 0);
+    }
+}
@@ -0,0 +1,207 @@
+//! An ENR record paired with a quality score, for ranking records to evict from a routing table.
+//!
+//! A [`ScoredEnr`] starts with a score of 1. Interactions with the node adjust the score with
+//! [`ScoredEnr::update_score`], and [`ScoredEnr::decay_score`] ages it according to the
+//! [`ScorePolicy`] of the record. Records scoring below a threshold are evictable.
+
+use crate::{Enr, EnrKey};
+use std::{
+    ops::Deref,
+    time::{Duration, Instant},
+};
+
+/// How the score of a [`ScoredEnr`] changes over time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScorePolicy {
+    /// The score only reflects how recently the node was seen. Decaying sets it to
+    /// `2^(-t / half_life)` for the time `t` since the node was last seen, discarding previous
+    /// updates.
+    Recency,
+
+    /// The score only reflects the outcome of interactions with the node, and does not decay.
+    Connectivity,
+
+    /// The score halves every `half_life` since it was last decayed.
+    Exponential,
+}
+
+/// An ENR record paired with a quality score and the time its node was last seen.
+pub struct ScoredEnr<K: EnrKey> {
+    /// The record.
+    pub enr: Enr<K>,
+
+    /// The score of the record, higher is better.
+    pub score: f64,
+
+    /// When the node was last seen.
+    pub last_seen: Instant,
+
+    /// How the score changes over time.
+    pub policy: ScorePolicy,
+
+    /// When the score was last decayed.
+    decayed_at: Instant,
+}
+
+impl<K: EnrKey> ScoredEnr<K> {
+    /// Pairs a record with a score of 1, its node seen now.
+    #[must_use]
+    pub fn new(enr: Enr<K>, policy: ScorePolicy) -> Self {
+        Self::seen_at(enr, policy, Instant::now())
+    }
+
+    /// Pairs a record with a score of 1, its node seen at the given time.
+    #[must_use]
+    pub const fn seen_at(enr: Enr<K>, policy: ScorePolicy, last_seen: Instant) -> Self {
+        Self {
+            enr,
+            score: 1.0,
+            last_seen,
+            policy,
+            decayed_at: last_seen,
+        }
+    }
+
+    /// Adds `delta` to the score, for example a positive delta for a successful contact and a
+    /// negative one for a timeout. A positive delta also marks the node as seen now.
+    pub fn update_score(&mut self, delta: f64) {
+        self.score += delta;
+        if delta > 0.0 {
+            self.last_seen = Instant::now();
+        }
+    }
+
+    /// Ages the score according to the policy of the record, see [`ScorePolicy`].
+    ///
+    /// # Panics
+    /// Panics if `half_life` is zero.
+    pub fn decay_score(&mut self, half_life: Duration) {
+        assert!(!half_life.is_zero(), "the half-life must not be zero");
+        let now = Instant::now();
+        let halvings = |since: Instant| {
+            now.saturating_duration_since(since).as_secs_f64() / half_life.as_secs_f64()
+        };
+        match self.policy {
+            ScorePolicy::Recency => self.score = 0.5_f64.powf(halvings(self.last_seen)),
+            ScorePolicy::Connectivity => {}
+            ScorePolicy::Exponential => self.score *= 0.5_f64.powf(halvings(self.decayed_at)),
+        }
+        self.decayed_at = now;
+    }
+
+    /// Returns `true` if the score is below `threshold`.
+    #[must_use]
+    pub fn is_evictable(&self, threshold: f64) -> bool {
+        self.score < threshold
+    }
+}
+
+impl<K: EnrKey> Deref for ScoredEnr<K> {
+    type Target = Enr<K>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.enr
+    }
+}
+
+impl<K: EnrKey> AsRef<Enr<K>> for ScoredEnr<K> {
+    fn as_ref(&self) -> &Enr<K> {
+        &self.enr
+    }
+}
+
+impl<K: EnrKey> Clone for ScoredEnr<K> {
+    fn clone(&self) -> Self {
+        Self {
+            enr: self.enr.clone(),
+            score: self.score,
+            last_seen: self.last_seen,
+            policy: self.policy,
+            decayed_at: self.decayed_at,
+        }
+    }
+}
+
+impl<K: EnrKey> std::fmt::Debug for ScoredEnr<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ScoredEnr")
+            .field("enr", &self.enr)
+            .field("score", &self.score)
+            .field("last_seen", &self.last_seen)
+            .field("policy", &self.policy)
+            .field("decayed_at", &self.decayed_at)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder};
+
+    fn ago(secs: u64) -> Instant {
+        Instant::now()
+            .checked_sub(Duration::from_secs(secs))
+            .unwrap()
+    }
+
+    fn scored_enr(policy: ScorePolicy, secs_ago: u64) -> ScoredEnr<MockSigner> {
+        let enr = EnrBuilder::new("v4")
+            .udp(9000)
+            .build(&MockSigner(1))
+            .unwrap();
+        ScoredEnr::seen_at(enr, policy, ago(secs_ago))
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_update_score() {
+        let mut scored = scored_enr(ScorePolicy::Connectivity, 60);
+        assert_close(scored.score, 1.0);
+        assert_eq!(scored.udp4(), Some(9000));
+
+        // a failure does not mark the node as seen
+        scored.update_score(-0.75);
+        assert_close(scored.score, 0.25);
+        assert!(scored.last_seen.elapsed() >= Duration::from_mins(1));
+        assert!(scored.is_evictable(0.5));
+
+        scored.update_score(0.5);
+        assert_close(scored.score, 0.75);
+        assert!(scored.last_seen.elapsed() < Duration::from_mins(1));
+        assert!(!scored.is_evictable(0.5));
+    }
+
+    #[test]
+    fn test_decay_score() {
+        let half_life = Duration::from_mins(1);
+
+        // two half-lives since the node was last seen
+        let mut scored = scored_enr(ScorePolicy::Exponential, 120);
+        scored.update_score(-0.5);
+        scored.decay_score(half_life);
+        assert_close(scored.score, 0.125);
+        // no time has passed since the last decay
+        scored.decay_score(half_life);
+        assert_close(scored.score, 0.125);
+
+        let mut scored = scored_enr(ScorePolicy::Recency, 120);
+        scored.update_score(-0.5);
+        scored.decay_score(half_life);
+        assert_close(scored.score, 0.25);
+        scored.decay_score(half_life);
+        assert_close(scored.score, 0.25);
+
+        let mut scored = scored_enr(ScorePolicy::Connectivity, 120);
+        scored.decay_score(half_life);
+        assert_close(scored.score, 1.0);
+    }
+}
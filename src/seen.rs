@@ -0,0 +1,126 @@
+//! Deduplication of records forwarded between peers. Enabled by the `lru` feature.
+
+use crate::{Enr, EnrKey};
+use lru::LruCache;
+use std::num::NonZeroUsize;
+
+/// A bounded set of the records seen by a node, for example to forward each record received over
+/// gossip only once.
+///
+/// Records are identified by their [`Enr::content_hash`], so a record received again with a
+/// different signature encoding is still recognized. The least recently marked records are
+/// forgotten once the capacity is exceeded.
+pub struct SeenEnrs {
+    /// The content hashes of the seen records.
+    hashes: LruCache<[u8; 32], ()>,
+}
+
+impl SeenEnrs {
+    /// Creates a set remembering at most `capacity` records.
+    #[must_use]
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            hashes: LruCache::new(capacity),
+        }
+    }
+
+    /// Returns `true` if the record has been marked as seen and not forgotten since.
+    #[must_use]
+    pub fn seen<K: EnrKey>(&self, enr: &Enr<K>) -> bool {
+        self.hashes.contains(&enr.content_hash())
+    }
+
+    /// Marks the record as seen, as the most recently seen record. Returns `true` if the record
+    /// had not been seen before.
+    ///
+    /// Records of an unknown identity scheme are not verified, so their content could be that of
+    /// a genuine record. They are never marked and return `false`.
+    pub fn mark_seen<K: EnrKey>(&mut self, enr: &Enr<K>) -> bool {
+        if enr.identity_scheme().is_none() {
+            return false;
+        }
+        self.hashes.put(enr.content_hash(), ()).is_none()
+    }
+
+    /// The number of remembered records.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Returns `true` if no records are remembered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Forgets all records.
+    pub fn clear(&mut self) {
+        self.hashes.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder, EnrDecodeOptions};
+
+    #[test]
+    fn test_seen_enrs() {
+        let key = MockSigner(1);
+        let mut seen = SeenEnrs::new(NonZeroUsize::new(2).unwrap());
+        let first = EnrBuilder::new("v4").udp(9000).build(&key).unwrap();
+        let mut second = first.clone();
+        second.set_udp(9001, &key).unwrap();
+        let other = EnrBuilder::new("v4").build(&MockSigner(2)).unwrap();
+
+        assert!(!seen.seen(&first));
+        assert!(seen.mark_seen(&first));
+        assert!(!seen.mark_seen(&first));
+        assert!(seen.seen(&first));
+        // a new version of a record is a different record
+        assert!(!seen.seen(&second));
+
+        // the same content with another signature is the same record
+        let mut resigned = first.clone();
+        resigned.signature = vec![0xff; 64];
+        assert!(seen.seen(&resigned));
+
+        assert!(seen.mark_seen(&second));
+        assert!(seen.mark_seen(&other));
+        assert_eq!(seen.len(), 2);
+        assert!(!seen.seen(&first));
+        assert!(seen.seen(&other));
+
+        seen.clear();
+        assert!(seen.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_schemes_are_not_marked() {
+        let genuine = EnrBuilder::new("v4")
+            .udp(9000)
+            .build(&MockSigner(1))
+            .unwrap();
+        let mut unknown = genuine.clone();
+        unknown
+            .content
+            .insert("id".into(), rlp::encode(&"v5").into());
+        let unknown = Enr::<MockSigner>::decode_with_options(
+            &unknown.encode(),
+            EnrDecodeOptions::new().allow_unknown_schemes(true),
+        )
+        .unwrap();
+
+        let mut seen = SeenEnrs::new(NonZeroUsize::new(2).unwrap());
+        assert!(!seen.mark_seen(&unknown));
+        assert!(seen.is_empty());
+        assert!(seen.mark_seen(&genuine));
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SeenEnrs>();
+    }
+}
@@ -0,0 +1,248 @@
+//! A set of ENR records holding the most recent record of each node.
+//!
+//! The set stores either plain records or records paired with an annotation, such as the
+//! [`AnnotatedEnr`] of the peer a record was received from. An annotation is kept for as long as
+//! its record is stored, and replaced along with the record. A [`TimedEnrSet`] stores records
+//! with their receipt time, evicting the stale ones on request.
+//!
+//! [`AnnotatedEnr`]: crate::AnnotatedEnr
+
+use crate::{Enr, EnrKey, NodeId, TimedEnr};
+use std::{collections::HashMap, marker::PhantomData, time::Duration};
+
+/// A set of records paired with the time they were received, see [`EnrSet::evict_older_than`].
+pub type TimedEnrSet<K> = EnrSet<K, TimedEnr<K>>;
+
+/// A set of records of type `T`, at most one per `NodeId`.
+///
+/// `T` is an [`Enr`] or a type wrapping one, such as [`crate::AnnotatedEnr`].
+pub struct EnrSet<K: EnrKey, T: AsRef<Enr<K>> = Enr<K>> {
+    /// The stored records.
+    records: HashMap<NodeId, T>,
+
+    /// Pins the generic key type.
+    phantom: PhantomData<K>,
+}
+
+impl<K: EnrKey, T: AsRef<Enr<K>>> EnrSet<K, T> {
+    /// Creates an empty set.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            records: HashMap::new(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Inserts a record into the set.
+    ///
+    /// An existing record for the same node, along with its annotation, is only replaced if the
+    /// new record has a higher sequence number. Records of an unknown identity scheme are not
+    /// verified and never inserted. Returns `true` if the record was inserted.
+    pub fn insert(&mut self, entry: T) -> bool {
+        let enr = entry.as_ref();
+        if enr.identity_scheme().is_none() {
+            return false;
+        }
+        let node_id = enr.node_id();
+        if let Some(existing) = self.records.get(&node_id) {
+            if existing.as_ref().seq() >= enr.seq() {
+                return false;
+            }
+        }
+        self.records.insert(node_id, entry);
+        true
+    }
+
+    /// Returns the record of a node, if stored.
+    #[must_use]
+    pub fn get(&self, node_id: &NodeId) -> Option<&T> {
+        self.records.get(node_id)
+    }
+
+    /// Returns `true` if a record of the node is stored.
+    #[must_use]
+    pub fn contains(&self, node_id: &NodeId) -> bool {
+        self.records.contains_key(node_id)
+    }
+
+    /// Removes and returns the record of a node, if stored.
+    pub fn remove(&mut self, node_id: &NodeId) -> Option<T> {
+        self.records.remove(node_id)
+    }
+
+    /// Iterates over the stored records in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.records.values()
+    }
+
+    /// The number of stored records.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns `true` if no records are stored.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+impl<K: EnrKey> EnrSet<K, TimedEnr<K>> {
+    /// Removes and returns the records received more than `duration` ago, see
+    /// [`TimedEnr::is_older_than`].
+    pub fn evict_older_than(&mut self, duration: Duration) -> Vec<TimedEnr<K>> {
+        let stale: Vec<NodeId> = self
+            .records
+            .iter()
+            .filter(|(_, timed)| timed.is_older_than(duration))
+            .map(|(node_id, _)| *node_id)
+            .collect();
+        stale
+            .iter()
+            .filter_map(|node_id| self.records.remove(node_id))
+            .collect()
+    }
+}
+
+impl<K: EnrKey, T: AsRef<Enr<K>>> Default for EnrSet<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: EnrKey, T: AsRef<Enr<K>> + Clone> Clone for EnrSet<K, T> {
+    fn clone(&self) -> Self {
+        Self {
+            records: self.records.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<K: EnrKey, T: AsRef<Enr<K>>> Extend<T> for EnrSet<K, T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for entry in iter {
+            self.insert(entry);
+        }
+    }
+}
+
+impl<K: EnrKey, T: AsRef<Enr<K>>> std::iter::FromIterator<T> for EnrSet<K, T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, AnnotatedEnr, EnrBuilder, EnrDecodeOptions};
+    use std::{net::SocketAddr, time::Instant};
+
+    #[test]
+    fn test_enr_set() {
+        let key = MockSigner(1);
+        let first = EnrBuilder::new("v4").udp(9000).build(&key).unwrap();
+        let mut second = first.clone();
+        second.set_udp(9001, &key).unwrap();
+        let other = EnrBuilder::new("v4").build(&MockSigner(2)).unwrap();
+
+        let mut set: EnrSet<MockSigner> = vec![first.clone(), other.clone()].into_iter().collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.insert(second.clone()));
+        assert!(!set.insert(first.clone()));
+        assert_eq!(set.get(&first.node_id()), Some(&second));
+
+        assert_eq!(set.remove(&other.node_id()), Some(other.clone()));
+        assert!(!set.contains(&other.node_id()));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![&second]);
+    }
+
+    #[test]
+    fn test_unknown_schemes_are_refused() {
+        let victim = EnrBuilder::new("v4")
+            .udp(9000)
+            .build(&MockSigner(1))
+            .unwrap();
+        // an unsigned record claiming the public key of the victim under another scheme
+        let mut forged = victim.clone();
+        forged
+            .content
+            .insert("id".into(), rlp::encode(&"v5").into());
+        forged.seq += 1;
+        let forged = Enr::<MockSigner>::decode_with_options(
+            &forged.encode(),
+            EnrDecodeOptions::new().allow_unknown_schemes(true),
+        )
+        .unwrap();
+        assert_eq!(forged.node_id(), victim.node_id());
+
+        let mut set = EnrSet::new();
+        assert!(!set.insert(forged.clone()));
+        assert!(set.is_empty());
+        assert!(set.insert(victim.clone()));
+        assert!(!set.insert(forged));
+        assert_eq!(set.get(&victim.node_id()), Some(&victim));
+    }
+
+    #[test]
+    fn test_annotations_follow_their_records() {
+        let key = MockSigner(1);
+        let peer_a: SocketAddr = "192.0.2.1:9000".parse().unwrap();
+        let peer_b: SocketAddr = "192.0.2.2:9000".parse().unwrap();
+        let older = EnrBuilder::new("v4").udp(9000).build(&key).unwrap();
+        let mut newer = older.clone();
+        newer.set_udp(9001, &key).unwrap();
+        let node_id = older.node_id();
+
+        let mut set = EnrSet::new();
+        assert!(set.insert(AnnotatedEnr::new(older.clone(), peer_a)));
+        // a stale or repeated record does not overwrite the origin of the stored one
+        assert!(!set.insert(AnnotatedEnr::new(older, peer_b)));
+        assert_eq!(set.get(&node_id).map(AnnotatedEnr::meta), Some(&peer_a));
+
+        assert!(set.insert(AnnotatedEnr::new(newer.clone(), peer_b)));
+        let stored = set.clone().remove(&node_id).unwrap();
+        assert_eq!(*stored.meta(), peer_b);
+        assert_eq!(stored.udp(), Some(9001));
+        assert_eq!(*stored.enr(), newer);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_annotation_serde() {
+        let key = MockSigner(1);
+        let peer: SocketAddr = "[2001:db8::1]:9000".parse().unwrap();
+        let annotated = AnnotatedEnr::new(EnrBuilder::new("v4").build(&key).unwrap(), peer);
+
+        let json = serde_json::to_string(&annotated).unwrap();
+        let decoded: AnnotatedEnr<MockSigner, SocketAddr> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, annotated);
+        assert_eq!(*decoded.meta(), peer);
+    }
+
+    #[test]
+    fn test_evict_older_than() {
+        let ago = |secs| {
+            Instant::now()
+                .checked_sub(Duration::from_secs(secs))
+                .unwrap()
+        };
+        let stale = EnrBuilder::new("v4").build(&MockSigner(1)).unwrap();
+        let fresh = EnrBuilder::new("v4").build(&MockSigner(2)).unwrap();
+        let mut set = TimedEnrSet::new();
+        set.insert(TimedEnr::received_at(stale.clone(), ago(600)));
+        set.insert(TimedEnr::received_at(fresh.clone(), ago(10)));
+
+        let evicted = set.evict_older_than(Duration::from_mins(1));
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].enr, stale);
+        assert_eq!(set.len(), 1);
+        assert!(set.contains(&fresh.node_id()));
+        assert!(set.evict_older_than(Duration::from_mins(1)).is_empty());
+    }
+}
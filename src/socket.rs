@@ -0,0 +1,133 @@
+//! Selection of the socket to dial when a record advertises both an IPv4 and an IPv6 endpoint.
+
+use crate::{address, Enr, EnrKey};
+use core::net::{SocketAddr, SocketAddrV4, SocketAddrV6};
+
+/// The address family preference of [`Enr::preferred_udp_socket`] and
+/// [`Enr::preferred_tcp_socket`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IpPreference {
+    /// The IPv4 socket, falling back to the IPv6 socket.
+    V4First,
+    /// The IPv6 socket, falling back to the IPv4 socket.
+    V6First,
+    /// Only the IPv4 socket.
+    V4Only,
+    /// Only the IPv6 socket.
+    V6Only,
+}
+
+impl IpPreference {
+    /// Selects between the sockets of both address families.
+    fn select(self, v4: Option<SocketAddrV4>, v6: Option<SocketAddrV6>) -> Option<SocketAddr> {
+        let v4 = v4.map(SocketAddr::V4);
+        let v6 = v6.map(SocketAddr::V6);
+        match self {
+            Self::V4First => v4.or(v6),
+            Self::V6First => v6.or(v4),
+            Self::V4Only => v4,
+            Self::V6Only => v6,
+        }
+    }
+}
+
+impl<K: EnrKey> Enr<K> {
+    /// Selects the UDP socket to dial according to the address family preference, from `ip` and
+    /// `udp` or `ip6` and `udp6`. An address without a matching port is not a socket.
+    ///
+    /// An IPv4-mapped (`::ffff:a.b.c.d`) or IPv4-compatible (`::a.b.c.d`) address in the `ip6`
+    /// field is skipped, as the record does not advertise a genuine IPv6 endpoint, see
+    /// [`Enr::ip6_normalized`].
+    #[must_use]
+    pub fn preferred_udp_socket(&self, prefer: IpPreference) -> Option<SocketAddr> {
+        prefer.select(
+            self.udp4_socket(),
+            self.udp6_socket().filter(is_genuine_ipv6),
+        )
+    }
+
+    /// Selects the TCP socket to dial according to the address family preference, from `ip` and
+    /// `tcp` or `ip6` and `tcp6`, see [`Enr::preferred_udp_socket`].
+    #[must_use]
+    pub fn preferred_tcp_socket(&self, prefer: IpPreference) -> Option<SocketAddr> {
+        prefer.select(
+            self.tcp4_socket(),
+            self.tcp6_socket().filter(is_genuine_ipv6),
+        )
+    }
+}
+
+/// Returns `false` for sockets of IPv6 addresses embedding an IPv4 address.
+const fn is_genuine_ipv6(socket: &SocketAddrV6) -> bool {
+    address::embedded_ipv4(*socket.ip()).is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder};
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_preferred_sockets() {
+        let key = MockSigner(1);
+        let ip4 = Ipv4Addr::new(192, 0, 2, 1);
+        let ip6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+        let v4 = SocketAddr::new(ip4.into(), 9000);
+        let v6 = SocketAddr::new(ip6.into(), 9006);
+
+        // every combination of the `ip`, `udp`, `ip6` and `udp6` fields
+        for fields in 0..16_u8 {
+            let has = |bit: u8| fields & (1 << bit) != 0;
+            let mut builder = EnrBuilder::new("v4");
+            builder.allow_orphan_ports(true);
+            if has(0) {
+                builder.ip4(ip4);
+            }
+            if has(1) {
+                builder.udp(9000).tcp(9000);
+            }
+            if has(2) {
+                builder.ip6(ip6);
+            }
+            if has(3) {
+                builder.udp6(9006).tcp6(9006);
+            }
+            let enr = builder.build(&key).unwrap();
+
+            let socket4 = if has(0) && has(1) { Some(v4) } else { None };
+            let socket6 = if has(2) && has(3) { Some(v6) } else { None };
+            let expected = [
+                (IpPreference::V4First, socket4.or(socket6)),
+                (IpPreference::V6First, socket6.or(socket4)),
+                (IpPreference::V4Only, socket4),
+                (IpPreference::V6Only, socket6),
+            ];
+            for (prefer, socket) in &expected {
+                assert_eq!(
+                    enr.preferred_udp_socket(*prefer),
+                    *socket,
+                    "{fields:04b} {prefer:?}"
+                );
+                assert_eq!(
+                    enr.preferred_tcp_socket(*prefer),
+                    *socket,
+                    "{fields:04b} {prefer:?}"
+                );
+            }
+        }
+
+        // an IPv4-mapped `ip6` is skipped
+        let mut enr = EnrBuilder::new("v4")
+            .udp6(9006)
+            .allow_orphan_ports(true)
+            .build(&key)
+            .unwrap();
+        let mapped = Ipv4Addr::new(198, 51, 100, 1).to_ipv6_mapped();
+        enr.insert_value("ip6", &mapped.octets().to_vec(), &key)
+            .unwrap();
+        assert!(enr.udp6_socket().is_some());
+        assert_eq!(enr.preferred_udp_socket(IpPreference::V6Only), None);
+        assert_eq!(enr.preferred_udp_socket(IpPreference::V6First), None);
+    }
+}
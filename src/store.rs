@@ -0,0 +1,112 @@
+//! A store of ENR records keyed by node id.
+//!
+//! [`EnrStore`] describes the storage of the records of a routing table, so that its users can
+//! depend on the trait and swap in other backends. [`HashMapEnrStore`], an [`EnrSet`] of plain
+//! records, is the in-memory implementation.
+
+use crate::{Enr, EnrKey, EnrSet, NodeId};
+
+/// The in-memory [`EnrStore`], backed by a `HashMap`.
+pub type HashMapEnrStore<K> = EnrSet<K>;
+
+/// A store of ENR records, at most one per `NodeId`.
+pub trait EnrStore<K: EnrKey> {
+    /// Inserts a record. Returns `true` if the record was stored, which a store may refuse if it
+    /// already holds a record of the node with the same or a higher sequence number. Records of
+    /// an unknown identity scheme are not verified and must be refused.
+    fn insert(&mut self, enr: Enr<K>) -> bool;
+
+    /// Returns the record of a node, if stored.
+    fn get(&self, node_id: &NodeId) -> Option<&Enr<K>>;
+
+    /// Removes and returns the record of a node, if stored.
+    fn remove(&mut self, node_id: &NodeId) -> Option<Enr<K>>;
+
+    /// The number of stored records.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if no records are stored.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over the stored records in arbitrary order.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a Enr<K>>
+    where
+        K: 'a;
+}
+
+impl<K: EnrKey> EnrStore<K> for EnrSet<K> {
+    /// Inserts a record, replacing the record of the same node only if the new record has a
+    /// higher sequence number. Records of an unknown identity scheme are refused.
+    fn insert(&mut self, enr: Enr<K>) -> bool {
+        Self::insert(self, enr)
+    }
+
+    fn get(&self, node_id: &NodeId) -> Option<&Enr<K>> {
+        Self::get(self, node_id)
+    }
+
+    fn remove(&mut self, node_id: &NodeId) -> Option<Enr<K>> {
+        Self::remove(self, node_id)
+    }
+
+    fn len(&self) -> usize {
+        Self::len(self)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a Enr<K>>
+    where
+        K: 'a,
+    {
+        Self::iter(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder, EnrDecodeOptions};
+
+    /// Exercises a store through the trait only.
+    fn exercise<S: EnrStore<MockSigner>>(store: &mut S) {
+        let first = EnrBuilder::new("v4").build(&MockSigner(1)).unwrap();
+        let second = EnrBuilder::new("v4").build(&MockSigner(2)).unwrap();
+        assert!(store.is_empty());
+        assert!(store.insert(first.clone()));
+        assert!(store.insert(second.clone()));
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get(&first.node_id()), Some(&first));
+        assert_eq!(store.iter().count(), 2);
+
+        let mut newer = first.clone();
+        newer.set_udp(9000, &MockSigner(1)).unwrap();
+        assert!(!store.insert(first.clone()));
+        assert!(store.insert(newer.clone()));
+        assert_eq!(store.get(&first.node_id()), Some(&newer));
+
+        // an unverified record claiming the public key of a stored node
+        let mut forged = newer.clone();
+        forged
+            .content
+            .insert("id".into(), rlp::encode(&"v5").into());
+        forged.seq += 1;
+        let forged = Enr::decode_with_options(
+            &forged.encode(),
+            EnrDecodeOptions::new().allow_unknown_schemes(true),
+        )
+        .unwrap();
+        assert!(!store.insert(forged));
+        assert_eq!(store.get(&first.node_id()), Some(&newer));
+
+        assert_eq!(store.remove(&second.node_id()), Some(second.clone()));
+        assert_eq!(store.remove(&second.node_id()), None);
+        assert_eq!(store.get(&second.node_id()), None);
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_hash_map_store() {
+        exercise(&mut HashMapEnrStore::new());
+    }
+}
@@ -0,0 +1,247 @@
+//! Discovery of the public address of a node with a STUN Binding Request (RFC 5389), provided
+//! by the `async` feature.
+//!
+//! Only the Binding Request and the mapped address attributes of the response are implemented.
+//! Responses are not authenticated, so the server has to be trusted.
+
+use crate::{EnrBuilder, EnrKey};
+use std::{
+    convert::TryFrom,
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+use tokio::{net::UdpSocket, time::timeout};
+
+/// The fixed value of the second word of every STUN message.
+const MAGIC_COOKIE: [u8; 4] = [0x21, 0x12, 0xa4, 0x42];
+/// The message type of a Binding Request.
+const BINDING_REQUEST: u16 = 0x0001;
+/// The message type of a Binding Success Response.
+const BINDING_RESPONSE: u16 = 0x0101;
+/// The `MAPPED-ADDRESS` attribute of RFC 3489 servers.
+const MAPPED_ADDRESS: u16 = 0x0001;
+/// The `XOR-MAPPED-ADDRESS` attribute.
+const XOR_MAPPED_ADDRESS: u16 = 0x0020;
+/// The size of the message header.
+const HEADER_SIZE: usize = 20;
+/// The timeout of the first request, doubled on every retransmission as recommended by RFC 5389.
+const INITIAL_TIMEOUT: Duration = Duration::from_millis(500);
+/// The number of requests sent before giving up.
+const MAX_REQUESTS: u32 = 4;
+
+/// Encodes a Binding Request without attributes.
+fn binding_request(transaction_id: &[u8; 12]) -> [u8; HEADER_SIZE] {
+    let mut request = [0_u8; HEADER_SIZE];
+    request[..2].copy_from_slice(&BINDING_REQUEST.to_be_bytes());
+    request[4..8].copy_from_slice(&MAGIC_COOKIE);
+    request[8..].copy_from_slice(transaction_id);
+    request
+}
+
+/// Returns the mapped address of a Binding Success Response to the request of
+/// `transaction_id`, preferring the `XOR-MAPPED-ADDRESS` attribute. Returns `None` for any
+/// other message.
+fn parse_binding_response(message: &[u8], transaction_id: &[u8; 12]) -> Option<SocketAddr> {
+    let header = message.get(..HEADER_SIZE)?;
+    if u16::from_be_bytes([header[0], header[1]]) != BINDING_RESPONSE
+        || header[4..8] != MAGIC_COOKIE
+        || header[8..] != transaction_id[..]
+    {
+        return None;
+    }
+    let length = usize::from(u16::from_be_bytes([header[2], header[3]]));
+    let mut attributes = message.get(HEADER_SIZE..HEADER_SIZE + length)?;
+
+    let mut mapped = None;
+    while attributes.len() >= 4 {
+        let kind = u16::from_be_bytes([attributes[0], attributes[1]]);
+        let length = usize::from(u16::from_be_bytes([attributes[2], attributes[3]]));
+        let value = attributes.get(4..4 + length)?;
+        match kind {
+            XOR_MAPPED_ADDRESS => return decode_address(value, Some(&header[4..])),
+            MAPPED_ADDRESS => mapped = decode_address(value, None),
+            _ => {}
+        }
+        // attributes are padded to a multiple of 4 bytes
+        attributes = attributes.get((4 + length + 3) & !3..).unwrap_or_default();
+    }
+    mapped
+}
+
+/// Decodes an address attribute. The port and address of `XOR-MAPPED-ADDRESS` are masked with
+/// `key`, the magic cookie followed by the transaction id.
+fn decode_address(value: &[u8], key: Option<&[u8]>) -> Option<SocketAddr> {
+    let xor = |bytes: &[u8]| -> Vec<u8> {
+        key.map_or_else(
+            || bytes.to_vec(),
+            |key| {
+                bytes
+                    .iter()
+                    .zip(key)
+                    .map(|(byte, key)| byte ^ key)
+                    .collect()
+            },
+        )
+    };
+    let port = xor(value.get(2..4)?);
+    let port = u16::from_be_bytes([port[0], port[1]]);
+    let ip = match value.get(1)? {
+        0x01 => IpAddr::V4(Ipv4Addr::from(
+            <[u8; 4]>::try_from(xor(value.get(4..8)?)).ok()?,
+        )),
+        0x02 => IpAddr::V6(Ipv6Addr::from(
+            <[u8; 16]>::try_from(xor(value.get(4..20)?)).ok()?,
+        )),
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+/// Queries the address `stun_server` sees the requests of a new UDP socket come from.
+async fn reflexive_address(stun_server: SocketAddr) -> io::Result<SocketAddr> {
+    let local: SocketAddr = match stun_server {
+        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = UdpSocket::bind(local).await?;
+    socket.connect(stun_server).await?;
+
+    let transaction_id: [u8; 12] = rand::random();
+    let request = binding_request(&transaction_id);
+    let mut response = [0_u8; 1024];
+    let mut wait = INITIAL_TIMEOUT;
+    for _ in 0..MAX_REQUESTS {
+        socket.send(&request).await?;
+        let received = timeout(wait, async {
+            loop {
+                let len = socket.recv(&mut response).await?;
+                if let Some(address) = parse_binding_response(&response[..len], &transaction_id) {
+                    return Ok::<_, io::Error>(address);
+                }
+            }
+        })
+        .await;
+        if let Ok(address) = received {
+            return address;
+        }
+        wait *= 2;
+    }
+    Err(io::Error::new(
+        io::ErrorKind::TimedOut,
+        "no response from the STUN server",
+    ))
+}
+
+impl<K: EnrKey> EnrBuilder<K> {
+    /// Adds the public address of the node, as seen by a STUN server, as the `ip` or `ip6`
+    /// field, see [`EnrBuilder::ip4`] and [`EnrBuilder::ip6`].
+    ///
+    /// A Binding Request is sent from a new UDP socket of the address family of `stun_server`,
+    /// and retransmitted a few times if no response arrives. Only the address of the response is
+    /// used, the port is that of the temporary socket.
+    pub async fn set_ip_from_stun(&mut self, stun_server: SocketAddr) -> io::Result<&mut Self> {
+        Ok(match reflexive_address(stun_server).await?.ip() {
+            IpAddr::V4(ip) => self.ip4(ip),
+            IpAddr::V6(ip) => self.ip6(ip),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder};
+    use std::convert::TryInto;
+
+    /// The transaction id of the RFC 5769 sample responses.
+    const TRANSACTION_ID: [u8; 12] = [
+        0xb7, 0xe7, 0xa7, 0x01, 0xbc, 0x34, 0xd6, 0x86, 0xfa, 0x87, 0xdf, 0xae,
+    ];
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_parse_binding_response() {
+        // RFC 5769, section 2.2, a response with an IPv4 address
+        let response = hex::decode(
+            "0101003c2112a442b7e7a701bc34d686fa87dfae8022000b7465737420766563746f7220\
+             002000080001a147e112a643000800142b91f599fd9e90c38c7489f92af9ba53f06be7d7\
+             80280004c07d4c96",
+        )
+        .unwrap();
+        assert_eq!(
+            parse_binding_response(&response, &TRANSACTION_ID),
+            Some("192.0.2.1:32853".parse().unwrap())
+        );
+
+        // RFC 5769, section 2.3, a response with an IPv6 address, up to its XOR-MAPPED-ADDRESS
+        let response = hex::decode(
+            "010100282112a442b7e7a701bc34d686fa87dfae8022000b7465737420766563746f7220\
+             002000140002a1470113a9faa5d3f179bc25f4b5bed2b9d9",
+        )
+        .unwrap();
+        assert_eq!(
+            parse_binding_response(&response, &TRANSACTION_ID),
+            Some(
+                "[2001:db8:1234:5678:11:2233:4455:6677]:32853"
+                    .parse()
+                    .unwrap()
+            )
+        );
+
+        // responses to other requests and requests are ignored
+        assert_eq!(parse_binding_response(&response, &[0; 12]), None);
+        assert_eq!(
+            parse_binding_response(&binding_request(&TRANSACTION_ID), &TRANSACTION_ID),
+            None
+        );
+        assert_eq!(
+            parse_binding_response(&response[..30], &TRANSACTION_ID),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_parse_mapped_address() {
+        // an RFC 3489 response with only a MAPPED-ADDRESS attribute
+        let mut response = binding_request(&TRANSACTION_ID).to_vec();
+        response[..4].copy_from_slice(&[0x01, 0x01, 0x00, 0x0c]);
+        response.extend_from_slice(&[
+            0x00, 0x01, 0x00, 0x08, 0x00, 0x01, 0x76, 0x5f, 198, 51, 100, 7,
+        ]);
+        assert_eq!(
+            parse_binding_response(&response, &TRANSACTION_ID),
+            Some("198.51.100.7:30303".parse().unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_ip_from_stun() {
+        // a STUN server answering every request from 203.0.113.9
+        let server = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server_address = server.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut request = [0_u8; 64];
+            let (_, from) = server.recv_from(&mut request).unwrap();
+            let mut response = binding_request(request[8..20].try_into().unwrap()).to_vec();
+            response[..4].copy_from_slice(&[0x01, 0x01, 0x00, 0x0c]);
+            response.extend_from_slice(&[0x00, 0x20, 0x00, 0x08, 0x00, 0x01, 0x57, 0x4d]);
+            response.extend(
+                [203_u8, 0, 113, 9]
+                    .iter()
+                    .zip(&MAGIC_COOKIE)
+                    .map(|(byte, key)| byte ^ key),
+            );
+            server.send_to(&response, from).unwrap();
+        });
+
+        let enr = EnrBuilder::new("v4")
+            .set_ip_from_stun(server_address)
+            .await
+            .unwrap()
+            .build(&MockSigner(1))
+            .unwrap();
+        assert_eq!(enr.ip(), Some(Ipv4Addr::new(203, 0, 113, 9)));
+    }
+}
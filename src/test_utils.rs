@@ -0,0 +1,491 @@
+//! Assertions, `proptest` strategies and networks of records for tests of this and dependent
+//! crates. Enabled by the `test-utils` feature.
+
+#[cfg(feature = "ed25519")]
+use crate::{digest, CombinedKey, EnrBuilder, NodeId};
+use crate::{Enr, EnrKey};
+use proptest::prelude::*;
+use std::{
+    convert::TryFrom,
+    fmt::Write,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+/// Encodes the record to its text representation, decodes it and asserts the decoded record is
+/// identical to the original, see [`assert_enr_eq`], and verifies.
+///
+/// # Panics
+/// Panics if the text cannot be decoded, the decoded record differs from the original or does
+/// not verify.
+pub fn assert_enr_roundtrip<K: EnrKey>(enr: &Enr<K>) {
+    let text = enr.to_base64();
+    let decoded = match text.parse::<Enr<K>>() {
+        Ok(decoded) => decoded,
+        Err(e) => panic!("ENR round-trip failed to decode {}: {}", text, e),
+    };
+    assert_enr_eq(enr, &decoded);
+    assert!(
+        decoded.verify(),
+        "ENR round-trip through {} does not verify",
+        text
+    );
+}
+
+/// Asserts two records have the same sequence number, signature and content.
+///
+/// # Panics
+/// Panics with a message listing every differing value of the records.
+pub fn assert_enr_eq<K: EnrKey>(left: &Enr<K>, right: &Enr<K>) {
+    let mut differences = String::new();
+    if left.seq() != right.seq() {
+        let _ = writeln!(differences, "  seq: {} != {}", left.seq(), right.seq());
+    }
+    if left.signature() != right.signature() {
+        let _ = writeln!(
+            differences,
+            "  signature: {} != {}",
+            hex::encode(left.signature()),
+            hex::encode(right.signature())
+        );
+    }
+    let keys = left.content.keys().chain(
+        right
+            .content
+            .keys()
+            .filter(|k| !left.content.contains_key(*k)),
+    );
+    for key in keys {
+        let (l, r) = (left.get_raw_rlp(key), right.get_raw_rlp(key));
+        if l != r {
+            let _ = writeln!(
+                differences,
+                "  {key}: {} != {}",
+                l.map_or_else(|| "<absent>".into(), hex::encode),
+                r.map_or_else(|| "<absent>".into(), hex::encode)
+            );
+        }
+    }
+    assert!(
+        differences.is_empty(),
+        "ENRs differ (left != right):\n{}",
+        differences
+    );
+}
+
+/// Generates signed records with random keys, sequence numbers, addresses, ports and
+/// application fields. Every generated record is at most 300 bytes.
+#[cfg(feature = "libsecp256k1")]
+pub fn valid_enr() -> impl Strategy<Value = Enr<crate::secp256k1::SecretKey>> {
+    use crate::{secp256k1::SecretKey, EnrBuilder};
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    let key = any::<[u8; 32]>()
+        .prop_filter_map("invalid secret key", |bytes| SecretKey::parse(&bytes).ok());
+    let fields = (
+        any::<u64>(),
+        proptest::option::of(any::<[u8; 4]>()),
+        proptest::option::of(any::<u16>()),
+        proptest::option::of(any::<u16>()),
+        proptest::option::of((any::<[u8; 16]>(), any::<u16>())),
+        proptest::collection::btree_map(
+            "[a-z][a-z0-9-]{3,8}",
+            proptest::collection::vec(any::<u8>(), 0..32),
+            0..4,
+        ),
+    );
+    (key, fields).prop_filter_map(
+        "record exceeds the maximum size or has a malformed address",
+        |(key, (seq, ip4, udp, tcp, ip6, extra))| {
+            let mut builder = EnrBuilder::new("v4");
+            builder.seq(seq);
+            if let Some(ip4) = ip4 {
+                builder.ip4(Ipv4Addr::from(ip4));
+            }
+            if let Some(udp) = udp {
+                builder.udp(udp);
+            }
+            if let Some(tcp) = tcp {
+                builder.tcp(tcp);
+            }
+            if let Some((ip6, udp6)) = ip6 {
+                builder.ip6(Ipv6Addr::from(ip6)).udp6(udp6);
+            }
+            for (key, value) in extra {
+                builder.add_value(key, value);
+            }
+            builder.build(&key).ok()
+        },
+    )
+}
+
+/// Generates the text form, `enr:...`, of the records of [`valid_enr`].
+#[cfg(feature = "libsecp256k1")]
+pub fn valid_enr_string() -> impl Strategy<Value = String> {
+    valid_enr().prop_map(|enr| enr.to_base64())
+}
+
+/// Generates arbitrary strings for fuzzing the text parser: random strings, the `enr:` prefix
+/// followed by the base64 encoding of random bytes, and valid records with a character replaced.
+#[cfg(feature = "libsecp256k1")]
+pub fn arbitrary_text() -> impl Strategy<Value = String> {
+    prop_oneof![
+        any::<String>(),
+        proptest::collection::vec(any::<u8>(), 0..320).prop_map(|bytes| format!(
+            "enr:{}",
+            base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+        )),
+        (
+            valid_enr_string(),
+            any::<prop::sample::Index>(),
+            any::<char>()
+        )
+            .prop_map(|(text, index, replacement)| {
+                let mut chars: Vec<char> = text.chars().collect();
+                let index = index.index(chars.len());
+                chars[index] = replacement;
+                chars.into_iter().collect()
+            }),
+    ]
+}
+
+/// The keys of the records of an [`EnrTopologyBuilder`].
+#[cfg(feature = "ed25519")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyType {
+    /// secp256k1 keys only.
+    Secp256k1,
+    /// ed25519 keys only.
+    Ed25519,
+    /// secp256k1 and ed25519 keys, alternating in the order of node ids.
+    Mixed,
+}
+
+/// A range of IP addresses, `<address>/<prefix length>`, such as `10.0.0.1/24`. The range starts
+/// at the address and ends with the last address of its network.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpNet {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNet {
+    /// The range from `addr` to the end of its network of `prefix_len` bits. Returns `None` if
+    /// the prefix is longer than the address.
+    #[must_use]
+    pub const fn new(addr: IpAddr, prefix_len: u8) -> Option<Self> {
+        let bits = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix_len > bits {
+            return None;
+        }
+        Some(Self { addr, prefix_len })
+    }
+
+    /// The first address of the range.
+    #[must_use]
+    pub const fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// The length of the network prefix in bits.
+    #[must_use]
+    pub const fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// The number of addresses of the range, saturating at `u128::MAX`.
+    #[must_use]
+    pub fn len(&self) -> u128 {
+        let (start, bits) = match self.addr {
+            IpAddr::V4(addr) => (u128::from(u32::from(addr)), 32),
+            IpAddr::V6(addr) => (u128::from(addr), 128),
+        };
+        let host_bits = bits - u32::from(self.prefix_len);
+        let end = if host_bits == 0 {
+            start
+        } else {
+            start | (u128::MAX >> (128 - host_bits))
+        };
+        (end - start).saturating_add(1)
+    }
+
+    /// Returns `false`, a range holds at least its first address.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// The address `index` addresses after the first, if it is part of the range.
+    #[must_use]
+    pub fn nth(&self, index: u128) -> Option<IpAddr> {
+        if index >= self.len() {
+            return None;
+        }
+        Some(match self.addr {
+            IpAddr::V4(addr) => {
+                let addr = u128::from(u32::from(addr)) + index;
+                IpAddr::V4(Ipv4Addr::from(u32::try_from(addr).ok()?))
+            }
+            IpAddr::V6(addr) => IpAddr::V6(Ipv6Addr::from(u128::from(addr) + index)),
+        })
+    }
+}
+
+impl FromStr for IpNet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = s
+            .split_once('/')
+            .ok_or_else(|| format!("Missing prefix length in {s}"))?;
+        let addr = addr
+            .parse()
+            .map_err(|e| format!("Invalid address in {s}: {e}"))?;
+        let prefix_len = prefix_len
+            .parse()
+            .map_err(|e| format!("Invalid prefix length in {s}: {e}"))?;
+        Self::new(addr, prefix_len).ok_or_else(|| format!("Prefix length too long in {s}"))
+    }
+}
+
+/// Builds a network of signed records for simulations of discovery protocols, with addresses,
+/// ports and node ids assigned systematically rather than at random.
+///
+/// The space of node ids is split into `count` intervals of equal size, and every interval holds
+/// the node id of exactly one record, so that the records fill the buckets of a routing table as
+/// a large network would. The keys are derived from a counter, so the same builder always builds
+/// the same records.
+///
+/// The records are returned in the order of their node ids. The `i`-th record advertises the
+/// `i`-th address of `ip_range`, cycling through the range, with the same UDP and TCP port. The
+/// port is `base_port`, increased by one on every cycle, so that every record has its own socket.
+#[cfg(feature = "ed25519")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EnrTopologyBuilder {
+    /// The keys of the records.
+    pub key_type: KeyType,
+    /// The addresses of the records.
+    pub ip_range: IpNet,
+    /// The port of the records of the first cycle through `ip_range`.
+    pub base_port: u16,
+    /// The number of records.
+    pub count: usize,
+}
+
+#[cfg(feature = "ed25519")]
+impl EnrTopologyBuilder {
+    /// Builds the records, see [`EnrTopologyBuilder`].
+    ///
+    /// # Panics
+    /// Panics if `base_port` is 0, or if `ip_range` and the ports from `base_port` have fewer
+    /// sockets than `count`.
+    #[must_use]
+    pub fn build(&self) -> Vec<Enr<CombinedKey>> {
+        assert!(self.base_port != 0, "The base port must not be 0");
+        let ports = u128::from(u16::MAX - self.base_port) + 1;
+        assert!(
+            self.ip_range.len().saturating_mul(ports) >= self.count as u128,
+            "{} records do not fit into {} from port {}",
+            self.count,
+            self.ip_range.addr(),
+            self.base_port
+        );
+
+        let mut keys: Vec<Option<CombinedKey>> = (0..self.count).map(|_| None).collect();
+        let mut missing = self.count;
+        let mut counter = 0_u64;
+        while missing > 0 {
+            let secp256k1 = match self.key_type {
+                KeyType::Secp256k1 => true,
+                KeyType::Ed25519 => false,
+                KeyType::Mixed => counter.is_multiple_of(2),
+            };
+            let mut bytes = digest(&counter.to_be_bytes());
+            counter += 1;
+            let key = if secp256k1 {
+                match CombinedKey::secp256k1_from_bytes(&mut bytes) {
+                    Ok(key) => key,
+                    // the digest is not a valid secret key
+                    Err(_) => continue,
+                }
+            } else {
+                CombinedKey::ed25519_from_bytes(&mut bytes).expect("32 bytes are an ed25519 key")
+            };
+            let interval = interval(&NodeId::from(key.public()), self.count);
+            let expected = match self.key_type {
+                KeyType::Mixed => interval.is_multiple_of(2),
+                _ => secp256k1,
+            };
+            if keys[interval].is_none() && expected == secp256k1 {
+                keys[interval] = Some(key);
+                missing -= 1;
+            }
+        }
+
+        let hosts = self.ip_range.len();
+        keys.into_iter()
+            .enumerate()
+            .map(|(index, key)| {
+                let index = index as u128;
+                let ip = self
+                    .ip_range
+                    .nth(index % hosts)
+                    .expect("the index is within the range");
+                let port = u16::try_from(u128::from(self.base_port) + index / hosts)
+                    .expect("the sockets were counted");
+                let mut builder = EnrBuilder::new("v4");
+                match ip {
+                    IpAddr::V4(ip) => builder.ip4(ip).udp(port).tcp(port),
+                    IpAddr::V6(ip) => builder.ip6(ip).udp6(port).tcp6(port),
+                };
+                builder
+                    .build(&key.expect("every interval has a key"))
+                    .expect("the record is valid")
+            })
+            .collect()
+    }
+}
+
+/// The interval of the node id when the space of node ids is split into `count` intervals of equal
+/// size.
+#[cfg(feature = "ed25519")]
+fn interval(node_id: &NodeId, count: usize) -> usize {
+    let mut prefix = [0_u8; 8];
+    prefix.copy_from_slice(&node_id.raw()[..8]);
+    let interval = (u128::from(u64::from_be_bytes(prefix)) * count as u128) >> 64;
+    usize::try_from(interval).expect("the interval is below count")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder};
+
+    #[test]
+    fn test_assert_enr_roundtrip() {
+        let enr = EnrBuilder::new("v4")
+            .udp(30303)
+            .build(&MockSigner(1))
+            .unwrap();
+        assert_enr_roundtrip(&enr);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "ENRs differ (left != right):\n  seq: 1 != 2\n  udp: 82765f != <absent>\n"
+    )]
+    fn test_assert_enr_eq_lists_differences() {
+        let left = EnrBuilder::new("v4")
+            .udp(30303)
+            .build(&MockSigner(1))
+            .unwrap();
+        let right = EnrBuilder::new("v4").seq(2).build(&MockSigner(1)).unwrap();
+        assert_enr_eq(&left, &right);
+    }
+
+    #[test]
+    fn test_ip_net() {
+        let net: IpNet = "10.0.0.1/30".parse().unwrap();
+        assert_eq!(net.len(), 3);
+        assert_eq!(net.nth(2), Some(Ipv4Addr::new(10, 0, 0, 3).into()));
+        assert_eq!(net.nth(3), None);
+        assert_eq!("10.0.0.1/32".parse::<IpNet>().unwrap().len(), 1);
+        assert_eq!("0.0.0.0/0".parse::<IpNet>().unwrap().len(), 1 << 32);
+        assert_eq!("::/0".parse::<IpNet>().unwrap().len(), u128::MAX);
+        let net: IpNet = "fd00::fe/120".parse().unwrap();
+        assert_eq!(net.len(), 2);
+        assert_eq!(
+            net.nth(1),
+            Some(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 0xff).into())
+        );
+        assert!("10.0.0.1".parse::<IpNet>().is_err());
+        assert!("10.0.0.1/33".parse::<IpNet>().is_err());
+        assert!("10.0.0/8".parse::<IpNet>().is_err());
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_topology_builder() {
+        use crate::CombinedPublicKey;
+        use std::net::{SocketAddrV4, SocketAddrV6};
+
+        let builder = EnrTopologyBuilder {
+            key_type: KeyType::Mixed,
+            ip_range: "10.0.0.1/30".parse().unwrap(),
+            base_port: 9000,
+            count: 32,
+        };
+        let enrs = builder.build();
+        assert_eq!(enrs.len(), 32);
+        for (i, enr) in enrs.iter().enumerate() {
+            assert!(enr.verify());
+            // every interval of node ids holds one record
+            assert_eq!(interval(&enr.node_id(), 32), i);
+            assert_eq!(
+                matches!(enr.public_key(), CombinedPublicKey::Secp256k1(_)),
+                i % 2 == 0
+            );
+            // the range has 3 addresses
+            let ip = Ipv4Addr::new(10, 0, 0, 1 + u8::try_from(i % 3).unwrap());
+            let port = 9000 + u16::try_from(i / 3).unwrap();
+            assert_eq!(enr.udp4_socket(), Some(SocketAddrV4::new(ip, port)));
+            assert_eq!(enr.tcp4_socket(), Some(SocketAddrV4::new(ip, port)));
+        }
+        assert_eq!(builder.build(), enrs);
+
+        let enrs = EnrTopologyBuilder {
+            key_type: KeyType::Ed25519,
+            ip_range: "fd00::1/64".parse().unwrap(),
+            base_port: 30303,
+            count: 4,
+        }
+        .build();
+        for (i, enr) in enrs.iter().enumerate() {
+            assert!(matches!(enr.public_key(), CombinedPublicKey::Ed25519(_)));
+            let ip = Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 1 + u16::try_from(i).unwrap());
+            assert_eq!(enr.udp6_socket(), Some(SocketAddrV6::new(ip, 30303, 0, 0)));
+        }
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    #[should_panic(expected = "2 records do not fit into 10.0.0.1 from port 65535")]
+    fn test_topology_builder_too_small() {
+        let _ = EnrTopologyBuilder {
+            key_type: KeyType::Secp256k1,
+            ip_range: "10.0.0.1/32".parse().unwrap(),
+            base_port: 65535,
+            count: 2,
+        }
+        .build();
+    }
+
+    #[cfg(feature = "libsecp256k1")]
+    mod prop_tests {
+        use super::*;
+        use crate::secp256k1::SecretKey;
+
+        proptest! {
+            #[test]
+            #[cfg_attr(miri, ignore)]
+            fn prop_text_roundtrip(text in valid_enr_string()) {
+                let enr: Enr<SecretKey> = text.parse().unwrap();
+                prop_assert_eq!(enr.to_base64(), text);
+                assert_enr_roundtrip(&enr);
+            }
+
+            #[test]
+            #[cfg_attr(miri, ignore)]
+            fn prop_parse_never_panics(text in arbitrary_text()) {
+                // any accepted string decodes to a record that round-trips
+                if let Ok(enr) = text.parse::<Enr<SecretKey>>() {
+                    assert_enr_roundtrip(&enr);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Serialization of ENR records as their base64 text form, `enr:-IS4Q...`, provided by the
+//! `serde` feature.
+//!
+//! This is the representation of the `serde` implementation of [`Enr`]. Fields of other types
+//! can opt into it with `#[serde(with = "enr::text")]`, for example to keep the text form in a
+//! format that would otherwise be given another representation. Deserialization accepts the text
+//! form with or without the `enr:` prefix and verifies the signature of the record.
+//!
+//! ```rust
+//! use enr::{secp256k1::SecretKey, Enr, EnrBuilder};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Bootnode {
+//!     #[serde(with = "enr::text")]
+//!     enr: Enr<SecretKey>,
+//!     name: String,
+//! }
+//!
+//! let key = SecretKey::random(&mut rand::thread_rng());
+//! let enr = EnrBuilder::new("v4").udp(30303).build(&key).unwrap();
+//! let node = Bootnode { enr, name: "boot-1".into() };
+//!
+//! let json = serde_json::to_string(&node).unwrap();
+//! assert!(json.starts_with(r#"{"enr":"enr:-"#));
+//! let decoded: Bootnode = serde_json::from_str(&json).unwrap();
+//! assert_eq!(decoded.enr, node.enr);
+//! ```
+
+use crate::{Enr, EnrKey};
+use serde::{de, Deserializer, Serializer};
+use std::{fmt, marker::PhantomData, str::FromStr};
+
+/// Serializes a record as its base64 text form.
+pub fn serialize<K: EnrKey, S: Serializer>(enr: &Enr<K>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&enr.to_base64())
+}
+
+/// Deserializes a record from its base64 text form. The signature of the record is verified.
+///
+/// The error names the reason a record is rejected, such as an invalid signature.
+pub fn deserialize<'de, K: EnrKey, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Enr<K>, D::Error> {
+    deserializer.deserialize_str(TextVisitor(PhantomData))
+}
+
+/// Reads the text form of a record, borrowed or owned.
+struct TextVisitor<K>(PhantomData<K>);
+
+impl<K: EnrKey> de::Visitor<'_> for TextVisitor<K> {
+    type Value = Enr<K>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a base64 encoded ENR")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Enr::from_str(v).map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "libsecp256k1")]
+mod tests {
+    use crate::{secp256k1::SecretKey, Enr, EnrBuilder};
+    use serde::{Deserialize, Serialize};
+    use std::net::Ipv4Addr;
+
+    type DefaultEnr = Enr<SecretKey>;
+
+    /// A configuration holding records.
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        #[serde(with = "crate::text")]
+        local: DefaultEnr,
+        bootnodes: Vec<DefaultEnr>,
+    }
+
+    fn config() -> Config {
+        let record = |port| {
+            let key = SecretKey::random(&mut rand::thread_rng());
+            EnrBuilder::new("v4")
+                .ip4(Ipv4Addr::new(192, 0, 2, 1))
+                .udp(port)
+                .build(&key)
+                .unwrap()
+        };
+        Config {
+            local: record(9000),
+            bootnodes: vec![record(9001), record(9002)],
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_serde_json_text() {
+        let config = config();
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["local"], config.local.to_base64());
+        assert_eq!(json["bootnodes"][1], config.bootnodes[1].to_base64());
+        assert_eq!(serde_json::from_value::<Config>(json).unwrap(), config);
+
+        // the prefix is optional
+        let text = config.local.to_base64();
+        let json = serde_json::to_string(&text[4..]).unwrap();
+        assert_eq!(
+            serde_json::from_str::<DefaultEnr>(&json).unwrap(),
+            config.local
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_serde_yaml_text() {
+        let config = config();
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        assert!(yaml.contains(&format!("local: {}", config.local.to_base64())));
+        assert_eq!(serde_yaml::from_str::<Config>(&yaml).unwrap(), config);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_serde_tampered_text() {
+        let enr = config().local;
+        // raise the udp port, the last byte of the record, without re-signing
+        let mut bytes = enr.encode();
+        *bytes.last_mut().unwrap() += 1;
+        let tampered = format!(
+            "enr:{}",
+            base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD)
+        );
+
+        let json = serde_json::to_string(&tampered).unwrap();
+        let error = serde_json::from_str::<DefaultEnr>(&json).unwrap_err();
+        assert!(error.to_string().contains("Invalid Signature"), "{}", error);
+        let error = serde_yaml::from_str::<DefaultEnr>(&tampered).unwrap_err();
+        assert!(error.to_string().contains("Invalid Signature"), "{}", error);
+
+        // a record of an unknown scheme cannot be verified
+        let forged = serde_json::to_string(&crate::forge_unknown_scheme(&enr)).unwrap();
+        let error = serde_json::from_str::<DefaultEnr>(&forged).unwrap_err();
+        assert!(
+            error.to_string().contains("Unsupported identity scheme"),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! An ENR record paired with the time it was received.
+//!
+//! Node tables evict records that have not been refreshed for a while. [`TimedEnr`] tracks the
+//! receipt time of a record, and an [`EnrSet`](crate::EnrSet) of timed records, a
+//! [`TimedEnrSet`](crate::TimedEnrSet), evicts stale ones with
+//! [`EnrSet::evict_older_than`](crate::EnrSet::evict_older_than).
+
+use crate::{Enr, EnrKey};
+use std::{
+    ops::Deref,
+    time::{Duration, Instant},
+};
+
+/// An ENR record paired with the time it was received.
+pub struct TimedEnr<K: EnrKey> {
+    /// The record.
+    pub enr: Enr<K>,
+
+    /// When the record was received.
+    pub received_at: Instant,
+}
+
+impl<K: EnrKey> TimedEnr<K> {
+    /// Pairs a record with the current time.
+    #[must_use]
+    pub fn new(enr: Enr<K>) -> Self {
+        Self::received_at(enr, Instant::now())
+    }
+
+    /// Pairs a record with the time it was received.
+    #[must_use]
+    pub const fn received_at(enr: Enr<K>, received_at: Instant) -> Self {
+        Self { enr, received_at }
+    }
+
+    /// The time elapsed since the record was received.
+    #[must_use]
+    pub fn age(&self) -> Duration {
+        self.received_at.elapsed()
+    }
+
+    /// Returns `true` if the record was received more than `duration` ago.
+    #[must_use]
+    pub fn is_older_than(&self, duration: Duration) -> bool {
+        self.age() > duration
+    }
+
+    /// Replaces the record with a newer record of the same node, received now.
+    ///
+    /// Returns the previous timed record if the new record has a higher sequence number, and
+    /// `None`, leaving the record and its receipt time untouched, otherwise.
+    pub fn refresh(&mut self, new_enr: Enr<K>) -> Option<Self> {
+        if !new_enr.same_identity(&self.enr) || new_enr.seq() <= self.enr.seq() {
+            return None;
+        }
+        Some(std::mem::replace(self, Self::new(new_enr)))
+    }
+}
+
+impl<K: EnrKey> Deref for TimedEnr<K> {
+    type Target = Enr<K>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.enr
+    }
+}
+
+impl<K: EnrKey> AsRef<Enr<K>> for TimedEnr<K> {
+    fn as_ref(&self) -> &Enr<K> {
+        &self.enr
+    }
+}
+
+impl<K: EnrKey> Clone for TimedEnr<K> {
+    fn clone(&self) -> Self {
+        Self {
+            enr: self.enr.clone(),
+            received_at: self.received_at,
+        }
+    }
+}
+
+impl<K: EnrKey> PartialEq for TimedEnr<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.enr == other.enr && self.received_at == other.received_at
+    }
+}
+
+impl<K: EnrKey> std::fmt::Debug for TimedEnr<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("TimedEnr")
+            .field("enr", &self.enr)
+            .field("received_at", &self.received_at)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder};
+
+    fn ago(secs: u64) -> Instant {
+        Instant::now()
+            .checked_sub(Duration::from_secs(secs))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_timed_enr() {
+        let key = MockSigner(1);
+        let enr = EnrBuilder::new("v4").udp(9000).build(&key).unwrap();
+        let mut timed = TimedEnr::received_at(enr.clone(), ago(60));
+        assert!(timed.age() >= Duration::from_mins(1));
+        assert!(timed.is_older_than(Duration::from_secs(30)));
+        assert!(!timed.is_older_than(Duration::from_hours(1)));
+        assert_eq!(timed.udp4(), Some(9000));
+
+        // a record that is not newer leaves the receipt time untouched
+        assert_eq!(timed.refresh(enr.clone()), None);
+        let other = EnrBuilder::new("v4").seq(10).build(&MockSigner(2)).unwrap();
+        assert_eq!(timed.refresh(other), None);
+        assert!(timed.is_older_than(Duration::from_secs(30)));
+
+        let mut newer = enr.clone();
+        newer.set_udp(9001, &key).unwrap();
+        let previous = timed.refresh(newer.clone()).unwrap();
+        assert_eq!(previous.enr, enr);
+        assert_eq!(timed.enr, newer);
+        assert!(!timed.is_older_than(Duration::from_secs(30)));
+    }
+}
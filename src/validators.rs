@@ -0,0 +1,233 @@
+//! Application-defined validation of field values, applied when decoding a record with
+//! [`Enr::decode_with_validators`] or [`Enr::from_str_with_validators`].
+//!
+//! A [`FieldValidators`] registry maps keys to validators. Each validator receives the
+//! RLP-encoded value of its key, if the key is present in the record, and returns a reason if the
+//! value is not acceptable. The constants of this module validate the well-known keys of
+//! EIP-778 as [`Enr::validate_fields`] does.
+
+use crate::{decode_text, field::validate, Enr, EnrError, EnrKey};
+use alloc::{collections::BTreeMap, format, string::String, vec::Vec};
+
+/// Validates an RLP-encoded field value, returning the reason it is not acceptable.
+pub type FieldValidator = fn(&[u8]) -> Result<(), String>;
+
+/// A field value rejected by a [`FieldValidator`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldValidationError {
+    /// The key of the rejected value.
+    pub key: String,
+    /// The reason given by the validator.
+    pub reason: String,
+}
+
+/// A registry of the validators of field values, keyed by the field they validate.
+#[derive(Clone, Debug, Default)]
+pub struct FieldValidators {
+    validators: BTreeMap<String, FieldValidator>,
+}
+
+impl FieldValidators {
+    /// Constructs an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a registry of the validators of the well-known keys.
+    #[must_use]
+    pub fn well_known() -> Self {
+        let mut validators = Self::new();
+        validators
+            .add("id", ID)
+            .add("secp256k1", SECP256K1)
+            .add("ed25519", ED25519)
+            .add("ip", IP)
+            .add("ip6", IP6)
+            .add("tcp", TCP)
+            .add("tcp6", TCP6)
+            .add("udp", UDP)
+            .add("udp6", UDP6)
+            .add("quic", QUIC)
+            .add("quic6", QUIC6);
+        validators
+    }
+
+    /// Sets the validator of a key, replacing any previous validator of the key.
+    pub fn add(&mut self, key: impl Into<String>, validator: FieldValidator) -> &mut Self {
+        self.validators.insert(key.into(), validator);
+        self
+    }
+
+    /// Removes the validator of a key.
+    pub fn remove(&mut self, key: &str) -> &mut Self {
+        self.validators.remove(key);
+        self
+    }
+
+    /// Runs the validator of each key present in the record, in key order, returning every
+    /// rejected value.
+    pub fn validate<K: EnrKey>(&self, enr: &Enr<K>) -> Result<(), Vec<FieldValidationError>> {
+        let errors: Vec<_> = self
+            .validators
+            .iter()
+            .filter_map(|(key, validator)| {
+                let raw = enr.get_raw_rlp(key)?;
+                validator(raw).err().map(|reason| FieldValidationError {
+                    key: key.clone(),
+                    reason,
+                })
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Validates a well-known key.
+fn well_known(key: &str, raw: &[u8]) -> Result<(), String> {
+    validate(key, raw).map_err(|e| format!("{e:?}"))
+}
+
+/// Validates the `id` field, a UTF-8 string.
+pub const ID: FieldValidator = |raw| well_known("id", raw);
+/// Validates the `secp256k1` field, a 33-byte compressed public key.
+pub const SECP256K1: FieldValidator = |raw| well_known("secp256k1", raw);
+/// Validates the `ed25519` field, a 32-byte public key.
+pub const ED25519: FieldValidator = |raw| well_known("ed25519", raw);
+/// Validates the `ip` field, a 4-byte IPv4 address.
+pub const IP: FieldValidator = |raw| well_known("ip", raw);
+/// Validates the `ip6` field, a 16-byte IPv6 address.
+pub const IP6: FieldValidator = |raw| well_known("ip6", raw);
+/// Validates the `tcp` field, a minimally encoded port.
+pub const TCP: FieldValidator = |raw| well_known("tcp", raw);
+/// Validates the `tcp6` field, a minimally encoded port.
+pub const TCP6: FieldValidator = |raw| well_known("tcp6", raw);
+/// Validates the `udp` field, a minimally encoded port.
+pub const UDP: FieldValidator = |raw| well_known("udp", raw);
+/// Validates the `udp6` field, a minimally encoded port.
+pub const UDP6: FieldValidator = |raw| well_known("udp6", raw);
+/// Validates the `quic` field, a minimally encoded port.
+pub const QUIC: FieldValidator = |raw| well_known("quic", raw);
+/// Validates the `quic6` field, a minimally encoded port.
+pub const QUIC6: FieldValidator = |raw| well_known("quic6", raw);
+
+impl<K: EnrKey> Enr<K> {
+    /// Decodes an RLP-encoded record, then runs the validators of the keys it contains.
+    ///
+    /// The signature is verified before any validator runs, and the validators only read the
+    /// record.
+    ///
+    /// # Errors
+    /// Fails with [`EnrError::InvalidRlpData`] if the record is malformed, its identity scheme is
+    /// unknown or its signature does not verify, and with [`EnrError::InvalidFields`] holding
+    /// every rejected value otherwise.
+    pub fn decode_with_validators(
+        bytes: &[u8],
+        validators: &FieldValidators,
+    ) -> Result<Self, EnrError> {
+        let enr: Self = rlp::decode(bytes).map_err(EnrError::InvalidRlpData)?;
+        validators.validate(&enr).map_err(EnrError::InvalidFields)?;
+        Ok(enr)
+    }
+
+    /// Decodes a base64 text record, accepting the same variants as [`str::parse`], then runs
+    /// the validators of the keys it contains, see [`Enr::decode_with_validators`].
+    pub fn from_str_with_validators(
+        text: &str,
+        validators: &FieldValidators,
+    ) -> Result<Self, EnrError> {
+        let bytes = decode_text(text).map_err(|_| EnrError::InvalidBase64)?;
+        Self::decode_with_validators(&bytes, validators)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keys::MockSigner, EnrBuilder};
+
+    /// Requires an `eth2` field of exactly 16 bytes.
+    fn eth2(raw: &[u8]) -> Result<(), String> {
+        let len = rlp::Rlp::new(raw).data().map_err(|e| e.to_string())?.len();
+        if len == 16 {
+            Ok(())
+        } else {
+            Err(format!("expected 16 bytes, got {len}"))
+        }
+    }
+
+    #[test]
+    fn test_decode_with_validators() {
+        let key = MockSigner(1);
+        let mut validators = FieldValidators::well_known();
+        validators.add("eth2", eth2);
+
+        let enr = EnrBuilder::new("v4")
+            .udp(30303)
+            .add_value("eth2".into(), vec![0_u8; 16])
+            .build(&key)
+            .unwrap();
+        assert_eq!(
+            Enr::decode_with_validators(&enr.encode(), &validators).unwrap(),
+            enr
+        );
+        assert_eq!(
+            Enr::from_str_with_validators(&enr.to_base64(), &validators).unwrap(),
+            enr
+        );
+
+        // absent keys are not validated
+        let enr = EnrBuilder::new("v4").build(&key).unwrap();
+        assert!(Enr::<MockSigner>::decode_with_validators(&enr.encode(), &validators).is_ok());
+
+        // all rejected values are reported, in key order
+        let mut enr = EnrBuilder::new("v4")
+            .add_value("eth2".into(), vec![0_u8; 4])
+            .build(&key)
+            .unwrap();
+        enr.content
+            .insert("udp".into(), vec![0x82, 0x00, 0x50].into());
+        let errors = match Enr::<MockSigner>::decode_with_validators(&enr.encode(), &validators) {
+            Err(EnrError::InvalidFields(errors)) => errors,
+            other => panic!("expected invalid fields, got {:?}", other),
+        };
+        assert_eq!(
+            errors,
+            vec![
+                FieldValidationError {
+                    key: "eth2".into(),
+                    reason: "expected 16 bytes, got 4".into(),
+                },
+                FieldValidationError {
+                    key: "udp".into(),
+                    reason: "NonMinimalInteger".into(),
+                },
+            ]
+        );
+
+        // the registry can be relaxed
+        validators.remove("udp");
+        assert!(matches!(
+            Enr::<MockSigner>::decode_with_validators(&enr.encode(), &validators),
+            Err(EnrError::InvalidFields(ref errors)) if errors.len() == 1 && errors[0].key == "eth2"
+        ));
+        assert!(matches!(
+            Enr::<MockSigner>::from_str_with_validators("enr:!!", &validators),
+            Err(EnrError::InvalidBase64)
+        ));
+
+        // records of unknown schemes are never handed to the validators
+        let forged = crate::forge_unknown_scheme(&EnrBuilder::new("v4").build(&key).unwrap());
+        assert!(matches!(
+            Enr::<MockSigner>::decode_with_validators(&forged.encode(), &validators),
+            Err(EnrError::InvalidRlpData(_))
+        ));
+        assert!(
+            Enr::<MockSigner>::from_str_with_validators(&forged.to_base64(), &validators).is_err()
+        );
+    }
+}
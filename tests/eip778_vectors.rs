@@ -0,0 +1,66 @@
+//! The reference vectors of EIP-778. A failure of any of these tests is a protocol-level
+//! regression.
+#![cfg(feature = "libsecp256k1")]
+
+use enr::{secp256k1, Enr, EnrBuilder, EnrPublicKey};
+use std::net::Ipv4Addr;
+
+/// The example record of EIP-778.
+const EIP778_ENR: &str = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+
+/// The private key the example record was signed with.
+const EIP778_KEY: &str = "b71c71a67e1177ad4e901695e1b4b9ee17ae16c6668d313eac2f96dbcda3f291";
+
+const EIP778_PUBLIC_KEY: &str =
+    "03ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd3138";
+
+const EIP778_NODE_ID: &str = "a448f24c6d18e575453db13171562b71999873db5b286df957af199ec94617f7";
+
+const EIP778_SIGNATURE: &str = "7098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c";
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_eip778_example_record() {
+    let enr = EIP778_ENR.parse::<Enr<secp256k1::SecretKey>>().unwrap();
+
+    assert_eq!(enr.seq(), 1);
+    assert_eq!(enr.id().as_deref(), Some("v4"));
+    assert_eq!(enr.ip(), Some(Ipv4Addr::new(127, 0, 0, 1)));
+    assert_eq!(enr.udp(), Some(30303));
+    assert_eq!(enr.tcp(), None);
+    assert_eq!(hex::encode(enr.public_key().encode()), EIP778_PUBLIC_KEY);
+    assert_eq!(hex::encode(enr.node_id().raw()), EIP778_NODE_ID);
+    assert_eq!(hex::encode(enr.signature()), EIP778_SIGNATURE);
+    assert!(enr.verify());
+
+    // re-encoding reproduces the record exactly
+    assert_eq!(enr.to_base64(), EIP778_ENR);
+}
+
+/// go-ethereum's interop test, `TestPythonInterop`, rebuilds the example record from its private
+/// key and expects the identical record and node id. Signing is deterministic, so the signature is
+/// reproduced as well.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_eip778_record_from_private_key() {
+    let key = secp256k1::SecretKey::parse_slice(&hex::decode(EIP778_KEY).unwrap()).unwrap();
+    let enr = EnrBuilder::new("v4")
+        .ip4(Ipv4Addr::new(127, 0, 0, 1))
+        .udp(30303)
+        .build(&key)
+        .unwrap();
+
+    assert_eq!(hex::encode(enr.node_id().raw()), EIP778_NODE_ID);
+    assert_eq!(hex::encode(enr.signature()), EIP778_SIGNATURE);
+    assert_eq!(enr.to_base64(), EIP778_ENR);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_eip778_tampered_record() {
+    let mut bytes = base64::decode_config(&EIP778_ENR[4..], base64::URL_SAFE_NO_PAD).unwrap();
+    // flip a bit of the `udp` value
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    assert!(rlp::decode::<Enr<secp256k1::SecretKey>>(&bytes).is_err());
+}
@@ -0,0 +1,2 @@
+//! Tests of `enr` on `wasm32-unknown-unknown`, see `tests/web.rs`. Run them with
+//! `wasm-pack test --node` from this directory.
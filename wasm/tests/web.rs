@@ -0,0 +1,38 @@
+use enr::{secp256k1::SecretKey, Enr, EnrBuilder, NodeId};
+use std::net::Ipv4Addr;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+type DefaultEnr = Enr<SecretKey>;
+
+/// The record of the EIP-778 test vector.
+const EIP_778: &str = "enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8";
+
+#[wasm_bindgen_test]
+fn test_decode_and_verify() {
+    let enr: DefaultEnr = EIP_778.parse().unwrap();
+    assert!(enr.verify());
+    assert_eq!(enr.ip(), Some(Ipv4Addr::LOCALHOST));
+    assert_eq!(enr.udp4(), Some(30303));
+    assert_eq!(
+        enr.node_id().to_hex(),
+        "a448f24c6d18e575453db13171562b71999873db5b286df957af199ec94617f7"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_sign_with_imported_key() {
+    let key = SecretKey::parse(&[0x11; 32]).unwrap();
+    let enr = EnrBuilder::new("v4")
+        .ip4(Ipv4Addr::new(192, 0, 2, 1))
+        .udp(30303)
+        .build(&key)
+        .unwrap();
+    assert!(enr.verify());
+    // signing is deterministic, so the record matches the one built on other targets
+    assert_eq!(enr.to_base64(), "enr:-IS4QBwtsT979cixWxbepKOXNnbq9DFYMGwOmJh_2DDyNUdEPsPkrM5bVEFta8Z6U3L5SGY0QrWjAwbPeVyEZ6hbXYwBgmlkgnY0gmlwhMAAAgGJc2VjcDI1NmsxoQNPNVvct8wK9yjvPM65YV2QaEu1sspfhZqw8LcEB1hxqoN1ZHCCdl8");
+}
+
+#[wasm_bindgen_test]
+fn test_random_node_id() {
+    assert_ne!(NodeId::random(), NodeId::random());
+}